@@ -1,4 +1,9 @@
-use std::{borrow::Cow, convert::TryFrom, fmt::Display};
+use crate::dns::write::Write;
+use alloc::{
+    borrow::Cow,
+    string::{String, ToString},
+};
+use core::{convert::TryFrom, fmt::Display};
 
 use crate::SimpleDnsError;
 
@@ -11,6 +16,7 @@ use super::{PacketPart, MAX_CHARACTER_STRING_LENGTH};
 /// Inside a " delimited string any character can occur, except for a " itself,  
 /// which must be quoted using \ (back slash).
 #[derive(PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct CharacterString<'a> {
     pub(crate) data: Cow<'a, [u8]>,
 }
@@ -29,6 +35,25 @@ impl<'a> CharacterString<'a> {
         Ok(Self { data })
     }
 
+    /// Returns the bytes of this character string, without the length prefix
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Formats this character string as a quoted string, as used in zone files.
+    /// Quotes and backslashes are escaped and non printable bytes are written as `\DDD`
+    pub(crate) fn fmt_quoted(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("\"")?;
+        for byte in self.data.iter() {
+            match byte {
+                b'"' | b'\\' => write!(f, "\\{}", *byte as char)?,
+                0x20..=0x7e => write!(f, "{}", *byte as char)?,
+                _ => write!(f, "\\{:03}", byte)?,
+            }
+        }
+        f.write_str("\"")
+    }
+
     /// Transforms the inner data into its owned type
     pub fn into_owned<'b>(self) -> CharacterString<'b> {
         CharacterString {
@@ -37,14 +62,31 @@ impl<'a> CharacterString<'a> {
     }
 }
 
+/// Deserialized character strings are validated like [`CharacterString::new`], so they can't be longer than 255 bytes
+#[cfg(feature = "serde")]
+impl<'de, 'a> serde::Deserialize<'de> for CharacterString<'a> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "CharacterString")]
+        struct Unvalidated {
+            data: alloc::vec::Vec<u8>,
+        }
+
+        let unvalidated = Unvalidated::deserialize(deserializer)?;
+        Self::internal_new(Cow::Owned(unvalidated.data)).map_err(serde::de::Error::custom)
+    }
+}
+
 impl<'a> PacketPart<'a> for CharacterString<'a> {
     fn parse(data: &'a [u8], position: usize) -> crate::Result<Self>
     where
         Self: Sized,
     {
-        let length = data[position] as usize;
+        let length = *data
+            .get(position)
+            .ok_or(SimpleDnsError::InvalidCharacterString)? as usize;
 
-        if length < MAX_CHARACTER_STRING_LENGTH && length + position < data.len() {
+        if length + position < data.len() {
             Ok(Self {
                 data: Cow::Borrowed(&data[position + 1..position + 1 + length]),
             })
@@ -53,10 +95,9 @@ impl<'a> PacketPart<'a> for CharacterString<'a> {
         }
     }
 
-    fn write_to<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         out.write_all(&[self.data.len() as u8])?;
         out.write_all(&self.data)
-            .map_err(crate::SimpleDnsError::from)
     }
 
     fn len(&self) -> usize {
@@ -81,14 +122,14 @@ impl<'a> TryFrom<String> for CharacterString<'a> {
 }
 
 impl<'a> Display for CharacterString<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let s = std::str::from_utf8(&self.data).unwrap();
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let s = core::str::from_utf8(&self.data).unwrap();
         f.write_str(s)
     }
 }
 
-impl<'a> std::fmt::Debug for CharacterString<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl<'a> core::fmt::Debug for CharacterString<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("CharacterString")
             .field("data", &self.to_string())
             .finish()
@@ -124,6 +165,45 @@ mod tests {
         assert_eq!("some_long_text", c_string.to_string());
     }
 
+    #[test]
+    fn parse_lengths() {
+        let empty = CharacterString::parse(b"\x00", 0).unwrap();
+        assert_eq!(b"", empty.as_bytes());
+        assert_eq!(1, empty.len());
+
+        let mut data = vec![255u8];
+        data.extend_from_slice(&[b'a'; 255]);
+        let max_length = CharacterString::parse(&data, 0).unwrap();
+        assert_eq!(&[b'a'; 255][..], max_length.as_bytes());
+        assert_eq!(256, max_length.len());
+
+        assert_eq!(
+            Err(SimpleDnsError::InvalidCharacterString),
+            CharacterString::parse(&data[..255], 0)
+        );
+        assert_eq!(
+            Err(SimpleDnsError::InvalidCharacterString),
+            CharacterString::parse(b"", 0)
+        );
+    }
+
+    #[test]
+    fn validate_lengths() {
+        assert_eq!(b"", CharacterString::try_from("").unwrap().as_bytes());
+
+        let max_length = "a".repeat(255);
+        let c_string = CharacterString::try_from(max_length.as_str()).unwrap();
+        let mut out = Vec::new();
+        c_string.write_to(&mut out).unwrap();
+        assert_eq!(255, out[0]);
+        assert_eq!(c_string, CharacterString::parse(&out, 0).unwrap());
+
+        assert_eq!(
+            Err(SimpleDnsError::InvalidCharacterString),
+            CharacterString::try_from("a".repeat(256))
+        );
+    }
+
     #[test]
     fn append_to_vec() {
         let mut out = Vec::new();
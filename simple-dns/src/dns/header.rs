@@ -1,4 +1,5 @@
-use std::{convert::TryInto, io::Write};
+use crate::dns::write::Write;
+use core::convert::TryInto;
 
 use crate::{rdata::OPT, ResourceRecord};
 
@@ -10,7 +11,8 @@ pub(crate) mod masks {
     pub const RESPONSE_CODE_MASK: u16 = 0b0000_0000_0000_1111;
 }
 /// Contains general information about the packet
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct Header<'a> {
     /// The identification of the packet, must be defined when querying
     pub id: u16,
@@ -102,13 +104,25 @@ impl<'a> Header<'a> {
     fn get_flags(&self) -> u16 {
         let mut flags = self.z_flags.bits();
 
-        flags |= (self.opcode as u16) << masks::OPCODE_MASK.trailing_zeros();
-        flags |= self.response_code as u16 & masks::RESPONSE_CODE_MASK;
+        flags |= (u16::from(u8::from(self.opcode)) << masks::OPCODE_MASK.trailing_zeros())
+            & masks::OPCODE_MASK;
+        flags |= u16::from(self.response_code) & masks::RESPONSE_CODE_MASK;
 
         flags
     }
 
-    pub(crate) fn opt_rr(&self) -> Option<ResourceRecord> {
+    /// Transforms the inner data into its owned type
+    pub fn into_owned<'b>(self) -> Header<'b> {
+        Header {
+            id: self.id,
+            opcode: self.opcode,
+            response_code: self.response_code,
+            z_flags: self.z_flags,
+            opt: self.opt.map(|opt| opt.into_owned()),
+        }
+    }
+
+    pub(crate) fn opt_rr(&self) -> Option<ResourceRecord<'_>> {
         self.opt.as_ref().map(|opt| {
             ResourceRecord::new(
                 crate::Name::new_unchecked("."),
@@ -138,7 +152,7 @@ mod tests {
 
     #[test]
     fn write_example_query() {
-        let mut header = Header::new_query(core::u16::MAX);
+        let mut header = Header::new_query(u16::MAX);
 
         header.set_flags(PacketFlag::TRUNCATION | PacketFlag::RECURSION_DESIRED);
 
@@ -156,7 +170,7 @@ mod tests {
         let buffer = b"\xff\xff\x03\x00\x00\x02\x00\x02\x00\x02\x00\x02";
         let header = Header::parse(&buffer[..]).unwrap();
 
-        assert_eq!(core::u16::MAX, header.id);
+        assert_eq!(u16::MAX, header.id);
         assert_eq!(OPCODE::StandardQuery, header.opcode);
         assert!(!header.has_flags(
             PacketFlag::AUTHORITATIVE_ANSWER
@@ -73,7 +73,7 @@ pub fn additional_records(buffer: &[u8]) -> crate::Result<u16> {
         .map_err(|_| crate::SimpleDnsError::InvalidHeaderData)
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "tsig"))]
 /// Writes the additional records count in the header buffer
 pub(crate) fn set_additional_records(buffer: &mut [u8], additional_records_count: u16) {
     buffer[10..12].copy_from_slice(&additional_records_count.to_be_bytes());
@@ -0,0 +1,217 @@
+//! Conversion of internationalized labels to and from their ASCII form (A-label),
+//! using the Punycode encoding from [RFC 3492](https://datatracker.ietf.org/doc/html/rfc3492)
+//!
+//! Labels are lowercased before encoding, no other mapping or normalization from UTS #46 is applied
+
+use alloc::{borrow::Cow, format, string::String, vec::Vec};
+
+const ACE_PREFIX: &str = "xn--";
+
+const BASE: u32 = 36;
+const TMIN: u32 = 1;
+const TMAX: u32 = 26;
+const SKEW: u32 = 38;
+const DAMP: u32 = 700;
+const INITIAL_BIAS: u32 = 72;
+const INITIAL_N: u32 = 128;
+
+/// Converts a label to its A-label form, ASCII labels are returned unchanged
+pub(crate) fn label_to_ascii(label: Cow<'_, [u8]>) -> crate::Result<Cow<'_, [u8]>> {
+    if label.is_ascii() {
+        return Ok(label);
+    }
+
+    let unicode = String::from_utf8_lossy(&label).to_lowercase();
+    match encode(&unicode) {
+        Some(encoded) => Ok(format!("{ACE_PREFIX}{encoded}").into_bytes().into()),
+        None => Err(crate::SimpleDnsError::InvalidUnicodeLabel),
+    }
+}
+
+/// Converts an A-label to its Unicode form, other labels are returned as they are
+pub(crate) fn label_to_unicode(label: &[u8]) -> String {
+    let label = String::from_utf8_lossy(label);
+    let is_a_label = label.len() > ACE_PREFIX.len()
+        && label.is_char_boundary(ACE_PREFIX.len())
+        && label[..ACE_PREFIX.len()].eq_ignore_ascii_case(ACE_PREFIX);
+
+    if is_a_label {
+        if let Some(decoded) = decode(&label[ACE_PREFIX.len()..]) {
+            return decoded;
+        }
+    }
+
+    label.into_owned()
+}
+
+fn encode(input: &str) -> Option<String> {
+    let code_points: Vec<u32> = input.chars().map(u32::from).collect();
+
+    let mut output: String = input.chars().filter(char::is_ascii).collect();
+    let basic_count = output.len() as u32;
+    if basic_count > 0 {
+        output.push('-');
+    }
+
+    let mut n = INITIAL_N;
+    let mut delta = 0u32;
+    let mut bias = INITIAL_BIAS;
+    let mut handled = basic_count;
+
+    while (handled as usize) < code_points.len() {
+        let m = code_points.iter().copied().filter(|c| *c >= n).min()?;
+        delta = delta.checked_add((m - n).checked_mul(handled + 1)?)?;
+        n = m;
+
+        for &c in code_points.iter() {
+            if c < n {
+                delta = delta.checked_add(1)?;
+            }
+
+            if c == n {
+                let mut q = delta;
+                let mut k = BASE;
+                loop {
+                    let t = threshold(k, bias);
+                    if q < t {
+                        break;
+                    }
+
+                    output.push(encode_digit(t + (q - t) % (BASE - t)));
+                    q = (q - t) / (BASE - t);
+                    k += BASE;
+                }
+
+                output.push(encode_digit(q));
+                bias = adapt(delta, handled + 1, handled == basic_count);
+                delta = 0;
+                handled += 1;
+            }
+        }
+
+        delta = delta.checked_add(1)?;
+        n = n.checked_add(1)?;
+    }
+
+    Some(output)
+}
+
+fn decode(input: &str) -> Option<String> {
+    let (basic, extended) = match input.rfind('-') {
+        Some(position) => (&input[..position], &input[position + 1..]),
+        None => ("", input),
+    };
+
+    if !basic.is_ascii() {
+        return None;
+    }
+
+    let mut output: Vec<char> = basic.chars().collect();
+    let mut n = INITIAL_N;
+    let mut i = 0u32;
+    let mut bias = INITIAL_BIAS;
+    let mut digits = extended.bytes().peekable();
+
+    while digits.peek().is_some() {
+        let old_i = i;
+        let mut w = 1u32;
+        let mut k = BASE;
+        loop {
+            let digit = decode_digit(digits.next()?)?;
+            i = i.checked_add(digit.checked_mul(w)?)?;
+
+            let t = threshold(k, bias);
+            if digit < t {
+                break;
+            }
+
+            w = w.checked_mul(BASE - t)?;
+            k += BASE;
+        }
+
+        let length = output.len() as u32 + 1;
+        bias = adapt(i - old_i, length, old_i == 0);
+        n = n.checked_add(i / length)?;
+        i %= length;
+
+        output.insert(i as usize, char::from_u32(n)?);
+        i += 1;
+    }
+
+    Some(output.into_iter().collect())
+}
+
+fn threshold(k: u32, bias: u32) -> u32 {
+    if k <= bias {
+        TMIN
+    } else if k >= bias + TMAX {
+        TMAX
+    } else {
+        k - bias
+    }
+}
+
+fn adapt(delta: u32, num_points: u32, first_time: bool) -> u32 {
+    let mut delta = if first_time { delta / DAMP } else { delta / 2 };
+    delta += delta / num_points;
+
+    let mut k = 0;
+    while delta > ((BASE - TMIN) * TMAX) / 2 {
+        delta /= BASE - TMIN;
+        k += BASE;
+    }
+
+    k + (BASE - TMIN + 1) * delta / (delta + SKEW)
+}
+
+fn encode_digit(digit: u32) -> char {
+    match digit {
+        0..=25 => (b'a' + digit as u8) as char,
+        _ => (b'0' + (digit - 26) as u8) as char,
+    }
+}
+
+fn decode_digit(byte: u8) -> Option<u32> {
+    match byte {
+        b'a'..=b'z' => Some((byte - b'a') as u32),
+        b'A'..=b'Z' => Some((byte - b'A') as u32),
+        b'0'..=b'9' => Some((byte - b'0') as u32 + 26),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_and_decode_punycode() {
+        for (unicode, encoded) in [
+            ("café", "caf-dma"),
+            ("münchen", "mnchen-3ya"),
+            ("bücher", "bcher-kva"),
+            ("日本語", "wgv71a119e"),
+        ] {
+            assert_eq!(Some(encoded.to_string()), encode(unicode));
+            assert_eq!(Some(unicode.to_string()), decode(encoded));
+        }
+
+        assert_eq!(None, decode("caf-dm!"));
+    }
+
+    #[test]
+    fn labels_to_ascii_and_unicode() {
+        assert_eq!(
+            b"xn--caf-dma",
+            &label_to_ascii(Cow::Borrowed("Café".as_bytes())).unwrap()[..]
+        );
+        assert!(matches!(
+            label_to_ascii(Cow::Borrowed(b"local")),
+            Ok(Cow::Borrowed(b"local"))
+        ));
+
+        assert_eq!("café", label_to_unicode(b"XN--caf-dma"));
+        assert_eq!("local", label_to_unicode(b"local"));
+        assert_eq!("xn--", label_to_unicode(b"xn--"));
+    }
+}
@@ -3,13 +3,17 @@
 mod character_string;
 pub use character_string::CharacterString;
 
+#[cfg(feature = "idna")]
+mod idna;
+
 mod name;
 pub use name::Name;
 
 mod packet;
-pub use packet::Packet;
+pub use packet::{IntoRecords, Packet, Records, Section, SectionRanges};
 
 mod header;
+use alloc::format;
 use header::Header;
 
 pub mod header_buffer;
@@ -17,6 +21,10 @@ pub mod header_buffer;
 mod packet_part;
 use packet_part::PacketPart;
 
+mod write;
+
+mod presentation;
+
 mod question;
 pub use question::Question;
 
@@ -24,10 +32,20 @@ pub mod rdata;
 pub use rdata::TYPE;
 
 mod resource_record;
-pub use resource_record::ResourceRecord;
+pub use resource_record::{ResourceRecord, ResourceRecordBuilder};
+
+#[cfg(feature = "tsig")]
+pub mod tsig;
+
+#[cfg(feature = "std")]
+pub mod transfer;
+
+pub mod update;
+
+pub mod zone;
 
 use bitflags::bitflags;
-use std::convert::TryFrom;
+use core::{convert::TryFrom, fmt::Display};
 
 const MAX_LABEL_LENGTH: usize = 63;
 const MAX_NAME_LENGTH: usize = 255;
@@ -36,7 +54,8 @@ const MAX_NULL_LENGTH: usize = 65535;
 
 bitflags! {
     /// Possible Packet Flags
-    #[derive(Clone, Debug, PartialEq, Copy)]
+    #[derive(Clone, Debug, PartialEq, Eq, Copy)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct PacketFlag: u16 {
         /// Indicates if this packet is a query or a response. This is the QR flag in the DNS
         /// specifications, this flag is called Response here to be more ergonomic
@@ -69,6 +88,7 @@ bitflags! {
 /// Possible QTYPE values for a Question in a DNS packet  
 /// Each value is described according to its own RFC
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum QTYPE {
     /// Query for the specific [TYPE]
     TYPE(TYPE),
@@ -100,10 +120,21 @@ impl TryFrom<u16> for QTYPE {
             253 => Ok(QTYPE::MAILB),
             254 => Ok(QTYPE::MAILA),
             255 => Ok(QTYPE::ANY),
-            v => match TYPE::from(v) {
-                TYPE::Unknown(_) => Err(Self::Error::InvalidQType(v)),
-                ty => Ok(ty.into()),
-            },
+            // unknown codes are kept as TYPE::Unknown, so questions for newer types can still be parsed
+            v => Ok(TYPE::from(v).into()),
+        }
+    }
+}
+
+impl Display for QTYPE {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            QTYPE::TYPE(ty) => ty.fmt(f),
+            QTYPE::IXFR => f.write_str("IXFR"),
+            QTYPE::AXFR => f.write_str("AXFR"),
+            QTYPE::MAILB => f.write_str("MAILB"),
+            QTYPE::MAILA => f.write_str("MAILA"),
+            QTYPE::ANY => f.write_str("ANY"),
         }
     }
 }
@@ -124,6 +155,7 @@ impl From<QTYPE> for u16 {
 /// Possible CLASS values for a Resource in a DNS packet  
 /// Each value is described according to its own RFC
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CLASS {
     /// The Internet, [RFC 1035](https://tools.ietf.org/html/rfc1035)
     IN = 1,
@@ -135,6 +167,9 @@ pub enum CLASS {
     HS = 4,
     /// [RFC 2136](https://datatracker.ietf.org/doc/html/rfc2136)
     NONE = 254,
+    /// Used by the records of UPDATE and TSIG messages, [RFC 2136](https://datatracker.ietf.org/doc/html/rfc2136).
+    /// Questions for any class use [`QCLASS::ANY`] instead
+    ANY = 255,
 }
 
 impl TryFrom<u16> for CLASS {
@@ -148,14 +183,57 @@ impl TryFrom<u16> for CLASS {
             3 => Ok(CH),
             4 => Ok(HS),
             254 => Ok(NONE),
+            255 => Ok(ANY),
             v => Err(Self::Error::InvalidClass(v)),
         }
     }
 }
 
+impl Display for CLASS {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let class = match self {
+            CLASS::IN => "IN",
+            CLASS::CS => "CS",
+            CLASS::CH => "CH",
+            CLASS::HS => "HS",
+            CLASS::NONE => "NONE",
+            CLASS::ANY => "ANY",
+        };
+
+        f.write_str(class)
+    }
+}
+
+impl core::str::FromStr for CLASS {
+    type Err = crate::SimpleDnsError;
+
+    /// Parses a class mnemonic ignoring case, or `CLASS` followed by the class code
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let class = match s.to_ascii_uppercase().as_str() {
+            "IN" => CLASS::IN,
+            "CS" => CLASS::CS,
+            "CH" => CLASS::CH,
+            "HS" => CLASS::HS,
+            "NONE" => CLASS::NONE,
+            "ANY" => CLASS::ANY,
+            upper => match upper.strip_prefix("CLASS").map(str::parse::<u16>) {
+                Some(Ok(code)) => CLASS::try_from(code)?,
+                _ => {
+                    return Err(crate::SimpleDnsError::InvalidPresentationFormat(format!(
+                        "unknown class {s}"
+                    )))
+                }
+            },
+        };
+
+        Ok(class)
+    }
+}
+
 /// Possible QCLASS values for a Question in a DNS packet  
 /// Each value is described according to its own RFC
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum QCLASS {
     /// Query for the specific [CLASS]
     CLASS(CLASS),
@@ -164,8 +242,12 @@ pub enum QCLASS {
 }
 
 impl From<CLASS> for QCLASS {
+    /// [`CLASS::ANY`] becomes [`QCLASS::ANY`], every other class becomes [`QCLASS::CLASS`]
     fn from(v: CLASS) -> Self {
-        Self::CLASS(v)
+        match v {
+            CLASS::ANY => Self::ANY,
+            v => Self::CLASS(v),
+        }
     }
 }
 
@@ -180,6 +262,15 @@ impl TryFrom<u16> for QCLASS {
     }
 }
 
+impl Display for QCLASS {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            QCLASS::CLASS(class) => class.fmt(f),
+            QCLASS::ANY => f.write_str("ANY"),
+        }
+    }
+}
+
 impl From<QCLASS> for u16 {
     fn from(val: QCLASS) -> Self {
         match val {
@@ -193,19 +284,20 @@ impl From<QCLASS> for u16 {
 /// [RFC 1035](https://tools.ietf.org/html/rfc1035): A four bit field that specifies kind of query in this message.  
 /// This value is set by the originator of a query and copied into the response.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OPCODE {
     /// Normal query
-    StandardQuery = 0,
+    StandardQuery,
     /// Inverse query (query a name by IP)
-    InverseQuery = 1,
+    InverseQuery,
     /// Server status request
-    ServerStatusRequest = 2,
+    ServerStatusRequest,
     /// Notify query
-    Notify = 4,
+    Notify,
     /// Update query [RFC 2136](https://datatracker.ietf.org/doc/html/rfc2136)
-    Update = 5,
-    /// Reserved opcode for future use
-    Reserved,
+    Update,
+    /// Reserved opcode for future use, contains the original opcode value
+    Reserved(u8),
 }
 
 impl From<u16> for OPCODE {
@@ -216,7 +308,33 @@ impl From<u16> for OPCODE {
             2 => OPCODE::ServerStatusRequest,
             4 => OPCODE::Notify,
             5 => OPCODE::Update,
-            _ => OPCODE::Reserved,
+            _ => OPCODE::Reserved((code & 0b1111) as u8),
+        }
+    }
+}
+
+impl TryFrom<u8> for OPCODE {
+    type Error = crate::SimpleDnsError;
+
+    /// Converts a 4 bit opcode value, values that do not fit in 4 bits are rejected
+    fn try_from(code: u8) -> Result<Self, Self::Error> {
+        if code > 0b1111 {
+            return Err(crate::SimpleDnsError::InvalidHeaderData);
+        }
+
+        Ok(OPCODE::from(code as u16))
+    }
+}
+
+impl From<OPCODE> for u8 {
+    fn from(opcode: OPCODE) -> Self {
+        match opcode {
+            OPCODE::StandardQuery => 0,
+            OPCODE::InverseQuery => 1,
+            OPCODE::ServerStatusRequest => 2,
+            OPCODE::Notify => 4,
+            OPCODE::Update => 5,
+            OPCODE::Reserved(code) => code,
         }
     }
 }
@@ -225,43 +343,44 @@ impl From<u16> for OPCODE {
 /// [RFC 1035](https://tools.ietf.org/html/rfc1035) Response code - this 4 bit field is set as part of responses.  
 /// The values have the following interpretation
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RCODE {
     /// No error condition
-    NoError = 0,
+    NoError,
     /// Format error - The name server was unable to interpret the query.
-    FormatError = 1,
+    FormatError,
     /// Server failure - The name server was unable to process this query due to a problem with the name server.
-    ServerFailure = 2,
+    ServerFailure,
     /// Name Error - Meaningful only for responses from an authoritative name server,  
     /// this code signifies that the domain name referenced in the query does not exist.
-    NameError = 3,
+    NameError,
     /// Not Implemented - The name server does not support the requested kind of query.
-    NotImplemented = 4,
+    NotImplemented,
     /// Refused - The name server refuses to perform the specified operation for policy reasons.  
     /// For example, a name server may not wish to provide the information to the particular requester,   
     /// or a name server may not wish to perform a particular operation (e.g., zone transfer) for particular data.
-    Refused = 5,
+    Refused,
     /// Some name that ought not to exist, does exist.
     /// [RFC 2136](https://datatracker.ietf.org/doc/html/rfc2136)
-    YXDOMAIN = 6,
+    YXDOMAIN,
     /// Some RRset that ought not to exist, does exist.
     /// [RFC 2136](https://datatracker.ietf.org/doc/html/rfc2136)
-    YXRRSET = 7,
+    YXRRSET,
     /// Some RRset that ought to exist, does not exist.
     /// [RFC 2136](https://datatracker.ietf.org/doc/html/rfc2136)
-    NXRRSET = 8,
+    NXRRSET,
     /// The server is not authoritative for the zone named in the Zone Section.
     /// [RFC 2136](https://datatracker.ietf.org/doc/html/rfc2136)
-    NOTAUTH = 9,
+    NOTAUTH,
     /// A name used in the Prerequisite or Update Section is not within the zone denoted by the Zone Section.
     /// [RFC 2136](https://datatracker.ietf.org/doc/html/rfc2136)
-    NOTZONE = 10,
+    NOTZONE,
     /// EDNS Version not supported by the responder
     /// [RFC 6891](https://datatracker.ietf.org/doc/html/rfc6891)
-    BADVERS = 16,
+    BADVERS,
 
-    /// Reserved for future use.
-    Reserved,
+    /// Response code not known by this library, the inner value is preserved
+    Unknown(u16),
 }
 
 impl From<u16> for RCODE {
@@ -280,7 +399,141 @@ impl From<u16> for RCODE {
             9 => NOTAUTH,
             10 => NOTZONE,
             16 => BADVERS,
-            _ => RCODE::Reserved,
+            code => Unknown(code),
+        }
+    }
+}
+
+impl From<RCODE> for u16 {
+    fn from(rcode: RCODE) -> Self {
+        use RCODE::*;
+        match rcode {
+            NoError => 0,
+            FormatError => 1,
+            ServerFailure => 2,
+            NameError => 3,
+            NotImplemented => 4,
+            Refused => 5,
+            YXDOMAIN => 6,
+            YXRRSET => 7,
+            NXRRSET => 8,
+            NOTAUTH => 9,
+            NOTZONE => 10,
+            BADVERS => 16,
+            Unknown(code) => code,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opcode_round_trip() {
+        for (code, opcode) in [
+            (0u8, OPCODE::StandardQuery),
+            (1, OPCODE::InverseQuery),
+            (2, OPCODE::ServerStatusRequest),
+            (4, OPCODE::Notify),
+            (5, OPCODE::Update),
+        ] {
+            assert_eq!(opcode, OPCODE::try_from(code).unwrap());
+            assert_eq!(code, u8::from(opcode));
+
+            let mut packet = Packet::new_query(1);
+            *packet.opcode_mut() = opcode;
+
+            let bytes = packet.build_bytes_vec().unwrap();
+            assert_eq!(opcode, Packet::parse(&bytes).unwrap().opcode());
         }
+
+        assert_eq!(OPCODE::Reserved(3), OPCODE::try_from(3u8).unwrap());
+        assert!(OPCODE::try_from(16u8).is_err());
+
+        let mut packet = Packet::new_query(1);
+        *packet.opcode_mut() = OPCODE::Reserved(9);
+        let bytes = packet.build_bytes_vec().unwrap();
+        assert_eq!(OPCODE::Reserved(9), Packet::parse(&bytes).unwrap().opcode());
+        assert_eq!(
+            bytes,
+            Packet::parse(&bytes).unwrap().build_bytes_vec().unwrap()
+        );
+    }
+
+    #[test]
+    fn rcode_round_trip() {
+        for (code, rcode) in [
+            (0, RCODE::NoError),
+            (3, RCODE::NameError),
+            (10, RCODE::NOTZONE),
+            (16, RCODE::BADVERS),
+        ] {
+            assert_eq!(rcode, RCODE::from(code));
+            assert_eq!(code, u16::from(rcode));
+        }
+    }
+
+    #[test]
+    fn unknown_qtype_preserves_value() {
+        assert_eq!(
+            QTYPE::TYPE(TYPE::Unknown(1234)),
+            QTYPE::try_from(1234).unwrap()
+        );
+        assert_eq!(1234, u16::from(QTYPE::TYPE(TYPE::Unknown(1234))));
+
+        let query = Packet::new_query(1).with_questions([Question::new(
+            Name::new_unchecked("host.local"),
+            QTYPE::TYPE(TYPE::Unknown(1234)),
+            CLASS::IN.into(),
+            false,
+        )]);
+        let bytes = query.build_bytes_vec().unwrap();
+        assert_eq!([0x04, 0xd2], bytes[bytes.len() - 4..bytes.len() - 2]);
+
+        let parsed = Packet::parse(&bytes).unwrap();
+        assert_eq!(QTYPE::TYPE(TYPE::Unknown(1234)), parsed.questions[0].qtype);
+        assert_eq!(bytes, parsed.build_bytes_vec().unwrap());
+    }
+
+    #[test]
+    fn display_mnemonics() {
+        assert_eq!("A", TYPE::A.to_string());
+        assert_eq!("AAAA", TYPE::AAAA.to_string());
+        assert_eq!("SRV", TYPE::SRV.to_string());
+        assert_eq!("NSAP-PTR", TYPE::NSAP_PTR.to_string());
+        assert_eq!("TYPE9999", TYPE::Unknown(9999).to_string());
+        assert_eq!("TYPE9999", TYPE::from(9999).to_string());
+
+        assert_eq!("PTR", QTYPE::TYPE(TYPE::PTR).to_string());
+        assert_eq!("ANY", QTYPE::ANY.to_string());
+        assert_eq!("AXFR", QTYPE::AXFR.to_string());
+
+        assert_eq!("IN", CLASS::IN.to_string());
+        assert_eq!("CH", CLASS::CH.to_string());
+        assert_eq!("NONE", CLASS::NONE.to_string());
+        assert_eq!("ANY", CLASS::ANY.to_string());
+        assert_eq!("IN", QCLASS::CLASS(CLASS::IN).to_string());
+        assert_eq!("ANY", QCLASS::ANY.to_string());
+    }
+
+    #[test]
+    fn class_any_converts_to_qclass_any() {
+        assert_eq!(QCLASS::ANY, CLASS::ANY.into());
+        assert_eq!(QCLASS::CLASS(CLASS::IN), CLASS::IN.into());
+        assert_eq!(QCLASS::ANY, QCLASS::try_from(255).unwrap());
+    }
+
+    #[test]
+    fn unknown_rcode_preserves_value() {
+        assert_eq!(RCODE::Unknown(11), RCODE::from(11));
+        assert_eq!(RCODE::Unknown(4095), RCODE::from(4095));
+        assert_eq!(11, u16::from(RCODE::Unknown(11)));
+
+        let mut packet = Packet::new_reply(1);
+        *packet.rcode_mut() = RCODE::Unknown(11);
+
+        let bytes = packet.build_bytes_vec().unwrap();
+        assert_eq!(RCODE::Unknown(11), Packet::parse(&bytes).unwrap().rcode());
     }
 }
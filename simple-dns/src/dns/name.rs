@@ -1,11 +1,13 @@
-use std::{
-    borrow::Cow,
-    collections::HashMap,
+use alloc::{borrow::Cow, format, string::ToString, vec::Vec};
+use core::{
     convert::{TryFrom, TryInto},
     fmt::Display,
-    hash::{BuildHasher, Hash, Hasher},
+    hash::Hash,
+    net::{Ipv4Addr, Ipv6Addr},
 };
 
+use crate::dns::write::{name_refs_key, NameRefs, Seek, Write};
+
 use super::{PacketPart, MAX_LABEL_LENGTH, MAX_NAME_LENGTH};
 
 const POINTER_MASK: u8 = 0b1100_0000;
@@ -18,6 +20,9 @@ const POINTER_MASK_U16: u16 = 0b1100_0000_0000_0000;
 /// A Name represents a domain-name, which consists of character strings separated by dots.  
 /// Each section of a name is called label  
 /// ex: `google.com` consists of two labels `google` and `com`
+///
+/// Parsing a name preserves the casing found on the wire, comparison and hashing ignore ASCII case,
+/// `Example.com` and `example.com` are the same name
 #[derive(Eq, Clone)]
 pub struct Name<'a> {
     labels: Vec<Label<'a>>,
@@ -25,12 +30,28 @@ pub struct Name<'a> {
 }
 
 impl<'a> Name<'a> {
-    /// Creates a new validated Name
+    /// Creates a new validated Name.  
+    /// Labels longer than 63 octets and names longer than 255 octets in wire format are rejected.  
+    /// With the `idna` feature, Unicode labels are converted to their A-label form, `café.local` becomes `xn--caf-dma.local`
     pub fn new(name: &'a str) -> crate::Result<Self> {
+        #[cfg(feature = "idna")]
+        if !name.is_ascii() {
+            return Self::new_validated(
+                NameSpliter::new(name.as_bytes()).map(super::idna::label_to_ascii),
+            );
+        }
+
+        Self::new_validated(NameSpliter::new(name.as_bytes()).map(Ok))
+    }
+
+    fn new_validated(
+        labels: impl Iterator<Item = crate::Result<Cow<'a, [u8]>>>,
+    ) -> crate::Result<Self> {
         let mut total_size = 1;
 
-        let labels = NameSpliter::new(name.as_bytes())
+        let labels = labels
             .map(|label| {
+                let label = label?;
                 total_size += label.len() + 1;
                 Label::new(label)
             })
@@ -39,7 +60,7 @@ impl<'a> Name<'a> {
         let name = Self { labels, total_size };
 
         if name.total_size > MAX_NAME_LENGTH {
-            Err(crate::SimpleDnsError::InvalidServiceName)
+            Err(crate::SimpleDnsError::NameTooLong(name.total_size))
         } else {
             Ok(name)
         }
@@ -59,26 +80,109 @@ impl<'a> Name<'a> {
         Self { labels, total_size }
     }
 
+    /// Creates the reverse lookup name of `address`, `192.0.2.1` becomes `1.2.0.192.in-addr.arpa`
+    pub fn from_ipv4_arpa(address: Ipv4Addr) -> Name<'static> {
+        let labels = address
+            .octets()
+            .into_iter()
+            .rev()
+            .map(|octet| octet.to_string().into_bytes())
+            .chain([b"in-addr".to_vec(), b"arpa".to_vec()]);
+
+        Self::from_owned_labels(labels)
+    }
+
+    /// Creates the reverse lookup name of `address`, with one label for each nibble in reverse order,
+    /// `2001:db8::1` becomes `1.0.0.0.[...].8.b.d.0.1.0.0.2.ip6.arpa`
+    pub fn from_ipv6_arpa(address: Ipv6Addr) -> Name<'static> {
+        let labels = address
+            .octets()
+            .into_iter()
+            .rev()
+            .flat_map(|octet| [octet & 0x0f, octet >> 4])
+            .map(|nibble| format!("{:x}", nibble).into_bytes())
+            .chain([b"ip6".to_vec(), b"arpa".to_vec()]);
+
+        Self::from_owned_labels(labels)
+    }
+
+    fn from_owned_labels(labels: impl Iterator<Item = Vec<u8>>) -> Name<'static> {
+        let mut total_size = 1;
+        let labels = labels
+            .map(|label| {
+                total_size += label.len() + 1;
+                Label::new_unchecked(label)
+            })
+            .collect();
+
+        Name { labels, total_size }
+    }
+
     /// Verify if name ends with .local.
     pub fn is_link_local(&self) -> bool {
         match self.iter().last() {
-            Some(label) => b"local".eq_ignore_ascii_case(&label.data),
+            Some(label) => b"local".eq_ignore_ascii_case(label),
             None => false,
         }
     }
 
-    /// Returns an Iter of this Name Labels
-    pub fn iter(&'a self) -> std::slice::Iter<Label<'a>> {
-        self.labels.iter()
+    /// Returns an iterator over the labels of this name, without the length prefix.
+    /// The root name has no labels, use [`get_labels`](Name::get_labels) to get the labels themselves
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &[u8]> + ExactSizeIterator {
+        self.labels.iter().map(|label| &label.data[..])
+    }
+
+    /// Returns the number of labels of this name, the root name has zero labels
+    pub fn labels_count(&self) -> usize {
+        self.labels.len()
+    }
+
+    /// Returns this name without its leftmost label, `www.example.com` becomes `example.com`.  
+    /// The root name has no parent
+    pub fn parent(&self) -> Option<Name<'a>> {
+        let labels = self.labels.get(1..)?.to_vec();
+        let total_size = labels.iter().map(|label| label.len() + 1).sum::<usize>() + 1;
+
+        Some(Name { labels, total_size })
     }
 
-    /// Returns true if self is a subdomain of other
+    /// Returns true if self is a subdomain of other, or both names are equal.  
+    /// Labels are compared ignoring ASCII case, every name is a subdomain of the root name
     pub fn is_subdomain_of(&self, other: &Name) -> bool {
-        other
-            .iter()
-            .rev()
-            .zip(self.iter().rev())
-            .all(|(o, s)| *o == *s)
+        self.labels_count() >= other.labels_count()
+            && other
+                .iter()
+                .rev()
+                .zip(self.iter().rev())
+                .all(|(o, s)| o.eq_ignore_ascii_case(s))
+    }
+
+    /// Appends `label` to the end of this name, `_tcp.local` becomes `_ipp._tcp.local`.  
+    /// The name is left unchanged when the label is longer than 63 octets or the name would be longer than 255 octets
+    pub fn append_label(&mut self, label: &[u8]) -> crate::Result<()> {
+        let label = Label::new(label.to_vec())?;
+        let total_size = self.total_size + label.len() + 1;
+        if total_size > MAX_NAME_LENGTH {
+            return Err(crate::SimpleDnsError::NameTooLong(total_size));
+        }
+
+        self.labels.push(label);
+        self.total_size = total_size;
+        Ok(())
+    }
+
+    /// Appends the labels of `other` to the end of this name.  
+    /// The name is left unchanged when the combined name would be longer than 255 octets
+    pub fn append_name(&mut self, other: &Name) -> crate::Result<()> {
+        let total_size = self.total_size + other.total_size - 1;
+        if total_size > MAX_NAME_LENGTH {
+            return Err(crate::SimpleDnsError::NameTooLong(total_size));
+        }
+
+        self.labels
+            .extend(other.labels.iter().map(|label| label.clone().into_owned()));
+        self.total_size = total_size;
+        Ok(())
     }
 
     /// Transforms the inner data into its owned type
@@ -89,13 +193,72 @@ impl<'a> Name<'a> {
         }
     }
 
+    /// Returns this name with A-labels converted to their Unicode form, `xn--caf-dma.local` becomes `café.local`
+    #[cfg(feature = "idna")]
+    pub fn to_unicode(&self) -> alloc::string::String {
+        self.iter()
+            .map(super::idna::label_to_unicode)
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    /// Returns the canonical form of this name, with every ASCII letter lowercased,
+    /// [RFC 4034 section 6.2](https://datatracker.ietf.org/doc/html/rfc4034#section-6.2)
+    pub fn to_canonical(&self) -> Name<'static> {
+        Name {
+            labels: self
+                .labels
+                .iter()
+                .map(|label| label.clone().into_owned().into_lowercase())
+                .collect(),
+            total_size: self.total_size,
+        }
+    }
+
     /// Get the labels that compose this name
     pub fn get_labels(&'_ self) -> &'_ [Label<'_>] {
         &self.labels[..]
     }
 
-    fn plain_append<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
-        for label in self.iter() {
+    /// Parse a name from `data` at `position`, lowercasing every ASCII letter.  
+    /// Parsing a [`Packet`](crate::Packet) keeps the on-wire casing, which is required for 0x20 verification
+    pub fn parse_normalized(data: &'a [u8], position: usize) -> crate::Result<Self> {
+        let mut name = Self::parse(data, position)?;
+        name.labels = name
+            .labels
+            .into_iter()
+            .map(|label| label.into_lowercase())
+            .collect();
+
+        Ok(name)
+    }
+
+    /// Returns the length of the name at `position` without decoding its labels,
+    /// compression pointers are not followed
+    pub(crate) fn wire_len(data: &[u8], position: usize) -> crate::Result<usize> {
+        let mut offset = position;
+        loop {
+            match data.get(offset) {
+                None => return Err(crate::SimpleDnsError::InsufficientData),
+                Some(0) => return Ok(offset + 1 - position),
+                Some(len) if len & POINTER_MASK == POINTER_MASK => {
+                    if offset + 2 > data.len() {
+                        return Err(crate::SimpleDnsError::InsufficientData);
+                    }
+                    return Ok(offset + 2 - position);
+                }
+                Some(len) => {
+                    offset += 1 + *len as usize;
+                    if offset - position > MAX_NAME_LENGTH {
+                        return Err(crate::SimpleDnsError::InvalidDnsPacket);
+                    }
+                }
+            }
+        }
+    }
+
+    fn plain_append<T: Write>(&self, out: &mut T) -> crate::Result<()> {
+        for label in self.labels.iter() {
             out.write_all(&[label.len() as u8])?;
             out.write_all(&label.data)?;
         }
@@ -104,26 +267,24 @@ impl<'a> Name<'a> {
         Ok(())
     }
 
-    fn compress_append<T: std::io::Write + std::io::Seek>(
+    fn compress_append<T: Write + Seek>(
         &self,
         out: &mut T,
-        name_refs: &mut HashMap<u64, usize>,
+        name_refs: &mut NameRefs,
     ) -> crate::Result<()> {
-        for label in self.iter() {
-            let mut h = name_refs.hasher().build_hasher();
-            label.hash(&mut h);
-            let key = h.finish();
-
-            if let std::collections::hash_map::Entry::Vacant(e) = name_refs.entry(key) {
-                e.insert(out.stream_position()? as usize);
-                out.write_all(&[label.len() as u8])?;
-                out.write_all(&label.data)?;
-            } else {
-                let p = name_refs[&key] as u16;
-                out.write_all(&(p | POINTER_MASK_U16).to_be_bytes())?;
+        for (i, label) in self.labels.iter().enumerate() {
+            // the key identifies the remaining labels, a pointer replaces the whole suffix
+            let key = name_refs_key(name_refs, &self.labels[i..]);
+
+            if let Some(p) = name_refs.get(&key) {
+                out.write_all(&(*p as u16 | POINTER_MASK_U16).to_be_bytes())?;
 
                 return Ok(());
             }
+
+            name_refs.insert(key, out.stream_position()? as usize);
+            out.write_all(&[label.len() as u8])?;
+            out.write_all(&label.data)?;
         }
 
         out.write_all(&[0])?;
@@ -141,6 +302,9 @@ impl<'a> PacketPart<'a> for Name<'a> {
         let mut labels = Vec::new();
 
         let mut position = initial_position;
+        // start of the labels being read, every pointer must point before it,
+        // so each jump goes further back on the packet and pointers can't loop
+        let mut segment_start = initial_position;
 
         // avoid invalid data caused oom
         let mut name_size = 0usize;
@@ -150,9 +314,9 @@ impl<'a> PacketPart<'a> for Name<'a> {
                 return Err(crate::SimpleDnsError::InsufficientData);
             }
 
-            // domain name max size is 255
-            if name_size > MAX_NAME_LENGTH {
-                return Err(crate::SimpleDnsError::InvalidDnsPacket);
+            // domain name max size is 255, including the root label
+            if name_size + 1 > MAX_NAME_LENGTH {
+                return Err(crate::SimpleDnsError::NameTooLong(name_size + 1));
             }
 
             match data[position] {
@@ -170,13 +334,14 @@ impl<'a> PacketPart<'a> for Name<'a> {
                         return Err(crate::SimpleDnsError::InsufficientData);
                     }
 
-                    // avoid pointer forward (RFC 1035)
+                    // avoid pointer forward (RFC 1035) and pointer loops
                     let pointer = (u16::from_be_bytes(data[position..position + 2].try_into()?)
                         & !POINTER_MASK_U16) as usize;
-                    if pointer >= position {
-                        return Err(crate::SimpleDnsError::InvalidDnsPacket);
+                    if pointer >= segment_start {
+                        return Err(crate::SimpleDnsError::InvalidName);
                     }
                     position = pointer;
+                    segment_start = pointer;
                 }
                 len => {
                     name_size += 1 + len as usize;
@@ -200,14 +365,14 @@ impl<'a> PacketPart<'a> for Name<'a> {
         Ok(Self { labels, total_size })
     }
 
-    fn write_to<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         self.plain_append(out)
     }
 
-    fn write_compressed_to<T: std::io::Write + std::io::Seek>(
+    fn write_compressed_to<T: Write + Seek>(
         &self,
         out: &mut T,
-        name_refs: &mut HashMap<u64, usize>,
+        name_refs: &mut NameRefs,
     ) -> crate::Result<()> {
         self.compress_append(out, name_refs)
     }
@@ -226,8 +391,8 @@ impl<'a> TryFrom<&'a str> for Name<'a> {
 }
 
 impl<'a> Display for Name<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for (i, label) in self.iter().enumerate() {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (i, label) in self.labels.iter().enumerate() {
             if i != 0 {
                 f.write_str(".")?;
             }
@@ -239,8 +404,8 @@ impl<'a> Display for Name<'a> {
     }
 }
 
-impl<'a> std::fmt::Debug for Name<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl<'a> core::fmt::Debug for Name<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_tuple("Name")
             .field(&format!("{}", self))
             .field(&format!("{}", self.total_size))
@@ -255,11 +420,93 @@ impl<'a> PartialEq for Name<'a> {
 }
 
 impl<'a> Hash for Name<'a> {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
         self.labels.hash(state);
     }
 }
 
+/// Names are serialized as their presentation string, `www.example.com`.  
+/// Dots and backslashes inside a label are escaped as `\.` and `\\`, other bytes that are not printable ASCII
+/// are escaped as `\DDD`, so every name round trips
+#[cfg(feature = "serde")]
+impl<'a> serde::Serialize for Name<'a> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut name = alloc::string::String::with_capacity(self.total_size);
+        for (i, label) in self.labels.iter().enumerate() {
+            if i > 0 {
+                name.push('.');
+            }
+
+            for &byte in label.data.iter() {
+                match byte {
+                    b'.' | b'\\' => {
+                        name.push('\\');
+                        name.push(byte as char);
+                    }
+                    0x21..=0x7e => name.push(byte as char),
+                    _ => name.push_str(&format!("\\{:03}", byte)),
+                }
+            }
+        }
+
+        serializer.serialize_str(&name)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, 'a> serde::Deserialize<'de> for Name<'a> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = alloc::string::String::deserialize(deserializer)?;
+        Name::new_validated(
+            unescape_labels(&name)
+                .map_err(serde::de::Error::custom)?
+                .into_iter()
+                .map(|label| Ok(label.into())),
+        )
+        .map(|name| name.into_owned())
+        .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Splits a presentation string into labels, resolving the `\.`, `\\` and `\DDD` escapes
+#[cfg(feature = "serde")]
+fn unescape_labels(name: &str) -> Result<Vec<Vec<u8>>, &'static str> {
+    let mut labels = Vec::new();
+    let mut label = Vec::new();
+    let mut bytes = name.bytes();
+
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'.' => labels.push(core::mem::take(&mut label)),
+            b'\\' => match bytes.next() {
+                Some(digit) if digit.is_ascii_digit() => {
+                    let value = [Some(digit), bytes.next(), bytes.next()]
+                        .into_iter()
+                        .try_fold(0u16, |value, digit| match digit {
+                            Some(digit) if digit.is_ascii_digit() => {
+                                Some(value * 10 + (digit - b'0') as u16)
+                            }
+                            _ => None,
+                        })
+                        .and_then(|value| u8::try_from(value).ok())
+                        .ok_or("invalid \\DDD escape in name")?;
+                    label.push(value);
+                }
+                Some(escaped) => label.push(escaped),
+                None => return Err("name ends with an escape"),
+            },
+            _ => label.push(byte),
+        }
+    }
+
+    // a trailing dot marks a fully qualified name, it does not start another label
+    if !label.is_empty() {
+        labels.push(label);
+    }
+
+    Ok(labels)
+}
+
 struct NameSpliter<'a> {
     bytes: &'a [u8],
     current: usize,
@@ -279,7 +526,7 @@ impl<'a> Iterator for NameSpliter<'a> {
 
         for i in self.current..self.bytes.len() {
             if self.bytes[i] == b'.' && i - self.current > 0 {
-                let current = std::mem::replace(&mut self.current, i + 1);
+                let current = core::mem::replace(&mut self.current, i + 1);
                 if self.bytes[i - 1] == b'\\' {
                     slices.push(&self.bytes[current..i - 1]);
                     continue;
@@ -289,8 +536,9 @@ impl<'a> Iterator for NameSpliter<'a> {
             }
         }
 
-        if self.current < self.bytes.len() {
-            let current = std::mem::replace(&mut self.current, self.bytes.len());
+        // a single dot is the root name, which has no labels
+        if self.current < self.bytes.len() && self.bytes != b"." {
+            let current = core::mem::replace(&mut self.current, self.bytes.len());
             Some(join_slices(slices, &self.bytes[current..]))
         } else {
             None
@@ -318,16 +566,31 @@ fn join_slices<'a>(mut slices: Vec<&'a [u8]>, slice: &'a [u8]) -> Cow<'a, [u8]>
     }
 }
 
-#[derive(Eq, PartialEq, Hash, Clone)]
+#[derive(Eq, Clone)]
 pub struct Label<'a> {
     data: Cow<'a, [u8]>,
 }
 
+impl<'a> PartialEq for Label<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.data.eq_ignore_ascii_case(&other.data)
+    }
+}
+
+impl<'a> Hash for Label<'a> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        state.write_usize(self.data.len());
+        for byte in self.data.iter() {
+            state.write_u8(byte.to_ascii_lowercase());
+        }
+    }
+}
+
 impl<'a> Label<'a> {
     pub fn new<T: Into<Cow<'a, [u8]>>>(data: T) -> crate::Result<Self> {
         let label = Self::new_unchecked(data);
         if label.len() > MAX_LABEL_LENGTH {
-            Err(crate::SimpleDnsError::InvalidServiceLabel)
+            Err(crate::SimpleDnsError::LabelTooLong(label.len()))
         } else {
             Ok(label)
         }
@@ -346,19 +609,30 @@ impl<'a> Label<'a> {
             data: self.data.into_owned().into(),
         }
     }
+
+    /// Returns this label with ASCII letters lowercased, borrowed data is only copied when needed
+    pub fn into_lowercase(self) -> Self {
+        if self.data.iter().any(|b| b.is_ascii_uppercase()) {
+            Label {
+                data: self.data.to_ascii_lowercase().into(),
+            }
+        } else {
+            self
+        }
+    }
 }
 
 impl<'a> Display for Label<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match std::str::from_utf8(&self.data) {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match core::str::from_utf8(&self.data) {
             Ok(s) => f.write_str(s),
-            Err(_) => Err(std::fmt::Error),
+            Err(_) => Err(core::fmt::Error),
         }
     }
 }
 
-impl<'a> std::fmt::Debug for Label<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl<'a> core::fmt::Debug for Label<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("Label")
             .field("data", &self.to_string())
             .finish()
@@ -367,10 +641,10 @@ impl<'a> std::fmt::Debug for Label<'a> {
 
 #[cfg(test)]
 mod tests {
-    use std::io::Cursor;
     use std::{collections::hash_map::DefaultHasher, hash::Hasher};
 
     use super::*;
+    use crate::dns::write::Buffer;
     use crate::SimpleDnsError;
 
     #[test]
@@ -386,6 +660,124 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn reject_names_exceeding_length_limits() {
+        let label_63 = "a".repeat(63);
+        let label_64 = "a".repeat(64);
+        assert!(Name::new(&label_63).is_ok());
+        assert_eq!(Err(SimpleDnsError::LabelTooLong(64)), Name::new(&label_64));
+
+        // 3 labels of 63 octets and one of 61 octets, 255 octets in wire format
+        let name_255 = format!("{0}.{0}.{0}.{1}", label_63, "b".repeat(61));
+        let name = Name::new(&name_255).unwrap();
+        assert_eq!(255, name.len());
+
+        let mut data = Vec::new();
+        name.write_to(&mut data).unwrap();
+        assert_eq!(name, Name::parse(&data, 0).unwrap());
+
+        let name_300 = vec!["abcdefghi"; 30].join(".");
+        assert_eq!(Err(SimpleDnsError::NameTooLong(301)), Name::new(&name_300));
+
+        let mut data = Vec::new();
+        Name::new_unchecked(&name_300).write_to(&mut data).unwrap();
+        assert!(matches!(
+            Name::parse(&data, 0),
+            Err(SimpleDnsError::NameTooLong(_))
+        ));
+
+        let mut data = vec![64];
+        data.extend_from_slice(label_64.as_bytes());
+        data.push(0);
+        assert_eq!(Err(SimpleDnsError::LabelTooLong(64)), Name::parse(&data, 0));
+    }
+
+    #[test]
+    #[cfg(feature = "idna")]
+    fn convert_internationalized_names() {
+        for (unicode, ascii) in [
+            ("café.local", "xn--caf-dma.local"),
+            ("Bücher.example", "xn--bcher-kva.example"),
+            ("www.日本語.jp", "www.xn--wgv71a119e.jp"),
+        ] {
+            let name = Name::try_from(unicode).unwrap();
+            assert_eq!(ascii, name.to_string());
+
+            let mut data = Vec::new();
+            name.write_to(&mut data).unwrap();
+            assert_eq!(name, Name::parse(&data, 0).unwrap());
+            assert_eq!(
+                unicode.to_lowercase(),
+                Name::parse(&data, 0).unwrap().to_unicode()
+            );
+        }
+
+        let name = Name::try_from("My-Host.local").unwrap();
+        assert_eq!("My-Host.local", name.to_string());
+        assert_eq!("My-Host.local", name.to_unicode());
+    }
+
+    #[test]
+    fn append_labels_and_names() {
+        let mut name = Name::new_unchecked(".");
+        name.append_label(b"My Printer").unwrap();
+        assert_eq!("My Printer", name.to_string());
+        assert_eq!(12, name.len());
+
+        name.append_name(&Name::new_unchecked("_ipp._tcp.local"))
+            .unwrap();
+        assert_eq!("My Printer._ipp._tcp.local", name.to_string());
+        assert_eq!(4, name.labels_count());
+        assert_eq!(
+            Name::new_unchecked("My Printer._ipp._tcp.local").len(),
+            name.len()
+        );
+
+        let mut data = Vec::new();
+        name.write_to(&mut data).unwrap();
+        assert_eq!(name, Name::parse(&data, 0).unwrap());
+
+        name.append_name(&Name::new_unchecked(".")).unwrap();
+        assert_eq!(4, name.labels_count());
+    }
+
+    #[test]
+    fn append_enforces_length_limits() {
+        let label_63 = "a".repeat(63);
+        let mut name = Name::new_unchecked(&label_63);
+        assert_eq!(
+            Err(SimpleDnsError::LabelTooLong(64)),
+            name.append_label(&[b'a'; 64])
+        );
+
+        name.append_name(&Name::new_unchecked(&format!("{0}.{0}", label_63)))
+            .unwrap();
+        // 3 labels of 63 octets, a label of 62 octets would make the name 256 octets long
+        assert_eq!(
+            Err(SimpleDnsError::NameTooLong(256)),
+            name.append_label(&[b'b'; 62])
+        );
+        assert_eq!(
+            Err(SimpleDnsError::NameTooLong(256)),
+            name.append_name(&Name::new_unchecked(&"b".repeat(62)))
+        );
+        assert_eq!(3, name.labels_count());
+
+        name.append_label(&[b'b'; 61]).unwrap();
+        assert_eq!(255, name.len());
+    }
+
+    #[test]
+    fn root_name_has_no_labels() {
+        let root = Name::new(".").unwrap();
+        assert_eq!(0, root.labels.len());
+        assert_eq!(1, root.len());
+
+        let mut bytes = Vec::new();
+        root.write_to(&mut bytes).unwrap();
+        assert_eq!(b"\x00", &bytes[..]);
+    }
+
     #[test]
     fn is_link_local() {
         assert!(!Name::new("some.example.com").unwrap().is_link_local());
@@ -424,9 +816,116 @@ mod tests {
         assert!(Name::parse(data, offset).is_err());
     }
 
+    #[test]
+    fn parse_rejects_pointer_loops() {
+        // self-referential pointer
+        let data = b"\x00\x00\xc0\x02";
+        assert_eq!(Err(SimpleDnsError::InvalidName), Name::parse(data, 2));
+
+        // two pointers referencing each other
+        let data = b"\xc0\x02\xc0\x00";
+        assert_eq!(Err(SimpleDnsError::InvalidName), Name::parse(data, 2));
+
+        // a pointer back to the start of its own name
+        let data = b"\x01a\xc0\x00";
+        assert_eq!(Err(SimpleDnsError::InvalidName), Name::parse(data, 0));
+        assert_eq!(Err(SimpleDnsError::InvalidName), Name::parse(data, 2));
+
+        // forward pointer
+        let data = b"\xc0\x02\x01a\x00";
+        assert_eq!(Err(SimpleDnsError::InvalidName), Name::parse(data, 0));
+    }
+
+    #[test]
+    fn canonical_form_lowercases_ascii() {
+        let name = Name::new_unchecked("WWW.Example.COM");
+        let canonical = name.to_canonical();
+        assert_eq!("www.example.com", canonical.to_string());
+        assert_eq!(name.len(), canonical.len());
+        assert_eq!(
+            vec![&b"WWW"[..], b"Example", b"COM"],
+            name.iter().collect::<Vec<_>>()
+        );
+
+        let name = Name::new_unchecked("CAFÉ.Local");
+        assert_eq!(
+            vec![&b"caf\xc3\x89"[..], b"local"],
+            name.to_canonical().iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn parent_strips_leftmost_label() {
+        let name = Name::new_unchecked("inst._http._tcp.local");
+        let parent = name.parent().unwrap();
+        assert_eq!("_http._tcp.local", parent.to_string());
+        assert_eq!(Name::new_unchecked("_http._tcp.local").len(), parent.len());
+
+        let mut data = Vec::new();
+        parent.write_to(&mut data).unwrap();
+        assert_eq!(data.len(), parent.len());
+        assert_eq!(parent, Name::parse(&data, 0).unwrap());
+
+        let name = Name::new_unchecked("local");
+        let root = name.parent().unwrap();
+        assert_eq!(0, root.labels_count());
+        assert_eq!(1, root.len());
+        assert!(root.parent().is_none());
+
+        // parsed compressed names have the length of the pointer, the parent has its full length
+        let data = b"\x01a\x05local\x00\x01b\xc0\x00";
+        let name = Name::parse(data, 9).unwrap();
+        assert_eq!(4, name.len());
+        assert_eq!(9, name.parent().unwrap().len());
+    }
+
+    #[test]
+    fn reverse_lookup_names() {
+        let name = Name::from_ipv4_arpa(Ipv4Addr::new(192, 0, 2, 1));
+        assert_eq!("1.2.0.192.in-addr.arpa", name.to_string());
+        assert_eq!(
+            Name::new_unchecked("1.2.0.192.in-addr.arpa").len(),
+            name.len()
+        );
+
+        let name = Name::from_ipv6_arpa("2001:db8::1".parse().unwrap());
+        assert_eq!(
+            "1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa",
+            name.to_string()
+        );
+        assert_eq!(34, name.labels_count());
+        assert_eq!(74, name.len());
+
+        let mut data = Vec::new();
+        name.write_to(&mut data).unwrap();
+        assert_eq!(name, Name::parse(&data, 0).unwrap());
+    }
+
+    #[test]
+    fn parse_preserves_case() {
+        let data = b"\x03wWw\x07ExAmPlE\x03CoM\x00";
+
+        let name = Name::parse(data, 0).unwrap();
+        assert_eq!("wWw.ExAmPlE.CoM", name.to_string());
+        assert!(matches!(name.labels[0].data, Cow::Borrowed(_)));
+
+        let name = Name::parse_normalized(data, 0).unwrap();
+        assert_eq!("www.example.com", name.to_string());
+        assert_eq!(data.len(), name.len());
+    }
+
+    #[test]
+    fn parse_normalized_with_compression() {
+        let data = b"\x00\x00\x00\x01F\x03ISI\x04ARPA\x00\x03foo\xc0\x03";
+
+        let name = Name::parse_normalized(data, 15).unwrap();
+        assert_eq!("foo.f.isi.arpa", name.to_string());
+        assert!(matches!(name.labels[0].data, Cow::Borrowed(_)));
+    }
+
     #[test]
     fn test_write() {
-        let mut bytes = Cursor::new(Vec::with_capacity(30));
+        let mut bytes = Buffer::new(Vec::with_capacity(30));
 
         Name::new_unchecked("_srv._udp.local")
             .write_to(&mut bytes)
@@ -434,7 +933,7 @@ mod tests {
 
         assert_eq!(b"\x04_srv\x04_udp\x05local\x00", &bytes.get_ref()[..]);
 
-        let mut bytes = Cursor::new(Vec::with_capacity(30));
+        let mut bytes = Buffer::new(Vec::with_capacity(30));
         Name::new_unchecked("_srv._udp.local2.")
             .write_to(&mut bytes)
             .unwrap();
@@ -444,10 +943,10 @@ mod tests {
 
     #[test]
     fn append_to_vec_with_compression() {
-        let mut buf = Cursor::new(vec![0, 0, 0]);
-        buf.set_position(3);
+        let mut buf = Buffer::new(vec![0, 0, 0]);
+        buf.seek_to(3).unwrap();
 
-        let mut name_refs = HashMap::new();
+        let mut name_refs = NameRefs::new();
 
         Name::new_unchecked("F.ISI.ARPA")
             .write_compressed_to(&mut buf, &mut name_refs)
@@ -465,8 +964,8 @@ mod tests {
 
     #[test]
     fn append_to_vec_with_compression_mult_names() {
-        let mut buf = Cursor::new(vec![]);
-        let mut name_refs = HashMap::new();
+        let mut buf = Buffer::new(vec![]);
+        let mut name_refs = NameRefs::new();
 
         Name::new_unchecked("ISI.ARPA")
             .write_compressed_to(&mut buf, &mut name_refs)
@@ -494,6 +993,45 @@ mod tests {
         assert_eq!("BAR.F.ISI.ARPA", fourth.to_string());
     }
 
+    #[test]
+    fn compression_only_points_to_matching_suffixes() {
+        let mut buf = Buffer::new(vec![]);
+        let mut name_refs = NameRefs::new();
+
+        Name::new_unchecked("a.local")
+            .write_compressed_to(&mut buf, &mut name_refs)
+            .expect("failed to add a.local");
+        Name::new_unchecked("a.b.local")
+            .write_compressed_to(&mut buf, &mut name_refs)
+            .expect("failed to add a.b.local");
+
+        let expected = b"\x01a\x05local\x00\x01a\x01b\xc0\x02";
+        assert_eq!(expected[..], buf.get_ref()[..]);
+
+        let first = Name::parse(buf.get_ref(), 0).unwrap();
+        let second = Name::parse(buf.get_ref(), first.len()).unwrap();
+        assert_eq!("a.b.local", second.to_string());
+    }
+
+    #[test]
+    fn iter_labels() {
+        let name = Name::new_unchecked("_http._tcp.local");
+        assert_eq!(3, name.labels_count());
+        assert_eq!(
+            vec![&b"_http"[..], &b"_tcp"[..], &b"local"[..]],
+            name.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(&b"local"[..]), name.iter().next_back());
+
+        let root = Name::new_unchecked(".");
+        assert_eq!(0, root.labels_count());
+        assert_eq!(0, root.iter().count());
+
+        let single = Name::new_unchecked("localhost");
+        assert_eq!(1, single.labels_count());
+        assert_eq!(vec![&b"localhost"[..]], single.iter().collect::<Vec<_>>());
+    }
+
     #[test]
     fn eq_other_name() -> Result<(), SimpleDnsError> {
         assert_eq!(Name::new("example.com")?, Name::new("example.com")?);
@@ -509,7 +1047,7 @@ mod tests {
 
     #[test]
     fn len() -> crate::Result<()> {
-        let mut bytes = Cursor::new(Vec::new());
+        let mut bytes = Buffer::new(Vec::new());
         let name_one = Name::new_unchecked("ex.com.");
         name_one.write_to(&mut bytes)?;
 
@@ -517,8 +1055,8 @@ mod tests {
         assert_eq!(bytes.get_ref().len(), name_one.len());
         assert_eq!(8, Name::parse(bytes.get_ref(), 0)?.len());
 
-        let mut name_refs = HashMap::new();
-        let mut bytes = Cursor::new(Vec::new());
+        let mut name_refs = NameRefs::new();
+        let mut bytes = Buffer::new(Vec::new());
         name_one.write_compressed_to(&mut bytes, &mut name_refs)?;
         name_one.write_compressed_to(&mut bytes, &mut name_refs)?;
 
@@ -543,6 +1081,25 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn eq_and_hash_ignore_case() {
+        let lower = Name::new_unchecked("example.com");
+        let mixed = Name::new_unchecked("Example.COM");
+
+        assert_eq!(lower, mixed);
+        assert_eq!(get_hash(&lower), get_hash(&mixed));
+        assert_ne!(lower, Name::new_unchecked("example.co"));
+        assert_ne!(
+            get_hash(&Name::new_unchecked("ab.c")),
+            get_hash(&Name::new_unchecked("a.bc"))
+        );
+
+        let mut names = std::collections::HashMap::new();
+        names.insert(lower, 1);
+        assert_eq!(Some(&1), names.get(&mixed));
+        assert_eq!(None, names.get(&Name::new_unchecked("example.org")));
+    }
+
     fn get_hash(name: &Name) -> u64 {
         let mut hasher = DefaultHasher::default();
         name.hash(&mut hasher);
@@ -564,5 +1121,18 @@ mod tests {
             .is_subdomain_of(&Name::new_unchecked("other.domain")));
         assert!(!Name::new_unchecked("domain.com")
             .is_subdomain_of(&Name::new_unchecked("domain.com.br")));
+
+        assert!(Name::new_unchecked("a.b.Example.COM")
+            .is_subdomain_of(&Name::new_unchecked("example.com")));
+        assert!(!Name::new_unchecked("a.b.example.com")
+            .is_subdomain_of(&Name::new_unchecked("example.org")));
+        assert!(!Name::new_unchecked("example.com")
+            .is_subdomain_of(&Name::new_unchecked("a.b.example.com")));
+        assert!(!Name::new_unchecked("com").is_subdomain_of(&Name::new_unchecked("example.com")));
+
+        let root = Name::new_unchecked(".");
+        assert!(Name::new_unchecked("example.com").is_subdomain_of(&root));
+        assert!(root.is_subdomain_of(&root));
+        assert!(!root.is_subdomain_of(&Name::new_unchecked("com")));
     }
 }
@@ -1,17 +1,111 @@
-use std::{
-    collections::HashMap,
-    io::{Cursor, Seek, Write},
-    usize,
-};
+use alloc::vec::Vec;
+use core::ops::Range;
 
-use crate::{header_buffer, rdata::OPT, RCODE};
+use crate::dns::write::{Buffer, Counter, NameRefs, Seek, Write};
 
-use super::{Header, PacketFlag, PacketPart, Question, ResourceRecord, OPCODE};
+use crate::{header_buffer, rdata::OPT, RCODE, TYPE};
 
-/// Represents a DNS message packet
+use super::{Header, Name, PacketFlag, PacketPart, Question, ResourceRecord, OPCODE};
+
+macro_rules! flag_accessors {
+    ($(#[doc = $doc:expr] $get:ident, $set:ident = $flag:ident;)+) => {
+        $(
+            #[doc = concat!("Returns the ", $doc)]
+            pub fn $get(&self) -> bool {
+                self.header.has_flags(PacketFlag::$flag)
+            }
+
+            #[doc = concat!("Sets or clears the ", $doc)]
+            pub fn $set(&mut self, value: bool) {
+                if value {
+                    self.header.set_flags(PacketFlag::$flag);
+                } else {
+                    self.header.remove_flags(PacketFlag::$flag);
+                }
+            }
+        )+
+    };
+}
+
+/// Byte ranges of each section of a packet in wire format, returned by [Packet::section_ranges]
 ///
-/// When working with EDNS packets, use [Packet::opt] and [Packet::opt_mut] to add or access [OPT] packet information
+/// Names in a section may contain compression pointers to other sections of the same packet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionRanges {
+    /// Header range, always the first 12 bytes
+    pub header: Range<usize>,
+    /// Questions section range
+    pub questions: Range<usize>,
+    /// Answers section range
+    pub answers: Range<usize>,
+    /// Name servers section range
+    pub name_servers: Range<usize>,
+    /// Additional records section range, including the OPT record when present
+    pub additional_records: Range<usize>,
+}
+
+/// Section of a packet holding resource records, yielded by [Packet::iter]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Section {
+    /// Answers section
+    Answers,
+    /// Name servers section, also known as the authority section
+    NameServers,
+    /// Additional records section, the OPT record is not part of it
+    AdditionalRecords,
+}
+
+/// Iterator over the resource records of every section of a packet, returned by [Packet::iter]
 #[derive(Debug, Clone)]
+pub struct Records<'b, 'a> {
+    sections: [(Section, core::slice::Iter<'b, ResourceRecord<'a>>); 3],
+    current: usize,
+}
+
+impl<'b, 'a> Iterator for Records<'b, 'a> {
+    type Item = (Section, &'b ResourceRecord<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((section, records)) = self.sections.get_mut(self.current) {
+            match records.next() {
+                Some(record) => return Some((*section, record)),
+                None => self.current += 1,
+            }
+        }
+
+        None
+    }
+}
+
+/// Owning iterator over the resource records of every section of a packet, returned by [Packet::into_iter]
+#[derive(Debug, Clone)]
+pub struct IntoRecords<'a> {
+    sections: [(Section, alloc::vec::IntoIter<ResourceRecord<'a>>); 3],
+    current: usize,
+}
+
+impl<'a> Iterator for IntoRecords<'a> {
+    type Item = (Section, ResourceRecord<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((section, records)) = self.sections.get_mut(self.current) {
+            match records.next() {
+                Some(record) => return Some((*section, record)),
+                None => self.current += 1,
+            }
+        }
+
+        None
+    }
+}
+
+/// Represents a DNS message packet
+///
+/// When working with EDNS packets, use [Packet::opt] and [Packet::opt_mut] to add or access [OPT] packet information.
+///
+/// Packets are equal when their headers and sections are equal, records are compared as described in [ResourceRecord]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Packet<'a> {
     /// Packet header
     header: Header<'a>,
@@ -21,7 +115,7 @@ pub struct Packet<'a> {
     pub answers: Vec<ResourceRecord<'a>>,
     /// Name servers section
     pub name_servers: Vec<ResourceRecord<'a>>,
-    /// Aditional records section.  
+    /// Additional records section.  
     /// DO NOT use this field to add OPT record, use [`opt_mut`] instead
     pub additional_records: Vec<ResourceRecord<'a>>,
 }
@@ -38,6 +132,28 @@ impl<'a> Packet<'a> {
         }
     }
 
+    /// Creates a new empty packet with a query header and a random id.  
+    /// The id is generated by a cryptographically secure generator, which makes it harder for
+    /// off-path attackers to spoof responses
+    #[cfg(feature = "rand")]
+    pub fn new_query_with_random_id() -> Self {
+        Self::new_query(rand::random())
+    }
+
+    /// Creates a new empty packet with a NOTIFY header, [RFC 1996](https://datatracker.ietf.org/doc/html/rfc1996)
+    pub fn new_notify(id: u16) -> Self {
+        let mut packet = Self::new_query(id);
+        packet.header.opcode = OPCODE::Notify;
+        packet
+    }
+
+    /// Creates a new empty packet with an UPDATE header, [RFC 2136](https://datatracker.ietf.org/doc/html/rfc2136)
+    pub fn new_update(id: u16) -> Self {
+        let mut packet = Self::new_query(id);
+        packet.header.opcode = OPCODE::Update;
+        packet
+    }
+
     /// Creates a new empty packet with a reply header
     pub fn new_reply(id: u16) -> Self {
         Self {
@@ -49,6 +165,29 @@ impl<'a> Packet<'a> {
         }
     }
 
+    /// Adds `questions` to the question section, consuming and returning Self.  
+    /// A single query can ask multiple questions, like the A and AAAA records of the same name
+    pub fn with_questions<I: IntoIterator<Item = Question<'a>>>(mut self, questions: I) -> Self {
+        self.questions.extend(questions);
+        self
+    }
+
+    /// Reserves capacity for at least the given number of records in each section, consuming and returning Self.  
+    /// Use it to allocate the sections once when the size of a reply is known, like a reply with many answers
+    pub fn with_capacity(
+        mut self,
+        questions: usize,
+        answers: usize,
+        name_servers: usize,
+        additional_records: usize,
+    ) -> Self {
+        self.questions.reserve(questions);
+        self.answers.reserve(answers);
+        self.name_servers.reserve(name_servers);
+        self.additional_records.reserve(additional_records);
+        self
+    }
+
     /// Get packet id
     pub fn id(&self) -> u16 {
         self.header.id
@@ -69,6 +208,21 @@ impl<'a> Packet<'a> {
         self.header.has_flags(flags)
     }
 
+    flag_accessors! {
+        /// Authoritative Answer (AA) bit, valid in responses
+        authoritative_answer, set_authoritative_answer = AUTHORITATIVE_ANSWER;
+        /// TrunCation (TC) bit, the message was truncated due to the transmission channel size
+        truncated, set_truncated = TRUNCATION;
+        /// Recursion Desired (RD) bit, set in a query and copied into the response
+        recursion_desired, set_recursion_desired = RECURSION_DESIRED;
+        /// Recursion Available (RA) bit, set in a response when recursion is supported
+        recursion_available, set_recursion_available = RECURSION_AVAILABLE;
+        /// Authentic Data (AD) bit, [RFC 4035](https://datatracker.ietf.org/doc/html/rfc4035#section-3.2.3)
+        authentic_data, set_authentic_data = AUTHENTIC_DATA;
+        /// Checking Disabled (CD) bit, [RFC 4035](https://datatracker.ietf.org/doc/html/rfc4035#section-3.2.2)
+        checking_disabled, set_checking_disabled = CHECKING_DISABLED;
+    }
+
     /// Get this packet [RCODE] information
     pub fn rcode(&self) -> RCODE {
         self.header.response_code
@@ -101,6 +255,21 @@ impl<'a> Packet<'a> {
         &mut self.header.opt
     }
 
+    /// Transforms the inner data into its owned type, the returned packet does not borrow the buffer it was parsed from
+    pub fn into_owned<'b>(self) -> Packet<'b> {
+        fn section_into_owned<'b>(section: Vec<ResourceRecord>) -> Vec<ResourceRecord<'b>> {
+            section.into_iter().map(|rr| rr.into_owned()).collect()
+        }
+
+        Packet {
+            header: self.header.into_owned(),
+            questions: self.questions.into_iter().map(|q| q.into_owned()).collect(),
+            answers: section_into_owned(self.answers),
+            name_servers: section_into_owned(self.name_servers),
+            additional_records: section_into_owned(self.additional_records),
+        }
+    }
+
     /// Changes this packet into a reply packet by replacing its header
     pub fn into_reply(mut self) -> Self {
         self.header = Header::new_reply(self.header.id, self.header.opcode);
@@ -135,6 +304,56 @@ impl<'a> Packet<'a> {
         })
     }
 
+    /// Reads a single length prefixed message from a stream, as written by [`Packet::write_tcp`], and parses it.  
+    /// The message is buffered and the returned packet owns its data, so it does not borrow a temporary buffer.
+    /// Returns [`InsufficientData`](crate::SimpleDnsError::InsufficientData) when the reader ends before the whole message is read
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: std::io::Read>(input: &mut R) -> crate::Result<Packet<'static>> {
+        let mut len = [0u8; 2];
+        read_exact(input, &mut len)?;
+
+        let mut data = vec![0u8; u16::from_be_bytes(len) as usize];
+        read_exact(input, &mut data)?;
+
+        Packet::parse(&data).map(|packet| packet.into_owned())
+    }
+
+    /// Find the byte ranges of each section in `data` without decoding names or rdata.  
+    /// This is useful to forward or manipulate whole sections of a received packet
+    pub fn section_ranges(data: &[u8]) -> crate::Result<SectionRanges> {
+        if data.len() < 12 {
+            return Err(crate::SimpleDnsError::InsufficientData);
+        }
+
+        let mut offset = 12;
+        let mut section = |count: u16, fixed_len: usize, has_rdata: bool| {
+            let start = offset;
+            for _ in 0..count {
+                offset += Name::wire_len(data, offset)? + fixed_len;
+                if has_rdata {
+                    let rdlength = data
+                        .get(offset - 2..offset)
+                        .ok_or(crate::SimpleDnsError::InsufficientData)?;
+                    offset += u16::from_be_bytes([rdlength[0], rdlength[1]]) as usize;
+                }
+
+                if offset > data.len() {
+                    return Err(crate::SimpleDnsError::InsufficientData);
+                }
+            }
+
+            Ok(start..offset)
+        };
+
+        Ok(SectionRanges {
+            header: 0..12,
+            questions: section(header_buffer::questions(data)?, 4, false)?,
+            answers: section(header_buffer::answers(data)?, 10, true)?,
+            name_servers: section(header_buffer::name_servers(data)?, 10, true)?,
+            additional_records: section(header_buffer::additional_records(data)?, 10, true)?,
+        })
+    }
+
     fn parse_section<T: PacketPart<'a>>(
         data: &'a [u8],
         offset: &mut usize,
@@ -153,28 +372,171 @@ impl<'a> Packet<'a> {
 
     /// Creates a new [Vec`<u8>`](`Vec<T>`) and write the contents of this package in wire format
     ///
-    /// This call will allocate a `Vec<u8>` of 900 bytes, which is enough for a jumbo UDP packet
+    /// This call will allocate a `Vec<u8>` large enough to hold the whole packet, avoiding reallocations
     pub fn build_bytes_vec(&self) -> crate::Result<Vec<u8>> {
-        let mut out = Cursor::new(Vec::with_capacity(900));
+        let mut out = Vec::with_capacity(self.uncompressed_len());
+        self.write_parts(&mut out)?;
 
-        self.write_to(&mut out)?;
-
-        Ok(out.into_inner())
+        Ok(out)
     }
 
     /// Creates a new [Vec`<u8>`](`Vec<T>`) and write the contents of this package in wire format
     /// with compression enabled
     ///
-    /// This call will allocate a `Vec<u8>` of 900 bytes, which is enough for a jumbo UDP packet
+    /// This call will allocate a `Vec<u8>` large enough to hold the uncompressed packet, avoiding reallocations
     pub fn build_bytes_vec_compressed(&self) -> crate::Result<Vec<u8>> {
-        let mut out = Cursor::new(Vec::with_capacity(900));
-        self.write_compressed_to(&mut out)?;
+        let mut out = Buffer::new(Vec::with_capacity(self.uncompressed_len()));
+        self.write_compressed_parts(&mut out)?;
 
         Ok(out.into_inner())
     }
 
+    /// Write the contents of this package in wire format with compression enabled into `buf`,
+    /// replacing its previous contents
+    ///
+    /// The capacity of `buf` is kept, a buffer created with [`Vec::with_capacity`] and reused across packets
+    /// is not reallocated while the packets fit within its capacity
+    pub fn build_bytes_into(&self, buf: &mut Vec<u8>) -> crate::Result<()> {
+        buf.clear();
+        buf.reserve(self.uncompressed_len());
+
+        let mut out = Buffer::new(core::mem::take(buf));
+        let written = self.write_compressed_parts(&mut out);
+        *buf = out.into_inner();
+        written
+    }
+
+    /// Creates a new [Vec`<u8>`](`Vec<T>`) and write the contents of this package in wire format
+    /// with compression enabled, without exceeding `max_size` bytes
+    ///
+    /// Records that do not fit are left out, a record is never partially written and the section counts
+    /// in the header match the records written. When a question, answer or name server record is left out,
+    /// the TC flag is set, additional records are dropped without setting it, as described in
+    /// [RFC 2181](https://datatracker.ietf.org/doc/html/rfc2181#section-9).
+    /// The [OPT] record, when present, is always written
+    pub fn build_bytes_vec_with_limit(&self, max_size: usize) -> crate::Result<Vec<u8>> {
+        let opt_rr = self.header.opt_rr();
+        let limit = max_size.saturating_sub(opt_rr.as_ref().map(|rr| rr.len()).unwrap_or_default());
+
+        let mut out = Buffer::new(Vec::with_capacity(self.uncompressed_len().min(max_size)));
+        out.write_all(&[0u8; 12])?;
+
+        let mut name_refs = NameRefs::new();
+        let mut complete = true;
+        let questions = Self::write_section_with_limit(
+            &self.questions,
+            &mut out,
+            &mut name_refs,
+            limit,
+            &mut complete,
+        )?;
+        let answers = Self::write_section_with_limit(
+            &self.answers,
+            &mut out,
+            &mut name_refs,
+            limit,
+            &mut complete,
+        )?;
+        let name_servers = Self::write_section_with_limit(
+            &self.name_servers,
+            &mut out,
+            &mut name_refs,
+            limit,
+            &mut complete,
+        )?;
+        let truncated = !complete;
+
+        if let Some(rr) = opt_rr {
+            rr.write_to(&mut out)?;
+        }
+        let additional_records = Self::write_section_with_limit(
+            &self.additional_records,
+            &mut out,
+            &mut name_refs,
+            max_size,
+            &mut complete,
+        )?;
+
+        let mut header = self.header.clone();
+        if truncated {
+            header.set_flags(PacketFlag::TRUNCATION);
+        }
+        let mut data = out.into_inner();
+        let mut header_bytes = Vec::with_capacity(12);
+        header.write_to(
+            &mut header_bytes,
+            questions,
+            answers,
+            name_servers,
+            additional_records + u16::from(self.header.opt.is_some()),
+        )?;
+        data[..12].copy_from_slice(&header_bytes);
+
+        Ok(data)
+    }
+
+    /// Writes as many items as possible without exceeding `limit` bytes and returns the number of written items.
+    /// `complete` is set to false when an item is left out, nothing is written if it is already false
+    fn write_section_with_limit<'b, T: PacketPart<'b>>(
+        items: &[T],
+        out: &mut Buffer,
+        name_refs: &mut NameRefs,
+        limit: usize,
+        complete: &mut bool,
+    ) -> crate::Result<u16> {
+        if !*complete {
+            return Ok(0);
+        }
+
+        for (written, item) in items.iter().enumerate() {
+            let start = out.len();
+            item.write_compressed_to(out, name_refs)?;
+
+            if out.len() > limit {
+                out.truncate(start);
+                name_refs.retain(|_, position| *position < start);
+                *complete = false;
+
+                return Ok(written as u16);
+            }
+        }
+
+        Ok(items.len() as u16)
+    }
+
+    /// Write the contents of this package in wire format with compression enabled, prefixed by its length
+    /// as a two bytes big endian integer, which is the message format of DNS over TCP, [RFC 1035 section 4.2.2](https://datatracker.ietf.org/doc/html/rfc1035#section-4.2.2).  
+    /// Packets longer than 65535 bytes can't be framed and return [`FailedToWrite`](crate::SimpleDnsError::FailedToWrite)
+    #[cfg(feature = "std")]
+    pub fn write_tcp<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+        let bytes = self.build_bytes_vec_compressed()?;
+        let len = u16::try_from(bytes.len()).map_err(|_| crate::SimpleDnsError::FailedToWrite)?;
+
+        out.write_all(&len.to_be_bytes())?;
+        out.write_all(&bytes)?;
+        Ok(())
+    }
+
     /// Write the contents of this package in wire format into the provided writer
-    pub fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
+    #[cfg(feature = "std")]
+    pub fn write_to<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+        self.write_parts(out)?;
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Write the contents of this package in wire format with enabled compression into the provided writer
+    #[cfg(feature = "std")]
+    pub fn write_compressed_to<T: std::io::Write + std::io::Seek>(
+        &self,
+        out: &mut T,
+    ) -> crate::Result<()> {
+        self.write_compressed_parts(out)?;
+        out.flush()?;
+        Ok(())
+    }
+
+    fn write_parts<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         self.write_header(out)?;
 
         for e in &self.questions {
@@ -195,15 +557,13 @@ impl<'a> Packet<'a> {
             e.write_to(out)?;
         }
 
-        out.flush()?;
         Ok(())
     }
 
-    /// Write the contents of this package in wire format with enabled compression into the provided writer
-    pub fn write_compressed_to<T: Write + Seek>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_compressed_parts<T: Write + Seek>(&self, out: &mut T) -> crate::Result<()> {
         self.write_header(out)?;
 
-        let mut name_refs = HashMap::new();
+        let mut name_refs = NameRefs::new();
         for e in &self.questions {
             e.write_compressed_to(out, &mut name_refs)?;
         }
@@ -221,11 +581,61 @@ impl<'a> Packet<'a> {
         for e in &self.additional_records {
             e.write_compressed_to(out, &mut name_refs)?;
         }
-        out.flush()?;
 
         Ok(())
     }
 
+    /// Returns an iterator over the resource records of the answers, name servers and additional records sections,
+    /// in this order, with the section of each record
+    pub fn iter(&self) -> Records<'_, 'a> {
+        Records {
+            sections: [
+                (Section::Answers, self.answers.iter()),
+                (Section::NameServers, self.name_servers.iter()),
+                (Section::AdditionalRecords, self.additional_records.iter()),
+            ],
+            current: 0,
+        }
+    }
+
+    /// Returns an iterator over the answers of type `rtype`.  
+    /// Unlike [`ResourceRecord::match_qtype`], `A` and `AAAA` do not match each other
+    pub fn answers_of_type(&self, rtype: TYPE) -> impl Iterator<Item = &ResourceRecord<'a>> {
+        self.answers
+            .iter()
+            .filter(move |answer| u16::from(answer.rdata.type_code()) == u16::from(rtype))
+    }
+
+    /// Returns the first answer of type `rtype`
+    pub fn first_answer_of_type(&self, rtype: TYPE) -> Option<&ResourceRecord<'a>> {
+        self.answers_of_type(rtype).next()
+    }
+
+    /// Returns the length in bytes of this packet in wire format, without compression.
+    /// This is the upper bound of the compressed length
+    pub fn uncompressed_len(&self) -> usize {
+        fn section_len<'a, T: PacketPart<'a>>(section: &[T]) -> usize {
+            section.iter().map(|item| item.len()).sum()
+        }
+
+        let opt_len = self.header.opt_rr().map(|rr| rr.len()).unwrap_or_default();
+
+        12 + section_len(&self.questions)
+            + section_len(&self.answers)
+            + section_len(&self.name_servers)
+            + section_len(&self.additional_records)
+            + opt_len
+    }
+
+    /// Returns the length in bytes of this packet in wire format with name compression, without writing it.
+    /// This is the length of [build_bytes_vec_compressed](`Packet::build_bytes_vec_compressed`), which can be
+    /// compared to the size of a datagram to decide if the packet must be truncated
+    pub fn estimated_len(&self) -> crate::Result<usize> {
+        let mut counter = Counter::default();
+        self.write_compressed_parts(&mut counter)?;
+        Ok(counter.len())
+    }
+
     fn write_header<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         self.header.write_to(
             out,
@@ -237,9 +647,47 @@ impl<'a> Packet<'a> {
     }
 }
 
+/// Consumes the packet and yields the resource records of the answers, name servers and additional records sections,
+/// in this order, with the section of each record. The questions and the OPT record are dropped
+impl<'a> IntoIterator for Packet<'a> {
+    type Item = (Section, ResourceRecord<'a>);
+    type IntoIter = IntoRecords<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoRecords {
+            sections: [
+                (Section::Answers, self.answers.into_iter()),
+                (Section::NameServers, self.name_servers.into_iter()),
+                (
+                    Section::AdditionalRecords,
+                    self.additional_records.into_iter(),
+                ),
+            ],
+            current: 0,
+        }
+    }
+}
+
+impl<'b, 'a> IntoIterator for &'b Packet<'a> {
+    type Item = (Section, &'b ResourceRecord<'a>);
+    type IntoIter = Records<'b, 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(feature = "std")]
+fn read_exact<R: std::io::Read>(input: &mut R, buf: &mut [u8]) -> crate::Result<()> {
+    input.read_exact(buf).map_err(|err| match err.kind() {
+        std::io::ErrorKind::UnexpectedEof => crate::SimpleDnsError::InsufficientData,
+        _ => crate::SimpleDnsError::from(err),
+    })
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{dns::CLASS, dns::TYPE, SimpleDnsError};
+    use crate::{dns::CLASS, dns::QTYPE, dns::TYPE, SimpleDnsError};
 
     use super::*;
     use std::convert::TryInto;
@@ -278,4 +726,547 @@ mod tests {
         assert_eq!("_srv._udp.local", parsed.questions[0].qname.to_string());
         assert_eq!("_srv2._udp.local", parsed.questions[1].qname.to_string());
     }
+
+    #[test]
+    fn iter_records_of_every_section() {
+        let record = |address| {
+            ResourceRecord::new(
+                crate::Name::new_unchecked("_srv._udp.local"),
+                CLASS::IN,
+                10,
+                crate::rdata::RData::A(crate::rdata::A { address }),
+            )
+        };
+
+        let mut reply = Packet::new_reply(1);
+        reply.answers.push(record(1));
+        reply.additional_records.push(record(2));
+
+        assert_eq!(
+            vec![
+                (Section::Answers, &record(1)),
+                (Section::AdditionalRecords, &record(2))
+            ],
+            reply.iter().collect::<Vec<_>>()
+        );
+
+        let mut sections = Vec::new();
+        for (section, _) in &reply {
+            sections.push(section);
+        }
+        assert_eq!(vec![Section::Answers, Section::AdditionalRecords], sections);
+
+        reply.name_servers.push(record(3));
+        assert_eq!(
+            vec![
+                (Section::Answers, record(1)),
+                (Section::NameServers, record(3)),
+                (Section::AdditionalRecords, record(2))
+            ],
+            reply.into_iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn filter_answers_by_type() {
+        use crate::rdata::{RData, A, AAAA, SRV};
+
+        let record = |rdata| {
+            ResourceRecord::new(
+                crate::Name::new_unchecked("_srv._udp.local"),
+                CLASS::IN,
+                10,
+                rdata,
+            )
+        };
+        let srv = record(RData::SRV(SRV {
+            priority: 0,
+            weight: 0,
+            port: 8080,
+            target: crate::Name::new_unchecked("host.local"),
+        }));
+
+        let mut reply = Packet::new_reply(1);
+        reply.answers.push(record(RData::A(A { address: 1 })));
+        reply.answers.push(record(RData::AAAA(AAAA { address: 1 })));
+        reply.answers.push(srv.clone());
+        reply.answers.push(record(RData::A(A { address: 2 })));
+
+        assert_eq!(
+            vec![
+                &record(RData::A(A { address: 1 })),
+                &record(RData::A(A { address: 2 }))
+            ],
+            reply.answers_of_type(TYPE::A).collect::<Vec<_>>()
+        );
+        assert_eq!(1, reply.answers_of_type(TYPE::AAAA).count());
+        assert_eq!(Some(&srv), reply.first_answer_of_type(TYPE::SRV));
+        assert_eq!(None, reply.first_answer_of_type(TYPE::TXT));
+    }
+
+    #[test]
+    fn compare_packets() {
+        let mut reply = Packet::new_reply(1);
+        reply.questions.push(Question::new(
+            crate::Name::new_unchecked("_srv._udp.local"),
+            TYPE::A.into(),
+            CLASS::IN.into(),
+            false,
+        ));
+        reply.answers.push(ResourceRecord::new(
+            crate::Name::new_unchecked("_srv._udp.local"),
+            CLASS::IN,
+            10,
+            crate::rdata::RData::A(crate::rdata::A { address: 1 }),
+        ));
+
+        assert_eq!(reply, reply.clone());
+
+        let bytes = reply.build_bytes_vec().unwrap();
+        assert_eq!(reply, Packet::parse(&bytes).unwrap());
+
+        let mut upper_case = reply.clone();
+        upper_case.answers[0].name = crate::Name::new_unchecked("_SRV._udp.local");
+        assert_eq!(reply, upper_case);
+
+        let mut changed = reply.clone();
+        changed.answers[0].rdata = crate::rdata::RData::A(crate::rdata::A { address: 2 });
+        assert_ne!(reply, changed);
+
+        assert_ne!(reply, Packet::new_reply(2));
+    }
+
+    #[test]
+    fn build_bytes_vec_allocates_once() {
+        let mut reply = Packet::new_reply(1).with_capacity(0, 50, 0, 0);
+        let answers = reply.answers.as_ptr();
+        for i in 0..50 {
+            reply.answers.push(ResourceRecord::new(
+                crate::Name::new_unchecked("_srv._udp.local"),
+                CLASS::IN,
+                10,
+                crate::rdata::RData::A(crate::rdata::A { address: i }),
+            ));
+        }
+        *reply.opt_mut() = Some(OPT {
+            opt_codes: Vec::new(),
+            udp_packet_size: 1232,
+            version: 0,
+            dnssec_ok: false,
+        });
+
+        // the answers were never reallocated
+        assert_eq!(answers, reply.answers.as_ptr());
+
+        let bytes = reply.build_bytes_vec().unwrap();
+        assert_eq!(bytes.len(), reply.uncompressed_len());
+
+        let bytes = reply.build_bytes_vec_compressed().unwrap();
+        assert!(bytes.len() < reply.uncompressed_len());
+        assert_eq!(50, Packet::parse(&bytes).unwrap().answers.len());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn tcp_framing_round_trip() {
+        let mut packet = Packet::new_reply(7);
+        for i in 0..30 {
+            let name = format!("host{}.example.com", i);
+            packet.answers.push(ResourceRecord::new(
+                Name::new(&name).unwrap().into_owned(),
+                CLASS::IN,
+                10,
+                crate::rdata::RData::A(crate::rdata::A { address: i }),
+            ));
+        }
+        let mut query = Packet::new_query(8);
+        query.questions.push(Question::new(
+            Name::new_unchecked("example.com"),
+            TYPE::A.into(),
+            CLASS::IN.into(),
+            false,
+        ));
+
+        let mut stream = std::io::Cursor::new(Vec::new());
+        packet.write_tcp(&mut stream).unwrap();
+        query.write_tcp(&mut stream).unwrap();
+
+        let data = stream.get_ref().clone();
+        let len = u16::from_be_bytes([data[0], data[1]]) as usize;
+        assert!(len > 255);
+        assert_eq!(packet.build_bytes_vec_compressed().unwrap().len(), len);
+
+        stream.set_position(0);
+        let read = Packet::from_reader(&mut stream).unwrap();
+        assert_eq!(7, read.id());
+        assert_eq!(packet.answers, read.answers);
+
+        let read = Packet::from_reader(&mut stream).unwrap();
+        assert_eq!(8, read.id());
+        assert_eq!(query.questions, read.questions);
+
+        assert!(matches!(
+            Packet::from_reader(&mut stream),
+            Err(SimpleDnsError::InsufficientData)
+        ));
+
+        let mut truncated = std::io::Cursor::new(&data[..len]);
+        assert!(matches!(
+            Packet::from_reader(&mut truncated),
+            Err(SimpleDnsError::InsufficientData)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn from_reader_parses_back_to_back_messages() {
+        let mut stream = Vec::new();
+        for id in [1, 2] {
+            let mut packet = Packet::new_query(id);
+            packet.questions.push(Question::new(
+                Name::new_unchecked("_srv._tcp.local"),
+                TYPE::PTR.into(),
+                CLASS::IN.into(),
+                false,
+            ));
+            packet.write_tcp(&mut stream).unwrap();
+        }
+
+        let mut reader = &stream[..];
+        for id in [1, 2] {
+            let packet = Packet::from_reader(&mut reader).unwrap();
+            assert_eq!(id, packet.id());
+            assert_eq!("_srv._tcp.local", packet.questions[0].qname.to_string());
+        }
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn estimated_len_matches_written_len() {
+        let mut packet = Packet::new_query(1);
+        packet.questions.push(Question::new(
+            Name::new_unchecked("example.com"),
+            TYPE::A.into(),
+            CLASS::IN.into(),
+            false,
+        ));
+        assert_eq!(
+            packet.build_bytes_vec().unwrap().len(),
+            packet.estimated_len().unwrap()
+        );
+        assert_eq!(packet.uncompressed_len(), packet.estimated_len().unwrap());
+
+        let reply = reply_with_a_records(10);
+        let compressed = reply.build_bytes_vec_compressed().unwrap();
+        assert_eq!(compressed.len(), reply.estimated_len().unwrap());
+        assert!(reply.estimated_len().unwrap() < reply.uncompressed_len());
+    }
+
+    #[test]
+    fn build_bytes_into_keeps_capacity() {
+        let reply = reply_with_a_records(20);
+        let mut buf = Vec::with_capacity(1024);
+        let capacity = buf.capacity();
+
+        for _ in 0..3 {
+            reply.build_bytes_into(&mut buf).unwrap();
+            assert_eq!(capacity, buf.capacity());
+            assert_eq!(reply.build_bytes_vec_compressed().unwrap(), buf);
+        }
+
+        let query = Packet::new_query(2);
+        query.build_bytes_into(&mut buf).unwrap();
+        assert_eq!(capacity, buf.capacity());
+        assert_eq!(12, buf.len());
+    }
+
+    fn reply_with_a_records(count: u32) -> Packet<'static> {
+        let mut reply = Packet::new_reply(1);
+        reply.questions.push(Question::new(
+            crate::Name::new_unchecked("_srv._udp.local"),
+            TYPE::A.into(),
+            CLASS::IN.into(),
+            false,
+        ));
+        for i in 0..count {
+            reply.answers.push(ResourceRecord::new(
+                crate::Name::new_unchecked("_srv._udp.local"),
+                CLASS::IN,
+                10,
+                crate::rdata::RData::A(crate::rdata::A { address: i }),
+            ));
+        }
+        reply
+    }
+
+    #[test]
+    fn build_with_limit_truncates_on_record_boundaries() {
+        let reply = reply_with_a_records(100);
+
+        let bytes = reply.build_bytes_vec_with_limit(512).unwrap();
+        assert!(bytes.len() <= 512);
+
+        let parsed = Packet::parse(&bytes).unwrap();
+        assert!(parsed.has_flags(PacketFlag::TRUNCATION));
+        assert_eq!(1, parsed.questions.len());
+        assert!(!parsed.answers.is_empty() && parsed.answers.len() < 100);
+        assert_eq!(reply.answers[..parsed.answers.len()], parsed.answers[..]);
+
+        // one more record would not fit
+        let mut bigger = reply.clone();
+        bigger.answers.truncate(parsed.answers.len() + 1);
+        assert!(bigger.build_bytes_vec_compressed().unwrap().len() > 512);
+    }
+
+    #[test]
+    fn build_with_limit_keeps_opt_record() {
+        let mut reply = reply_with_a_records(100);
+        *reply.opt_mut() = Some(OPT {
+            opt_codes: Vec::new(),
+            udp_packet_size: 512,
+            version: 0,
+            dnssec_ok: false,
+        });
+        reply.additional_records.push(reply.answers[0].clone());
+
+        let bytes = reply.build_bytes_vec_with_limit(512).unwrap();
+        assert!(bytes.len() <= 512);
+
+        let parsed = Packet::parse(&bytes).unwrap();
+        assert!(parsed.has_flags(PacketFlag::TRUNCATION));
+        assert!(parsed.opt().is_some());
+        assert!(parsed.additional_records.is_empty());
+    }
+
+    #[test]
+    fn build_with_limit_drops_additional_records_without_truncation() {
+        let mut reply = reply_with_a_records(10);
+        let size = reply.build_bytes_vec_compressed().unwrap().len();
+        assert_eq!(
+            reply.build_bytes_vec_compressed().unwrap(),
+            reply.build_bytes_vec_with_limit(size).unwrap()
+        );
+
+        reply.additional_records.push(ResourceRecord::new(
+            crate::Name::new_unchecked("other.local"),
+            CLASS::IN,
+            10,
+            crate::rdata::RData::A(crate::rdata::A { address: 0 }),
+        ));
+
+        let parsed = reply.build_bytes_vec_with_limit(size).unwrap();
+        let parsed = Packet::parse(&parsed).unwrap();
+        assert!(!parsed.has_flags(PacketFlag::TRUNCATION));
+        assert_eq!(10, parsed.answers.len());
+        assert!(parsed.additional_records.is_empty());
+    }
+
+    #[test]
+    fn section_ranges_reconstruct_packet() {
+        let mut packet = Packet::new_reply(1);
+        packet.questions.push(Question::new(
+            "_srv._udp.local".try_into().unwrap(),
+            TYPE::A.into(),
+            CLASS::IN.into(),
+            false,
+        ));
+        for i in 0..3 {
+            packet.answers.push(ResourceRecord::new(
+                crate::Name::new_unchecked("a._srv._udp.local"),
+                CLASS::IN,
+                10,
+                crate::rdata::RData::A(crate::rdata::A { address: i }),
+            ));
+        }
+        packet.name_servers.push(ResourceRecord::new(
+            crate::Name::new_unchecked("_udp.local"),
+            CLASS::IN,
+            10,
+            crate::rdata::RData::NS(crate::Name::new_unchecked("ns._udp.local").into()),
+        ));
+        packet.additional_records.push(ResourceRecord::new(
+            crate::Name::new_unchecked("a._srv._udp.local"),
+            CLASS::IN,
+            10,
+            crate::rdata::RData::TXT(crate::rdata::TXT::new().with_string("a=b").unwrap()),
+        ));
+        *packet.opt_mut() = Some(OPT {
+            opt_codes: Vec::new(),
+            udp_packet_size: 1232,
+            version: 0,
+            dnssec_ok: false,
+        });
+
+        for bytes in [
+            packet.build_bytes_vec().unwrap(),
+            packet.build_bytes_vec_compressed().unwrap(),
+        ] {
+            let ranges = Packet::section_ranges(&bytes).unwrap();
+
+            assert_eq!(ranges.header.end, ranges.questions.start);
+            assert_eq!(ranges.questions.end, ranges.answers.start);
+            assert_eq!(ranges.answers.end, ranges.name_servers.start);
+            assert_eq!(ranges.name_servers.end, ranges.additional_records.start);
+
+            let rebuilt: Vec<u8> = [
+                &bytes[ranges.header],
+                &bytes[ranges.questions],
+                &bytes[ranges.answers.clone()],
+                &bytes[ranges.name_servers],
+                &bytes[ranges.additional_records],
+            ]
+            .concat();
+            assert_eq!(bytes, rebuilt);
+
+            let answers = ResourceRecord::parse(&bytes, ranges.answers.start).unwrap();
+            assert_eq!("a._srv._udp.local", answers.name.to_string());
+        }
+
+        assert!(Packet::section_ranges(&packet.build_bytes_vec().unwrap()[..40]).is_err());
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn random_ids_do_not_repeat() {
+        let ids: std::collections::HashSet<u16> = (0..8)
+            .map(|_| Packet::new_query_with_random_id().id())
+            .collect();
+
+        // the chance of 8 random ids having a collision is below 0.05%
+        assert!(ids.len() >= 7);
+        assert!(!Packet::new_query_with_random_id().has_flags(PacketFlag::RESPONSE));
+    }
+
+    #[test]
+    fn query_with_multiple_questions() {
+        let name = crate::Name::new_unchecked("host.local");
+        let query = Packet::new_query(1).with_questions([
+            Question::new(name.clone(), TYPE::A.into(), CLASS::IN.into(), false),
+            Question::new(name, TYPE::AAAA.into(), CLASS::IN.into(), false),
+        ]);
+
+        let bytes = query.build_bytes_vec_compressed().unwrap();
+        let parsed = Packet::parse(&bytes).unwrap();
+
+        assert_eq!(query.questions, parsed.questions);
+        assert_eq!(QTYPE::TYPE(TYPE::A), parsed.questions[0].qtype);
+        assert_eq!(QTYPE::TYPE(TYPE::AAAA), parsed.questions[1].qtype);
+    }
+
+    #[test]
+    fn owned_packet_outlives_buffer() {
+        let mut packet = reply_with_a_records(3);
+        packet.name_servers.push(ResourceRecord::new(
+            crate::Name::new_unchecked("_udp.local"),
+            CLASS::IN,
+            10,
+            crate::rdata::RData::NS(crate::Name::new_unchecked("ns._udp.local").into()),
+        ));
+        packet.additional_records.push(ResourceRecord::new(
+            crate::Name::new_unchecked("_srv._udp.local"),
+            CLASS::IN,
+            10,
+            crate::rdata::RData::TXT(crate::rdata::TXT::new().with_string("a=b").unwrap()),
+        ));
+        *packet.opt_mut() = Some(OPT {
+            opt_codes: vec![crate::rdata::OPTCode {
+                code: 10,
+                data: std::borrow::Cow::Borrowed(&[1, 2, 3, 4]),
+            }],
+            udp_packet_size: 1232,
+            version: 0,
+            dnssec_ok: false,
+        });
+
+        let owned: Packet<'static> = {
+            let bytes = packet.build_bytes_vec_compressed().unwrap();
+            Packet::parse(&bytes).unwrap().into_owned()
+        };
+
+        assert_eq!(packet.id(), owned.id());
+        assert_eq!(packet.questions, owned.questions);
+        assert_eq!(packet.answers, owned.answers);
+        assert_eq!(packet.name_servers, owned.name_servers);
+        assert_eq!(packet.additional_records, owned.additional_records);
+        assert_eq!(packet.opt(), owned.opt());
+        assert_eq!(packet.rcode(), owned.rcode());
+        assert!(owned.has_flags(PacketFlag::RESPONSE));
+
+        let handle = std::thread::spawn(move || owned.answers.len());
+        assert_eq!(3, handle.join().unwrap());
+    }
+
+    #[test]
+    fn notify_and_update_round_trip() {
+        let notify = Packet::new_notify(10).build_bytes_vec().unwrap();
+        let notify = Packet::parse(&notify).unwrap();
+        assert_eq!(OPCODE::Notify, notify.opcode());
+        assert!(!notify.has_flags(PacketFlag::RESPONSE));
+
+        let update = Packet::new_update(11).build_bytes_vec().unwrap();
+        let update = Packet::parse(&update).unwrap();
+        assert_eq!(OPCODE::Update, update.opcode());
+        assert_eq!(11, update.id());
+
+        let reply = update.into_reply();
+        assert_eq!(OPCODE::Update, reply.opcode());
+    }
+
+    #[test]
+    fn flag_accessors_toggle_a_single_bit() {
+        let flags = |bytes: &[u8]| u16::from_be_bytes([bytes[2], bytes[3]]);
+        type Getter = fn(&Packet) -> bool;
+        type Setter = fn(&mut Packet, bool);
+
+        let accessors: [(Getter, Setter, u16); 6] = [
+            (
+                |p| p.authoritative_answer(),
+                |p, v| p.set_authoritative_answer(v),
+                0b0000_0100_0000_0000,
+            ),
+            (
+                |p| p.truncated(),
+                |p, v| p.set_truncated(v),
+                0b0000_0010_0000_0000,
+            ),
+            (
+                |p| p.recursion_desired(),
+                |p, v| p.set_recursion_desired(v),
+                0b0000_0001_0000_0000,
+            ),
+            (
+                |p| p.recursion_available(),
+                |p, v| p.set_recursion_available(v),
+                0b0000_0000_1000_0000,
+            ),
+            (
+                |p| p.authentic_data(),
+                |p, v| p.set_authentic_data(v),
+                0b0000_0000_0010_0000,
+            ),
+            (
+                |p| p.checking_disabled(),
+                |p, v| p.set_checking_disabled(v),
+                0b0000_0000_0001_0000,
+            ),
+        ];
+
+        for (get, set, bit) in accessors {
+            let mut packet = Packet::new_reply(1);
+            let before = flags(&packet.build_bytes_vec().unwrap());
+
+            assert!(!get(&packet));
+            set(&mut packet, true);
+            assert!(get(&packet));
+
+            let bytes = packet.build_bytes_vec().unwrap();
+            assert_eq!(before ^ bit, flags(&bytes));
+            assert!(get(&Packet::parse(&bytes).unwrap()));
+
+            set(&mut packet, false);
+            assert!(!get(&packet));
+            let bytes = packet.build_bytes_vec().unwrap();
+            assert_eq!(before, flags(&bytes));
+        }
+    }
 }
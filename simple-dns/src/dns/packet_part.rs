@@ -1,7 +1,4 @@
-use std::{
-    collections::HashMap,
-    io::{Seek, Write},
-};
+use crate::dns::write::{NameRefs, Seek, Write};
 
 /// Represents anything that can be part of a dns packet (Question, Resource Record, RData)
 pub(crate) trait PacketPart<'a> {
@@ -17,7 +14,7 @@ pub(crate) trait PacketPart<'a> {
     fn write_compressed_to<T: Write + Seek>(
         &self,
         out: &mut T,
-        _name_refs: &mut HashMap<u64, usize>,
+        _name_refs: &mut NameRefs,
     ) -> crate::Result<()> {
         self.write_to(out)
     }
@@ -0,0 +1,335 @@
+//! Parsing of resource records in presentation format, as used by zone files,
+//! [RFC 1035 section 5.1](https://datatracker.ietf.org/doc/html/rfc1035#section-5.1)
+
+use alloc::{format, string::String, vec::Vec};
+use core::{
+    convert::TryFrom,
+    net::{Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+use crate::{
+    rdata::{RData, A, AAAA, CNAME, MX, NS, PTR, SRV, TXT},
+    CharacterString, Name, ResourceRecord, SimpleDnsError, CLASS, TYPE,
+};
+
+use super::PacketPart;
+
+/// A single field of a line, quoted strings are a single field
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Token {
+    /// Unquoted text, escape sequences are kept as they are
+    Text(String),
+    /// Contents of a quoted string, with escape sequences replaced
+    Quoted(Vec<u8>),
+}
+
+/// Values used to complete records with omitted fields
+#[derive(Debug, Default)]
+pub(crate) struct RecordDefaults {
+    /// Appended to relative names and used for `@`
+    pub origin: Option<Name<'static>>,
+    /// Used when the record has no TTL
+    pub ttl: Option<u32>,
+    /// Used when the line starts with a blank, the owner of the previous record
+    pub owner: Option<Name<'static>>,
+}
+
+pub(crate) fn invalid(message: impl Into<String>) -> SimpleDnsError {
+    SimpleDnsError::InvalidPresentationFormat(message.into())
+}
+
+/// Splits `line` into its fields, text after an unquoted `;` is a comment
+pub(crate) fn tokenize(line: &str) -> crate::Result<Vec<Token>> {
+    let bytes = line.as_bytes();
+    let is_delimiter = |byte: u8| byte.is_ascii_whitespace() || byte == b';' || byte == b'"';
+
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b';' => break,
+            b'"' => {
+                let start = i + 1;
+                i = start;
+                loop {
+                    match bytes.get(i) {
+                        None => return Err(invalid("unterminated quoted string")),
+                        Some(b'\\') => i += 2,
+                        Some(b'"') => break,
+                        Some(_) => i += 1,
+                    }
+                }
+
+                tokens.push(Token::Quoted(unescape(&bytes[start..i])?));
+                i += 1;
+            }
+            byte if byte.is_ascii_whitespace() => i += 1,
+            _ => {
+                let start = i;
+                while i < bytes.len() && !is_delimiter(bytes[i]) {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+
+                let end = i.min(bytes.len());
+                tokens.push(Token::Text(
+                    String::from_utf8_lossy(&bytes[start..end]).into_owned(),
+                ));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Replaces `\X` by `X` and `\DDD` by the byte with the decimal value `DDD`
+fn unescape(text: &[u8]) -> crate::Result<Vec<u8>> {
+    let mut unescaped = Vec::with_capacity(text.len());
+
+    let mut i = 0;
+    while i < text.len() {
+        if text[i] != b'\\' {
+            unescaped.push(text[i]);
+            i += 1;
+            continue;
+        }
+
+        match text.get(i + 1..i + 4) {
+            Some(digits) if digits.iter().all(u8::is_ascii_digit) => {
+                let value = digits
+                    .iter()
+                    .fold(0u16, |value, digit| value * 10 + (digit - b'0') as u16);
+                let value = u8::try_from(value)
+                    .map_err(|_| invalid(format!("invalid escape \\{value}")))?;
+
+                unescaped.push(value);
+                i += 4;
+            }
+            _ => match text.get(i + 1) {
+                Some(byte) => {
+                    unescaped.push(*byte);
+                    i += 2;
+                }
+                None => return Err(invalid("incomplete escape sequence")),
+            },
+        }
+    }
+
+    Ok(unescaped)
+}
+
+/// Parses a single record, returns `None` for lines without records, like comments.
+/// Owner names without a trailing dot are relative to the origin of `defaults`
+pub(crate) fn parse_record(
+    line: &str,
+    defaults: &RecordDefaults,
+) -> crate::Result<Option<ResourceRecord<'static>>> {
+    let tokens = tokenize(line)?;
+    if tokens.is_empty() {
+        return Ok(None);
+    }
+
+    let mut tokens = tokens.iter().peekable();
+    let name = if line.starts_with(|c: char| c.is_ascii_whitespace()) {
+        defaults
+            .owner
+            .clone()
+            .ok_or_else(|| invalid("record without owner name"))?
+    } else {
+        parse_name(next_text(&mut tokens, "owner name")?, defaults)?
+    };
+
+    // TTL and class are optional and can be in any order
+    let mut ttl = None;
+    let mut class = None;
+    while let Some(Token::Text(text)) = tokens.peek() {
+        if ttl.is_none() && text.bytes().all(|b| b.is_ascii_digit()) {
+            ttl = Some(parse_number(text, "TTL")?);
+        } else if let (None, Ok(value)) = (class, CLASS::from_str(text)) {
+            class = Some(value);
+        } else {
+            break;
+        }
+        tokens.next();
+    }
+
+    let ttl = ttl
+        .or(defaults.ttl)
+        .ok_or_else(|| invalid("record without TTL"))?;
+    let class = class.unwrap_or(CLASS::IN);
+    let rtype = TYPE::from_str(next_text(&mut tokens, "type")?)?;
+
+    let fields: Vec<&Token> = tokens.collect();
+    let rdata = parse_rdata(rtype, class, &fields, defaults)?;
+
+    Ok(Some(ResourceRecord::new(name, class, ttl, rdata)))
+}
+
+fn parse_rdata(
+    rtype: TYPE,
+    class: CLASS,
+    fields: &[&Token],
+    defaults: &RecordDefaults,
+) -> crate::Result<RData<'static>> {
+    if let Some(Token::Text(text)) = fields.first() {
+        if text == "\\#" {
+            return parse_generic_rdata(rtype, class, &fields[1..]);
+        }
+    }
+
+    let mut fields = fields.iter().copied().peekable();
+    let rdata = match rtype {
+        TYPE::A => {
+            let address = next_text(&mut fields, "IPv4 address")?;
+            let address = Ipv4Addr::from_str(address)
+                .map_err(|_| invalid(format!("invalid IPv4 address {address}")))?;
+            RData::A(A::from(address))
+        }
+        TYPE::AAAA => {
+            let address = next_text(&mut fields, "IPv6 address")?;
+            let address = Ipv6Addr::from_str(address)
+                .map_err(|_| invalid(format!("invalid IPv6 address {address}")))?;
+            RData::AAAA(AAAA::from(address))
+        }
+        TYPE::CNAME => RData::CNAME(CNAME(parse_name(
+            next_text(&mut fields, "canonical name")?,
+            defaults,
+        )?)),
+        TYPE::NS => RData::NS(NS(parse_name(
+            next_text(&mut fields, "name server")?,
+            defaults,
+        )?)),
+        TYPE::PTR => RData::PTR(PTR(parse_name(
+            next_text(&mut fields, "domain name")?,
+            defaults,
+        )?)),
+        TYPE::MX => RData::MX(MX {
+            preference: parse_number(next_text(&mut fields, "preference")?, "preference")?,
+            exchange: parse_name(next_text(&mut fields, "exchange")?, defaults)?,
+        }),
+        TYPE::SRV => RData::SRV(SRV {
+            priority: parse_number(next_text(&mut fields, "priority")?, "priority")?,
+            weight: parse_number(next_text(&mut fields, "weight")?, "weight")?,
+            port: parse_number(next_text(&mut fields, "port")?, "port")?,
+            target: parse_name(next_text(&mut fields, "target")?, defaults)?,
+        }),
+        TYPE::TXT => {
+            let mut txt = TXT::new();
+            for field in fields.by_ref() {
+                let data = match field {
+                    Token::Text(text) => unescape(text.as_bytes())?,
+                    Token::Quoted(data) => data.clone(),
+                };
+                txt.add_char_string(CharacterString::new(&data)?.into_owned());
+            }
+            RData::TXT(txt)
+        }
+        _ => {
+            return Err(invalid(format!(
+                "{rtype} records are only supported in the generic \\# format"
+            )))
+        }
+    };
+
+    match fields.next() {
+        Some(_) => Err(invalid(format!("unexpected data after {rtype} record"))),
+        None => Ok(rdata),
+    }
+}
+
+/// Parses rdata in the generic format `\# <length> <hex data>`,
+/// [RFC 3597 section 5](https://datatracker.ietf.org/doc/html/rfc3597#section-5)
+fn parse_generic_rdata(
+    rtype: TYPE,
+    class: CLASS,
+    fields: &[&Token],
+) -> crate::Result<RData<'static>> {
+    let mut fields = fields.iter().copied();
+    let len: u16 = parse_number(next_text(&mut fields, "rdata length")?, "rdata length")?;
+
+    let hex: String = fields
+        .map(|field| match field {
+            Token::Text(text) => Ok(text.as_str()),
+            Token::Quoted(_) => Err(invalid("unexpected quoted string in generic rdata")),
+        })
+        .collect::<crate::Result<_>>()?;
+    if hex.len() != len as usize * 2 {
+        return Err(invalid(format!(
+            "generic rdata has {} bytes, expected {len}",
+            hex.len() / 2
+        )));
+    }
+
+    // the rdata is parsed from a resource record header without the name
+    let mut data = Vec::with_capacity(10 + len as usize);
+    data.extend_from_slice(&u16::from(rtype).to_be_bytes());
+    data.extend_from_slice(&(class as u16).to_be_bytes());
+    data.extend_from_slice(&0u32.to_be_bytes());
+    data.extend_from_slice(&len.to_be_bytes());
+    for i in (0..hex.len()).step_by(2) {
+        let byte = hex
+            .get(i..i + 2)
+            .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+            .ok_or_else(|| invalid(format!("invalid hex data {hex}")))?;
+        data.push(byte);
+    }
+
+    Ok(RData::parse(&data, 0)?.into_owned())
+}
+
+/// Parses a name, names without a trailing dot are relative to the origin
+pub(crate) fn parse_name(text: &str, defaults: &RecordDefaults) -> crate::Result<Name<'static>> {
+    if text == "@" {
+        return defaults
+            .origin
+            .clone()
+            .ok_or_else(|| invalid("@ used without an origin"));
+    }
+
+    let mut name = Name::new(text)?.into_owned();
+    let is_absolute = text.ends_with('.') && !text.ends_with("\\.");
+    if let (false, Some(origin)) = (is_absolute, &defaults.origin) {
+        name.append_name(origin)?;
+    }
+
+    Ok(name)
+}
+
+pub(crate) fn parse_number<T: FromStr>(text: &str, field: &str) -> crate::Result<T> {
+    text.parse()
+        .map_err(|_| invalid(format!("invalid {field} {text}")))
+}
+
+fn next_text<'t>(
+    tokens: &mut impl Iterator<Item = &'t Token>,
+    field: &str,
+) -> crate::Result<&'t str> {
+    match tokens.next() {
+        Some(Token::Text(text)) => Ok(text),
+        Some(Token::Quoted(_)) => Err(invalid(format!("expected {field}, found a quoted string"))),
+        None => Err(invalid(format!("missing {field}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_fields() {
+        assert_eq!(
+            vec![
+                Token::Text("www".to_string()),
+                Token::Text("TXT".to_string()),
+                Token::Quoted(b"a \"quoted\" ; string".to_vec()),
+                Token::Text("a\\ b".to_string()),
+                Token::Quoted(vec![0, b'x']),
+            ],
+            tokenize("www\tTXT \"a \\\"quoted\\\" ; string\" a\\ b \"\\000x\" ; comment").unwrap()
+        );
+
+        assert!(tokenize("; only a comment").unwrap().is_empty());
+        assert!(tokenize("www TXT \"unterminated").is_err());
+        assert!(tokenize("www TXT \"\\256\"").is_err());
+    }
+}
@@ -1,12 +1,11 @@
-use std::{
-    collections::HashMap,
-    convert::{TryFrom, TryInto},
-};
+use crate::dns::write::{NameRefs, Seek, Write};
+use core::convert::{TryFrom, TryInto};
 
 use super::{Name, PacketPart, QCLASS, QTYPE};
 
 /// Question represents a query in the DNS Packet
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Question<'a> {
     /// a [Name](`Name`)  to query for
     pub qname: Name<'a>,
@@ -40,7 +39,7 @@ impl<'a> Question<'a> {
         }
     }
 
-    fn write_common<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_common<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         let qclass: u16 = match self.unicast_response {
             true => Into::<u16>::into(self.qclass) | 0x8000,
             false => self.qclass.into(),
@@ -48,7 +47,13 @@ impl<'a> Question<'a> {
 
         out.write_all(&Into::<u16>::into(self.qtype).to_be_bytes())?;
         out.write_all(&qclass.to_be_bytes())
-            .map_err(crate::SimpleDnsError::from)
+    }
+}
+
+impl<'a> core::fmt::Display for Question<'a> {
+    /// Formats this question in the presentation format, like `example.com. IN A`
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}. {} {}", self.qname, self.qclass, self.qtype)
     }
 }
 
@@ -75,15 +80,15 @@ impl<'a> PacketPart<'a> for Question<'a> {
         self.qname.len() + 4
     }
 
-    fn write_to<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         self.qname.write_to(out)?;
         self.write_common(out)
     }
 
-    fn write_compressed_to<T: std::io::Write + std::io::Seek>(
+    fn write_compressed_to<T: Write + Seek>(
         &self,
         out: &mut T,
-        name_refs: &mut HashMap<u64, usize>,
+        name_refs: &mut NameRefs,
     ) -> crate::Result<()> {
         self.qname.write_compressed_to(out, name_refs)?;
         self.write_common(out)
@@ -125,6 +130,25 @@ mod tests {
         assert_eq!(bytes.len(), question.len());
     }
 
+    #[test]
+    fn display_presentation_format() {
+        let question = Question::new(
+            "_srv._udp.local".try_into().unwrap(),
+            TYPE::SRV.into(),
+            CLASS::IN.into(),
+            false,
+        );
+        assert_eq!("_srv._udp.local. IN SRV", question.to_string());
+
+        let question = Question::new(
+            "example.com".try_into().unwrap(),
+            QTYPE::ANY,
+            QCLASS::ANY,
+            false,
+        );
+        assert_eq!("example.com. ANY ANY", question.to_string());
+    }
+
     #[test]
     fn unicast_response() {
         let mut bytes = Vec::new();
@@ -1,10 +1,12 @@
+use crate::dns::write::Write;
 use crate::dns::PacketPart;
-use std::{convert::TryInto, net::Ipv4Addr};
+use core::{convert::TryInto, net::Ipv4Addr};
 
 use super::RR;
 
 /// Represents a Resource Address (IPv4)
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct A {
     /// a 32 bit ip address
     pub address: u32,
@@ -19,13 +21,16 @@ impl<'a> PacketPart<'a> for A {
     where
         Self: Sized,
     {
+        if data.len() < position + 4 {
+            return Err(crate::SimpleDnsError::InsufficientData);
+        }
+
         let address = u32::from_be_bytes(data[position..position + 4].try_into()?);
         Ok(Self { address })
     }
 
-    fn write_to<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         out.write_all(&self.address.to_be_bytes())
-            .map_err(crate::SimpleDnsError::from)
     }
 
     fn len(&self) -> usize {
@@ -1,10 +1,12 @@
+use crate::dns::write::Write;
 use crate::dns::PacketPart;
-use std::{convert::TryInto, net::Ipv6Addr};
+use core::{convert::TryInto, net::Ipv6Addr};
 
 use super::RR;
 
 /// Represents a Resource Address (IPv6) [rfc3596](https://tools.ietf.org/html/rfc3596)
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AAAA {
     /// a 128 bit ip address
     pub address: u128,
@@ -19,6 +21,10 @@ impl<'a> PacketPart<'a> for AAAA {
     where
         Self: Sized,
     {
+        if data.len() < position + 16 {
+            return Err(crate::SimpleDnsError::InsufficientData);
+        }
+
         let address = u128::from_be_bytes(data[position..position + 16].try_into()?);
         Ok(Self { address })
     }
@@ -27,9 +33,8 @@ impl<'a> PacketPart<'a> for AAAA {
         16
     }
 
-    fn write_to<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         out.write_all(&self.address.to_be_bytes())
-            .map_err(crate::SimpleDnsError::from)
     }
 }
 
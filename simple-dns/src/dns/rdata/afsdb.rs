@@ -1,11 +1,11 @@
-use std::collections::HashMap;
-
+use crate::dns::write::{NameRefs, Seek, Write};
 use crate::dns::{Name, PacketPart};
 
 use super::RR;
 
 /// AFSDB records represents servers with ASD cells
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AFSDB<'a> {
     /// An integer that represents the subtype
     pub subtype: u16,
@@ -32,21 +32,25 @@ impl<'a> PacketPart<'a> for AFSDB<'a> {
     where
         Self: Sized,
     {
+        if data.len() < position + 2 {
+            return Err(crate::SimpleDnsError::InsufficientData);
+        }
+
         let subtype = u16::from_be_bytes(data[position..position + 2].try_into()?);
         let hostname = Name::parse(data, position + 2)?;
 
         Ok(Self { subtype, hostname })
     }
 
-    fn write_to<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         out.write_all(&self.subtype.to_be_bytes())?;
         self.hostname.write_to(out)
     }
 
-    fn write_compressed_to<T: std::io::Write + std::io::Seek>(
+    fn write_compressed_to<T: Write + Seek>(
         &self,
         out: &mut T,
-        name_refs: &mut HashMap<u64, usize>,
+        name_refs: &mut NameRefs,
     ) -> crate::Result<()> {
         out.write_all(&self.subtype.to_be_bytes())?;
         self.hostname.write_compressed_to(out, name_refs)
@@ -1,3 +1,4 @@
+use crate::dns::write::Write;
 use crate::dns::{CharacterString, PacketPart};
 
 use super::RR;
@@ -6,6 +7,7 @@ use super::RR;
 /// Used as a security policy for certificate authorities
 /// This implementation does not validate the tag or value; it splits based on packet byte structure
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CAA<'a> {
     /// Critical or noncritical indicator
     pub flag: u8,
@@ -35,6 +37,10 @@ impl<'a> PacketPart<'a> for CAA<'a> {
     where
         Self: Sized,
     {
+        if data.len() < position + 1 {
+            return Err(crate::SimpleDnsError::InsufficientData);
+        }
+
         let flag = u8::from_be_bytes(data[position..position + 1].try_into()?);
         let tag = CharacterString::parse(data, position + 1)?;
         let value = CharacterString::parse(data, position + 1 + tag.len())?;
@@ -42,7 +48,7 @@ impl<'a> PacketPart<'a> for CAA<'a> {
         Ok(Self { flag, tag, value })
     }
 
-    fn write_to<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         out.write_all(&self.flag.to_be_bytes())?;
         self.tag.write_to(out)?;
         self.value.write_to(out)
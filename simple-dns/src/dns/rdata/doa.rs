@@ -0,0 +1,133 @@
+use crate::dns::write::Write;
+use alloc::borrow::Cow;
+use core::convert::TryInto;
+
+use crate::{
+    dns::{CharacterString, PacketPart},
+    SimpleDnsError,
+};
+
+use super::RR;
+
+/// The DOA record is used to store Digital Object Architecture data,
+/// [draft-durand-doa-over-dns](https://datatracker.ietf.org/doc/html/draft-durand-doa-over-dns-03)
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DOA<'a> {
+    /// Identifies the organization that defines the `doa_type`
+    pub doa_enterprise: u32,
+    /// The type of the DOA data, as defined by the `doa_enterprise`
+    pub doa_type: u32,
+    /// Indicates how `doa_data` must be interpreted, 1 for local data and 2 for an URI
+    pub doa_location: u8,
+    /// A [CharacterString](`CharacterString`) with the media type of `doa_data`
+    pub doa_media_type: CharacterString<'a>,
+    /// The DOA data, presented as base64 in zone files but stored as raw bytes
+    pub doa_data: Cow<'a, [u8]>,
+}
+
+impl<'a> RR for DOA<'a> {
+    const TYPE_CODE: u16 = 259;
+}
+
+impl<'a> DOA<'a> {
+    /// Transforms the inner data into its owned type
+    pub fn into_owned<'b>(self) -> DOA<'b> {
+        DOA {
+            doa_enterprise: self.doa_enterprise,
+            doa_type: self.doa_type,
+            doa_location: self.doa_location,
+            doa_media_type: self.doa_media_type.into_owned(),
+            doa_data: self.doa_data.into_owned().into(),
+        }
+    }
+}
+
+impl<'a> PacketPart<'a> for DOA<'a> {
+    fn parse(data: &'a [u8], position: usize) -> crate::Result<Self>
+    where
+        Self: Sized,
+    {
+        if data.len() < position + 9 {
+            return Err(SimpleDnsError::InsufficientData);
+        }
+
+        let doa_enterprise = u32::from_be_bytes(data[position..position + 4].try_into()?);
+        let doa_type = u32::from_be_bytes(data[position + 4..position + 8].try_into()?);
+        let doa_location = data[position + 8];
+        let doa_media_type = CharacterString::parse(data, position + 9)?;
+        let doa_data = Cow::Borrowed(&data[position + 9 + doa_media_type.len()..]);
+
+        Ok(Self {
+            doa_enterprise,
+            doa_type,
+            doa_location,
+            doa_media_type,
+            doa_data,
+        })
+    }
+
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
+        out.write_all(&self.doa_enterprise.to_be_bytes())?;
+        out.write_all(&self.doa_type.to_be_bytes())?;
+        out.write_all(&[self.doa_location])?;
+        self.doa_media_type.write_to(out)?;
+        out.write_all(&self.doa_data)
+    }
+
+    fn len(&self) -> usize {
+        9 + self.doa_media_type.len() + self.doa_data.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use crate::{rdata::RData, Name, ResourceRecord, CLASS};
+
+    use super::*;
+
+    #[test]
+    fn parse_and_write_doa() {
+        let rr = ResourceRecord::new(
+            Name::new_unchecked("doa.example.com"),
+            CLASS::IN,
+            10,
+            RData::DOA(DOA {
+                doa_enterprise: 0,
+                doa_type: 1,
+                doa_location: 2,
+                doa_media_type: CharacterString::try_from("text/plain").unwrap(),
+                doa_data: Cow::Borrowed(b"https://www.example.com/"),
+            }),
+        );
+
+        let mut data = Vec::new();
+        assert!(rr.write_to(&mut data).is_ok());
+        assert_eq!(data.len(), rr.len());
+
+        let doa = match ResourceRecord::parse(&data, 0).unwrap().rdata {
+            RData::DOA(rdata) => rdata,
+            _ => unreachable!(),
+        };
+
+        assert_eq!(0, doa.doa_enterprise);
+        assert_eq!(1, doa.doa_type);
+        assert_eq!(2, doa.doa_location);
+        assert_eq!("text/plain", doa.doa_media_type.to_string());
+        assert_eq!(&b"https://www.example.com/"[..], &doa.doa_data[..]);
+    }
+
+    #[test]
+    fn parse_doa_without_data() {
+        let data = b"\x00\x00\x00\x00\x00\x00\x00\x01\x01\x00";
+
+        let doa = DOA::parse(data, 0).unwrap();
+        assert_eq!(data.len(), doa.len());
+        assert!(doa.doa_media_type.to_string().is_empty());
+        assert!(doa.doa_data.is_empty());
+
+        assert!(DOA::parse(&data[..8], 0).is_err());
+    }
+}
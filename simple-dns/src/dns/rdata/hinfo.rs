@@ -1,5 +1,4 @@
-use std::collections::HashMap;
-
+use crate::dns::write::{NameRefs, Seek, Write};
 use crate::dns::{CharacterString, PacketPart};
 
 use super::RR;
@@ -8,6 +7,7 @@ use super::RR;
 /// The main use is for protocols such as FTP that can use special procedures
 /// when talking between machines or operating systems of the same type.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HINFO<'a> {
     /// A [CharacterString](`CharacterString`) which specifies the CPU type.
     pub cpu: CharacterString<'a>,
@@ -40,15 +40,15 @@ impl<'a> PacketPart<'a> for HINFO<'a> {
         Ok(Self { cpu, os })
     }
 
-    fn write_to<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         self.cpu.write_to(out)?;
         self.os.write_to(out)
     }
 
-    fn write_compressed_to<T: std::io::Write + std::io::Seek>(
+    fn write_compressed_to<T: Write + Seek>(
         &self,
         out: &mut T,
-        name_refs: &mut HashMap<u64, usize>,
+        name_refs: &mut NameRefs,
     ) -> crate::Result<()> {
         self.cpu.write_compressed_to(out, name_refs)?;
         self.os.write_compressed_to(out, name_refs)
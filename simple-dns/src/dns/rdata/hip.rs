@@ -0,0 +1,166 @@
+use crate::dns::write::Write;
+use alloc::{borrow::Cow, vec::Vec};
+use core::convert::TryInto;
+
+use crate::{
+    dns::{Name, PacketPart},
+    SimpleDnsError,
+};
+
+use super::RR;
+
+/// The HIP record is used to store a Host Identity (HI, the public key portion of the asymmetric key pair),
+/// its Host Identity Tag (HIT, a truncated hash of its HI), and the Domain Names of its rendezvous servers (RVSs)
+/// [RFC 8005](https://datatracker.ietf.org/doc/html/rfc8005)
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HIP<'a> {
+    /// The PK algorithm field indicates the public key cryptographic algorithm and the implied public key field format
+    pub pk_algorithm: u8,
+    /// The HIT is stored as a binary value in network byte order
+    pub hit: Cow<'a, [u8]>,
+    /// The public key field contains the algorithm-specific portion of the KEY RR RDATA
+    pub public_key: Cow<'a, [u8]>,
+    /// The Domain Names of the rendezvous servers, in order of preference. These names are not compressed
+    pub rendezvous_servers: Vec<Name<'a>>,
+}
+
+impl<'a> RR for HIP<'a> {
+    const TYPE_CODE: u16 = 55;
+}
+
+impl<'a> HIP<'a> {
+    /// Transforms the inner data into its owned type
+    pub fn into_owned<'b>(self) -> HIP<'b> {
+        HIP {
+            pk_algorithm: self.pk_algorithm,
+            hit: self.hit.into_owned().into(),
+            public_key: self.public_key.into_owned().into(),
+            rendezvous_servers: self
+                .rendezvous_servers
+                .into_iter()
+                .map(|n| n.into_owned())
+                .collect(),
+        }
+    }
+}
+
+impl<'a> PacketPart<'a> for HIP<'a> {
+    fn parse(data: &'a [u8], position: usize) -> crate::Result<Self>
+    where
+        Self: Sized,
+    {
+        if data.len() < position + 4 {
+            return Err(SimpleDnsError::InsufficientData);
+        }
+
+        let hit_length = data[position] as usize;
+        let pk_algorithm = data[position + 1];
+        let pk_length = u16::from_be_bytes(data[position + 2..position + 4].try_into()?) as usize;
+
+        let hit_start = position + 4;
+        let pk_start = hit_start + hit_length;
+        let mut offset = pk_start + pk_length;
+
+        if data.len() < offset {
+            return Err(SimpleDnsError::InsufficientData);
+        }
+
+        let hit = Cow::Borrowed(&data[hit_start..pk_start]);
+        let public_key = Cow::Borrowed(&data[pk_start..offset]);
+
+        let mut rendezvous_servers = Vec::new();
+        while offset < data.len() {
+            let name = Name::parse(data, offset)?;
+            offset += name.len();
+            rendezvous_servers.push(name);
+        }
+
+        Ok(Self {
+            pk_algorithm,
+            hit,
+            public_key,
+            rendezvous_servers,
+        })
+    }
+
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
+        out.write_all(&[self.hit.len() as u8, self.pk_algorithm])?;
+        out.write_all(&(self.public_key.len() as u16).to_be_bytes())?;
+        out.write_all(&self.hit)?;
+        out.write_all(&self.public_key)?;
+
+        for name in self.rendezvous_servers.iter() {
+            name.write_to(out)?;
+        }
+
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        let names_len: usize = self.rendezvous_servers.iter().map(|n| n.len()).sum();
+        4 + self.hit.len() + self.public_key.len() + names_len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dns::write::{Buffer, NameRefs};
+    use crate::{rdata::RData, ResourceRecord, CLASS};
+
+    use super::*;
+
+    #[test]
+    fn parse_and_write_hip() {
+        let hit = [
+            0x20, 0x01, 0x00, 0x10, 0x7b, 0x1a, 0x74, 0xdf, 0x36, 0x56, 0x39, 0xcc, 0x39, 0xf1,
+            0xd5, 0x78,
+        ];
+        let public_key = [0x03u8; 260];
+
+        let hip = HIP {
+            pk_algorithm: 2,
+            hit: Cow::Borrowed(&hit),
+            public_key: Cow::Borrowed(&public_key),
+            rendezvous_servers: vec![Name::new_unchecked("rvs.example.com")],
+        };
+
+        let mut data = Vec::new();
+        assert!(hip.write_to(&mut data).is_ok());
+
+        let hip = HIP::parse(&data, 0);
+        assert!(hip.is_ok());
+        let hip = hip.unwrap();
+
+        assert_eq!(data.len(), hip.len());
+        assert_eq!(2, hip.pk_algorithm);
+        assert_eq!(&hit[..], &hip.hit[..]);
+        assert_eq!(&public_key[..], &hip.public_key[..]);
+        assert_eq!(1, hip.rendezvous_servers.len());
+        assert_eq!("rvs.example.com", hip.rendezvous_servers[0].to_string());
+    }
+
+    #[test]
+    fn hip_should_not_be_compressed() {
+        let rr = ResourceRecord::new(
+            Name::new_unchecked("www.example.com"),
+            CLASS::IN,
+            10,
+            RData::HIP(HIP {
+                pk_algorithm: 2,
+                hit: Cow::Owned(vec![1, 2, 3, 4]),
+                public_key: Cow::Owned(vec![5, 6, 7, 8]),
+                rendezvous_servers: vec![Name::new_unchecked("rvs.example.com")],
+            }),
+        );
+
+        let mut plain = Vec::new();
+        let mut compressed = Buffer::new(Vec::new());
+        let mut names = NameRefs::new();
+
+        assert!(rr.write_to(&mut plain).is_ok());
+        assert!(rr.write_compressed_to(&mut compressed, &mut names).is_ok());
+
+        assert_eq!(plain, compressed.into_inner());
+    }
+}
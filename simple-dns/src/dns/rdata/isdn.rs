@@ -1,11 +1,11 @@
-use std::collections::HashMap;
-
+use crate::dns::write::{NameRefs, Seek, Write};
 use crate::dns::{CharacterString, PacketPart};
 
 use super::RR;
 
 /// An ISDN (Integrated Service Digital Network) number is simply a telephone number.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ISDN<'a> {
     /// A [CharacterString](`CharacterString`) which specifies the address.
     pub address: CharacterString<'a>,
@@ -38,15 +38,15 @@ impl<'a> PacketPart<'a> for ISDN<'a> {
         Ok(Self { address, sa })
     }
 
-    fn write_to<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         self.address.write_to(out)?;
         self.sa.write_to(out)
     }
 
-    fn write_compressed_to<T: std::io::Write + std::io::Seek>(
+    fn write_compressed_to<T: Write + Seek>(
         &self,
         out: &mut T,
-        name_refs: &mut HashMap<u64, usize>,
+        name_refs: &mut NameRefs,
     ) -> crate::Result<()> {
         self.address.write_compressed_to(out, name_refs)?;
         self.sa.write_compressed_to(out, name_refs)
@@ -1,9 +1,13 @@
+use crate::dns::write::Write;
+use core::fmt::Write as _;
+
 use crate::{dns::packet_part::PacketPart, SimpleDnsError};
 
 use super::RR;
 
 ///  A Means for Expressing Location Information in the Domain Name System [RFC 1876](https://datatracker.ietf.org/doc/html/rfc1876)
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LOC {
     /// Version number of the representation.  This must be zero.
     pub version: u8,
@@ -30,6 +34,30 @@ impl LOC {
     pub fn into_owned(self) -> Self {
         self
     }
+
+    /// Formats the location as described in [RFC 1876 section 3](https://datatracker.ietf.org/doc/html/rfc1876#section-3),
+    /// like `52 22 23.000 N 4 53 32.000 E -2.00m 1.00m 10000.00m 10.00m`
+    pub(crate) fn fmt_presentation(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        fmt_coordinate(f, self.latitude, 'N', 'S')?;
+        f.write_char(' ')?;
+        fmt_coordinate(f, self.longitude, 'E', 'W')?;
+        f.write_char(' ')?;
+
+        // the altitude is in centimeters from a base of 100000m below the reference spheroid
+        fmt_centimeters(f, self.altitude as u32 as i64 - 10_000_000)?;
+        for precision in [
+            self.size,
+            self.horizontal_precision,
+            self.vertical_precision,
+        ] {
+            f.write_char(' ')?;
+            let mantissa = (precision >> 4) as i64;
+            let exponent = (precision & 0x0f) as u32;
+            fmt_centimeters(f, mantissa * 10i64.pow(exponent.min(9)))?;
+        }
+
+        Ok(())
+    }
 }
 
 impl<'a> PacketPart<'a> for LOC {
@@ -66,7 +94,7 @@ impl<'a> PacketPart<'a> for LOC {
         })
     }
 
-    fn write_to<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         if self.version != 0 {
             return Err(SimpleDnsError::InvalidDnsPacket);
         }
@@ -89,6 +117,34 @@ impl<'a> PacketPart<'a> for LOC {
     }
 }
 
+/// Coordinates are in thousandths of a second of arc, with 2^31 being the equator or prime meridian
+fn fmt_coordinate(
+    f: &mut core::fmt::Formatter<'_>,
+    value: i32,
+    positive: char,
+    negative: char,
+) -> core::fmt::Result {
+    let value = value as u32 as i64 - (1 << 31);
+    let hemisphere = if value < 0 { negative } else { positive };
+    let value = value.abs();
+
+    write!(
+        f,
+        "{} {} {}.{:03} {}",
+        value / 3_600_000,
+        value / 60_000 % 60,
+        value / 1000 % 60,
+        value % 1000,
+        hemisphere
+    )
+}
+
+fn fmt_centimeters(f: &mut core::fmt::Formatter<'_>, value: i64) -> core::fmt::Result {
+    let sign = if value < 0 { "-" } else { "" };
+    let value = value.abs();
+    write!(f, "{}{}.{:02}m", sign, value / 100, value % 100)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{rdata::RData, ResourceRecord};
@@ -1,6 +1,7 @@
 macro_rules! rr_wrapper {
     (#[doc=$doc:expr] $t:ident: $w:ident = $c:literal) => {
         #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         #[doc = $doc]
         pub struct $t<'a>(pub $w<'a>);
 
@@ -29,14 +30,14 @@ macro_rules! rr_wrapper {
                 $w::parse(data, position).map(|n| $t(n))
             }
 
-            fn write_to<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+            fn write_to<T: crate::dns::write::Write>(&self, out: &mut T) -> crate::Result<()> {
                 self.0.write_to(out)
             }
 
-            fn write_compressed_to<T: std::io::Write + std::io::Seek>(
+            fn write_compressed_to<T: crate::dns::write::Write + crate::dns::write::Seek>(
                 &self,
                 out: &mut T,
-                name_refs: &mut std::collections::HashMap<u64, usize>,
+                name_refs: &mut crate::dns::write::NameRefs,
             ) -> crate::Result<()> {
                 self.0.write_compressed_to(out, name_refs)
             }
@@ -46,7 +47,7 @@ macro_rules! rr_wrapper {
             }
         }
 
-        impl<'a> std::ops::Deref for $t<'a> {
+        impl<'a> core::ops::Deref for $t<'a> {
             type Target = $w<'a>;
 
             fn deref(&self) -> &Self::Target {
@@ -54,7 +55,7 @@ macro_rules! rr_wrapper {
             }
         }
 
-        impl<'a> std::ops::DerefMut for $t<'a> {
+        impl<'a> core::ops::DerefMut for $t<'a> {
             fn deref_mut(&mut self) -> &mut Self::Target {
                 &mut self.0
             }
@@ -66,12 +67,15 @@ macro_rules! rdata_enum {
     ($($i:tt$(<$x:lifetime>)?,)+) => {
         /// Represents the RData of each [`TYPE`]
         #[derive(Debug, Eq, PartialEq, Hash, Clone)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         #[allow(missing_docs)]
         pub enum RData<'a> {
             $(
                 $i($i$(<$x>)?),
             )+
 
+            /// NULL records and records of types without a specific RData, with their type code.
+            /// The rdata is kept verbatim, so packets with unknown record types can be parsed and written back unchanged
             NULL(u16, NULL<'a>),
         }
 
@@ -91,10 +95,18 @@ macro_rules! rdata_enum {
                     return Err(crate::SimpleDnsError::InsufficientData);
                 }
 
+                // UPDATE prerequisites and deletions have an empty rdata for any type, with the ANY or NONE class,
+                // RFC 2136 section 2.4. Other records with an empty rdata are parsed as their type
+                let class = u16::from_be_bytes(data[position + 2..position + 4].try_into()?);
+                let is_meta_class = class == crate::CLASS::ANY as u16 || class == crate::CLASS::NONE as u16;
+                if rdatalen == 0 && is_meta_class && !matches!(rdatatype, TYPE::OPT | TYPE::TXT) {
+                    return Ok(RData::NULL(rdatatype.into(), NULL::new(&[])?));
+                }
+
                 parse_rdata(&data[..position + 10 + rdatalen], position + 10, rdatatype)
             }
 
-            fn write_to<T: std::io::Write>(
+            fn write_to<T: crate::dns::write::Write>(
                 &self,
                 out: &mut T,
             ) -> crate::Result<()> {
@@ -107,10 +119,10 @@ macro_rules! rdata_enum {
                 }
             }
 
-            fn write_compressed_to<T: std::io::Write + std::io::Seek>(
+            fn write_compressed_to<T: crate::dns::write::Write + crate::dns::write::Seek>(
                 &self,
                 out: &mut T,
-                name_refs: &mut  HashMap<u64, usize>,
+                name_refs: &mut crate::dns::write::NameRefs,
             ) -> crate::Result<()> {
                 match &self {
                     $(
@@ -146,6 +158,21 @@ macro_rules! rdata_enum {
                 }
             }
 
+            /// Returns the mnemonic of the type of this RData, like `A` or `NSAP-PTR`.
+            /// Types without a specific RData return `NULL`, use [`type_code`](RData::type_code) to get their code
+            #[allow(unreachable_patterns)]
+            pub fn type_name(&self) -> &'static str {
+                match self {
+                    RData::NSAP_PTR(_) => "NSAP-PTR",
+                    RData::RouteThrough(_) => "RT",
+                    $(
+                        RData::$i(_) => stringify!($i),
+                    )+
+
+                    RData::NULL(..) => "NULL",
+                }
+            }
+
             /// Transforms the inner data into its owned type
             pub fn into_owned<'b>(self) -> RData<'b> {
                 match self {
@@ -158,7 +185,7 @@ macro_rules! rdata_enum {
             }
         }
 
-        fn parse_rdata(data: &[u8], position: usize, rdatatype: TYPE) -> crate::Result<RData> {
+        fn parse_rdata(data: &[u8], position: usize, rdatatype: TYPE) -> crate::Result<RData<'_>> {
             let rdata = match rdatatype {
                 $(
                     TYPE::$i => RData::$i($i::parse(data, position)?),
@@ -175,6 +202,7 @@ macro_rules! rdata_enum {
         /// Possible TYPE values in DNS Resource Records
         /// Each value is described according to its own RFC
         #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         #[allow(missing_docs)]
         pub enum TYPE {
             $( $i,)+
@@ -197,6 +225,49 @@ macro_rules! rdata_enum {
             }
         }
 
+        impl core::fmt::Display for TYPE {
+            /// Formats the type mnemonic, unknown types are formatted as `TYPE` followed by the type code,
+            /// as described in [RFC 3597](https://datatracker.ietf.org/doc/html/rfc3597#section-5)
+            #[allow(unreachable_patterns)]
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                match self {
+                    TYPE::NSAP_PTR => f.write_str("NSAP-PTR"),
+                    TYPE::RouteThrough => f.write_str("RT"),
+                    $(
+                        TYPE::$i => f.write_str(stringify!($i)),
+                    )+
+
+                    TYPE::NULL => f.write_str("NULL"),
+                    TYPE::Unknown(x) => write!(f, "TYPE{}", x),
+                }
+            }
+        }
+
+        impl core::str::FromStr for TYPE {
+            type Err = crate::SimpleDnsError;
+
+            /// Parses a type mnemonic ignoring case, or `TYPE` followed by the type code
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                $(
+                    if s.eq_ignore_ascii_case(&alloc::string::ToString::to_string(&TYPE::$i)) {
+                        return Ok(TYPE::$i);
+                    }
+                )+
+
+                if s.eq_ignore_ascii_case("NULL") {
+                    return Ok(TYPE::NULL);
+                }
+
+                match s.get(..4) {
+                    Some(prefix) if prefix.eq_ignore_ascii_case("TYPE") => s[4..]
+                        .parse::<u16>()
+                        .map(TYPE::from)
+                        .map_err(|_| crate::SimpleDnsError::InvalidPresentationFormat(alloc::format!("invalid type {s}"))),
+                    _ => Err(crate::SimpleDnsError::InvalidPresentationFormat(alloc::format!("unknown type {s}"))),
+                }
+            }
+        }
+
         impl From<u16> for TYPE {
             fn from(value: u16) -> Self {
                 match value {
@@ -1,11 +1,11 @@
-use std::collections::HashMap;
-
+use crate::dns::write::{NameRefs, Seek, Write};
 use crate::dns::{Name, PacketPart};
 
 use super::RR;
 
 /// MINFO recors are used to acquire mailbox or mail list information
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MINFO<'a> {
     /// A [Name](`Name`) which specifies a mailbox which is responsible for the mailing list or mailbox.  
     pub rmailbox: Name<'a>,
@@ -39,15 +39,15 @@ impl<'a> PacketPart<'a> for MINFO<'a> {
         Ok(Self { rmailbox, emailbox })
     }
 
-    fn write_to<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         self.rmailbox.write_to(out)?;
         self.emailbox.write_to(out)
     }
 
-    fn write_compressed_to<T: std::io::Write + std::io::Seek>(
+    fn write_compressed_to<T: Write + Seek>(
         &self,
         out: &mut T,
-        name_refs: &mut HashMap<u64, usize>,
+        name_refs: &mut NameRefs,
     ) -> crate::Result<()> {
         self.rmailbox.write_compressed_to(out, name_refs)?;
         self.emailbox.write_compressed_to(out, name_refs)
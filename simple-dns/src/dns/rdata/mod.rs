@@ -4,8 +4,13 @@
 use crate::CharacterString;
 
 use super::{Name, PacketPart};
+use alloc::vec::Vec;
 use core::fmt::Debug;
-use std::{collections::HashMap, convert::TryInto};
+use core::{
+    convert::TryInto,
+    fmt::{Display, Write},
+    net::{Ipv4Addr, Ipv6Addr},
+};
 
 mod macros;
 
@@ -21,9 +26,15 @@ pub use afsdb::AFSDB;
 mod caa;
 pub use caa::CAA;
 
+mod doa;
+pub use doa::DOA;
+
 mod hinfo;
 pub use hinfo::HINFO;
 
+mod hip;
+pub use hip::HIP;
+
 mod isdn;
 pub use isdn::ISDN;
 
@@ -39,11 +50,20 @@ pub use mx::MX;
 mod nsap;
 pub use nsap::NSAP;
 
+mod nsec;
+pub use nsec::{NsecTypeBitMap, NSEC};
+
 mod null;
 pub use null::NULL;
 
+mod openpgpkey;
+pub use openpgpkey::OPENPGPKEY;
+
 mod opt;
-pub use opt::{OPTCode, OPT};
+pub use opt::{EdnsOption, OPTCode, OPT};
+
+mod px;
+pub use px::PX;
 
 mod route_through;
 pub use route_through::RouteThrough;
@@ -57,12 +77,181 @@ pub use soa::SOA;
 mod srv;
 pub use srv::SRV;
 
+mod tkey;
+pub use tkey::TKEY;
+
+mod tsig;
+pub use tsig::TSIG;
+
+mod tlsa;
+pub use tlsa::TLSA;
+
 mod txt;
 pub use txt::TXT;
 
 mod wks;
 pub use wks::WKS;
 
+impl<'a> Display for RData<'a> {
+    /// Formats this RData in the presentation format used by zone files, as defined by the RFC of each type.
+    /// Types without a presentation format, like NULL, OPT, TKEY and TSIG, are written in the generic format described in
+    /// [RFC 3597](https://datatracker.ietf.org/doc/html/rfc3597#section-5)
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RData::A(a) => write!(f, "{}", Ipv4Addr::from(a.address)),
+            RData::AAAA(aaaa) => write!(f, "{}", Ipv6Addr::from(aaaa.address)),
+            RData::NS(NS(name))
+            | RData::MD(MD(name))
+            | RData::MF(MF(name))
+            | RData::CNAME(CNAME(name))
+            | RData::MB(MB(name))
+            | RData::MG(MG(name))
+            | RData::MR(MR(name))
+            | RData::PTR(PTR(name))
+            | RData::NSAP_PTR(NSAP_PTR(name)) => write!(f, "{}.", name),
+            RData::MX(mx) => write!(f, "{} {}.", mx.preference, mx.exchange),
+            RData::SRV(srv) => write!(
+                f,
+                "{} {} {} {}.",
+                srv.priority, srv.weight, srv.port, srv.target
+            ),
+            RData::HINFO(hinfo) => {
+                hinfo.cpu.fmt_quoted(f)?;
+                f.write_char(' ')?;
+                hinfo.os.fmt_quoted(f)
+            }
+            RData::TXT(txt) | RData::AVC(AVC(txt)) => {
+                for (i, string) in txt.strings.iter().enumerate() {
+                    if i != 0 {
+                        f.write_char(' ')?;
+                    }
+                    string.fmt_quoted(f)?;
+                }
+                Ok(())
+            }
+            RData::MINFO(minfo) => write!(f, "{}. {}.", minfo.rmailbox, minfo.emailbox),
+            RData::SOA(soa) => write!(
+                f,
+                "{}. {}. {} {} {} {} {}",
+                soa.mname, soa.rname, soa.serial, soa.refresh, soa.retry, soa.expire, soa.minimum
+            ),
+            RData::WKS(wks) => {
+                write!(f, "{} {}", Ipv4Addr::from(wks.address), wks.protocol)?;
+                for port in wks.ports() {
+                    write!(f, " {}", port)?;
+                }
+                Ok(())
+            }
+            RData::RP(rp) => write!(f, "{}. {}.", rp.mbox, rp.txt),
+            RData::AFSDB(afsdb) => write!(f, "{} {}.", afsdb.subtype, afsdb.hostname),
+            RData::ISDN(isdn) => {
+                isdn.address.fmt_quoted(f)?;
+                f.write_char(' ')?;
+                isdn.sa.fmt_quoted(f)
+            }
+            RData::RouteThrough(rt) => {
+                write!(f, "{} {}.", rt.preference, rt.intermediate_host)
+            }
+            RData::NSAP(_) => {
+                f.write_str("0x")?;
+                write_hex(f, &self.to_bytes()?)
+            }
+            RData::PX(px) => write!(f, "{} {}. {}.", px.preference, px.map822, px.mapx400),
+            RData::LOC(loc) => loc.fmt_presentation(f),
+            RData::NSEC(nsec) => {
+                write!(f, "{}.", nsec.next_name)?;
+                for rtype in nsec.types() {
+                    write!(f, " {}", rtype)?;
+                }
+                Ok(())
+            }
+            RData::TLSA(tlsa) | RData::SMIMEA(SMIMEA(tlsa)) => {
+                write!(
+                    f,
+                    "{} {} {} ",
+                    tlsa.cert_usage, tlsa.selector, tlsa.matching_type
+                )?;
+                write_hex(f, &tlsa.data)
+            }
+            RData::HIP(hip) => {
+                write!(f, "{} ", hip.pk_algorithm)?;
+                write_hex(f, &hip.hit)?;
+                f.write_char(' ')?;
+                write_base64(f, &hip.public_key)?;
+                for server in hip.rendezvous_servers.iter() {
+                    write!(f, " {}.", server)?;
+                }
+                Ok(())
+            }
+            RData::OPENPGPKEY(key) => write_base64(f, &key.public_key),
+            RData::CAA(caa) => {
+                write!(f, "{} {} ", caa.flag, caa.tag)?;
+                caa.value.fmt_quoted(f)
+            }
+            RData::DOA(doa) => {
+                write!(
+                    f,
+                    "{} {} {} ",
+                    doa.doa_enterprise, doa.doa_type, doa.doa_location
+                )?;
+                doa.doa_media_type.fmt_quoted(f)?;
+                f.write_char(' ')?;
+                if doa.doa_data.is_empty() {
+                    f.write_char('-')
+                } else {
+                    write_base64(f, &doa.doa_data)
+                }
+            }
+            RData::NULL(..) | RData::OPT(_) | RData::TKEY(_) | RData::TSIG(_) => {
+                let data = self.to_bytes()?;
+
+                write!(f, "\\# {}", data.len())?;
+                if !data.is_empty() {
+                    f.write_char(' ')?;
+                    write_hex(f, &data)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<'a> RData<'a> {
+    fn to_bytes(&self) -> Result<Vec<u8>, core::fmt::Error> {
+        let mut data = Vec::with_capacity(self.len());
+        self.write_to(&mut data).map_err(|_| core::fmt::Error)?;
+        Ok(data)
+    }
+}
+
+fn write_hex(f: &mut core::fmt::Formatter<'_>, data: &[u8]) -> core::fmt::Result {
+    for byte in data {
+        write!(f, "{:02X}", byte)?;
+    }
+    Ok(())
+}
+
+/// Writes `data` in the base64 encoding from [RFC 4648](https://datatracker.ietf.org/doc/html/rfc4648#section-4)
+fn write_base64(f: &mut core::fmt::Formatter<'_>, data: &[u8]) -> core::fmt::Result {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    for chunk in data.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, byte)| {
+            bits | (*byte as u32) << (16 - 8 * i)
+        });
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (bits >> (18 - 6 * i)) & 0x3f;
+                f.write_char(ALPHABET[index as usize] as char)?;
+            } else {
+                f.write_char('=')?;
+            }
+        }
+    }
+    Ok(())
+}
+
 pub(crate) trait RR {
     const TYPE_CODE: u16;
 }
@@ -117,6 +306,16 @@ macros::rr_wrapper! {
     NSAP_PTR:Name = 23
 }
 
+macros::rr_wrapper! {
+    #[doc = "S/MIME cert association, shares the [`TLSA`] layout, [RFC 8162](https://datatracker.ietf.org/doc/html/rfc8162)"]
+    SMIMEA:TLSA = 53
+}
+
+macros::rr_wrapper! {
+    #[doc = "Application Visibility and Control, shares the [`TXT`] layout, [IANA](https://www.iana.org/assignments/dns-parameters/AVC/avc-completed-template)"]
+    AVC:TXT = 258
+}
+
 macros::rdata_enum! {
     A,
     AAAA,
@@ -141,7 +340,280 @@ macros::rdata_enum! {
     RouteThrough<'a>,
     NSAP,
     NSAP_PTR<'a>,
+    PX<'a>,
     LOC,
     OPT<'a>,
+    NSEC<'a>,
+    TLSA<'a>,
+    SMIMEA<'a>,
+    HIP<'a>,
+    OPENPGPKEY<'a>,
+    TKEY<'a>,
+    TSIG<'a>,
     CAA<'a>,
+    AVC<'a>,
+    DOA<'a>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::*;
+    use crate::TYPE;
+
+    fn name(name: &str) -> Name<'_> {
+        Name::new_unchecked(name)
+    }
+
+    fn string(value: &str) -> CharacterString<'_> {
+        CharacterString::new(value.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn display_names_and_numbers() {
+        assert_eq!(
+            "ns.example.com. admin.example.com. 2024010101 7200 3600 1209600 300",
+            RData::SOA(SOA {
+                mname: name("ns.example.com"),
+                rname: name("admin.example.com"),
+                serial: 2024010101,
+                refresh: 7200,
+                retry: 3600,
+                expire: 1209600,
+                minimum: 300
+            })
+            .to_string()
+        );
+        assert_eq!(
+            "admin.example.com. errors.example.com.",
+            RData::MINFO(MINFO {
+                rmailbox: name("admin.example.com"),
+                emailbox: name("errors.example.com")
+            })
+            .to_string()
+        );
+        assert_eq!(
+            "admin.example.com. info.example.com.",
+            RData::RP(RP {
+                mbox: name("admin.example.com"),
+                txt: name("info.example.com")
+            })
+            .to_string()
+        );
+        assert_eq!(
+            "1 afs.example.com.",
+            RData::AFSDB(AFSDB {
+                subtype: 1,
+                hostname: name("afs.example.com")
+            })
+            .to_string()
+        );
+        assert_eq!(
+            "10 relay.example.com.",
+            RData::RouteThrough(RouteThrough {
+                preference: 10,
+                intermediate_host: name("relay.example.com")
+            })
+            .to_string()
+        );
+        assert_eq!(
+            "10 example.com. px.example.com.",
+            RData::PX(PX {
+                preference: 10,
+                map822: name("example.com"),
+                mapx400: name("px.example.com")
+            })
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn display_quoted_strings() {
+        assert_eq!(
+            r#""150862028003217" "004""#,
+            RData::ISDN(ISDN {
+                address: string("150862028003217"),
+                sa: string("004")
+            })
+            .to_string()
+        );
+        assert_eq!(
+            r#"0 issue "ca.example.net; account=\"230123\"""#,
+            RData::CAA(CAA {
+                flag: 0,
+                tag: string("issue"),
+                value: string("ca.example.net; account=\"230123\"")
+            })
+            .to_string()
+        );
+        assert_eq!(
+            r#"0 1 2 "image/gif" AQID"#,
+            RData::DOA(DOA {
+                doa_enterprise: 0,
+                doa_type: 1,
+                doa_location: 2,
+                doa_media_type: string("image/gif"),
+                doa_data: Cow::Borrowed(&[1, 2, 3])
+            })
+            .to_string()
+        );
+        assert_eq!(
+            r#"0 1 2 "" -"#,
+            RData::DOA(DOA {
+                doa_enterprise: 0,
+                doa_type: 1,
+                doa_location: 2,
+                doa_media_type: string(""),
+                doa_data: Cow::Borrowed(&[])
+            })
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn display_binary_data() {
+        let tlsa = TLSA {
+            cert_usage: 3,
+            selector: 1,
+            matching_type: 1,
+            data: Cow::Borrowed(&[0x0d, 0x6f, 0xce, 0x13]),
+        };
+        assert_eq!("3 1 1 0D6FCE13", RData::TLSA(tlsa.clone()).to_string());
+        assert_eq!("3 1 1 0D6FCE13", RData::SMIMEA(SMIMEA(tlsa)).to_string());
+
+        assert_eq!(
+            "Zm9vYg==",
+            RData::OPENPGPKEY(OPENPGPKEY {
+                public_key: Cow::Borrowed(b"foob")
+            })
+            .to_string()
+        );
+        assert_eq!(
+            "2 200100107B1A74DF365639CC39F1D578 Zm9vYmFy rvs1.example.com. rvs2.example.com.",
+            RData::HIP(HIP {
+                pk_algorithm: 2,
+                hit: Cow::Owned(
+                    0x2001_0010_7b1a_74df_3656_39cc_39f1_d578u128
+                        .to_be_bytes()
+                        .to_vec()
+                ),
+                public_key: Cow::Borrowed(b"foobar"),
+                rendezvous_servers: vec![name("rvs1.example.com"), name("rvs2.example.com")]
+            })
+            .to_string()
+        );
+        assert_eq!(
+            "0x47000580005A0000000001E133FFFFFF00016100",
+            RData::NSAP(NSAP {
+                afi: 0x47,
+                idi: 0x0005,
+                dfi: 0x80,
+                aa: 0x005a00,
+                rsvd: 0x0000,
+                rd: 0x0001,
+                area: 0xe133,
+                id: 0xffffff000161,
+                sel: 0x00
+            })
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn display_bit_maps() {
+        assert_eq!(
+            "host.example.com. A MX TYPE46 NSEC TYPE1234",
+            RData::NSEC(NSEC::new(
+                name("host.example.com"),
+                &[
+                    TYPE::NSEC,
+                    TYPE::A,
+                    TYPE::Unknown(46),
+                    TYPE::MX,
+                    TYPE::Unknown(1234)
+                ]
+            ))
+            .to_string()
+        );
+        assert_eq!(
+            "192.168.1.1 6 21 25 80",
+            RData::WKS(WKS {
+                address: u32::from(Ipv4Addr::new(192, 168, 1, 1)),
+                protocol: 6,
+                bit_map: Cow::Borrowed(&[
+                    0,
+                    0,
+                    0b0000_0100,
+                    0b0100_0000,
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                    0b1000_0000
+                ])
+            })
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn display_location() {
+        assert_eq!(
+            "52 22 23.000 N 4 53 32.000 E -2.00m 0.00m 10000.00m 10.00m",
+            RData::LOC(LOC {
+                version: 0,
+                size: 0x00,
+                horizontal_precision: 0x16,
+                vertical_precision: 0x13,
+                latitude: ((1u32 << 31) + 188_543_000) as i32,
+                longitude: ((1u32 << 31) + 17_612_000) as i32,
+                altitude: 9_999_800
+            })
+            .to_string()
+        );
+        assert_eq!(
+            "0 0 0.500 S 0 0 0.000 E 0.00m 1.00m 1.00m 1.00m",
+            RData::LOC(LOC {
+                version: 0,
+                size: 0x12,
+                horizontal_precision: 0x12,
+                vertical_precision: 0x12,
+                latitude: ((1u32 << 31) - 500) as i32,
+                longitude: (1u32 << 31) as i32,
+                altitude: 10_000_000
+            })
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn type_names() {
+        assert_eq!("A", RData::A(A { address: 1 }).type_name());
+        assert_eq!(
+            "NSAP-PTR",
+            RData::NSAP_PTR(NSAP_PTR(name("a.local"))).type_name()
+        );
+        assert_eq!(
+            "RT",
+            RData::RouteThrough(RouteThrough {
+                preference: 1,
+                intermediate_host: name("a.local")
+            })
+            .type_name()
+        );
+        assert_eq!(
+            "OPENPGPKEY",
+            RData::OPENPGPKEY(OPENPGPKEY {
+                public_key: Cow::Borrowed(&[])
+            })
+            .type_name()
+        );
+        assert_eq!(
+            "NULL",
+            RData::NULL(1234, NULL::new(&[]).unwrap()).type_name()
+        );
+    }
 }
@@ -1,4 +1,5 @@
-use std::{collections::HashMap, convert::TryInto};
+use crate::dns::write::{NameRefs, Seek, Write};
+use core::convert::TryInto;
 
 use crate::dns::{Name, PacketPart};
 
@@ -6,6 +7,7 @@ use super::RR;
 
 /// MX is used to acquire mail exchange information
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MX<'a> {
     /// A 16 bit integer which specifies the preference given to this RR among others at the same owner.  
     /// Lower values are preferred.
@@ -34,6 +36,10 @@ impl<'a> PacketPart<'a> for MX<'a> {
     where
         Self: Sized,
     {
+        if data.len() < position + 2 {
+            return Err(crate::SimpleDnsError::InsufficientData);
+        }
+
         let preference = u16::from_be_bytes(data[position..position + 2].try_into()?);
         let exchange = Name::parse(data, position + 2)?;
 
@@ -43,15 +49,15 @@ impl<'a> PacketPart<'a> for MX<'a> {
         })
     }
 
-    fn write_to<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         out.write_all(&self.preference.to_be_bytes())?;
         self.exchange.write_to(out)
     }
 
-    fn write_compressed_to<T: std::io::Write + std::io::Seek>(
+    fn write_compressed_to<T: Write + Seek>(
         &self,
         out: &mut T,
-        name_refs: &mut HashMap<u64, usize>,
+        name_refs: &mut NameRefs,
     ) -> crate::Result<()> {
         out.write_all(&self.preference.to_be_bytes())?;
         self.exchange.write_compressed_to(out, name_refs)
@@ -1,8 +1,10 @@
+use crate::dns::write::Write;
 use crate::{dns::packet_part::PacketPart, SimpleDnsError};
 
 use super::RR;
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// NSAP structure [RFC 1706](https://datatracker.ietf.org/doc/html/rfc1706)  
 ///  ATTENTION: this code doesn't validade the content of the NSAP RR, it just split the bytes in the correct order
 pub struct NSAP {
@@ -74,7 +76,7 @@ impl<'a> PacketPart<'a> for NSAP {
         })
     }
 
-    fn write_to<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         out.write_all(&[self.afi.to_be()])?;
         out.write_all(&self.idi.to_be_bytes())?;
         out.write_all(&[self.dfi.to_be()])?;
@@ -95,7 +97,10 @@ impl<'a> PacketPart<'a> for NSAP {
 
 #[cfg(test)]
 mod tests {
-    use crate::{rdata::RData, ResourceRecord};
+    use crate::{
+        rdata::{RData, NSAP_PTR},
+        Name, ResourceRecord, CLASS,
+    };
 
     use super::*;
 
@@ -154,4 +159,48 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn nsap_round_trip_through_resource_record() {
+        //  0x47.0005.80.005a00.0000.0001.e133.ffffff000164.00
+        let address =
+            b"\x47\x00\x05\x80\x00\x5a\x00\x00\x00\x00\x01\xe1\x33\xff\xff\xff\x00\x01\x64\x00";
+
+        let nsap = NSAP::parse(address, 0).unwrap();
+        let rr = ResourceRecord::new(
+            Name::new_unchecked("sample"),
+            CLASS::IN,
+            60,
+            RData::NSAP(nsap),
+        );
+
+        let mut data = Vec::new();
+        rr.write_to(&mut data).unwrap();
+        assert_eq!(&address[..], &data[data.len() - 20..]);
+
+        assert_eq!(rr, ResourceRecord::parse(&data, 0).unwrap());
+    }
+
+    #[test]
+    fn nsap_ptr_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let sample_file = std::fs::read("samples/zonefile/NSAP-PTR.sample")?;
+
+        let sample_rdata = match ResourceRecord::parse(&sample_file, 0)?.rdata {
+            RData::NSAP_PTR(rdata) => rdata,
+            _ => unreachable!(),
+        };
+        assert_eq!("foo", sample_rdata.to_string());
+
+        let rr = ResourceRecord::new(
+            Name::new_unchecked("sample"),
+            CLASS::IN,
+            60,
+            RData::NSAP_PTR(NSAP_PTR(Name::new_unchecked("foo"))),
+        );
+        let mut data = Vec::new();
+        rr.write_to(&mut data)?;
+        assert_eq!(sample_file, data);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,240 @@
+use crate::dns::write::Write;
+use alloc::{borrow::Cow, vec::Vec};
+
+use crate::{
+    dns::{Name, PacketPart},
+    SimpleDnsError, TYPE,
+};
+
+use super::RR;
+
+/// The NSEC record lists the next owner name in a zone and the record types present at its own name,
+/// proving the non-existence of other names and types.
+/// [RFC 4034](https://datatracker.ietf.org/doc/html/rfc4034#section-4)
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NSEC<'a> {
+    /// The next owner name in the canonical ordering of the zone. This name is not compressed
+    pub next_name: Name<'a>,
+    /// The type bit maps of the record types present at the NSEC record owner name
+    pub type_bit_maps: Vec<NsecTypeBitMap<'a>>,
+}
+
+/// A window block of the NSEC type bit maps, each window covers 256 record types
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NsecTypeBitMap<'a> {
+    /// The window block number, the upper 8 bits of the covered types
+    pub window_block: u8,
+    /// Bitmap of the covered types, from 1 to 32 bytes long
+    pub bitmap: Cow<'a, [u8]>,
+}
+
+impl<'a> RR for NSEC<'a> {
+    const TYPE_CODE: u16 = 47;
+}
+
+impl<'a> NSEC<'a> {
+    /// Creates a new NSEC record with the type bit maps for `types`
+    pub fn new(next_name: Name<'a>, types: &[TYPE]) -> Self {
+        let mut type_bit_maps: Vec<NsecTypeBitMap> = Vec::new();
+
+        let mut codes: Vec<u16> = types.iter().map(|t| u16::from(*t)).collect();
+        codes.sort_unstable();
+        codes.dedup();
+
+        for code in codes {
+            let window_block = (code >> 8) as u8;
+            let offset = (code & 0xff) as usize;
+
+            let bitmap = match type_bit_maps.last_mut() {
+                Some(map) if map.window_block == window_block => map.bitmap.to_mut(),
+                _ => {
+                    type_bit_maps.push(NsecTypeBitMap {
+                        window_block,
+                        bitmap: Cow::Owned(Vec::new()),
+                    });
+                    type_bit_maps.last_mut().unwrap().bitmap.to_mut()
+                }
+            };
+
+            if bitmap.len() <= offset / 8 {
+                bitmap.resize(offset / 8 + 1, 0);
+            }
+            bitmap[offset / 8] |= 0b1000_0000 >> (offset % 8);
+        }
+
+        Self {
+            next_name,
+            type_bit_maps,
+        }
+    }
+
+    /// Returns true if `rtype` is present in the type bit maps
+    pub fn contains(&self, rtype: TYPE) -> bool {
+        let code = u16::from(rtype);
+        let window_block = (code >> 8) as u8;
+        let offset = (code & 0xff) as usize;
+
+        self.type_bit_maps
+            .iter()
+            .filter(|map| map.window_block == window_block)
+            .any(|map| {
+                map.bitmap
+                    .get(offset / 8)
+                    .map(|byte| byte & (0b1000_0000 >> (offset % 8)) != 0)
+                    .unwrap_or_default()
+            })
+    }
+
+    /// Returns the types present in the type bit maps, in the order they appear
+    pub(crate) fn types(&self) -> impl Iterator<Item = TYPE> + '_ {
+        self.type_bit_maps.iter().flat_map(|map| {
+            map.bitmap
+                .iter()
+                .enumerate()
+                .flat_map(move |(index, byte)| {
+                    (0..8)
+                        .filter(move |bit| byte & (0b1000_0000 >> bit) != 0)
+                        .map(move |bit| {
+                            TYPE::from(((map.window_block as u16) << 8) | (index * 8 + bit) as u16)
+                        })
+                })
+        })
+    }
+
+    /// Transforms the inner data into its owned type
+    pub fn into_owned<'b>(self) -> NSEC<'b> {
+        NSEC {
+            next_name: self.next_name.into_owned(),
+            type_bit_maps: self
+                .type_bit_maps
+                .into_iter()
+                .map(|map| NsecTypeBitMap {
+                    window_block: map.window_block,
+                    bitmap: map.bitmap.into_owned().into(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<'a> PacketPart<'a> for NSEC<'a> {
+    fn parse(data: &'a [u8], position: usize) -> crate::Result<Self>
+    where
+        Self: Sized,
+    {
+        let next_name = Name::parse(data, position)?;
+        let mut offset = position + next_name.len();
+
+        let mut type_bit_maps = Vec::new();
+        while offset < data.len() {
+            if offset + 2 > data.len() {
+                return Err(SimpleDnsError::InsufficientData);
+            }
+
+            let window_block = data[offset];
+            let length = data[offset + 1] as usize;
+            if length == 0 || length > 32 {
+                return Err(SimpleDnsError::InvalidDnsPacket);
+            }
+
+            offset += 2;
+            if offset + length > data.len() {
+                return Err(SimpleDnsError::InsufficientData);
+            }
+
+            type_bit_maps.push(NsecTypeBitMap {
+                window_block,
+                bitmap: Cow::Borrowed(&data[offset..offset + length]),
+            });
+            offset += length;
+        }
+
+        Ok(Self {
+            next_name,
+            type_bit_maps,
+        })
+    }
+
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
+        self.next_name.write_to(out)?;
+
+        for map in self.type_bit_maps.iter() {
+            out.write_all(&[map.window_block, map.bitmap.len() as u8])?;
+            out.write_all(&map.bitmap)?;
+        }
+
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        let bit_maps_len: usize = self.type_bit_maps.iter().map(|m| m.bitmap.len() + 2).sum();
+        self.next_name.len() + bit_maps_len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{rdata::RData, ResourceRecord, CLASS};
+
+    use super::*;
+
+    #[test]
+    fn parse_and_write_nsec() {
+        // example from RFC 4034 section 4.3
+        let data = b"\x04host\x07example\x03com\x00\x00\x06\x40\x01\x00\x00\x00\x03\x04\x1b\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x20";
+
+        let nsec = NSEC::parse(data, 0).unwrap();
+        assert_eq!("host.example.com", nsec.next_name.to_string());
+        assert_eq!(2, nsec.type_bit_maps.len());
+        assert_eq!(data.len(), nsec.len());
+
+        for rtype in [
+            TYPE::A,
+            TYPE::MX,
+            TYPE::Unknown(46),
+            TYPE::NSEC,
+            TYPE::Unknown(1234),
+        ] {
+            assert!(nsec.contains(rtype));
+        }
+        assert!(!nsec.contains(TYPE::AAAA));
+
+        assert_eq!(
+            nsec,
+            NSEC::new(
+                Name::new_unchecked("host.example.com"),
+                &[
+                    TYPE::Unknown(1234),
+                    TYPE::A,
+                    TYPE::MX,
+                    TYPE::Unknown(46),
+                    TYPE::NSEC
+                ]
+            )
+        );
+
+        let mut written = Vec::new();
+        nsec.write_to(&mut written).unwrap();
+        assert_eq!(&data[..], &written[..]);
+    }
+
+    #[test]
+    fn nsec_round_trip_through_resource_record() {
+        let rr = ResourceRecord::new(
+            Name::new_unchecked("host.local"),
+            CLASS::IN,
+            10,
+            RData::NSEC(NSEC::new(
+                Name::new_unchecked("host.local"),
+                &[TYPE::A, TYPE::TXT],
+            )),
+        );
+
+        let mut data = Vec::new();
+        rr.write_to(&mut data).unwrap();
+        assert_eq!(data.len(), rr.len());
+        assert_eq!(rr, ResourceRecord::parse(&data, 0).unwrap());
+    }
+}
@@ -1,4 +1,5 @@
-use std::borrow::Cow;
+use crate::dns::write::Write;
+use alloc::{borrow::Cow, vec::Vec};
 
 use crate::dns::{PacketPart, MAX_NULL_LENGTH};
 
@@ -6,6 +7,7 @@ use super::RR;
 
 /// NULL resources are used to represent any kind of information.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NULL<'a> {
     length: u16,
     data: Cow<'a, [u8]>,
@@ -18,13 +20,17 @@ impl<'a> RR for NULL<'a> {
 impl<'a> NULL<'a> {
     /// Creates a new NULL rdata
     pub fn new(data: &'a [u8]) -> crate::Result<Self> {
+        Self::internal_new(Cow::Borrowed(data))
+    }
+
+    fn internal_new(data: Cow<'a, [u8]>) -> crate::Result<Self> {
         if data.len() > MAX_NULL_LENGTH {
             return Err(crate::SimpleDnsError::InvalidDnsPacket);
         }
 
         Ok(Self {
             length: data.len() as u16,
-            data: Cow::Borrowed(data),
+            data,
         })
     }
 
@@ -42,6 +48,14 @@ impl<'a> NULL<'a> {
     }
 }
 
+impl<'a> TryFrom<Vec<u8>> for NULL<'a> {
+    type Error = crate::SimpleDnsError;
+
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        NULL::internal_new(Cow::Owned(value))
+    }
+}
+
 impl<'a> PacketPart<'a> for NULL<'a> {
     fn parse(data: &'a [u8], position: usize) -> crate::Result<Self>
     where
@@ -50,9 +64,8 @@ impl<'a> PacketPart<'a> for NULL<'a> {
         Self::new(&data[position..])
     }
 
-    fn write_to<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         out.write_all(&self.data)
-            .map_err(crate::SimpleDnsError::from)
     }
 
     fn len(&self) -> usize {
@@ -0,0 +1,81 @@
+use crate::dns::write::Write;
+use alloc::borrow::Cow;
+
+use crate::dns::PacketPart;
+
+use super::RR;
+
+/// The OPENPGPKEY record is used to associate an OpenPGP transferable public key with an email address,
+/// [RFC 7929](https://datatracker.ietf.org/doc/html/rfc7929)
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OPENPGPKEY<'a> {
+    /// A single OpenPGP Transferable Public Key, as defined in [RFC 4880](https://datatracker.ietf.org/doc/html/rfc4880#section-11.1)
+    pub public_key: Cow<'a, [u8]>,
+}
+
+impl<'a> RR for OPENPGPKEY<'a> {
+    const TYPE_CODE: u16 = 61;
+}
+
+impl<'a> OPENPGPKEY<'a> {
+    /// Transforms the inner data into its owned type
+    pub fn into_owned<'b>(self) -> OPENPGPKEY<'b> {
+        OPENPGPKEY {
+            public_key: self.public_key.into_owned().into(),
+        }
+    }
+}
+
+impl<'a> PacketPart<'a> for OPENPGPKEY<'a> {
+    fn parse(data: &'a [u8], position: usize) -> crate::Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self {
+            public_key: Cow::Borrowed(&data[position..]),
+        })
+    }
+
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
+        out.write_all(&self.public_key)
+    }
+
+    fn len(&self) -> usize {
+        self.public_key.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{rdata::RData, Name, ResourceRecord, CLASS};
+
+    use super::*;
+
+    #[test]
+    fn parse_and_write_openpgpkey() {
+        let public_key: Vec<u8> = (0..4096u32).map(|i| (i % 251) as u8).collect();
+        let rr = ResourceRecord::new(
+            Name::new_unchecked(
+                "c93f1e400f26708f98cb19d936620da35eec8f72e57f9eec01c1afd6._openpgpkey.example.com",
+            ),
+            CLASS::IN,
+            10,
+            RData::OPENPGPKEY(OPENPGPKEY {
+                public_key: Cow::Borrowed(&public_key),
+            }),
+        );
+
+        let mut data = Vec::new();
+        assert!(rr.write_to(&mut data).is_ok());
+        assert_eq!(data.len(), rr.len());
+
+        let parsed = match ResourceRecord::parse(&data, 0).unwrap().rdata {
+            RData::OPENPGPKEY(rdata) => rdata,
+            _ => unreachable!(),
+        };
+
+        assert_eq!(4096, parsed.len());
+        assert_eq!(public_key, *parsed.public_key);
+    }
+}
@@ -1,14 +1,16 @@
+use crate::dns::write::Write;
 use crate::{
     dns::{header::Header, PacketPart},
     RCODE,
 };
-use std::borrow::Cow;
+use alloc::{borrow::Cow, string::String, vec::Vec};
 
 use super::RR;
 
 pub mod masks {
-    pub const RCODE_MASK: u32 = 0b0000_0000_0000_0000_0000_0000_1111_1111;
-    pub const VERSION_MASK: u32 = 0b0000_0000_0000_0000_1111_1111_0000_0000;
+    pub const RCODE_MASK: u32 = 0b1111_1111_0000_0000_0000_0000_0000_0000;
+    pub const VERSION_MASK: u32 = 0b0000_0000_1111_1111_0000_0000_0000_0000;
+    pub const DNSSEC_OK_MASK: u32 = 0b0000_0000_0000_0000_1000_0000_0000_0000;
 }
 
 /// OPT is a pseudo-rr used to carry control information  
@@ -18,6 +20,7 @@ pub mod masks {
 /// There must be only one OPT record in the message.
 /// If a query message with more than one OPT RR is received, a FORMERR (RCODE=1) MUST be returned.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OPT<'a> {
     /// The variable part of this OPT RR
     pub opt_codes: Vec<OPTCode<'a>>,
@@ -26,6 +29,9 @@ pub struct OPT<'a> {
 
     /// EDNS version supported by the responder
     pub version: u8,
+    /// The DNSSEC OK (DO) bit, set by queriers to request the DNSSEC records (RRSIG, DNSKEY...) of the answers,
+    /// [RFC 3225](https://datatracker.ietf.org/doc/html/rfc3225)
+    pub dnssec_ok: bool,
 }
 
 impl<'a> RR for OPT<'a> {
@@ -46,6 +52,7 @@ impl<'a> PacketPart<'a> for OPT<'a> {
         // version comes from ttl
         let ttl = u32::from_be_bytes(data[position - 6..position - 2].try_into()?);
         let version = ((ttl & masks::VERSION_MASK) >> masks::VERSION_MASK.trailing_zeros()) as u8;
+        let dnssec_ok = ttl & masks::DNSSEC_OK_MASK != 0;
 
         let mut opt_codes = Vec::new();
         while position < data.len() {
@@ -74,10 +81,11 @@ impl<'a> PacketPart<'a> for OPT<'a> {
             opt_codes,
             udp_packet_size,
             version,
+            dnssec_ok,
         })
     }
 
-    fn write_to<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         for code in self.opt_codes.iter() {
             out.write_all(&code.code.to_be_bytes())?;
             out.write_all(&(code.data.len() as u16).to_be_bytes())?;
@@ -94,22 +102,35 @@ impl<'a> PacketPart<'a> for OPT<'a> {
 
 impl<'a> OPT<'a> {
     pub(crate) fn extract_rcode_from_ttl(ttl: u32, header: &Header) -> RCODE {
-        let mut rcode = (ttl & masks::RCODE_MASK) << 4;
-        rcode |= header.response_code as u32;
+        let mut rcode = ((ttl & masks::RCODE_MASK) >> masks::RCODE_MASK.trailing_zeros()) << 4;
+        rcode |= u16::from(header.response_code) as u32;
         RCODE::from(rcode as u16)
     }
 
     pub(crate) fn encode_ttl(&self, header: &Header) -> u32 {
-        let mut ttl: u32 = (header.response_code as u32 & masks::RCODE_MASK) >> 4;
+        // the TTL holds the upper 8 bits of the extended RCODE, the header holds the lower 4 bits
+        let mut ttl: u32 = ((u16::from(header.response_code) as u32) >> 4)
+            << masks::RCODE_MASK.trailing_zeros()
+            & masks::RCODE_MASK;
         ttl |= (self.version as u32) << masks::VERSION_MASK.trailing_zeros();
+        if self.dnssec_ok {
+            ttl |= masks::DNSSEC_OK_MASK;
+        }
         ttl
     }
+
+    /// Returns the options of this record, parsed with [`EdnsOption::parse`]
+    pub fn options(&self) -> impl Iterator<Item = crate::Result<EdnsOption<'_>>> {
+        self.opt_codes.iter().map(EdnsOption::parse)
+    }
+
     /// Transforms the inner data into its owned type
     pub fn into_owned<'b>(self) -> OPT<'b> {
         OPT {
             // length: self.length,
             udp_packet_size: self.udp_packet_size,
             version: self.version,
+            dnssec_ok: self.dnssec_ok,
             opt_codes: self.opt_codes.into_iter().map(|o| o.into_owned()).collect(),
         }
     }
@@ -117,6 +138,7 @@ impl<'a> OPT<'a> {
 
 /// Represents the variable part of an OPT rr
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OPTCode<'a> {
     // TODO: include an OPT_CODE enum???
     /// Assigned by the Expert Review process as defined by the DNSEXT working group and the IESG.
@@ -135,6 +157,138 @@ impl<'a> OPTCode<'a> {
     }
 }
 
+const NSID_CODE: u16 = 3;
+const COOKIE_CODE: u16 = 10;
+const EXTENDED_ERROR_CODE: u16 = 15;
+
+/// An EDNS option with a known representation, read from and written to the [`OPTCode`] list of an [`OPT`] record
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EdnsOption<'a> {
+    /// Name Server Identifier, [RFC 5001](https://datatracker.ietf.org/doc/html/rfc5001).
+    /// Clients request it with an empty identifier, servers reply with an opaque identifier
+    Nsid(Cow<'a, [u8]>),
+    /// DNS Cookie, used to detect spoofed responses, [RFC 7873](https://datatracker.ietf.org/doc/html/rfc7873)
+    Cookie {
+        /// The cookie generated by the client
+        client: [u8; 8],
+        /// The cookie generated by the server, between 8 and 32 bytes long. Clients send `None` until they learn it
+        server: Option<Cow<'a, [u8]>>,
+    },
+    /// Extended DNS Error, describes why a query failed, [RFC 8914](https://datatracker.ietf.org/doc/html/rfc8914)
+    ExtendedError {
+        /// The reason of the error, like 15 (Blocked) or 18 (Prohibited)
+        info_code: u16,
+        /// Additional text for humans, empty when not present
+        extra_text: Cow<'a, str>,
+    },
+    /// An option without a specific representation
+    Unknown(OPTCode<'a>),
+}
+
+impl<'a> EdnsOption<'a> {
+    /// Parses the data of `option` according to its code
+    pub fn parse(option: &'a OPTCode<'_>) -> crate::Result<Self> {
+        let data = &option.data;
+        match option.code {
+            NSID_CODE => Ok(EdnsOption::Nsid(Cow::Borrowed(data))),
+            COOKIE_CODE => {
+                if data.len() < 8 {
+                    return Err(crate::SimpleDnsError::InsufficientData);
+                }
+
+                let server = match &data[8..] {
+                    [] => None,
+                    server if is_valid_server_cookie(server) => Some(Cow::Borrowed(server)),
+                    _ => return Err(crate::SimpleDnsError::InvalidDnsPacket),
+                };
+
+                Ok(EdnsOption::Cookie {
+                    client: data[..8].try_into()?,
+                    server,
+                })
+            }
+            EXTENDED_ERROR_CODE => {
+                if data.len() < 2 {
+                    return Err(crate::SimpleDnsError::InsufficientData);
+                }
+
+                Ok(EdnsOption::ExtendedError {
+                    info_code: u16::from_be_bytes(data[..2].try_into()?),
+                    extra_text: String::from_utf8_lossy(&data[2..]),
+                })
+            }
+            code => Ok(EdnsOption::Unknown(OPTCode {
+                code,
+                data: Cow::Borrowed(data),
+            })),
+        }
+    }
+
+    /// Returns the option code of this option
+    pub fn code(&self) -> u16 {
+        match self {
+            EdnsOption::Nsid(_) => NSID_CODE,
+            EdnsOption::Cookie { .. } => COOKIE_CODE,
+            EdnsOption::ExtendedError { .. } => EXTENDED_ERROR_CODE,
+            EdnsOption::Unknown(option) => option.code,
+        }
+    }
+
+    /// Transforms the inner data into its owned type
+    pub fn into_owned<'b>(self) -> EdnsOption<'b> {
+        match self {
+            EdnsOption::Nsid(nsid) => EdnsOption::Nsid(nsid.into_owned().into()),
+            EdnsOption::Cookie { client, server } => EdnsOption::Cookie {
+                client,
+                server: server.map(|server| server.into_owned().into()),
+            },
+            EdnsOption::ExtendedError {
+                info_code,
+                extra_text,
+            } => EdnsOption::ExtendedError {
+                info_code,
+                extra_text: extra_text.into_owned().into(),
+            },
+            EdnsOption::Unknown(option) => EdnsOption::Unknown(option.into_owned()),
+        }
+    }
+}
+
+fn is_valid_server_cookie(server: &[u8]) -> bool {
+    (8..=32).contains(&server.len())
+}
+
+impl<'a> TryFrom<EdnsOption<'a>> for OPTCode<'a> {
+    type Error = crate::SimpleDnsError;
+
+    /// Writes the data of `option`, fails for server cookies outside of the 8 to 32 bytes range
+    fn try_from(option: EdnsOption<'a>) -> Result<Self, Self::Error> {
+        let code = option.code();
+        let data = match option {
+            EdnsOption::Nsid(nsid) => nsid.into_owned(),
+            EdnsOption::Cookie { client, server } => {
+                let server = server.unwrap_or_default();
+                if !server.is_empty() && !is_valid_server_cookie(&server) {
+                    return Err(crate::SimpleDnsError::AttemptedInvalidOperation);
+                }
+
+                [&client[..], &server].concat()
+            }
+            EdnsOption::ExtendedError {
+                info_code,
+                extra_text,
+            } => [&info_code.to_be_bytes()[..], extra_text.as_bytes()].concat(),
+            EdnsOption::Unknown(option) => return Ok(option),
+        };
+
+        Ok(OPTCode {
+            code,
+            data: data.into(),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{rdata::RData, Name, ResourceRecord};
@@ -148,6 +302,7 @@ mod tests {
         let opt = OPT {
             udp_packet_size: 500,
             version: 2,
+            dnssec_ok: false,
             opt_codes: Vec::new(),
         };
         let opt_rr = ResourceRecord {
@@ -182,6 +337,7 @@ mod tests {
         let opt = OPT {
             udp_packet_size: 500,
             version: 2,
+            dnssec_ok: false,
             opt_codes: vec![
                 OPTCode {
                     code: 1,
@@ -227,6 +383,224 @@ mod tests {
         assert_eq!(vec![255, 255], *opt_code.data);
     }
 
+    #[test]
+    fn dnssec_ok_round_trip() {
+        for dnssec_ok in [true, false] {
+            let mut packet = crate::Packet::new_query(1);
+            *packet.opt_mut() = Some(OPT {
+                udp_packet_size: 1232,
+                version: 0,
+                dnssec_ok,
+                opt_codes: Vec::new(),
+            });
+
+            let data = packet.build_bytes_vec().unwrap();
+            // the DO bit is the top bit of the TTL, after the root name, type and class
+            let ttl_position = data.len() - 6;
+            assert_eq!(dnssec_ok, data[ttl_position + 2] & 0b1000_0000 != 0);
+
+            let parsed = crate::Packet::parse(&data).unwrap();
+            assert_eq!(dnssec_ok, parsed.opt().unwrap().dnssec_ok);
+            assert_eq!(0, parsed.opt().unwrap().version);
+        }
+    }
+
+    #[test]
+    fn extended_rcode_and_version_ttl_layout() {
+        let mut packet = crate::Packet::new_reply(1);
+        *packet.rcode_mut() = RCODE::Unknown(0xab5);
+        *packet.opt_mut() = Some(OPT {
+            udp_packet_size: 1232,
+            version: 7,
+            dnssec_ok: true,
+            opt_codes: Vec::new(),
+        });
+
+        let data = packet.build_bytes_vec().unwrap();
+        // the TTL is [extended rcode, version, DO and Z, Z]
+        let ttl_position = data.len() - 6;
+        assert_eq!(
+            [0xab, 7, 0b1000_0000, 0],
+            data[ttl_position..ttl_position + 4]
+        );
+
+        let parsed = crate::Packet::parse(&data).unwrap();
+        assert_eq!(RCODE::Unknown(0xab5), parsed.rcode());
+        assert_eq!(7, parsed.opt().unwrap().version);
+        assert!(parsed.opt().unwrap().dnssec_ok);
+    }
+
+    #[test]
+    fn parse_and_write_extended_error() {
+        let header = Header::new_reply(1, crate::OPCODE::StandardQuery);
+
+        let mut opt = OPT {
+            udp_packet_size: 1232,
+            version: 0,
+            dnssec_ok: false,
+            opt_codes: Vec::new(),
+        };
+        opt.opt_codes.push(OPTCode {
+            code: 15,
+            data: Cow::Borrowed(b"\x00\x0fblocked by policy"),
+        });
+
+        let opt_rr = ResourceRecord {
+            ttl: opt.encode_ttl(&header),
+            name: Name::new_unchecked("."),
+            class: crate::CLASS::IN,
+            cache_flush: false,
+            rdata: RData::OPT(opt),
+        };
+
+        let mut data = Vec::new();
+        opt_rr.write_to(&mut data).unwrap();
+
+        let opt = match ResourceRecord::parse(&data, 0).unwrap().rdata {
+            RData::OPT(rdata) => rdata,
+            _ => unreachable!(),
+        };
+
+        let options: Vec<EdnsOption> = opt.options().collect::<crate::Result<_>>().unwrap();
+        let extended_error = EdnsOption::ExtendedError {
+            info_code: 15,
+            extra_text: "blocked by policy".into(),
+        };
+        assert_eq!(vec![extended_error.clone()], options);
+
+        let written = OPTCode::try_from(extended_error).unwrap();
+        assert_eq!(opt.opt_codes[0], written);
+
+        let without_text = OPTCode::try_from(EdnsOption::ExtendedError {
+            info_code: 18,
+            extra_text: "".into(),
+        })
+        .unwrap();
+        assert_eq!(vec![0, 18], *without_text.data);
+        assert_eq!(
+            EdnsOption::ExtendedError {
+                info_code: 18,
+                extra_text: "".into()
+            },
+            EdnsOption::parse(&without_text).unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_other_options() {
+        let padding = OPTCode {
+            code: 12,
+            data: Cow::Borrowed(&[0, 0, 0, 0]),
+        };
+        assert_eq!(
+            EdnsOption::Unknown(padding.clone()),
+            EdnsOption::parse(&padding).unwrap()
+        );
+
+        let truncated = OPTCode {
+            code: 15,
+            data: Cow::Borrowed(&[0]),
+        };
+        assert!(EdnsOption::parse(&truncated).is_err());
+    }
+
+    #[test]
+    fn parse_and_write_client_cookie() {
+        let client = [1, 2, 3, 4, 5, 6, 7, 8];
+        let option = OPTCode::try_from(EdnsOption::Cookie {
+            client,
+            server: None,
+        })
+        .unwrap();
+
+        assert_eq!(10, option.code);
+        assert_eq!(client, *option.data);
+        assert_eq!(
+            EdnsOption::Cookie {
+                client,
+                server: None
+            },
+            EdnsOption::parse(&option).unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_and_write_client_and_server_cookie() {
+        let client = [1, 2, 3, 4, 5, 6, 7, 8];
+        let server = [9u8; 16];
+        let option = OPTCode::try_from(EdnsOption::Cookie {
+            client,
+            server: Some(Cow::Borrowed(&server)),
+        })
+        .unwrap();
+
+        assert_eq!(24, option.data.len());
+        assert_eq!(
+            EdnsOption::Cookie {
+                client,
+                server: Some(Cow::Borrowed(&server))
+            },
+            EdnsOption::parse(&option).unwrap()
+        );
+
+        for len in [1, 7, 33] {
+            let invalid = EdnsOption::Cookie {
+                client,
+                server: Some(vec![0; len].into()),
+            };
+            assert!(OPTCode::try_from(invalid).is_err());
+
+            let invalid = OPTCode {
+                code: 10,
+                data: vec![0; 8 + len].into(),
+            };
+            assert!(EdnsOption::parse(&invalid).is_err());
+        }
+
+        let truncated = OPTCode {
+            code: 10,
+            data: Cow::Borrowed(&[0; 4]),
+        };
+        assert!(EdnsOption::parse(&truncated).is_err());
+    }
+
+    #[test]
+    fn nsid_round_trip() {
+        let header = Header::new_reply(1, crate::OPCODE::StandardQuery);
+
+        let request = EdnsOption::Nsid(Cow::Borrowed(&[]));
+        let response = EdnsOption::Nsid(Cow::Borrowed(b"ns1.example"));
+        let opt = OPT {
+            udp_packet_size: 1232,
+            version: 0,
+            dnssec_ok: false,
+            opt_codes: vec![
+                OPTCode::try_from(request.clone()).unwrap(),
+                OPTCode::try_from(response.clone()).unwrap(),
+            ],
+        };
+        assert_eq!(3, opt.opt_codes[0].code);
+
+        let opt_rr = ResourceRecord {
+            ttl: opt.encode_ttl(&header),
+            name: Name::new_unchecked("."),
+            class: crate::CLASS::IN,
+            cache_flush: false,
+            rdata: RData::OPT(opt),
+        };
+
+        let mut data = Vec::new();
+        opt_rr.write_to(&mut data).unwrap();
+
+        let opt = match ResourceRecord::parse(&data, 0).unwrap().rdata {
+            RData::OPT(rdata) => rdata,
+            _ => unreachable!(),
+        };
+
+        let options: Vec<EdnsOption> = opt.options().collect::<crate::Result<_>>().unwrap();
+        assert_eq!(vec![request, response], options);
+    }
+
     // #[test]
     // fn parse_sample() -> Result<(), Box<dyn std::error::Error>> {
     //     let sample_file = std::fs::read("samples/zonefile/OPT.sample")?;
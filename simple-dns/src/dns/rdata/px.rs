@@ -0,0 +1,126 @@
+use crate::dns::write::Write;
+use core::convert::TryInto;
+
+use crate::{
+    dns::{Name, PacketPart},
+    SimpleDnsError,
+};
+
+use super::RR;
+
+/// The PX record is used to map between RFC 822 and X.400 addresses,
+/// [RFC 2163](https://datatracker.ietf.org/doc/html/rfc2163#section-4)
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PX<'a> {
+    /// The preference given to this RR among others at the same owner, lower values are preferred
+    pub preference: u16,
+    /// A [Name](`Name`) with the RFC 822 part of the mapping. This name is not compressed
+    pub map822: Name<'a>,
+    /// A [Name](`Name`) with the X.400 part of the mapping. This name is not compressed
+    pub mapx400: Name<'a>,
+}
+
+impl<'a> RR for PX<'a> {
+    const TYPE_CODE: u16 = 26;
+}
+
+impl<'a> PX<'a> {
+    /// Transforms the inner data into its owned type
+    pub fn into_owned<'b>(self) -> PX<'b> {
+        PX {
+            preference: self.preference,
+            map822: self.map822.into_owned(),
+            mapx400: self.mapx400.into_owned(),
+        }
+    }
+}
+
+impl<'a> PacketPart<'a> for PX<'a> {
+    fn parse(data: &'a [u8], position: usize) -> crate::Result<Self>
+    where
+        Self: Sized,
+    {
+        if data.len() < position + 2 {
+            return Err(SimpleDnsError::InsufficientData);
+        }
+
+        let preference = u16::from_be_bytes(data[position..position + 2].try_into()?);
+        let map822 = Name::parse(data, position + 2)?;
+        let mapx400 = Name::parse(data, position + 2 + map822.len())?;
+
+        Ok(Self {
+            preference,
+            map822,
+            mapx400,
+        })
+    }
+
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
+        out.write_all(&self.preference.to_be_bytes())?;
+        self.map822.write_to(out)?;
+        self.mapx400.write_to(out)
+    }
+
+    fn len(&self) -> usize {
+        2 + self.map822.len() + self.mapx400.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dns::write::{Buffer, NameRefs};
+    use crate::{rdata::RData, ResourceRecord, CLASS};
+
+    use super::*;
+
+    #[test]
+    fn parse_and_write_px() {
+        let rr = ResourceRecord::new(
+            Name::new_unchecked("*.ab.net2.it"),
+            CLASS::IN,
+            10,
+            RData::PX(PX {
+                preference: 10,
+                map822: Name::new_unchecked("ab.net2.it"),
+                mapx400: Name::new_unchecked("O-ab.PRMD-net2.ADMDb.C-it"),
+            }),
+        );
+
+        let mut data = Vec::new();
+        assert!(rr.write_to(&mut data).is_ok());
+        assert_eq!(data.len(), rr.len());
+
+        let px = match ResourceRecord::parse(&data, 0).unwrap().rdata {
+            RData::PX(rdata) => rdata,
+            _ => unreachable!(),
+        };
+
+        assert_eq!(10, px.preference);
+        assert_eq!("ab.net2.it", px.map822.to_string());
+        assert_eq!("O-ab.PRMD-net2.ADMDb.C-it", px.mapx400.to_string());
+    }
+
+    #[test]
+    fn px_should_not_be_compressed() {
+        let rr = ResourceRecord::new(
+            Name::new_unchecked("ab.net2.it"),
+            CLASS::IN,
+            10,
+            RData::PX(PX {
+                preference: 10,
+                map822: Name::new_unchecked("ab.net2.it"),
+                mapx400: Name::new_unchecked("PRMD-net2.ADMDb.C-it"),
+            }),
+        );
+
+        let mut plain = Vec::new();
+        let mut compressed = Buffer::new(Vec::new());
+        let mut names = NameRefs::new();
+
+        assert!(rr.write_to(&mut plain).is_ok());
+        assert!(rr.write_compressed_to(&mut compressed, &mut names).is_ok());
+
+        assert_eq!(plain, compressed.into_inner());
+    }
+}
@@ -1,11 +1,11 @@
-use std::collections::HashMap;
-
+use crate::dns::write::{NameRefs, Seek, Write};
 use crate::dns::{Name, PacketPart};
 
 use super::RR;
 
 /// RP Responsible Person, [RFC 1183](https://datatracker.ietf.org/doc/html/rfc1183#section-2.2)
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RP<'a> {
     /// A [Name](`Name`) which specifies a mailbox for the responsble person.
     pub mbox: Name<'a>,
@@ -37,15 +37,15 @@ impl<'a> PacketPart<'a> for RP<'a> {
         Ok(RP { mbox, txt })
     }
 
-    fn write_to<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         self.mbox.write_to(out)?;
         self.txt.write_to(out)
     }
 
-    fn write_compressed_to<T: std::io::Write + std::io::Seek>(
+    fn write_compressed_to<T: Write + Seek>(
         &self,
         out: &mut T,
-        name_refs: &mut HashMap<u64, usize>,
+        name_refs: &mut NameRefs,
     ) -> crate::Result<()> {
         self.mbox.write_compressed_to(out, name_refs)?;
         self.txt.write_compressed_to(out, name_refs)
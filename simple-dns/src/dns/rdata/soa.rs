@@ -1,4 +1,5 @@
-use std::{collections::HashMap, convert::TryInto};
+use crate::dns::write::{NameRefs, Seek, Write};
+use core::convert::TryInto;
 
 use crate::dns::{Name, PacketPart};
 
@@ -6,6 +7,7 @@ use super::RR;
 
 /// SOA records are used to mark the start of a zone of authority
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SOA<'a> {
     /// The [Name](`Name`) of the name server that was the original or primary source of data for this zone.
     pub mname: Name<'a>,
@@ -42,7 +44,7 @@ impl<'a> SOA<'a> {
         }
     }
 
-    fn write_common<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_common<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         out.write_all(&self.serial.to_be_bytes())?;
         out.write_all(&self.refresh.to_be_bytes())?;
         out.write_all(&self.retry.to_be_bytes())?;
@@ -62,6 +64,10 @@ impl<'a> PacketPart<'a> for SOA<'a> {
         let rname = Name::parse(data, position + mname.len())?;
         let offset = position + mname.len() + rname.len();
 
+        if data.len() < offset + 20 {
+            return Err(crate::SimpleDnsError::InsufficientData);
+        }
+
         let serial = u32::from_be_bytes(data[offset..offset + 4].try_into()?);
         let refresh = i32::from_be_bytes(data[offset + 4..offset + 8].try_into()?);
         let retry = i32::from_be_bytes(data[offset + 8..offset + 12].try_into()?);
@@ -79,16 +85,16 @@ impl<'a> PacketPart<'a> for SOA<'a> {
         })
     }
 
-    fn write_to<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         self.mname.write_to(out)?;
         self.rname.write_to(out)?;
         self.write_common(out)
     }
 
-    fn write_compressed_to<T: std::io::Write + std::io::Seek>(
+    fn write_compressed_to<T: Write + Seek>(
         &self,
         out: &mut T,
-        name_refs: &mut HashMap<u64, usize>,
+        name_refs: &mut NameRefs,
     ) -> crate::Result<()> {
         self.mname.write_compressed_to(out, name_refs)?;
         self.rname.write_compressed_to(out, name_refs)?;
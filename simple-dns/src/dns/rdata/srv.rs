@@ -1,4 +1,5 @@
-use std::convert::TryInto;
+use crate::dns::write::{NameRefs, Seek, Write};
+use core::convert::TryInto;
 
 use crate::dns::PacketPart;
 use crate::Name;
@@ -7,6 +8,7 @@ use super::RR;
 
 /// SRV records specifies the location of the server(s) for a specific protocol and domain.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SRV<'a> {
     /// The priority of this target host.  
     /// A client MUST attempt to contact the target host with the lowest-numbered priority it can
@@ -18,7 +20,9 @@ pub struct SRV<'a> {
     pub weight: u16,
     /// The port on this target host of this service
     pub port: u16,
-    /// The domain name of the target host
+    /// The domain name of the target host.  
+    /// The target is compressed when writing with compression, like Multicast DNS does,
+    /// [RFC 6762 section 18.14](https://datatracker.ietf.org/doc/html/rfc6762#section-18.14)
     pub target: Name<'a>,
 }
 
@@ -43,6 +47,10 @@ impl<'a> PacketPart<'a> for SRV<'a> {
     where
         Self: Sized,
     {
+        if data.len() < position + 6 {
+            return Err(crate::SimpleDnsError::InsufficientData);
+        }
+
         let priority = u16::from_be_bytes(data[position..position + 2].try_into()?);
         let weight = u16::from_be_bytes(data[position + 2..position + 4].try_into()?);
         let port = u16::from_be_bytes(data[position + 4..position + 6].try_into()?);
@@ -56,7 +64,7 @@ impl<'a> PacketPart<'a> for SRV<'a> {
         })
     }
 
-    fn write_to<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         out.write_all(&self.priority.to_be_bytes())?;
         out.write_all(&self.weight.to_be_bytes())?;
         out.write_all(&self.port.to_be_bytes())?;
@@ -64,6 +72,18 @@ impl<'a> PacketPart<'a> for SRV<'a> {
         self.target.write_to(out)
     }
 
+    fn write_compressed_to<T: Write + Seek>(
+        &self,
+        out: &mut T,
+        name_refs: &mut NameRefs,
+    ) -> crate::Result<()> {
+        out.write_all(&self.priority.to_be_bytes())?;
+        out.write_all(&self.weight.to_be_bytes())?;
+        out.write_all(&self.port.to_be_bytes())?;
+
+        self.target.write_compressed_to(out, name_refs)
+    }
+
     fn len(&self) -> usize {
         self.target.len() + 6
     }
@@ -71,8 +91,7 @@ impl<'a> PacketPart<'a> for SRV<'a> {
 
 #[cfg(test)]
 mod tests {
-    use std::{collections::HashMap, io::Cursor};
-
+    use crate::dns::write::{Buffer, NameRefs};
     use crate::{rdata::RData, ResourceRecord};
 
     use super::*;
@@ -100,22 +119,33 @@ mod tests {
     }
 
     #[test]
-    fn srv_should_not_be_compressed() {
-        let srv = SRV {
-            priority: 1,
-            weight: 2,
-            port: 3,
-            target: Name::new("_srv._tcp.example.com").unwrap(),
-        };
+    fn srv_target_is_compressed() -> Result<(), Box<dyn std::error::Error>> {
+        let name = Name::new("_srv._tcp.example.com")?;
+        let srv = ResourceRecord::new(
+            name.clone(),
+            crate::CLASS::IN,
+            10,
+            RData::SRV(SRV {
+                priority: 1,
+                weight: 2,
+                port: 3,
+                target: name.clone(),
+            }),
+        );
 
         let mut plain = Vec::new();
-        let mut compressed = Cursor::new(Vec::new());
-        let mut names = HashMap::new();
+        let mut compressed = Buffer::new(Vec::new());
+        let mut names = NameRefs::new();
 
-        assert!(srv.write_to(&mut plain).is_ok());
-        assert!(srv.write_compressed_to(&mut compressed, &mut names).is_ok());
+        srv.write_to(&mut plain)?;
+        srv.write_compressed_to(&mut compressed, &mut names)?;
 
-        assert_eq!(plain, compressed.into_inner());
+        // the target is written as a pointer to the owner name
+        let compressed = compressed.into_inner();
+        assert_eq!(plain.len() - name.len() + 2, compressed.len());
+        assert_eq!(srv, ResourceRecord::parse(&compressed, 0)?);
+
+        Ok(())
     }
 
     #[test]
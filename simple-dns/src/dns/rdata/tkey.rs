@@ -0,0 +1,183 @@
+use crate::dns::write::Write;
+use alloc::borrow::Cow;
+use core::convert::TryInto;
+
+use crate::{
+    dns::{Name, PacketPart},
+    SimpleDnsError,
+};
+
+use super::RR;
+
+/// The TKEY record is used to establish shared secret keys between a DNS resolver and server,
+/// [RFC 2930](https://datatracker.ietf.org/doc/html/rfc2930#section-2)
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TKEY<'a> {
+    /// A [Name](`Name`) that specifies the key algorithm. This name is not compressed
+    pub algorithm: Name<'a>,
+    /// The start of the key validity interval, in number of seconds since the beginning of 1 January 1970 GMT
+    pub inception: u32,
+    /// The end of the key validity interval, in number of seconds since the beginning of 1 January 1970 GMT
+    pub expiration: u32,
+    /// The general scheme for key agreement or the purpose of the TKEY DNS message
+    pub mode: u16,
+    /// The error code, when the TKEY is part of a response
+    pub error: u16,
+    /// The key exchange data, its meaning depends on the `mode`
+    pub key_data: Cow<'a, [u8]>,
+    /// Reserved for future use
+    pub other_data: Cow<'a, [u8]>,
+}
+
+impl<'a> RR for TKEY<'a> {
+    const TYPE_CODE: u16 = 249;
+}
+
+impl<'a> TKEY<'a> {
+    /// Transforms the inner data into its owned type
+    pub fn into_owned<'b>(self) -> TKEY<'b> {
+        TKEY {
+            algorithm: self.algorithm.into_owned(),
+            inception: self.inception,
+            expiration: self.expiration,
+            mode: self.mode,
+            error: self.error,
+            key_data: self.key_data.into_owned().into(),
+            other_data: self.other_data.into_owned().into(),
+        }
+    }
+}
+
+impl<'a> PacketPart<'a> for TKEY<'a> {
+    fn parse(data: &'a [u8], position: usize) -> crate::Result<Self>
+    where
+        Self: Sized,
+    {
+        let algorithm = Name::parse(data, position)?;
+        let mut offset = position + algorithm.len();
+
+        if data.len() < offset + 14 {
+            return Err(SimpleDnsError::InsufficientData);
+        }
+
+        let inception = u32::from_be_bytes(data[offset..offset + 4].try_into()?);
+        let expiration = u32::from_be_bytes(data[offset + 4..offset + 8].try_into()?);
+        let mode = u16::from_be_bytes(data[offset + 8..offset + 10].try_into()?);
+        let error = u16::from_be_bytes(data[offset + 10..offset + 12].try_into()?);
+        let key_size = u16::from_be_bytes(data[offset + 12..offset + 14].try_into()?) as usize;
+        offset += 14;
+
+        if data.len() < offset + key_size + 2 {
+            return Err(SimpleDnsError::InsufficientData);
+        }
+
+        let key_data = Cow::Borrowed(&data[offset..offset + key_size]);
+        offset += key_size;
+
+        let other_size = u16::from_be_bytes(data[offset..offset + 2].try_into()?) as usize;
+        offset += 2;
+
+        if data.len() < offset + other_size {
+            return Err(SimpleDnsError::InsufficientData);
+        }
+
+        let other_data = Cow::Borrowed(&data[offset..offset + other_size]);
+
+        Ok(Self {
+            algorithm,
+            inception,
+            expiration,
+            mode,
+            error,
+            key_data,
+            other_data,
+        })
+    }
+
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
+        self.algorithm.write_to(out)?;
+        out.write_all(&self.inception.to_be_bytes())?;
+        out.write_all(&self.expiration.to_be_bytes())?;
+        out.write_all(&self.mode.to_be_bytes())?;
+        out.write_all(&self.error.to_be_bytes())?;
+        out.write_all(&(self.key_data.len() as u16).to_be_bytes())?;
+        out.write_all(&self.key_data)?;
+        out.write_all(&(self.other_data.len() as u16).to_be_bytes())?;
+        out.write_all(&self.other_data)
+    }
+
+    fn len(&self) -> usize {
+        self.algorithm.len() + 16 + self.key_data.len() + self.other_data.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{rdata::RData, ResourceRecord, CLASS};
+
+    use super::*;
+
+    #[test]
+    fn parse_and_write_tkey() {
+        let key_data = [
+            0x60, 0x82, 0x01, 0x2a, 0x06, 0x06, 0x2b, 0x06, 0x01, 0x05, 0x05, 0x02, 0xa0, 0x82,
+            0x01, 0x1e, 0x30, 0x82, 0x01, 0x1a, 0xa0, 0x30,
+        ];
+
+        let rr = ResourceRecord::new(
+            Name::new_unchecked("1234-ms-7.example.com"),
+            CLASS::IN,
+            0,
+            RData::TKEY(TKEY {
+                algorithm: Name::new_unchecked("gss-tsig"),
+                inception: 1_700_000_000,
+                expiration: 1_700_086_400,
+                mode: 3,
+                error: 0,
+                key_data: Cow::Borrowed(&key_data),
+                other_data: Cow::Borrowed(&[]),
+            }),
+        );
+
+        let mut data = Vec::new();
+        assert!(rr.write_to(&mut data).is_ok());
+        assert_eq!(data.len(), rr.len());
+
+        let parsed = ResourceRecord::parse(&data, 0).unwrap();
+        assert_eq!(rr, parsed);
+
+        let tkey = match parsed.rdata {
+            RData::TKEY(rdata) => rdata,
+            _ => unreachable!(),
+        };
+
+        assert_eq!("gss-tsig", tkey.algorithm.to_string());
+        assert_eq!(1_700_000_000, tkey.inception);
+        assert_eq!(1_700_086_400, tkey.expiration);
+        assert_eq!(3, tkey.mode);
+        assert_eq!(0, tkey.error);
+        assert_eq!(&key_data[..], &tkey.key_data[..]);
+        assert!(tkey.other_data.is_empty());
+    }
+
+    #[test]
+    fn parse_tkey_with_invalid_lengths() {
+        let tkey = TKEY {
+            algorithm: Name::new_unchecked("gss-tsig"),
+            inception: 0,
+            expiration: 0,
+            mode: 3,
+            error: 0,
+            key_data: Cow::Borrowed(&[1, 2, 3, 4]),
+            other_data: Cow::Borrowed(&[5, 6]),
+        };
+
+        let mut data = Vec::new();
+        assert!(tkey.write_to(&mut data).is_ok());
+        assert_eq!(tkey, TKEY::parse(&data, 0).unwrap());
+
+        assert!(TKEY::parse(&data[..data.len() - 1], 0).is_err());
+        assert!(TKEY::parse(&data[..data.len() - 4], 0).is_err());
+    }
+}
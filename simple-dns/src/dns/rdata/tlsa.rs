@@ -0,0 +1,134 @@
+use crate::dns::write::Write;
+use alloc::borrow::Cow;
+
+use crate::{dns::PacketPart, SimpleDnsError};
+
+use super::RR;
+
+/// The TLSA record is used to associate a TLS server certificate or public key with the domain name where the record is found,
+/// [RFC 6698](https://datatracker.ietf.org/doc/html/rfc6698)
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TLSA<'a> {
+    /// Specifies the provided association that will be used to match the certificate presented in the TLS handshake
+    pub cert_usage: u8,
+    /// Specifies which part of the TLS certificate presented by the server will be matched against the association data
+    pub selector: u8,
+    /// Specifies how the certificate association is presented
+    pub matching_type: u8,
+    /// The "certificate association data" to be matched
+    pub data: Cow<'a, [u8]>,
+}
+
+impl<'a> RR for TLSA<'a> {
+    const TYPE_CODE: u16 = 52;
+}
+
+impl<'a> TLSA<'a> {
+    /// Transforms the inner data into its owned type
+    pub fn into_owned<'b>(self) -> TLSA<'b> {
+        TLSA {
+            cert_usage: self.cert_usage,
+            selector: self.selector,
+            matching_type: self.matching_type,
+            data: self.data.into_owned().into(),
+        }
+    }
+}
+
+impl<'a> PacketPart<'a> for TLSA<'a> {
+    fn parse(data: &'a [u8], position: usize) -> crate::Result<Self>
+    where
+        Self: Sized,
+    {
+        if data.len() < position + 3 {
+            return Err(SimpleDnsError::InsufficientData);
+        }
+
+        Ok(Self {
+            cert_usage: data[position],
+            selector: data[position + 1],
+            matching_type: data[position + 2],
+            data: Cow::Borrowed(&data[position + 3..]),
+        })
+    }
+
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
+        out.write_all(&[self.cert_usage, self.selector, self.matching_type])?;
+        out.write_all(&self.data)
+    }
+
+    fn len(&self) -> usize {
+        self.data.len() + 3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{rdata::RData, Name, ResourceRecord, CLASS};
+
+    use super::*;
+
+    #[test]
+    fn parse_and_write_tlsa() {
+        let tlsa = TLSA {
+            cert_usage: 3,
+            selector: 1,
+            matching_type: 1,
+            data: Cow::Borrowed(b"\x0d\x6f\xce\x33\x35\x34\x07\x76\x0c\xb4\x21\xd2"),
+        };
+
+        let mut data = Vec::new();
+        assert!(tlsa.write_to(&mut data).is_ok());
+
+        let tlsa = TLSA::parse(&data, 0);
+        assert!(tlsa.is_ok());
+        let tlsa = tlsa.unwrap();
+
+        assert_eq!(data.len(), tlsa.len());
+        assert_eq!(3, tlsa.cert_usage);
+        assert_eq!(1, tlsa.selector);
+        assert_eq!(1, tlsa.matching_type);
+        assert_eq!(
+            &b"\x0d\x6f\xce\x33\x35\x34\x07\x76\x0c\xb4\x21\xd2"[..],
+            &tlsa.data[..]
+        );
+    }
+
+    #[test]
+    fn parse_and_write_smimea() {
+        let rr = ResourceRecord::new(
+            Name::new_unchecked(
+                "c93f1e400f26708f98cb19d936620da35eec8f72e57f9eec01c1afd6._smimecert.example.com",
+            ),
+            CLASS::IN,
+            10,
+            RData::SMIMEA(
+                TLSA {
+                    cert_usage: 3,
+                    selector: 0,
+                    matching_type: 1,
+                    data: Cow::Borrowed(b"\x0d\x6f\xce\x33\x35\x34\x07\x76"),
+                }
+                .into(),
+            ),
+        );
+
+        let mut data = Vec::new();
+        assert!(rr.write_to(&mut data).is_ok());
+        assert_eq!(data.len(), rr.len());
+
+        let parsed = ResourceRecord::parse(&data, 0).unwrap();
+        assert_eq!(crate::TYPE::SMIMEA, parsed.rdata.type_code());
+
+        let smimea = match parsed.rdata {
+            RData::SMIMEA(rdata) => rdata,
+            _ => unreachable!(),
+        };
+
+        assert_eq!(3, smimea.cert_usage);
+        assert_eq!(0, smimea.selector);
+        assert_eq!(1, smimea.matching_type);
+        assert_eq!(&b"\x0d\x6f\xce\x33\x35\x34\x07\x76"[..], &smimea.data[..]);
+    }
+}
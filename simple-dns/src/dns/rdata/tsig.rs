@@ -0,0 +1,149 @@
+use crate::dns::write::Write;
+use alloc::borrow::Cow;
+use core::convert::TryInto;
+
+use crate::{
+    dns::{Name, PacketPart},
+    SimpleDnsError,
+};
+
+use super::RR;
+
+/// The TSIG record authenticates a DNS message with a shared secret key, it is always the last record
+/// of the additional section, [RFC 8945](https://datatracker.ietf.org/doc/html/rfc8945#section-4.2)
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TSIG<'a> {
+    /// A [Name](`Name`) that specifies the MAC algorithm, like `hmac-sha256`. This name is not compressed
+    pub algorithm: Name<'a>,
+    /// When the message was signed, in number of seconds since the beginning of 1 January 1970 GMT.
+    /// Only the lower 48 bits are written
+    pub time_signed: u64,
+    /// Seconds of error permitted in `time_signed`
+    pub fudge: u16,
+    /// The MAC of the message
+    pub mac: Cow<'a, [u8]>,
+    /// The ID of the message when it was signed
+    pub original_id: u16,
+    /// The extended RCODE covering TSIG processing, like 16 (BADSIG) or 18 (BADTIME)
+    pub error: u16,
+    /// Empty unless `error` is BADTIME, in which case it holds the time of the server
+    pub other_data: Cow<'a, [u8]>,
+}
+
+impl<'a> RR for TSIG<'a> {
+    const TYPE_CODE: u16 = 250;
+}
+
+impl<'a> TSIG<'a> {
+    /// Transforms the inner data into its owned type
+    pub fn into_owned<'b>(self) -> TSIG<'b> {
+        TSIG {
+            algorithm: self.algorithm.into_owned(),
+            time_signed: self.time_signed,
+            fudge: self.fudge,
+            mac: self.mac.into_owned().into(),
+            original_id: self.original_id,
+            error: self.error,
+            other_data: self.other_data.into_owned().into(),
+        }
+    }
+}
+
+impl<'a> PacketPart<'a> for TSIG<'a> {
+    fn parse(data: &'a [u8], position: usize) -> crate::Result<Self>
+    where
+        Self: Sized,
+    {
+        let algorithm = Name::parse(data, position)?;
+        let mut offset = position + algorithm.len();
+
+        if data.len() < offset + 10 {
+            return Err(SimpleDnsError::InsufficientData);
+        }
+
+        let mut time_signed = [0u8; 8];
+        time_signed[2..].copy_from_slice(&data[offset..offset + 6]);
+        let time_signed = u64::from_be_bytes(time_signed);
+        let fudge = u16::from_be_bytes(data[offset + 6..offset + 8].try_into()?);
+        let mac_size = u16::from_be_bytes(data[offset + 8..offset + 10].try_into()?) as usize;
+        offset += 10;
+
+        if data.len() < offset + mac_size + 6 {
+            return Err(SimpleDnsError::InsufficientData);
+        }
+
+        let mac = Cow::Borrowed(&data[offset..offset + mac_size]);
+        offset += mac_size;
+
+        let original_id = u16::from_be_bytes(data[offset..offset + 2].try_into()?);
+        let error = u16::from_be_bytes(data[offset + 2..offset + 4].try_into()?);
+        let other_size = u16::from_be_bytes(data[offset + 4..offset + 6].try_into()?) as usize;
+        offset += 6;
+
+        if data.len() < offset + other_size {
+            return Err(SimpleDnsError::InsufficientData);
+        }
+
+        let other_data = Cow::Borrowed(&data[offset..offset + other_size]);
+
+        Ok(Self {
+            algorithm,
+            time_signed,
+            fudge,
+            mac,
+            original_id,
+            error,
+            other_data,
+        })
+    }
+
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
+        self.algorithm.write_to(out)?;
+        out.write_all(&self.time_signed.to_be_bytes()[2..])?;
+        out.write_all(&self.fudge.to_be_bytes())?;
+        out.write_all(&(self.mac.len() as u16).to_be_bytes())?;
+        out.write_all(&self.mac)?;
+        out.write_all(&self.original_id.to_be_bytes())?;
+        out.write_all(&self.error.to_be_bytes())?;
+        out.write_all(&(self.other_data.len() as u16).to_be_bytes())?;
+        out.write_all(&self.other_data)
+    }
+
+    fn len(&self) -> usize {
+        self.algorithm.len() + 16 + self.mac.len() + self.other_data.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{rdata::RData, ResourceRecord, CLASS};
+
+    use super::*;
+
+    #[test]
+    fn parse_and_write_tsig() {
+        let rr = ResourceRecord::new(
+            Name::new_unchecked("key.example.com"),
+            CLASS::ANY,
+            0,
+            RData::TSIG(TSIG {
+                algorithm: Name::new_unchecked("hmac-sha256"),
+                time_signed: 0x0000_1234_5678_9abc,
+                fudge: 300,
+                mac: Cow::Borrowed(&[1, 2, 3, 4]),
+                original_id: 42,
+                error: 0,
+                other_data: Cow::Borrowed(&[]),
+            }),
+        );
+
+        let mut data = Vec::new();
+        rr.write_to(&mut data).unwrap();
+        assert_eq!(data.len(), rr.len());
+
+        let parsed = ResourceRecord::parse(&data, 0).unwrap();
+        assert_eq!(CLASS::ANY, parsed.class);
+        assert_eq!(rr.rdata, parsed.rdata);
+    }
+}
@@ -1,7 +1,9 @@
-use std::{
-    collections::HashMap,
-    convert::{TryFrom, TryInto},
-};
+use alloc::{format, string::String, vec, vec::Vec};
+use core::convert::TryInto;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use crate::dns::write::Write;
 
 use crate::{dns::PacketPart, CharacterString};
 
@@ -10,10 +12,26 @@ use super::RR;
 /// Represents a TXT Resource Record
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct TXT<'a> {
-    strings: Vec<CharacterString<'a>>,
+    pub(crate) strings: Vec<CharacterString<'a>>,
     size: usize,
 }
 
+/// TXT records are serialized as the list of their character strings
+#[cfg(feature = "serde")]
+impl<'a> serde::Serialize for TXT<'a> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.strings.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, 'a> serde::Deserialize<'de> for TXT<'a> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let strings = Vec::<CharacterString<'a>>::deserialize(deserializer)?;
+        Ok(strings.into_iter().fold(TXT::new(), TXT::with_char_string))
+    }
+}
+
 impl<'a> RR for TXT<'a> {
     const TYPE_CODE: u16 = 16;
 }
@@ -57,19 +75,38 @@ impl<'a> TXT<'a> {
         self
     }
 
+    /// Creates a TXT record from key/value `attributes`, in iteration order.
+    /// Each attribute is written as a `key=value` character string, or a bare `key` when it has no value.
+    ///
+    /// Returns `InvalidCharacterString` when an attribute is longer than 255 bytes
+    pub fn from_attributes<I>(attributes: I) -> crate::Result<Self>
+    where
+        I: IntoIterator<Item = (String, Option<String>)>,
+    {
+        let mut txt = TXT::new();
+        for (key, value) in attributes {
+            match value {
+                Some(value) => txt.add_char_string(format!("{}={}", &key, &value).try_into()?),
+                None => txt.add_char_string(key.try_into()?),
+            }
+        }
+        Ok(txt)
+    }
+
     /// Returns parsed attributes from this TXT Record, valid formats are:
     /// - key=value
     /// - key=
     /// - key
     ///
     /// If a key is duplicated, only the first one will be considered
+    #[cfg(feature = "std")]
     pub fn attributes(&self) -> HashMap<String, Option<String>> {
         let mut attributes = HashMap::new();
 
         for char_str in &self.strings {
-            let mut splited = char_str.data.splitn(2, |c| *c == b'=');
+            let mut splited = char_str.as_bytes().splitn(2, |c| *c == b'=');
             let key = match splited.next() {
-                Some(key) => match std::str::from_utf8(key) {
+                Some(key) => match core::str::from_utf8(key) {
                     Ok(key) => key.to_owned(),
                     Err(_) => continue,
                 },
@@ -77,7 +114,7 @@ impl<'a> TXT<'a> {
             };
 
             let value = match splited.next() {
-                Some(value) if !value.is_empty() => match std::str::from_utf8(value) {
+                Some(value) if !value.is_empty() => match core::str::from_utf8(value) {
                     Ok(v) => Some(v.to_owned()),
                     Err(_) => Some(String::new()),
                 },
@@ -91,6 +128,11 @@ impl<'a> TXT<'a> {
         attributes
     }
 
+    /// Returns the character strings of this record
+    pub fn strings(&self) -> &[CharacterString<'a>] {
+        &self.strings
+    }
+
     /// Transforms the inner data into its owned type
     pub fn into_owned<'b>(self) -> TXT<'b> {
         TXT {
@@ -100,20 +142,12 @@ impl<'a> TXT<'a> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<'a> TryFrom<HashMap<String, Option<String>>> for TXT<'a> {
     type Error = crate::SimpleDnsError;
 
     fn try_from(value: HashMap<String, Option<String>>) -> Result<Self, Self::Error> {
-        let mut txt = TXT::new();
-        for (key, value) in value {
-            match value {
-                Some(value) => {
-                    txt.add_char_string(format!("{}={}", &key, &value).try_into()?);
-                }
-                None => txt.add_char_string(key.try_into()?),
-            }
-        }
-        Ok(txt)
+        TXT::from_attributes(value)
     }
 }
 
@@ -145,7 +179,7 @@ impl<'a> PacketPart<'a> for TXT<'a> {
         }
     }
 
-    fn write_to<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         if self.strings.is_empty() {
             out.write_all(&[0])?;
         } else {
@@ -183,6 +217,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "std")]
     pub fn get_attributes() -> Result<(), Box<dyn std::error::Error>> {
         let attributes = TXT::new()
             .with_string("version=0.1")?
@@ -201,6 +236,73 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    #[cfg(feature = "std")]
+    fn from_attributes_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let attributes: std::collections::BTreeMap<String, Option<String>> = [
+            ("path".to_string(), Some("/api".to_string())),
+            ("secure".to_string(), None),
+            ("version".to_string(), Some("2".to_string())),
+        ]
+        .into_iter()
+        .collect();
+
+        let txt = TXT::from_attributes(attributes.clone())?;
+        let mut out = vec![];
+        txt.write_to(&mut out)?;
+        assert_eq!(b"\x09path=/api\x06secure\x09version=2", &out[..]);
+
+        let parsed = TXT::parse(&out, 0)?;
+        assert_eq!(txt, parsed);
+        assert_eq!(
+            attributes.into_iter().collect::<HashMap<_, _>>(),
+            parsed.attributes()
+        );
+
+        assert_eq!(
+            Err(crate::SimpleDnsError::InvalidCharacterString),
+            TXT::from_attributes([("key".to_string(), Some("v".repeat(252)))])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn parse_and_write_avc() -> Result<(), Box<dyn std::error::Error>> {
+        let rr = ResourceRecord::new(
+            crate::Name::new_unchecked("avc.example.com"),
+            crate::CLASS::IN,
+            10,
+            RData::AVC(
+                TXT::new()
+                    .with_string("app=webex")?
+                    .with_string("business=yes")?
+                    .into(),
+            ),
+        );
+
+        let mut data = Vec::new();
+        rr.write_to(&mut data)?;
+        assert_eq!(data.len(), rr.len());
+
+        let parsed = ResourceRecord::parse(&data, 0)?;
+        assert_eq!(crate::TYPE::AVC, parsed.rdata.type_code());
+        assert_eq!(
+            r#"avc.example.com. 10 IN AVC "app=webex" "business=yes""#,
+            parsed.to_string()
+        );
+
+        let attributes = match parsed.rdata {
+            RData::AVC(avc) => avc.attributes(),
+            _ => unreachable!(),
+        };
+        assert_eq!(Some("webex".to_owned()), attributes["app"]);
+        assert_eq!(Some("yes".to_owned()), attributes["business"]);
+
+        Ok(())
+    }
+
     #[test]
     fn parse_sample() -> Result<(), Box<dyn std::error::Error>> {
         let sample_file = std::fs::read("samples/zonefile/TXT.sample")?;
@@ -1,4 +1,6 @@
-use std::{borrow::Cow, convert::TryInto};
+use crate::dns::write::Write;
+use alloc::borrow::Cow;
+use core::convert::TryInto;
 
 use crate::dns::PacketPart;
 
@@ -6,6 +8,7 @@ use super::RR;
 
 /// The WKS record is used to describe the well known services supported by a particular protocol on a particular internet address.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WKS<'a> {
     /// An 32 bit Internet address
     pub address: u32,
@@ -28,6 +31,24 @@ impl<'a> WKS<'a> {
             bit_map: self.bit_map.into_owned().into(),
         }
     }
+
+    /// Returns true if `port` is set in the bit map.
+    /// The first bit of the bit map corresponds to port 0, the most significant bit of each byte comes first
+    pub fn has_port(&self, port: u16) -> bool {
+        self.bit_map
+            .get(port as usize / 8)
+            .map(|byte| byte & (0b1000_0000 >> (port % 8)) != 0)
+            .unwrap_or_default()
+    }
+
+    /// Returns the ports set in the bit map, in ascending order
+    pub(crate) fn ports(&self) -> impl Iterator<Item = u16> + '_ {
+        self.bit_map.iter().enumerate().flat_map(|(index, byte)| {
+            (0..8)
+                .filter(move |bit| byte & (0b1000_0000 >> bit) != 0)
+                .map(move |bit| (index * 8 + bit) as u16)
+        })
+    }
 }
 
 impl<'a> PacketPart<'a> for WKS<'a> {
@@ -35,6 +56,10 @@ impl<'a> PacketPart<'a> for WKS<'a> {
     where
         Self: Sized,
     {
+        if data.len() < position + 5 {
+            return Err(crate::SimpleDnsError::InsufficientData);
+        }
+
         let address = u32::from_be_bytes(data[position..position + 4].try_into()?);
         Ok(Self {
             address,
@@ -43,7 +68,7 @@ impl<'a> PacketPart<'a> for WKS<'a> {
         })
     }
 
-    fn write_to<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         out.write_all(&self.address.to_be_bytes())?;
         out.write_all(&[self.protocol])?;
         out.write_all(&self.bit_map)?;
@@ -80,4 +105,24 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn has_port() {
+        // ports 25 (SMTP) and 80 (HTTP): bit 1 of byte 3 and bit 0 of byte 10
+        let mut bit_map = vec![0u8; 11];
+        bit_map[3] = 0b0100_0000;
+        bit_map[10] = 0b1000_0000;
+        let wks = WKS {
+            address: 0x0a000001,
+            protocol: 6,
+            bit_map: bit_map.into(),
+        };
+
+        assert!(wks.has_port(25));
+        assert!(wks.has_port(80));
+        assert!(!wks.has_port(24));
+        assert!(!wks.has_port(81));
+        assert!(!wks.has_port(443));
+        assert_eq!(vec![25, 80], wks.ports().collect::<Vec<_>>());
+    }
 }
@@ -1,14 +1,20 @@
+use crate::dns::write::{NameRefs, Seek, Write};
 use crate::{QCLASS, QTYPE};
+use alloc::{format, string::ToString};
 
-use super::{rdata::RData, Name, PacketPart, CLASS, TYPE};
+use super::{rdata::RData, Name, PacketPart, CLASS, MAX_LABEL_LENGTH, MAX_NAME_LENGTH, TYPE};
 use core::fmt::Debug;
-use std::{collections::HashMap, convert::TryInto, hash::Hash};
+use core::{convert::TryInto, hash::Hash};
 
 mod flag {
     pub const CACHE_FLUSH: u16 = 0b1000_0000_0000_0000;
 }
 /// Resource Records are used to represent the answer, authority, and additional sections in DNS packets.
+///
+/// Records are equal, and hash the same, when they have the same name (ignoring ASCII case), class and rdata.
+/// The TTL and the cache_flush bit are not compared, so copies of a record collapse into one entry of a `HashSet`
 #[derive(Debug, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ResourceRecord<'a> {
     /// A [`Name`] to which this resource record pertains.
     pub name: Name<'a>,
@@ -20,7 +26,11 @@ pub struct ResourceRecord<'a> {
     /// A [`RData`] with the contents of this resource record
     pub rdata: RData<'a>,
 
-    /// Indicates if this RR is a cache flush
+    /// Indicates if this RR is a cache flush.  
+    /// In mDNS, the top bit of the class is the cache-flush bit, telling other hosts to replace the cached records
+    /// of the same name, type and class with this one,
+    /// [RFC 6762 section 10.2](https://datatracker.ietf.org/doc/html/rfc6762#section-10.2).
+    /// The bit is masked out of [`class`](ResourceRecord::class) when parsing
     pub cache_flush: bool,
 }
 
@@ -36,6 +46,30 @@ impl<'a> ResourceRecord<'a> {
         }
     }
 
+    /// Creates a [`ResourceRecordBuilder`] for a record of `name`, the record is validated when built
+    ///
+    /// ```
+    /// # use simple_dns::{rdata::{RData, A}, Name, ResourceRecord, CLASS, TYPE};
+    /// let record = ResourceRecord::builder(Name::new_unchecked("host.local"))
+    ///     .rtype(TYPE::A)
+    ///     .ttl(120)
+    ///     .class(CLASS::IN)
+    ///     .data(RData::A(A { address: 0x7f000001 }))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(120, record.ttl);
+    /// ```
+    pub fn builder(name: Name<'a>) -> ResourceRecordBuilder<'a> {
+        ResourceRecordBuilder {
+            name,
+            class: CLASS::IN,
+            ttl: 0,
+            rtype: None,
+            rdata: None,
+            cache_flush: false,
+        }
+    }
+
     /// Consume self and change the cache_flush bit
     pub fn with_cache_flush(mut self, cache_flush: bool) -> Self {
         self.cache_flush = cache_flush;
@@ -50,8 +84,8 @@ impl<'a> ResourceRecord<'a> {
     /// Return true if current resource match given query class
     pub fn match_qclass(&self, qclass: QCLASS) -> bool {
         match qclass {
+            QCLASS::CLASS(CLASS::ANY) | QCLASS::ANY => true,
             QCLASS::CLASS(class) => class == self.class,
-            QCLASS::ANY => true,
         }
     }
 
@@ -83,7 +117,7 @@ impl<'a> ResourceRecord<'a> {
         }
     }
 
-    fn write_common<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_common<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         out.write_all(&u16::from(self.rdata.type_code()).to_be_bytes())?;
 
         if let RData::OPT(ref opt) = self.rdata {
@@ -100,7 +134,119 @@ impl<'a> ResourceRecord<'a> {
 
         out.write_all(&self.ttl.to_be_bytes())?;
         out.write_all(&(self.rdata.len() as u16).to_be_bytes())
-            .map_err(crate::SimpleDnsError::from)
+    }
+}
+
+/// Builder of [`ResourceRecord`], created by [`ResourceRecord::builder`].  
+/// The class defaults to `IN`, the TTL to 0 and the cache_flush bit to false
+#[derive(Debug, Clone)]
+pub struct ResourceRecordBuilder<'a> {
+    name: Name<'a>,
+    class: CLASS,
+    ttl: u32,
+    rtype: Option<TYPE>,
+    rdata: Option<RData<'a>>,
+    cache_flush: bool,
+}
+
+impl<'a> ResourceRecordBuilder<'a> {
+    /// Set the class of the record
+    pub fn class(mut self, class: CLASS) -> Self {
+        self.class = class;
+        self
+    }
+
+    /// Set the TTL of the record, in seconds
+    pub fn ttl(mut self, ttl: u32) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set the expected type of the record, [`build`](ResourceRecordBuilder::build) fails if the rdata is of another type
+    pub fn rtype(mut self, rtype: TYPE) -> Self {
+        self.rtype = Some(rtype);
+        self
+    }
+
+    /// Set the rdata of the record
+    pub fn data(mut self, rdata: RData<'a>) -> Self {
+        self.rdata = Some(rdata);
+        self
+    }
+
+    /// Set the cache_flush bit of the record
+    pub fn cache_flush(mut self, cache_flush: bool) -> Self {
+        self.cache_flush = cache_flush;
+        self
+    }
+
+    /// Build the record.  
+    /// Returns `NameTooLong` or `LabelTooLong` when the name is not valid, and `InvalidResourceRecord` when the rdata
+    /// is missing or doesn't match the expected type
+    pub fn build(self) -> crate::Result<ResourceRecord<'a>> {
+        if let Some(label) = self
+            .name
+            .iter()
+            .find(|label| label.len() > MAX_LABEL_LENGTH)
+        {
+            return Err(crate::SimpleDnsError::LabelTooLong(label.len()));
+        }
+        if self.name.len() > MAX_NAME_LENGTH {
+            return Err(crate::SimpleDnsError::NameTooLong(self.name.len()));
+        }
+
+        let rdata = self.rdata.ok_or_else(|| {
+            crate::SimpleDnsError::InvalidResourceRecord("missing rdata".to_string())
+        })?;
+
+        // NULL rdata reports other types as unknown, comparing the type codes covers it
+        if let Some(rtype) = self.rtype {
+            if u16::from(rtype) != u16::from(rdata.type_code()) {
+                return Err(crate::SimpleDnsError::InvalidResourceRecord(format!(
+                    "{} rdata for a record of type {}",
+                    rdata.type_name(),
+                    rtype
+                )));
+            }
+        }
+
+        Ok(ResourceRecord {
+            name: self.name,
+            class: self.class,
+            ttl: self.ttl,
+            rdata,
+            cache_flush: self.cache_flush,
+        })
+    }
+}
+
+impl<'a> core::fmt::Display for ResourceRecord<'a> {
+    /// Formats this resource record in the presentation format used by zone files, like
+    /// `example.com. 300 IN A 1.2.3.4`
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // NULL rdata reports its type code as unknown, converting it back gives the type mnemonic
+        write!(
+            f,
+            "{}. {} {} {} {}",
+            self.name,
+            self.ttl,
+            self.class,
+            TYPE::from(u16::from(self.rdata.type_code())),
+            self.rdata
+        )
+    }
+}
+
+impl core::str::FromStr for ResourceRecord<'static> {
+    type Err = crate::SimpleDnsError;
+
+    /// Parses a record in the presentation format used by zone files, like `www.example.com. 300 IN A 1.2.3.4`.  
+    /// The class is optional and defaults to `IN`. A, AAAA, CNAME, NS, PTR, MX, TXT and SRV records are supported,
+    /// any type can be parsed from the generic format `\# <length> <hex data>`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        super::presentation::parse_record(s, &Default::default())?.ok_or_else(|| {
+            crate::SimpleDnsError::InvalidPresentationFormat("missing record".to_string())
+        })
     }
 }
 
@@ -146,25 +292,38 @@ impl<'a> PacketPart<'a> for ResourceRecord<'a> {
         self.name.len() + self.rdata.len() + 10
     }
 
-    fn write_to<T: std::io::Write>(&self, out: &mut T) -> crate::Result<()> {
+    fn write_to<T: Write>(&self, out: &mut T) -> crate::Result<()> {
         self.name.write_to(out)?;
         self.write_common(out)?;
         self.rdata.write_to(out)
     }
 
-    fn write_compressed_to<T: std::io::Write + std::io::Seek>(
+    fn write_compressed_to<T: Write + Seek>(
         &self,
         out: &mut T,
-        name_refs: &mut HashMap<u64, usize>,
+        name_refs: &mut NameRefs,
     ) -> crate::Result<()> {
         self.name.write_compressed_to(out, name_refs)?;
         self.write_common(out)?;
-        self.rdata.write_compressed_to(out, name_refs)
+
+        let rdata_start = out.stream_position()?;
+        self.rdata.write_compressed_to(out, name_refs)?;
+        let rdata_end = out.stream_position()?;
+
+        // names inside rdata may have been compressed, fix the rdlength written by write_common
+        let rdlength = (rdata_end - rdata_start) as usize;
+        if rdlength != self.rdata.len() {
+            out.seek_to(rdata_start - 2)?;
+            out.write_all(&(rdlength as u16).to_be_bytes())?;
+            out.seek_to(rdata_end)?;
+        }
+
+        Ok(())
     }
 }
 
 impl<'a> Hash for ResourceRecord<'a> {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
         self.name.hash(state);
         self.class.hash(state);
         self.rdata.hash(state);
@@ -182,12 +341,12 @@ mod tests {
     use std::{
         collections::hash_map::DefaultHasher,
         hash::{Hash, Hasher},
-        io::Cursor,
     };
 
     use crate::{dns::rdata::NULL, rdata::TXT};
 
     use super::*;
+    use crate::dns::write::Buffer;
 
     #[test]
     fn test_parse() {
@@ -215,9 +374,33 @@ mod tests {
         assert!(rr.cache_flush);
     }
 
+    #[test]
+    fn test_cache_flush_round_trip() {
+        let rr = ResourceRecord::new(
+            "_srv._udp.local".try_into().unwrap(),
+            CLASS::IN,
+            10,
+            RData::PTR(Name::new_unchecked("a._srv._udp.local").into()),
+        )
+        .with_cache_flush(true);
+
+        let mut plain = Vec::new();
+        rr.write_to(&mut plain).unwrap();
+        let mut compressed = Buffer::new(Vec::new());
+        rr.write_compressed_to(&mut compressed, &mut NameRefs::new())
+            .unwrap();
+
+        for bytes in [plain, compressed.into_inner()] {
+            let parsed = ResourceRecord::parse(&bytes, 0).unwrap();
+            assert_eq!(rr, parsed);
+            assert_eq!(CLASS::IN, parsed.class);
+            assert!(parsed.cache_flush);
+        }
+    }
+
     #[test]
     fn test_write() {
-        let mut out = Cursor::new(Vec::new());
+        let mut out = Buffer::new(Vec::new());
         let rdata = [255u8; 4];
 
         let rr = ResourceRecord {
@@ -236,9 +419,31 @@ mod tests {
         assert_eq!(out.get_ref().len(), rr.len());
     }
 
+    #[test]
+    fn test_write_compressed_rdata_length() {
+        let mut out = Buffer::new(Vec::new());
+        let mut name_refs = NameRefs::new();
+
+        let rr = ResourceRecord::new(
+            "_srv._udp.local".try_into().unwrap(),
+            CLASS::IN,
+            10,
+            RData::PTR(Name::new_unchecked("a._srv._udp.local").into()),
+        );
+
+        assert!(rr.write_compressed_to(&mut out, &mut name_refs).is_ok());
+        assert_eq!(
+            b"\x04_srv\x04_udp\x05local\x00\x00\x0c\x00\x01\x00\x00\x00\x0a\x00\x04\x01a\xc0\x00",
+            &out.get_ref()[..]
+        );
+
+        let parsed = ResourceRecord::parse(out.get_ref(), 0).unwrap();
+        assert_eq!(rr, parsed);
+    }
+
     #[test]
     fn test_append_to_vec_cache_flush() {
-        let mut out = Cursor::new(Vec::new());
+        let mut out = Buffer::new(Vec::new());
         let rdata = [255u8; 4];
 
         let rr = ResourceRecord {
@@ -346,16 +551,208 @@ mod tests {
         assert_eq!(get_hash(&a), get_hash(&b));
     }
 
+    #[test]
+    fn test_hash_set_dedup_copies() {
+        let record = |name: &'static str, ttl: u32, text: &'static str| {
+            ResourceRecord::new(
+                Name::new_unchecked(name),
+                CLASS::IN,
+                ttl,
+                RData::TXT(TXT::new().with_string(text).unwrap()),
+            )
+        };
+
+        let mut records = std::collections::HashSet::new();
+        assert!(records.insert(record("_srv.local", 10, "text")));
+        assert!(!records.insert(record("_SRV.local", 120, "text")));
+        assert_eq!(1, records.len());
+
+        assert!(records.insert(record("_srv.local", 10, "other")));
+        assert_eq!(2, records.len());
+    }
+
     fn get_hash(rr: &ResourceRecord) -> u64 {
         let mut hasher = DefaultHasher::default();
         rr.hash(&mut hasher);
         hasher.finish()
     }
 
+    #[test]
+    fn display_presentation_format() {
+        use crate::rdata::{A, AAAA, SRV};
+
+        let record =
+            |rdata| ResourceRecord::new(Name::new_unchecked("example.com"), CLASS::IN, 300, rdata);
+
+        assert_eq!(
+            "example.com. 300 IN A 192.168.1.22",
+            record(RData::A(A {
+                address: u32::from(std::net::Ipv4Addr::new(192, 168, 1, 22))
+            }))
+            .to_string()
+        );
+        assert_eq!(
+            "example.com. 300 IN AAAA 2001:db8::1",
+            record(RData::AAAA(AAAA {
+                address: u128::from("2001:db8::1".parse::<std::net::Ipv6Addr>().unwrap())
+            }))
+            .to_string()
+        );
+        assert_eq!(
+            "example.com. 300 IN SRV 10 60 5060 sip.example.com.",
+            record(RData::SRV(SRV {
+                priority: 10,
+                weight: 60,
+                port: 5060,
+                target: Name::new_unchecked("sip.example.com")
+            }))
+            .to_string()
+        );
+        assert_eq!(
+            r#"example.com. 300 IN TXT "path=/" "say \"hi\"\\" "\007""#,
+            record(RData::TXT(
+                TXT::new()
+                    .with_string("path=/")
+                    .unwrap()
+                    .with_string("say \"hi\"\\")
+                    .unwrap()
+                    .with_string("\x07")
+                    .unwrap()
+            ))
+            .to_string()
+        );
+        assert_eq!(
+            r"example.com. 300 IN NULL \# 3 010203",
+            record(RData::NULL(10, NULL::new(&[1, 2, 3]).unwrap())).to_string()
+        );
+        assert_eq!(
+            r"example.com. 300 IN TYPE1234 \# 0",
+            record(RData::NULL(1234, NULL::new(&[]).unwrap())).to_string()
+        );
+    }
+
+    #[test]
+    fn from_str_presentation_format() {
+        use crate::rdata::{A, AAAA, CNAME, MX, NS, SRV};
+        use std::str::FromStr;
+
+        let parse = |line: &str| ResourceRecord::from_str(line).unwrap();
+
+        let record = parse("www.example.com. 300 IN A 1.2.3.4");
+        assert_eq!(Name::new_unchecked("www.example.com"), record.name);
+        assert_eq!(CLASS::IN, record.class);
+        assert_eq!(300, record.ttl);
+        assert_eq!(
+            RData::A(A::from(std::net::Ipv4Addr::new(1, 2, 3, 4))),
+            record.rdata
+        );
+
+        assert_eq!(
+            RData::AAAA(AAAA::from(
+                "2001:db8::1".parse::<std::net::Ipv6Addr>().unwrap()
+            )),
+            parse("www.example.com. IN 300 AAAA 2001:db8::1").rdata
+        );
+        assert_eq!(
+            RData::CNAME(CNAME(Name::new_unchecked("www.example.com"))),
+            parse("alias.example.com. 300 CNAME www.example.com.").rdata
+        );
+        assert_eq!(
+            RData::NS(NS(Name::new_unchecked("ns1.example.com"))),
+            parse("example.com. 3600 IN NS ns1.example.com.").rdata
+        );
+        assert_eq!(
+            RData::MX(MX {
+                preference: 10,
+                exchange: Name::new_unchecked("mail.example.com")
+            }),
+            parse("example.com. 300 IN MX 10 mail.example.com. ; primary").rdata
+        );
+        assert_eq!(
+            RData::SRV(SRV {
+                priority: 0,
+                weight: 5,
+                port: 5060,
+                target: Name::new_unchecked("sip.example.com")
+            }),
+            parse("_sip._tcp.example.com. 300 IN SRV 0 5 5060 sip.example.com.").rdata
+        );
+        assert_eq!(
+            RData::TXT(
+                TXT::new()
+                    .with_string("hello world")
+                    .unwrap()
+                    .with_string("say \"hi\"")
+                    .unwrap()
+                    .with_string("key=value")
+                    .unwrap()
+            ),
+            parse(r#"example.com. 300 IN TXT "hello world" "say \"hi\"" key=value"#).rdata
+        );
+        assert_eq!(
+            RData::NULL(10, NULL::new(&[1, 2, 3]).unwrap()),
+            parse(r"example.com. 300 IN NULL \# 3 010203").rdata
+        );
+    }
+
+    #[test]
+    fn from_str_round_trips_display() {
+        use std::str::FromStr;
+
+        for line in [
+            "example.com. 300 IN A 192.168.1.22",
+            "example.com. 300 IN SRV 10 60 5060 sip.example.com.",
+            r#"example.com. 300 IN TXT "path=/" "say \"hi\"\\" "\007""#,
+            r"example.com. 300 IN TYPE1234 \# 2 ABCD",
+        ] {
+            assert_eq!(line, ResourceRecord::from_str(line).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn from_str_reports_malformed_records() {
+        use std::str::FromStr;
+
+        for (line, error) in [
+            ("", "missing record"),
+            ("www.example.com. IN A 1.2.3.4", "record without TTL"),
+            ("www.example.com. 300 IN A", "missing IPv4 address"),
+            (
+                "www.example.com. 300 IN A 1.2.3",
+                "invalid IPv4 address 1.2.3",
+            ),
+            (
+                "www.example.com. 300 IN A 1.2.3.4 5",
+                "unexpected data after A record",
+            ),
+            ("www.example.com. 300 IN FOO 1.2.3.4", "unknown type FOO"),
+            (
+                "example.com. 300 IN MX ten mail.example.com.",
+                "invalid preference ten",
+            ),
+            (
+                r"example.com. 300 IN NULL \# 3 0102",
+                "generic rdata has 2 bytes, expected 3",
+            ),
+            (
+                "example.com. 300 IN SOA a b 1 2 3 4 5",
+                "SOA records are only supported in the generic \\# format",
+            ),
+        ] {
+            assert_eq!(
+                Err(crate::SimpleDnsError::InvalidPresentationFormat(
+                    error.to_string()
+                )),
+                ResourceRecord::from_str(line),
+                "{line}"
+            );
+        }
+    }
+
     #[test]
     fn parse_sample_files() -> Result<(), Box<dyn std::error::Error>> {
         for file_path in std::fs::read_dir("samples/zonefile")? {
-            let data = std::fs::read(&file_path?.path())?;
+            let data = std::fs::read(file_path?.path())?;
             let mut pos = 0;
             while pos < data.len() {
                 let res = crate::ResourceRecord::parse(&data, pos)?;
@@ -365,4 +762,66 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn build_valid_record() {
+        let record = ResourceRecord::builder(Name::new_unchecked("host.local"))
+            .rtype(TYPE::A)
+            .ttl(120)
+            .class(CLASS::CH)
+            .data(RData::A(crate::rdata::A { address: 1 }))
+            .cache_flush(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(Name::new_unchecked("host.local"), record.name);
+        assert_eq!(CLASS::CH, record.class);
+        assert_eq!(120, record.ttl);
+        assert_eq!(RData::A(crate::rdata::A { address: 1 }), record.rdata);
+        assert!(record.cache_flush);
+
+        let null = ResourceRecord::builder(Name::new_unchecked("host.local"))
+            .rtype(TYPE::NULL)
+            .data(RData::NULL(10, NULL::new(&[1, 2]).unwrap()))
+            .build()
+            .unwrap();
+        assert_eq!(CLASS::IN, null.class);
+        assert_eq!(0, null.ttl);
+    }
+
+    #[test]
+    fn build_invalid_record() {
+        assert_eq!(
+            Err(crate::SimpleDnsError::InvalidResourceRecord(
+                "AAAA rdata for a record of type A".to_string()
+            )),
+            ResourceRecord::builder(Name::new_unchecked("host.local"))
+                .rtype(TYPE::A)
+                .data(RData::AAAA(crate::rdata::AAAA { address: 1 }))
+                .build()
+        );
+
+        assert_eq!(
+            Err(crate::SimpleDnsError::InvalidResourceRecord(
+                "missing rdata".to_string()
+            )),
+            ResourceRecord::builder(Name::new_unchecked("host.local")).build()
+        );
+
+        let long_name = ["a"; 130].join(".");
+        assert_eq!(
+            Err(crate::SimpleDnsError::NameTooLong(261)),
+            ResourceRecord::builder(Name::new_unchecked(&long_name))
+                .data(RData::A(crate::rdata::A { address: 1 }))
+                .build()
+        );
+
+        let long_label = "a".repeat(64);
+        assert_eq!(
+            Err(crate::SimpleDnsError::LabelTooLong(64)),
+            ResourceRecord::builder(Name::new_unchecked(&long_label))
+                .data(RData::A(crate::rdata::A { address: 1 }))
+                .build()
+        );
+    }
 }
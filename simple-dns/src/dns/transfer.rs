@@ -0,0 +1,502 @@
+//! Provides a client for zone transfers over TCP, AXFR [RFC 5936](https://datatracker.ietf.org/doc/html/rfc5936)
+//! and IXFR [RFC 1995](https://datatracker.ietf.org/doc/html/rfc1995)
+
+use std::{
+    collections::VecDeque,
+    io::{Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use crate::{
+    rdata::{RData, SOA},
+    Name, Packet, Question, ResourceRecord, SimpleDnsError, CLASS, QTYPE, RCODE,
+};
+
+/// Time [`axfr`] and [`ixfr`] wait for each read from or write to the server, an operation that takes longer fails
+/// the transfer. Use [`ZoneTransfer::new`] or [`ixfr_with_stream`] with a configured stream to pick another timeout
+pub const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Connects to `server` and requests a full transfer of `zone`.
+///
+/// The returned iterator yields every record of the zone, starting with its SOA record.
+/// The connection is closed when the iterator is dropped
+///
+/// ```no_run
+/// use simple_dns::{transfer, Name};
+///
+/// let records = transfer::axfr("192.168.1.1:53", Name::new_unchecked("example.com"))?
+///     .collect::<simple_dns::Result<Vec<_>>>()?;
+/// # Ok::<(), simple_dns::SimpleDnsError>(())
+/// ```
+pub fn axfr<A: ToSocketAddrs>(server: A, zone: Name<'_>) -> crate::Result<ZoneTransfer<TcpStream>> {
+    ZoneTransfer::new(connect(server)?, 0, zone)
+}
+
+/// Iterator over the records of a zone transfer, which can span multiple messages.
+///
+/// The transfer starts and ends with the SOA record of the zone, the closing SOA record is not yielded.
+/// Iteration stops after the first error, which is returned when the server replies with a response code other than
+/// [`RCODE::NoError`] ([`TransferFailed`](crate::SimpleDnsError::TransferFailed)), a reply has a different id
+/// than the query or the first record is not a SOA record ([`InvalidDnsPacket`](crate::SimpleDnsError::InvalidDnsPacket))
+/// and the connection is closed before the closing SOA record ([`InsufficientData`](crate::SimpleDnsError::InsufficientData))
+#[derive(Debug)]
+pub struct ZoneTransfer<S> {
+    replies: Replies<S>,
+    started: bool,
+    done: bool,
+}
+
+impl<S: Read + Write> ZoneTransfer<S> {
+    /// Sends an AXFR query with `id` for `zone` through `stream`, a connection to the server that frames messages
+    /// like DNS over TCP does
+    pub fn new(stream: S, id: u16, zone: Name<'_>) -> crate::Result<Self> {
+        let query = Packet::new_query(id).with_questions([Question::new(
+            zone,
+            QTYPE::AXFR,
+            CLASS::IN.into(),
+            false,
+        )]);
+
+        Ok(Self {
+            replies: Replies::new(stream, query)?,
+            started: false,
+            done: false,
+        })
+    }
+}
+
+impl<S: Read> Iterator for ZoneTransfer<S> {
+    type Item = crate::Result<ResourceRecord<'static>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let record = match self.replies.next_record() {
+            Ok(record) => record,
+            Err(err) => {
+                self.done = true;
+                return Some(Err(err));
+            }
+        };
+
+        let is_soa = soa_serial(&record).is_some();
+        if self.started && is_soa {
+            self.done = true;
+            return None;
+        }
+        if !self.started && !is_soa {
+            self.done = true;
+            return Some(Err(SimpleDnsError::InvalidDnsPacket));
+        }
+
+        self.started = true;
+        Some(Ok(record))
+    }
+}
+
+/// Connects to `server` and requests the changes of `zone` since the version with `serial`.
+///
+/// Servers that can't provide the changes reply with the whole zone instead, which is returned as
+/// [`IxfrResponse::Full`]. See [`ZoneTransfer`] for the errors
+///
+/// ```no_run
+/// use simple_dns::{transfer::{self, IxfrResponse, ZoneChange}, Name};
+///
+/// match transfer::ixfr("192.168.1.1:53", Name::new_unchecked("example.com"), 2024010101)? {
+///     IxfrResponse::Incremental(changes) => {
+///         for change in changes {
+///             match change {
+///                 ZoneChange::Remove(record) => println!("- {record}"),
+///                 ZoneChange::Add(record) => println!("+ {record}"),
+///             }
+///         }
+///     }
+///     IxfrResponse::Full(records) => println!("{} records", records.len()),
+/// }
+/// # Ok::<(), simple_dns::SimpleDnsError>(())
+/// ```
+pub fn ixfr<A: ToSocketAddrs>(
+    server: A,
+    zone: Name<'_>,
+    serial: u32,
+) -> crate::Result<IxfrResponse> {
+    ixfr_with_stream(connect(server)?, 0, zone, serial)
+}
+
+fn connect<A: ToSocketAddrs>(server: A) -> crate::Result<TcpStream> {
+    let stream = TcpStream::connect(server)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    Ok(stream)
+}
+
+/// Sends an IXFR query with `id` through `stream` and reads the whole response, see [`ixfr`]
+pub fn ixfr_with_stream<S: Read + Write>(
+    stream: S,
+    id: u16,
+    zone: Name<'_>,
+    serial: u32,
+) -> crate::Result<IxfrResponse> {
+    // only the serial of the SOA record in the authority section is used by the server
+    let mut query = Packet::new_query(id).with_questions([Question::new(
+        zone.clone(),
+        QTYPE::IXFR,
+        CLASS::IN.into(),
+        false,
+    )]);
+    query.name_servers.push(ResourceRecord::new(
+        zone.clone(),
+        CLASS::IN,
+        0,
+        RData::SOA(SOA {
+            mname: zone.clone(),
+            rname: zone,
+            serial,
+            refresh: 0,
+            retry: 0,
+            expire: 0,
+            minimum: 0,
+        }),
+    ));
+    let mut replies = Replies::new(stream, query)?;
+
+    let first = replies.next_record()?;
+    let current_serial = soa_serial(&first).ok_or(SimpleDnsError::InvalidDnsPacket)?;
+    // a single SOA record no newer than the version of the client means the client is up to date,
+    // the rest of the response may still arrive in another message
+    if !serial_is_newer(current_serial, serial) {
+        return Ok(IxfrResponse::Incremental(Vec::new()));
+    }
+
+    // the first change starts with the SOA record of the version of the client,
+    // a full transfer has the records of the zone after the SOA instead
+    let second = replies.next_record()?;
+    match soa_serial(&second) {
+        Some(serial) if serial != current_serial => {}
+        Some(_) => return Ok(IxfrResponse::Full(vec![first])),
+        None => {
+            let mut records = vec![first, second];
+            loop {
+                let record = replies.next_record()?;
+                if soa_serial(&record).is_some() {
+                    return Ok(IxfrResponse::Full(records));
+                }
+                records.push(record);
+            }
+        }
+    }
+
+    // each change is a SOA record followed by the removed records, then the new SOA record
+    // followed by the added records. The SOA record of the current version ends the response
+    let mut changes = vec![ZoneChange::Remove(second)];
+    let mut adding = false;
+    loop {
+        let record = replies.next_record()?;
+        match soa_serial(&record) {
+            Some(serial) if adding && serial == current_serial => break,
+            Some(_) => adding = !adding,
+            None => {}
+        }
+
+        changes.push(if adding {
+            ZoneChange::Add(record)
+        } else {
+            ZoneChange::Remove(record)
+        });
+    }
+
+    Ok(IxfrResponse::Incremental(changes))
+}
+
+/// Response of an incremental zone transfer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IxfrResponse {
+    /// Changes to apply, in order, to the version of the client to get the current version of the zone.
+    /// Each change removes the previous SOA record and adds the new one. Empty when the client is up to date
+    Incremental(Vec<ZoneChange>),
+    /// Every record of the zone, starting with the SOA record, when the server does not provide the changes
+    Full(Vec<ResourceRecord<'static>>),
+}
+
+/// A change to a zone, as received in an incremental zone transfer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ZoneChange {
+    /// The record was removed from the zone
+    Remove(ResourceRecord<'static>),
+    /// The record was added to the zone
+    Add(ResourceRecord<'static>),
+}
+
+/// Records of the replies to a zone transfer query, read from the stream as needed
+#[derive(Debug)]
+struct Replies<S> {
+    stream: S,
+    id: u16,
+    pending: VecDeque<ResourceRecord<'static>>,
+}
+
+impl<S: Read> Replies<S> {
+    fn new(mut stream: S, query: Packet<'_>) -> crate::Result<Self>
+    where
+        S: Write,
+    {
+        query.write_tcp(&mut stream)?;
+        stream.flush()?;
+
+        Ok(Self {
+            stream,
+            id: query.id(),
+            pending: VecDeque::new(),
+        })
+    }
+
+    fn next_record(&mut self) -> crate::Result<ResourceRecord<'static>> {
+        loop {
+            if let Some(record) = self.pending.pop_front() {
+                return Ok(record);
+            }
+
+            let reply = Packet::from_reader(&mut self.stream)?;
+            if reply.id() != self.id {
+                return Err(SimpleDnsError::InvalidDnsPacket);
+            }
+            if reply.rcode() != RCODE::NoError {
+                return Err(SimpleDnsError::TransferFailed(reply.rcode()));
+            }
+
+            self.pending.extend(reply.answers);
+        }
+    }
+}
+
+fn soa_serial(record: &ResourceRecord) -> Option<u32> {
+    match &record.rdata {
+        RData::SOA(soa) => Some(soa.serial),
+        _ => None,
+    }
+}
+
+/// Compares serials with the sequence space arithmetic of [RFC 1982](https://datatracker.ietf.org/doc/html/rfc1982)
+fn serial_is_newer(serial: u32, other: u32) -> bool {
+    serial != other && serial.wrapping_sub(other) < 1 << 31
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::TcpListener, thread};
+
+    use crate::rdata::A;
+
+    use super::*;
+
+    fn soa() -> ResourceRecord<'static> {
+        soa_with_serial(2024010101)
+    }
+
+    fn soa_with_serial(serial: u32) -> ResourceRecord<'static> {
+        ResourceRecord::new(
+            Name::new_unchecked("example.com"),
+            CLASS::IN,
+            3600,
+            RData::SOA(SOA {
+                mname: Name::new_unchecked("ns.example.com"),
+                rname: Name::new_unchecked("admin.example.com"),
+                serial,
+                refresh: 3600,
+                retry: 600,
+                expire: 86400,
+                minimum: 300,
+            }),
+        )
+    }
+
+    fn a(name: &'static str, address: u32) -> ResourceRecord<'static> {
+        ResourceRecord::new(
+            Name::new_unchecked(name),
+            CLASS::IN,
+            300,
+            RData::A(A { address }),
+        )
+    }
+
+    /// Accepts a single connection, reads the query and replies with a message for each group of records
+    fn mock_server(
+        rcode: RCODE,
+        messages: Vec<Vec<ResourceRecord<'static>>>,
+    ) -> std::net::SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let query = Packet::from_reader(&mut stream).unwrap();
+            if query.questions[0].qtype == QTYPE::IXFR {
+                assert_eq!(Some(1), soa_serial(&query.name_servers[0]));
+            } else {
+                assert_eq!(QTYPE::AXFR, query.questions[0].qtype);
+            }
+
+            for answers in messages {
+                let mut reply = Packet::new_reply(query.id());
+                *reply.rcode_mut() = rcode;
+                reply.questions = query.questions.clone();
+                reply.answers = answers;
+                reply.write_tcp(&mut stream).unwrap();
+            }
+        });
+
+        addr
+    }
+
+    #[test]
+    fn axfr_collects_every_record() {
+        let server = mock_server(
+            RCODE::NoError,
+            vec![
+                vec![soa(), a("www.example.com", 1)],
+                vec![a("mail.example.com", 2), a("www.example.com", 3), soa()],
+            ],
+        );
+
+        let records = axfr(server, Name::new_unchecked("example.com"))
+            .unwrap()
+            .collect::<crate::Result<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                soa(),
+                a("www.example.com", 1),
+                a("mail.example.com", 2),
+                a("www.example.com", 3)
+            ],
+            records
+        );
+    }
+
+    #[test]
+    fn axfr_failures() {
+        let refused = mock_server(RCODE::Refused, vec![vec![]]);
+        assert_eq!(
+            Err(SimpleDnsError::TransferFailed(RCODE::Refused)),
+            axfr(refused, Name::new_unchecked("example.com"))
+                .unwrap()
+                .collect::<crate::Result<Vec<_>>>()
+        );
+
+        let truncated = mock_server(RCODE::NoError, vec![vec![soa(), a("www.example.com", 1)]]);
+        assert_eq!(
+            Err(SimpleDnsError::InsufficientData),
+            axfr(truncated, Name::new_unchecked("example.com"))
+                .unwrap()
+                .collect::<crate::Result<Vec<_>>>()
+        );
+
+        let without_soa = mock_server(RCODE::NoError, vec![vec![a("www.example.com", 1)]]);
+        let mut transfer = axfr(without_soa, Name::new_unchecked("example.com")).unwrap();
+        assert_eq!(Some(Err(SimpleDnsError::InvalidDnsPacket)), transfer.next());
+        assert_eq!(None, transfer.next());
+    }
+
+    #[test]
+    fn ixfr_single_change() {
+        let server = mock_server(
+            RCODE::NoError,
+            vec![
+                vec![
+                    soa_with_serial(3),
+                    soa_with_serial(1),
+                    a("old.example.com", 1),
+                ],
+                vec![
+                    soa_with_serial(2),
+                    a("new.example.com", 2),
+                    soa_with_serial(2),
+                    soa_with_serial(3),
+                    a("www.example.com", 3),
+                    soa_with_serial(3),
+                ],
+            ],
+        );
+
+        let response = ixfr(server, Name::new_unchecked("example.com"), 1).unwrap();
+        assert_eq!(
+            IxfrResponse::Incremental(vec![
+                ZoneChange::Remove(soa_with_serial(1)),
+                ZoneChange::Remove(a("old.example.com", 1)),
+                ZoneChange::Add(soa_with_serial(2)),
+                ZoneChange::Add(a("new.example.com", 2)),
+                ZoneChange::Remove(soa_with_serial(2)),
+                ZoneChange::Add(soa_with_serial(3)),
+                ZoneChange::Add(a("www.example.com", 3)),
+            ]),
+            response
+        );
+
+        let up_to_date = mock_server(RCODE::NoError, vec![vec![soa_with_serial(1)]]);
+        assert_eq!(
+            IxfrResponse::Incremental(Vec::new()),
+            ixfr(up_to_date, Name::new_unchecked("example.com"), 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn ixfr_first_message_with_only_the_soa() {
+        let server = mock_server(
+            RCODE::NoError,
+            vec![
+                vec![soa_with_serial(2)],
+                vec![
+                    soa_with_serial(1),
+                    a("old.example.com", 1),
+                    soa_with_serial(2),
+                    a("new.example.com", 2),
+                    soa_with_serial(2),
+                ],
+            ],
+        );
+
+        assert_eq!(
+            IxfrResponse::Incremental(vec![
+                ZoneChange::Remove(soa_with_serial(1)),
+                ZoneChange::Remove(a("old.example.com", 1)),
+                ZoneChange::Add(soa_with_serial(2)),
+                ZoneChange::Add(a("new.example.com", 2)),
+            ]),
+            ixfr(server, Name::new_unchecked("example.com"), 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn serial_arithmetic() {
+        assert!(serial_is_newer(2, 1));
+        assert!(serial_is_newer(0, u32::MAX));
+        assert!(!serial_is_newer(1, 1));
+        assert!(!serial_is_newer(1, 2));
+    }
+
+    #[test]
+    fn ixfr_fallback_to_full_transfer() {
+        let server = mock_server(
+            RCODE::NoError,
+            vec![vec![
+                soa_with_serial(3),
+                a("www.example.com", 1),
+                a("mail.example.com", 2),
+                soa_with_serial(3),
+            ]],
+        );
+
+        assert_eq!(
+            IxfrResponse::Full(vec![
+                soa_with_serial(3),
+                a("www.example.com", 1),
+                a("mail.example.com", 2),
+            ]),
+            ixfr(server, Name::new_unchecked("example.com"), 1).unwrap()
+        );
+    }
+}
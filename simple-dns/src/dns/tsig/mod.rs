@@ -0,0 +1,331 @@
+//! Sign and verify messages with TSIG, authenticating them with a secret key shared by both ends,
+//! [RFC 8945](https://datatracker.ietf.org/doc/html/rfc8945).
+//!
+//! Signatures are computed over the message in wire format, so signing and verifying work on the encoded message
+//! instead of a [`Packet`](crate::Packet)
+//!
+//! ```
+//! use simple_dns::{tsig, Name, Packet};
+//!
+//! let key_name = Name::new_unchecked("key.example.com");
+//! let mut message = Packet::new_query(1).build_bytes_vec_compressed().unwrap();
+//! tsig::sign_packet(&mut message, &key_name, tsig::Algorithm::HmacSha256, b"secret").unwrap();
+//!
+//! assert!(tsig::verify_packet(&message, b"secret").is_ok());
+//! ```
+
+use std::{borrow::Cow, time::SystemTime};
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{
+    header_buffer,
+    rdata::{RData, TSIG},
+    Packet, ResourceRecord, CLASS,
+};
+
+use super::{Name, PacketPart};
+
+/// Seconds of difference allowed between the clocks of the signer and the verifier
+pub const DEFAULT_FUDGE: u16 = 300;
+
+/// The MAC of the message is wrong or the algorithm is not supported
+pub const BADSIG: u16 = 16;
+/// The key is not known or the algorithm is not supported
+pub const BADKEY: u16 = 17;
+/// The message was signed outside of the fudge interval
+pub const BADTIME: u16 = 18;
+
+/// MAC algorithms supported to sign messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Algorithm {
+    /// HMAC with SHA-256, the algorithm every implementation supports
+    HmacSha256,
+}
+
+impl Algorithm {
+    /// Returns the name of this algorithm, used in the algorithm field of the TSIG record
+    pub fn name(&self) -> Name<'static> {
+        match self {
+            Algorithm::HmacSha256 => Name::new_unchecked("hmac-sha256"),
+        }
+    }
+
+    /// Returns the algorithm named `name`, comparing names ignoring case
+    pub fn from_name(name: &Name) -> Option<Self> {
+        if *name == Algorithm::HmacSha256.name() {
+            Some(Algorithm::HmacSha256)
+        } else {
+            None
+        }
+    }
+
+    fn mac(&self, secret: &[u8], data: &[u8]) -> Vec<u8> {
+        match self {
+            Algorithm::HmacSha256 => {
+                // HMAC accepts keys of any length, so creating it can't fail
+                let mut mac =
+                    Hmac::<Sha256>::new_from_slice(secret).expect("any key length is valid");
+                mac.update(data);
+                mac.finalize().into_bytes().to_vec()
+            }
+        }
+    }
+}
+
+/// Sign `message`, a packet in wire format, appending a TSIG record named `key_name` to its additional records.
+/// The message must not be changed after being signed, the TSIG record must stay the last record
+pub fn sign_packet(
+    message: &mut Vec<u8>,
+    key_name: &Name<'_>,
+    algorithm: Algorithm,
+    secret: &[u8],
+) -> crate::Result<()> {
+    sign_packet_at(message, key_name, algorithm, secret, unix_time())
+}
+
+fn sign_packet_at(
+    message: &mut Vec<u8>,
+    key_name: &Name<'_>,
+    algorithm: Algorithm,
+    secret: &[u8],
+    time_signed: u64,
+) -> crate::Result<()> {
+    let additional_records = header_buffer::additional_records(message)?;
+
+    let mut tsig = TSIG {
+        algorithm: algorithm.name(),
+        time_signed,
+        fudge: DEFAULT_FUDGE,
+        mac: Cow::Borrowed(&[]),
+        original_id: header_buffer::id(message)?,
+        error: 0,
+        other_data: Cow::Borrowed(&[]),
+    };
+    let mac = algorithm.mac(secret, &signed_data(message, key_name, &tsig)?);
+    tsig.mac = mac.into();
+
+    ResourceRecord::new(key_name.clone(), CLASS::ANY, 0, RData::TSIG(tsig)).write_to(message)?;
+    header_buffer::set_additional_records(message, additional_records + 1);
+
+    Ok(())
+}
+
+/// Verify the TSIG record of `message`, a packet in wire format, with `secret`.
+/// The TSIG record must be the last additional record, the caller is responsible for checking that its name
+/// is the name of the key of `secret`.
+///
+/// Returns [`InvalidTsig`](crate::SimpleDnsError::InvalidTsig) with [`BADSIG`], [`BADKEY`] or [`BADTIME`] when
+/// the verification fails, and [`InvalidDnsPacket`](crate::SimpleDnsError::InvalidDnsPacket) when the message is
+/// not signed
+pub fn verify_packet(message: &[u8], secret: &[u8]) -> crate::Result<()> {
+    verify_packet_at(message, secret, unix_time())
+}
+
+fn verify_packet_at(message: &[u8], secret: &[u8], now: u64) -> crate::Result<()> {
+    let tsig_position = last_record_position(message)?;
+    let record = ResourceRecord::parse(message, tsig_position)?;
+    let tsig = match record.rdata {
+        RData::TSIG(tsig) if record.class == CLASS::ANY => tsig,
+        _ => return Err(crate::SimpleDnsError::InvalidDnsPacket),
+    };
+
+    let algorithm =
+        Algorithm::from_name(&tsig.algorithm).ok_or(crate::SimpleDnsError::InvalidTsig(BADKEY))?;
+
+    // the MAC covers the message as it was before the TSIG record was added
+    let mut unsigned = message[..tsig_position].to_vec();
+    unsigned[..2].copy_from_slice(&tsig.original_id.to_be_bytes());
+    header_buffer::set_additional_records(
+        &mut unsigned,
+        header_buffer::additional_records(message)? - 1,
+    );
+
+    let expected = algorithm.mac(secret, &signed_data(&unsigned, &record.name, &tsig)?);
+    if !constant_time_eq(&expected, &tsig.mac) {
+        return Err(crate::SimpleDnsError::InvalidTsig(BADSIG));
+    }
+
+    if now.abs_diff(tsig.time_signed) > tsig.fudge as u64 {
+        return Err(crate::SimpleDnsError::InvalidTsig(BADTIME));
+    }
+
+    Ok(())
+}
+
+/// Returns the message followed by the TSIG variables covered by the MAC,
+/// [RFC 8945 section 4.3.3](https://datatracker.ietf.org/doc/html/rfc8945#section-4.3.3)
+fn signed_data(message: &[u8], key_name: &Name<'_>, tsig: &TSIG<'_>) -> crate::Result<Vec<u8>> {
+    let mut data = message.to_vec();
+    write_canonical_name(&mut data, key_name)?;
+    data.extend_from_slice(&(CLASS::ANY as u16).to_be_bytes());
+    data.extend_from_slice(&0u32.to_be_bytes());
+    write_canonical_name(&mut data, &tsig.algorithm)?;
+    data.extend_from_slice(&tsig.time_signed.to_be_bytes()[2..]);
+    data.extend_from_slice(&tsig.fudge.to_be_bytes());
+    data.extend_from_slice(&tsig.error.to_be_bytes());
+    data.extend_from_slice(&(tsig.other_data.len() as u16).to_be_bytes());
+    data.extend_from_slice(&tsig.other_data);
+
+    Ok(data)
+}
+
+/// Names are covered by the MAC uncompressed and lowercased.
+/// Label lengths are never over 63, so they are not changed by lowercasing
+fn write_canonical_name(out: &mut Vec<u8>, name: &Name<'_>) -> crate::Result<()> {
+    let start = out.len();
+    name.write_to(out)?;
+    out[start..].make_ascii_lowercase();
+    Ok(())
+}
+
+/// Returns the position of the last additional record of `message`
+fn last_record_position(message: &[u8]) -> crate::Result<usize> {
+    let ranges = Packet::section_ranges(message)?;
+    let additional_records = header_buffer::additional_records(message)?;
+    if additional_records == 0 {
+        return Err(crate::SimpleDnsError::InvalidDnsPacket);
+    }
+
+    let mut position = ranges.additional_records.start;
+    for _ in 1..additional_records {
+        position += Name::wire_len(message, position)? + 10;
+        let rdlength = message
+            .get(position - 2..position)
+            .ok_or(crate::SimpleDnsError::InsufficientData)?;
+        position += u16::from_be_bytes([rdlength[0], rdlength[1]]) as usize;
+    }
+
+    Ok(position)
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        rdata::{A, OPT},
+        Question, TYPE,
+    };
+
+    use super::*;
+
+    #[test]
+    fn hmac_sha256_test_vectors() {
+        let hex =
+            |data: Vec<u8>| -> String { data.iter().map(|byte| format!("{byte:02x}")).collect() };
+
+        // RFC 4231 test cases 2 and 6
+        assert_eq!(
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            hex(Algorithm::HmacSha256.mac(b"Jefe", b"what do ya want for nothing?"))
+        );
+        assert_eq!(
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+            hex(Algorithm::HmacSha256.mac(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            ))
+        );
+    }
+
+    fn signed_message(time_signed: u64) -> Vec<u8> {
+        let mut packet = Packet::new_reply(42).with_questions([Question::new(
+            Name::new_unchecked("host.example.com"),
+            TYPE::A.into(),
+            CLASS::IN.into(),
+            false,
+        )]);
+        packet.answers.push(ResourceRecord::new(
+            Name::new_unchecked("host.example.com"),
+            CLASS::IN,
+            300,
+            RData::A(A {
+                address: 0xc0a80001,
+            }),
+        ));
+        *packet.opt_mut() = Some(OPT {
+            opt_codes: Vec::new(),
+            udp_packet_size: 1232,
+            version: 0,
+            dnssec_ok: false,
+        });
+
+        let mut message = packet.build_bytes_vec_compressed().unwrap();
+        sign_packet_at(
+            &mut message,
+            &Name::new_unchecked("Key.Example.com"),
+            Algorithm::HmacSha256,
+            b"secret",
+            time_signed,
+        )
+        .unwrap();
+        message
+    }
+
+    #[test]
+    fn sign_and_verify() {
+        let message = signed_message(1_700_000_000);
+        assert_eq!(Ok(()), verify_packet_at(&message, b"secret", 1_700_000_000));
+        assert_eq!(Ok(()), verify_packet_at(&message, b"secret", 1_700_000_300));
+
+        let packet = Packet::parse(&message).unwrap();
+        assert_eq!(1, packet.answers.len());
+        assert!(packet.opt().is_some());
+        let tsig = match &packet.additional_records[..] {
+            [record] => match &record.rdata {
+                RData::TSIG(tsig) => tsig,
+                _ => panic!("expected a TSIG record"),
+            },
+            _ => panic!("expected only the TSIG record"),
+        };
+        assert_eq!(Algorithm::HmacSha256.name(), tsig.algorithm);
+        assert_eq!(42, tsig.original_id);
+        assert_eq!(32, tsig.mac.len());
+    }
+
+    #[test]
+    fn verify_fails_for_tampered_message() {
+        let message = signed_message(1_700_000_000);
+
+        // flip a bit of the address of the answer
+        let mut tampered = message.clone();
+        let address = tampered
+            .windows(4)
+            .position(|window| window == [0xc0, 0xa8, 0x00, 0x01])
+            .unwrap();
+        tampered[address + 3] ^= 1;
+
+        assert_eq!(
+            Err(crate::SimpleDnsError::InvalidTsig(BADSIG)),
+            verify_packet_at(&tampered, b"secret", 1_700_000_000)
+        );
+        assert_eq!(
+            Err(crate::SimpleDnsError::InvalidTsig(BADSIG)),
+            verify_packet_at(&message, b"other secret", 1_700_000_000)
+        );
+        assert_eq!(
+            Err(crate::SimpleDnsError::InvalidTsig(BADTIME)),
+            verify_packet_at(&message, b"secret", 1_700_000_301)
+        );
+        assert_eq!(
+            Err(crate::SimpleDnsError::InvalidDnsPacket),
+            verify_packet_at(
+                &Packet::new_query(1).build_bytes_vec().unwrap(),
+                b"secret",
+                1_700_000_000
+            )
+        );
+    }
+}
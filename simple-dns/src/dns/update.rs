@@ -0,0 +1,201 @@
+//! Provides a builder for dynamic UPDATE messages, [RFC 2136](https://datatracker.ietf.org/doc/html/rfc2136)
+
+use crate::{
+    rdata::{RData, NULL},
+    Name, Packet, Question, ResourceRecord, CLASS, QTYPE, TYPE,
+};
+
+/// Builds an UPDATE message for a zone.
+///
+/// The zone is the only question of the message, prerequisites are written to the answers section and updates
+/// to the name servers section, as described in [RFC 2136 section 2](https://datatracker.ietf.org/doc/html/rfc2136#section-2).
+/// The server applies the updates only when every prerequisite is satisfied
+///
+/// ```
+/// use simple_dns::{rdata::{RData, A}, update::UpdateBuilder, Name, ResourceRecord, CLASS, TYPE};
+///
+/// let host = Name::new_unchecked("host.example.com");
+/// let packet = UpdateBuilder::new(1, Name::new_unchecked("example.com"))
+///     .require_not_exists(host.clone(), TYPE::A)
+///     .add_record(ResourceRecord::new(host, CLASS::IN, 300, RData::A(A { address: 0xc0a80001 })))
+///     .build();
+///
+/// assert_eq!(1, packet.answers.len());
+/// assert_eq!(1, packet.name_servers.len());
+/// ```
+#[derive(Debug, Clone)]
+pub struct UpdateBuilder<'a> {
+    packet: Packet<'a>,
+    class: CLASS,
+}
+
+impl<'a> UpdateBuilder<'a> {
+    /// Creates a builder for an UPDATE message of `zone`, in the `IN` class
+    pub fn new(id: u16, zone: Name<'a>) -> Self {
+        Self::new_with_class(id, zone, CLASS::IN)
+    }
+
+    /// Creates a builder for an UPDATE message of `zone`, in `class`
+    pub fn new_with_class(id: u16, zone: Name<'a>, class: CLASS) -> Self {
+        let packet = Packet::new_update(id).with_questions([Question::new(
+            zone,
+            TYPE::SOA.into(),
+            class.into(),
+            false,
+        )]);
+
+        Self { packet, class }
+    }
+
+    /// Require the `rtype` records of `name` to exist, whatever their rdata is
+    pub fn require_exists(self, name: Name<'a>, rtype: TYPE) -> Self {
+        self.prerequisite(meta_record(name, rtype.into(), CLASS::ANY))
+    }
+
+    /// Require `record` to exist, with the same rdata. The TTL of the record is ignored by the server
+    pub fn require_record(mut self, mut record: ResourceRecord<'a>) -> Self {
+        record.class = self.class;
+        record.ttl = 0;
+        self.packet.answers.push(record);
+        self
+    }
+
+    /// Require `name` to have no `rtype` records
+    pub fn require_not_exists(self, name: Name<'a>, rtype: TYPE) -> Self {
+        self.prerequisite(meta_record(name, rtype.into(), CLASS::NONE))
+    }
+
+    /// Require `name` to have at least one record, of any type
+    pub fn require_name_in_use(self, name: Name<'a>) -> Self {
+        self.prerequisite(meta_record(name, QTYPE::ANY.into(), CLASS::ANY))
+    }
+
+    /// Require `name` to have no records
+    pub fn require_name_not_in_use(self, name: Name<'a>) -> Self {
+        self.prerequisite(meta_record(name, QTYPE::ANY.into(), CLASS::NONE))
+    }
+
+    /// Add `record` to the zone, its class is replaced by the class of the zone
+    pub fn add_record(mut self, mut record: ResourceRecord<'a>) -> Self {
+        record.class = self.class;
+        self.packet.name_servers.push(record);
+        self
+    }
+
+    /// Delete `record` from the zone, matching its name and rdata
+    pub fn delete_record(mut self, mut record: ResourceRecord<'a>) -> Self {
+        record.class = CLASS::NONE;
+        record.ttl = 0;
+        self.packet.name_servers.push(record);
+        self
+    }
+
+    /// Delete every `rtype` record of `name`
+    pub fn delete_rrset(mut self, name: Name<'a>, rtype: TYPE) -> Self {
+        self.packet
+            .name_servers
+            .push(meta_record(name, rtype.into(), CLASS::ANY));
+        self
+    }
+
+    /// Delete every record of `name`
+    pub fn delete_name(mut self, name: Name<'a>) -> Self {
+        self.packet
+            .name_servers
+            .push(meta_record(name, QTYPE::ANY.into(), CLASS::ANY));
+        self
+    }
+
+    /// Returns the UPDATE packet
+    pub fn build(self) -> Packet<'a> {
+        self.packet
+    }
+
+    fn prerequisite(mut self, record: ResourceRecord<'a>) -> Self {
+        self.packet.answers.push(record);
+        self
+    }
+}
+
+/// Records with an empty rdata and a TTL of 0, the class tells the meaning of the record.
+/// See [RFC 2136 section 2.4](https://datatracker.ietf.org/doc/html/rfc2136#section-2.4) for prerequisites and
+/// [section 2.5](https://datatracker.ietf.org/doc/html/rfc2136#section-2.5) for updates
+fn meta_record(name: Name<'_>, rtype: u16, class: CLASS) -> ResourceRecord<'_> {
+    let empty = NULL::new(&[]).expect("empty rdata is always valid");
+    ResourceRecord::new(name, class, 0, RData::NULL(rtype, empty))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{dns::PacketPart, header_buffer, rdata::A, OPCODE};
+
+    use super::*;
+
+    fn a_record(address: u32) -> ResourceRecord<'static> {
+        ResourceRecord::new(
+            Name::new_unchecked("host.example.com"),
+            CLASS::IN,
+            300,
+            RData::A(A { address }),
+        )
+    }
+
+    #[test]
+    fn build_update() {
+        let host = Name::new_unchecked("host.example.com");
+        let packet = UpdateBuilder::new(7, Name::new_unchecked("example.com"))
+            .require_name_in_use(host.clone())
+            .require_not_exists(host.clone(), TYPE::AAAA)
+            .delete_rrset(host.clone(), TYPE::A)
+            .add_record(a_record(1))
+            .delete_record(a_record(2))
+            .delete_name(Name::new_unchecked("old.example.com"))
+            .build();
+
+        let data = packet.build_bytes_vec().unwrap();
+        assert_eq!(1, header_buffer::questions(&data).unwrap());
+        assert_eq!(2, header_buffer::answers(&data).unwrap());
+        assert_eq!(4, header_buffer::name_servers(&data).unwrap());
+        assert_eq!(0, header_buffer::additional_records(&data).unwrap());
+
+        let parsed = Packet::parse(&data).unwrap();
+        assert_eq!(OPCODE::Update, parsed.opcode());
+        assert_eq!(7, parsed.id());
+        assert_eq!(QTYPE::TYPE(TYPE::SOA), parsed.questions[0].qtype);
+        assert_eq!("example.com", parsed.questions[0].qname.to_string());
+
+        let encoding = |record: &ResourceRecord| {
+            (
+                u16::from(record.rdata.type_code()),
+                record.class,
+                record.ttl,
+                record.rdata.len(),
+            )
+        };
+        assert_eq!(
+            vec![(255, CLASS::ANY, 0, 0), (28, CLASS::NONE, 0, 0)],
+            parsed.answers.iter().map(encoding).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![
+                (1, CLASS::ANY, 0, 0),
+                (1, CLASS::IN, 300, 4),
+                (1, CLASS::NONE, 0, 4),
+                (255, CLASS::ANY, 0, 0)
+            ],
+            parsed.name_servers.iter().map(encoding).collect::<Vec<_>>()
+        );
+        assert_eq!(RData::A(A { address: 2 }), parsed.name_servers[2].rdata);
+    }
+
+    #[test]
+    fn prerequisite_encoding() {
+        let packet = UpdateBuilder::new(1, Name::new_unchecked("example.com"))
+            .require_exists(Name::new_unchecked("host.example.com"), TYPE::A)
+            .build();
+
+        let data = packet.build_bytes_vec().unwrap();
+        // the prerequisite is the last record: TYPE A, CLASS ANY, TTL 0, RDLENGTH 0
+        assert_eq!([0, 1, 0, 255, 0, 0, 0, 0, 0, 0], data[data.len() - 10..]);
+    }
+}
@@ -0,0 +1,202 @@
+//! Writers used to encode packets, so the wire format can be written without the standard library
+
+use core::hash::Hash;
+
+/// Destination of the wire format of a packet.
+/// With the `std` feature, every [`std::io::Write`] is a writer
+pub(crate) trait Write {
+    /// Write all of `data`
+    fn write_all(&mut self, data: &[u8]) -> crate::Result<()>;
+}
+
+/// A writer that can go back to a position already written, required by name compression
+pub(crate) trait Seek {
+    /// Returns the current position, from the start of the packet
+    fn stream_position(&mut self) -> crate::Result<u64>;
+    /// Moves to `position`, from the start of the packet
+    fn seek_to(&mut self, position: u64) -> crate::Result<()>;
+}
+
+#[cfg(feature = "std")]
+impl<T: std::io::Write> Write for T {
+    fn write_all(&mut self, data: &[u8]) -> crate::Result<()> {
+        std::io::Write::write_all(self, data).map_err(crate::SimpleDnsError::from)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: std::io::Seek> Seek for T {
+    fn stream_position(&mut self) -> crate::Result<u64> {
+        std::io::Seek::stream_position(self).map_err(crate::SimpleDnsError::from)
+    }
+
+    fn seek_to(&mut self, position: u64) -> crate::Result<()> {
+        std::io::Seek::seek(self, std::io::SeekFrom::Start(position))?;
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Write for alloc::vec::Vec<u8> {
+    fn write_all(&mut self, data: &[u8]) -> crate::Result<()> {
+        self.extend_from_slice(data);
+        Ok(())
+    }
+}
+
+/// A buffer that overwrites its contents after a seek, like a `Cursor<Vec<u8>>`
+#[derive(Debug)]
+pub(crate) struct Buffer {
+    data: alloc::vec::Vec<u8>,
+    position: usize,
+}
+
+impl Buffer {
+    /// Creates a buffer that overwrites `data`, starting at its beginning
+    pub fn new(data: alloc::vec::Vec<u8>) -> Self {
+        Self { data, position: 0 }
+    }
+
+    /// Returns the number of bytes written to this buffer
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Discards the bytes after `len` and moves to the end of the buffer
+    pub fn truncate(&mut self, len: usize) {
+        self.data.truncate(len);
+        self.position = self.data.len();
+    }
+
+    /// Returns the bytes written to this buffer
+    pub fn into_inner(self) -> alloc::vec::Vec<u8> {
+        self.data
+    }
+
+    /// Returns the bytes written so far
+    #[cfg(test)]
+    pub fn get_ref(&self) -> &alloc::vec::Vec<u8> {
+        &self.data
+    }
+
+    fn write(&mut self, data: &[u8]) {
+        let overwritten = data.len().min(self.data.len() - self.position);
+        self.data[self.position..self.position + overwritten].copy_from_slice(&data[..overwritten]);
+        self.data.extend_from_slice(&data[overwritten..]);
+        self.position += data.len();
+    }
+}
+
+impl Write for Buffer {
+    fn write_all(&mut self, data: &[u8]) -> crate::Result<()> {
+        self.write(data);
+        Ok(())
+    }
+}
+
+impl Seek for Buffer {
+    fn stream_position(&mut self) -> crate::Result<u64> {
+        Ok(self.position as u64)
+    }
+
+    fn seek_to(&mut self, position: u64) -> crate::Result<()> {
+        if position as usize > self.data.len() {
+            return Err(crate::SimpleDnsError::FailedToWrite);
+        }
+
+        self.position = position as usize;
+        Ok(())
+    }
+}
+
+/// A writer that only counts bytes, used to get the length of a packet without writing it
+#[derive(Debug, Default)]
+pub(crate) struct Counter {
+    position: usize,
+    len: usize,
+}
+
+impl Counter {
+    /// Returns the number of bytes that would have been written
+    pub fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl Write for Counter {
+    fn write_all(&mut self, data: &[u8]) -> crate::Result<()> {
+        self.position += data.len();
+        self.len = self.len.max(self.position);
+        Ok(())
+    }
+}
+
+impl Seek for Counter {
+    fn stream_position(&mut self) -> crate::Result<u64> {
+        Ok(self.position as u64)
+    }
+
+    fn seek_to(&mut self, position: u64) -> crate::Result<()> {
+        if position as usize > self.len {
+            return Err(crate::SimpleDnsError::FailedToWrite);
+        }
+
+        self.position = position as usize;
+        Ok(())
+    }
+}
+
+/// Positions of the names already written, by the hash of their labels, used to compress names
+#[cfg(feature = "std")]
+pub(crate) type NameRefs = std::collections::HashMap<u64, usize>;
+/// Positions of the names already written, by the bytes of their lowercase labels, used to compress names
+#[cfg(not(feature = "std"))]
+pub(crate) type NameRefs = alloc::collections::BTreeMap<alloc::vec::Vec<u8>, usize>;
+
+/// Returns the key of `labels` in `name_refs`
+#[cfg(feature = "std")]
+pub(crate) fn name_refs_key<T: Hash + ?Sized>(name_refs: &NameRefs, labels: &T) -> u64 {
+    use std::hash::BuildHasher;
+    name_refs.hasher().hash_one(labels)
+}
+
+/// Returns the key of `labels` in `name_refs`. Without `std` there is no random hasher, so the key is every byte fed
+/// to the hasher instead of a hash, names that only share a hash can't be mistaken for each other
+#[cfg(not(feature = "std"))]
+pub(crate) fn name_refs_key<T: Hash + ?Sized>(
+    _name_refs: &NameRefs,
+    labels: &T,
+) -> alloc::vec::Vec<u8> {
+    struct Collect(alloc::vec::Vec<u8>);
+
+    impl core::hash::Hasher for Collect {
+        fn finish(&self) -> u64 {
+            0
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            self.0.extend_from_slice(bytes);
+        }
+    }
+
+    let mut hasher = Collect(alloc::vec::Vec::new());
+    labels.hash(&mut hasher);
+    hasher.0
+}
+
+#[cfg(all(test, not(feature = "std")))]
+mod tests {
+    use crate::Name;
+
+    use super::*;
+
+    #[test]
+    fn name_refs_key_identifies_the_labels() {
+        let name_refs = NameRefs::new();
+        let key = |name: &str| name_refs_key(&name_refs, Name::new_unchecked(name).get_labels());
+
+        assert_eq!(key("example.com"), key("EXAMPLE.com"));
+        assert_ne!(key("ab.c"), key("a.bc"));
+        assert_ne!(key("example.com"), key("example.org"));
+    }
+}
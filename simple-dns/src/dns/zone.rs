@@ -0,0 +1,167 @@
+//! Provides a minimal parser for zone files, [RFC 1035 section 5](https://datatracker.ietf.org/doc/html/rfc1035#section-5)
+
+use crate::{ResourceRecord, SimpleDnsError};
+use alloc::{format, vec::Vec};
+
+use super::presentation::{self, RecordDefaults, Token};
+
+/// Parses every record in `zone`, one record per line.
+///
+/// - `$ORIGIN <name>` sets the origin appended to relative names and used for `@`
+/// - `$TTL <ttl>` sets the TTL of records without one, otherwise the TTL of the previous record is used
+/// - Lines starting with a blank use the owner name of the previous record
+/// - Text after `;` is a comment
+///
+/// Records spanning multiple lines with parentheses and `$INCLUDE` are not supported.
+/// Each record is parsed like `FromStr` for [`ResourceRecord`] does,
+/// errors contain the line number where they happened
+///
+/// ```
+/// let zone = r#"
+/// $ORIGIN example.com.
+/// $TTL 300
+/// @     IN A   192.168.1.1
+/// www   IN A   192.168.1.2
+///       IN TXT "web server"
+/// "#;
+///
+/// let records = simple_dns::zone::parse_zone(zone).unwrap();
+/// assert_eq!(3, records.len());
+/// assert_eq!("www.example.com", records[2].name.to_string());
+/// ```
+pub fn parse_zone(zone: &str) -> crate::Result<Vec<ResourceRecord<'static>>> {
+    let mut defaults = RecordDefaults::default();
+    let mut default_ttl = None;
+    let mut records = Vec::new();
+
+    for (index, line) in zone.lines().enumerate() {
+        let with_line = |err: SimpleDnsError| match err {
+            SimpleDnsError::InvalidPresentationFormat(message) => {
+                presentation::invalid(format!("line {}: {message}", index + 1))
+            }
+            err => err,
+        };
+
+        if line.starts_with('$') {
+            parse_directive(line, &mut defaults, &mut default_ttl).map_err(with_line)?;
+            continue;
+        }
+
+        defaults.ttl = default_ttl.or_else(|| records.last().map(|r: &ResourceRecord| r.ttl));
+        if let Some(record) = presentation::parse_record(line, &defaults).map_err(with_line)? {
+            defaults.owner = Some(record.name.clone());
+            records.push(record);
+        }
+    }
+
+    Ok(records)
+}
+
+fn parse_directive(
+    line: &str,
+    defaults: &mut RecordDefaults,
+    default_ttl: &mut Option<u32>,
+) -> crate::Result<()> {
+    let tokens = presentation::tokenize(line)?;
+    let (directive, value) = match &tokens[..] {
+        [Token::Text(directive), Token::Text(value)] => (directive.as_str(), value.as_str()),
+        _ => return Err(presentation::invalid(format!("invalid directive {line}"))),
+    };
+
+    match directive.to_ascii_uppercase().as_str() {
+        "$ORIGIN" => defaults.origin = Some(presentation::parse_name(value, defaults)?),
+        "$TTL" => *default_ttl = Some(presentation::parse_number(value, "TTL")?),
+        _ => {
+            return Err(presentation::invalid(format!(
+                "unsupported directive {directive}"
+            )))
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        rdata::{RData, A, CNAME, MX},
+        Name,
+    };
+
+    use super::*;
+
+    #[test]
+    fn expand_origin_and_inherit_owner() {
+        let zone = r#"
+; sample zone
+$ORIGIN example.com.
+$TTL 3600
+@               IN  MX    10 mail        ; relative exchange
+                IN  TXT   "v=spf1 -all"
+mail     300    IN  A     192.168.1.10
+www             IN  CNAME mail
+ftp.other.com.  IN  A     192.168.1.11
+$ORIGIN sub
+host            IN  A     192.168.1.12
+"#;
+
+        let records = parse_zone(zone).unwrap();
+        let names: Vec<String> = records.iter().map(|r| r.name.to_string()).collect();
+        assert_eq!(
+            vec![
+                "example.com",
+                "example.com",
+                "mail.example.com",
+                "www.example.com",
+                "ftp.other.com",
+                "host.sub.example.com"
+            ],
+            names
+        );
+
+        let ttls: Vec<u32> = records.iter().map(|r| r.ttl).collect();
+        assert_eq!(vec![3600, 3600, 300, 3600, 3600, 3600], ttls);
+
+        assert_eq!(
+            RData::MX(MX {
+                preference: 10,
+                exchange: Name::new_unchecked("mail.example.com")
+            }),
+            records[0].rdata
+        );
+        assert!(matches!(records[1].rdata, RData::TXT(_)));
+        assert_eq!(
+            RData::CNAME(CNAME(Name::new_unchecked("mail.example.com"))),
+            records[3].rdata
+        );
+        assert_eq!(
+            RData::A(A::from(std::net::Ipv4Addr::new(192, 168, 1, 12))),
+            records[5].rdata
+        );
+    }
+
+    #[test]
+    fn ttl_defaults_to_previous_record() {
+        let zone = "a.local. 120 IN A 10.0.0.1\nb.local. IN A 10.0.0.2\n";
+        let records = parse_zone(zone).unwrap();
+        assert_eq!(120, records[1].ttl);
+    }
+
+    #[test]
+    fn errors_contain_the_line_number() {
+        let zone = "$ORIGIN local.\n  IN A 10.0.0.1\n";
+        assert_eq!(
+            Err(SimpleDnsError::InvalidPresentationFormat(
+                "line 2: record without owner name".to_string()
+            )),
+            parse_zone(zone)
+        );
+
+        assert_eq!(
+            Err(SimpleDnsError::InvalidPresentationFormat(
+                "line 1: unsupported directive $INCLUDE".to_string()
+            )),
+            parse_zone("$INCLUDE other.zone\n")
+        );
+    }
+}
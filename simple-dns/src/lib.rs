@@ -1,5 +1,8 @@
 #![warn(missing_docs)]
 #![doc = include_str!("../README.md")]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
 mod dns;
 mod simple_dns_error;
@@ -8,4 +11,4 @@ pub use simple_dns_error::SimpleDnsError;
 pub use dns::*;
 
 /// Alias type for Result<T, SimpleDnsError>;
-pub type Result<T> = std::result::Result<T, SimpleDnsError>;
+pub type Result<T> = core::result::Result<T, SimpleDnsError>;
@@ -1,4 +1,7 @@
-use std::{array::TryFromSliceError, error::Error, fmt::Display};
+use alloc::string::String;
+use core::{array::TryFromSliceError, error::Error, fmt::Display};
+
+use crate::RCODE;
 
 /// Error types for SimpleDns
 #[derive(Debug, PartialEq, Eq)]
@@ -14,6 +17,14 @@ pub enum SimpleDnsError {
     InvalidServiceName,
     /// Service Name Label doesn't follow RFC rules
     InvalidServiceLabel,
+    /// Name is longer than 255 octets in wire format, contains the length of the name
+    NameTooLong(usize),
+    /// Label is longer than 63 octets, contains the length of the label
+    LabelTooLong(usize),
+    /// Name contains a compression pointer that points forward or creates a loop
+    InvalidName,
+    /// Unicode label can't be converted to its A-label form, the Punycode encoding overflows
+    InvalidUnicodeLabel,
     /// Character String doesn't follow RFC rules
     InvalidCharacterString,
     /// Provided data is not valid for a header
@@ -26,6 +37,14 @@ pub enum SimpleDnsError {
     InsufficientData,
     /// Failed to write the packet to the provided buffer
     FailedToWrite,
+    /// Text in presentation format, as used by zone files, is invalid. Contains a description of the problem
+    InvalidPresentationFormat(String),
+    /// Resource record is not valid, like rdata of a different type than the record. Contains a description of the problem
+    InvalidResourceRecord(String),
+    /// TSIG verification failed, contains the TSIG error, like 16 (BADSIG) or 18 (BADTIME)
+    InvalidTsig(u16),
+    /// Zone transfer was refused or failed, contains the response code of the server
+    TransferFailed(RCODE),
 }
 
 impl From<TryFromSliceError> for SimpleDnsError {
@@ -34,6 +53,7 @@ impl From<TryFromSliceError> for SimpleDnsError {
     }
 }
 
+#[cfg(feature = "std")]
 impl From<std::io::Error> for SimpleDnsError {
     fn from(_value: std::io::Error) -> Self {
         Self::FailedToWrite
@@ -43,7 +63,7 @@ impl From<std::io::Error> for SimpleDnsError {
 impl Error for SimpleDnsError {}
 
 impl Display for SimpleDnsError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             SimpleDnsError::InvalidClass(class) => {
                 write!(f, "Provided class is invalid: {0}", class)
@@ -58,6 +78,18 @@ impl Display for SimpleDnsError {
             SimpleDnsError::InvalidServiceLabel => {
                 write!(f, "Provied service name contains invalid label")
             }
+            SimpleDnsError::NameTooLong(len) => {
+                write!(f, "Name is {len} octets long, the maximum is 255 octets")
+            }
+            SimpleDnsError::LabelTooLong(len) => {
+                write!(f, "Label is {len} octets long, the maximum is 63 octets")
+            }
+            SimpleDnsError::InvalidName => {
+                write!(f, "Name contains an invalid compression pointer")
+            }
+            SimpleDnsError::InvalidUnicodeLabel => {
+                write!(f, "Unicode label can't be converted to its ASCII form")
+            }
             SimpleDnsError::InvalidCharacterString => {
                 write!(f, "Provided character string is not valid")
             }
@@ -74,6 +106,18 @@ impl Display for SimpleDnsError {
             SimpleDnsError::FailedToWrite => {
                 write!(f, "Failed to write the packet to provided buffer")
             }
+            SimpleDnsError::InvalidPresentationFormat(message) => {
+                write!(f, "Invalid presentation format: {message}")
+            }
+            SimpleDnsError::InvalidResourceRecord(message) => {
+                write!(f, "Invalid resource record: {message}")
+            }
+            SimpleDnsError::InvalidTsig(error) => {
+                write!(f, "TSIG verification failed with error {error}")
+            }
+            SimpleDnsError::TransferFailed(rcode) => {
+                write!(f, "Zone transfer failed with response code {rcode:?}")
+            }
         }
     }
 }
@@ -0,0 +1,47 @@
+#![cfg(not(feature = "std"))]
+
+use simple_dns::{
+    rdata::{RData, A, SRV},
+    Name, Packet, Question, ResourceRecord, CLASS, QCLASS, QTYPE, TYPE,
+};
+
+#[test]
+fn parse_and_write_without_std() {
+    let mut packet = Packet::new_reply(1);
+    packet.questions.push(Question::new(
+        Name::new_unchecked("_srv._tcp.local"),
+        QTYPE::TYPE(TYPE::SRV),
+        QCLASS::CLASS(CLASS::IN),
+        false,
+    ));
+    packet.answers.push(ResourceRecord::new(
+        Name::new_unchecked("_srv._tcp.local"),
+        CLASS::IN,
+        10,
+        RData::SRV(SRV {
+            priority: 1,
+            weight: 2,
+            port: 8080,
+            target: Name::new_unchecked("host.local"),
+        }),
+    ));
+    packet.additional_records.push(ResourceRecord::new(
+        Name::new_unchecked("host.local"),
+        CLASS::IN,
+        10,
+        RData::A(A {
+            address: 0x7f000001,
+        }),
+    ));
+
+    let uncompressed = packet.build_bytes_vec().unwrap();
+    let compressed = packet.build_bytes_vec_compressed().unwrap();
+    assert!(compressed.len() < uncompressed.len());
+
+    for data in [uncompressed, compressed] {
+        let parsed = Packet::parse(&data).unwrap();
+        assert_eq!(packet.questions, parsed.questions);
+        assert_eq!(packet.answers, parsed.answers);
+        assert_eq!(packet.additional_records, parsed.additional_records);
+    }
+}
@@ -1,6 +1,9 @@
+use std::borrow::Cow;
+
 use simple_dns::{
-    rdata::{RData, A},
-    Name, Packet, ResourceRecord, SimpleDnsError, CLASS, QCLASS, QTYPE, RCODE, TYPE,
+    rdata::{RData, A, AAAA, CAA, HINFO, HIP, MX, NSEC, NULL, SOA, SRV, TKEY, TLSA, TXT, WKS},
+    CharacterString, Name, Packet, ResourceRecord, SimpleDnsError, CLASS, QCLASS, QTYPE, RCODE,
+    TYPE,
 };
 
 #[test]
@@ -120,6 +123,7 @@ fn parse_edns_packet() {
         opt_codes: Default::default(),
         udp_packet_size: 500,
         version: 3,
+        dnssec_ok: false,
     });
 
     let buffer = packet.build_bytes_vec().expect("Failed to write packet");
@@ -129,3 +133,426 @@ fn parse_edns_packet() {
     assert_eq!(RCODE::BADVERS, packet.rcode());
     assert_eq!(3, packet.opt().map(|opt| opt.version).unwrap());
 }
+
+#[test]
+fn parse_announcement_without_questions() -> Result<(), SimpleDnsError> {
+    let mut announcement = Packet::new_reply(0);
+    announcement.set_flags(simple_dns::PacketFlag::AUTHORITATIVE_ANSWER);
+    announcement.answers.push(ResourceRecord::new(
+        Name::new_unchecked("a._tcp.local"),
+        CLASS::IN,
+        120,
+        RData::A(A { address: 10 }),
+    ));
+    announcement.answers.push(ResourceRecord::new(
+        Name::new_unchecked("b._tcp.local"),
+        CLASS::IN,
+        120,
+        RData::A(A { address: 20 }),
+    ));
+    announcement.additional_records.push(ResourceRecord::new(
+        Name::new_unchecked("c._tcp.local"),
+        CLASS::IN,
+        120,
+        RData::A(A { address: 30 }),
+    ));
+
+    let buffer = announcement.build_bytes_vec_compressed()?;
+    let packet = Packet::parse(&buffer[..])?;
+
+    assert!(packet.has_flags(simple_dns::PacketFlag::RESPONSE));
+    assert!(packet.questions.is_empty());
+    assert_eq!(2, packet.answers.len());
+    assert_eq!(1, packet.additional_records.len());
+
+    assert_eq!("a._tcp.local", packet.answers[0].name.to_string());
+    assert_eq!("b._tcp.local", packet.answers[1].name.to_string());
+    match &packet.answers[1].rdata {
+        RData::A(a) => assert_eq!(20, a.address),
+        _ => panic!("invalid RDATA"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn parsed_rdata_borrows_from_buffer() -> Result<(), SimpleDnsError> {
+    let mut packet = Packet::new_reply(1);
+    let name = Name::new_unchecked("host.local");
+    packet.answers.push(ResourceRecord::new(
+        name.clone(),
+        CLASS::IN,
+        10,
+        RData::NULL(9999, NULL::try_from(vec![1, 2, 3])?),
+    ));
+    packet.answers.push(ResourceRecord::new(
+        name.clone(),
+        CLASS::IN,
+        10,
+        RData::TXT(TXT::new().with_string("key=value")?),
+    ));
+    packet.answers.push(ResourceRecord::new(
+        name,
+        CLASS::IN,
+        10,
+        RData::TLSA(TLSA {
+            cert_usage: 3,
+            selector: 1,
+            matching_type: 1,
+            data: Cow::Borrowed(&[4, 5, 6]),
+        }),
+    ));
+
+    let bytes = packet.build_bytes_vec()?;
+    let in_buffer = |slice: &[u8]| bytes.as_ptr_range().contains(&slice.as_ptr());
+
+    let parsed = Packet::parse(&bytes)?;
+    match (
+        &parsed.answers[0].rdata,
+        &parsed.answers[1].rdata,
+        &parsed.answers[2].rdata,
+    ) {
+        (RData::NULL(9999, null), RData::TXT(txt), RData::TLSA(tlsa)) => {
+            assert!(in_buffer(null.get_data()));
+            assert!(in_buffer(txt.strings()[0].as_bytes()));
+            assert!(matches!(tlsa.data, Cow::Borrowed(_)));
+            assert!(in_buffer(&tlsa.data));
+        }
+        rdata => panic!("unexpected rdata {rdata:?}"),
+    }
+
+    let owned: Packet<'static> = parsed.into_owned();
+    match (
+        &owned.answers[0].rdata,
+        &owned.answers[1].rdata,
+        &owned.answers[2].rdata,
+    ) {
+        (RData::NULL(9999, null), RData::TXT(txt), RData::TLSA(tlsa)) => {
+            assert!(!in_buffer(null.get_data()));
+            assert!(!in_buffer(txt.strings()[0].as_bytes()));
+            assert!(matches!(tlsa.data, Cow::Owned(_)));
+        }
+        rdata => panic!("unexpected rdata {rdata:?}"),
+    }
+
+    drop(bytes);
+    assert_eq!(packet.answers, owned.answers);
+
+    Ok(())
+}
+
+#[test]
+fn compression_spans_every_section() -> Result<(), SimpleDnsError> {
+    let mut packet = Packet::new_reply(0);
+    packet.answers.push(ResourceRecord::new(
+        Name::new_unchecked("_http._tcp.local"),
+        CLASS::IN,
+        10,
+        RData::SRV(SRV {
+            priority: 0,
+            weight: 0,
+            port: 80,
+            target: Name::new_unchecked("host.local"),
+        }),
+    ));
+    packet.additional_records.push(ResourceRecord::new(
+        Name::new_unchecked("host.local"),
+        CLASS::IN,
+        10,
+        RData::A(A {
+            address: 0xc0a80001,
+        }),
+    ));
+
+    let data = packet.build_bytes_vec_compressed()?;
+    // the SRV target points to "local" in the answers section, saving 5 bytes, and the additional record name
+    // is a single pointer to the SRV target, saving 10 bytes
+    assert_eq!(packet.uncompressed_len() - 5 - 10, data.len());
+
+    // the additional record name points to the SRV target, in the answers section
+    let record_start = data.len() - 16;
+    assert_eq!(0xc0, data[record_start] & 0xc0);
+    let pointer =
+        (u16::from_be_bytes([data[record_start], data[record_start + 1]]) & 0x3fff) as usize;
+    assert!(pointer > 12 && pointer < record_start);
+    assert_eq!(b"\x04host", &data[pointer..pointer + 5]);
+
+    let parsed = Packet::parse(&data)?;
+    assert_eq!(packet.answers, parsed.answers);
+    assert_eq!(packet.additional_records, parsed.additional_records);
+
+    Ok(())
+}
+
+#[test]
+fn unknown_record_type_round_trip() -> Result<(), SimpleDnsError> {
+    // reply with a record of the made up TYPE 9999 followed by an A record
+    let bytes = b"\x00\x05\x84\x00\x00\x00\x00\x02\x00\x00\x00\x00\
+        \x04host\x05local\x00\x27\x0f\x00\x01\x00\x00\x00\x78\x00\x05\x01\x02\x03\x04\x05\
+        \x04host\x05local\x00\x00\x01\x00\x01\x00\x00\x00\x78\x00\x04\xc0\xa8\x01\x02";
+    let packet = Packet::parse(bytes)?;
+
+    assert_eq!(2, packet.answers.len());
+    assert_eq!(TYPE::Unknown(9999), packet.answers[0].rdata.type_code());
+    match &packet.answers[0].rdata {
+        RData::NULL(9999, data) => assert_eq!(&[1, 2, 3, 4, 5], data.get_data()),
+        rdata => panic!("unexpected rdata {rdata:?}"),
+    }
+    assert_eq!(
+        RData::A(A {
+            address: 0xc0a80102
+        }),
+        packet.answers[1].rdata
+    );
+
+    assert_eq!(&bytes[..], &packet.build_bytes_vec()?[..]);
+
+    Ok(())
+}
+
+#[test]
+fn parse_empty_rdata() -> Result<(), SimpleDnsError> {
+    let record = |class: CLASS| {
+        let mut packet = Packet::new_reply(1);
+        packet.answers.push(ResourceRecord::new(
+            Name::new_unchecked("host.example.com"),
+            class,
+            0,
+            RData::A(A { address: 1 }),
+        ));
+        let mut data = packet.build_bytes_vec().unwrap();
+        // drop the address and set RDLENGTH to 0
+        data.truncate(data.len() - 4);
+        let rdlength_position = data.len() - 2;
+        data[rdlength_position..].copy_from_slice(&[0, 0]);
+        data
+    };
+
+    // an empty A rdata is only valid for UPDATE prerequisites and deletions
+    assert_eq!(
+        Err(SimpleDnsError::InsufficientData),
+        Packet::parse(&record(CLASS::IN))
+    );
+    for class in [CLASS::ANY, CLASS::NONE] {
+        let data = record(class);
+        let packet = Packet::parse(&data)?;
+        assert!(matches!(packet.answers[0].rdata, RData::NULL(1, _)));
+        assert_eq!(data, packet.build_bytes_vec()?);
+    }
+
+    // empty rdata of every type is either parsed or rejected, without panicking
+    for rtype in 0..=u16::MAX {
+        let mut data = record(CLASS::IN);
+        let type_position = data.len() - 10;
+        data[type_position..type_position + 2].copy_from_slice(&rtype.to_be_bytes());
+        let _ = Packet::parse(&data);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn parse_truncated_records() -> Result<(), SimpleDnsError> {
+    let name = Name::new_unchecked;
+    let string = |value: &'static str| CharacterString::new(value.as_bytes()).unwrap();
+    let rdatas = [
+        RData::A(A { address: 1 }),
+        RData::AAAA(AAAA { address: 1 }),
+        RData::MX(MX {
+            preference: 10,
+            exchange: name("mail.example.com"),
+        }),
+        RData::SRV(SRV {
+            priority: 1,
+            weight: 2,
+            port: 8080,
+            target: name("host.example.com"),
+        }),
+        RData::SOA(SOA {
+            mname: name("ns.example.com"),
+            rname: name("admin.example.com"),
+            serial: 1,
+            refresh: 2,
+            retry: 3,
+            expire: 4,
+            minimum: 5,
+        }),
+        RData::TXT(TXT::new().with_string("key=value")?.with_string("other")?),
+        RData::HINFO(HINFO {
+            cpu: string("cpu"),
+            os: string("os"),
+        }),
+        RData::CAA(CAA {
+            flag: 0,
+            tag: string("issue"),
+            value: string("ca.example.net"),
+        }),
+        RData::WKS(WKS {
+            address: 1,
+            protocol: 6,
+            bit_map: Cow::Borrowed(&[0, 0, 0, 0b0100_0000]),
+        }),
+        RData::TLSA(TLSA {
+            cert_usage: 3,
+            selector: 1,
+            matching_type: 1,
+            data: Cow::Borrowed(&[1, 2, 3, 4]),
+        }),
+        RData::HIP(HIP {
+            pk_algorithm: 2,
+            hit: Cow::Borrowed(&[1; 16]),
+            public_key: Cow::Borrowed(b"key"),
+            rendezvous_servers: vec![name("rvs.example.com")],
+        }),
+        RData::NSEC(NSEC::new(name("next.example.com"), &[TYPE::A, TYPE::MX])),
+        RData::TKEY(TKEY {
+            algorithm: name("hmac-sha256"),
+            inception: 1,
+            expiration: 2,
+            mode: 3,
+            error: 0,
+            key_data: Cow::Borrowed(&[1, 2, 3]),
+            other_data: Cow::Borrowed(&[4]),
+        }),
+    ];
+
+    for rdata in rdatas {
+        let mut packet = Packet::new_reply(1);
+        packet.answers.push(ResourceRecord::new(
+            name("host.example.com"),
+            CLASS::IN,
+            300,
+            rdata,
+        ));
+        let data = packet.build_bytes_vec()?;
+        assert!(Packet::parse(&data).is_ok());
+
+        // header, owner name of 18 bytes, type, class and ttl
+        let rdlength_position = 12 + 18 + 8;
+        let rdata_start = rdlength_position + 2;
+        let rdlength = data.len() - rdata_start;
+
+        // every truncation of the packet is rejected
+        for len in 0..data.len() {
+            assert!(Packet::parse(&data[..len]).is_err());
+        }
+
+        // rdata lengths past the end of the packet are rejected
+        for declared in rdlength + 1..rdlength + 64 {
+            let mut data = data.clone();
+            data[rdlength_position..rdata_start].copy_from_slice(&(declared as u16).to_be_bytes());
+            assert_eq!(Err(SimpleDnsError::InsufficientData), Packet::parse(&data));
+        }
+
+        // shorter rdata lengths may or may not be valid, but never panic
+        for declared in 0..rdlength {
+            let mut data = data[..rdata_start + declared].to_vec();
+            data[rdlength_position..rdata_start].copy_from_slice(&(declared as u16).to_be_bytes());
+            let _ = Packet::parse(&data);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn compressed_rdata_updates_rdlength() -> Result<(), SimpleDnsError> {
+    let mut packet = Packet::new_reply(1);
+    for instance in ["a._http._tcp.local", "b._http._tcp.local"] {
+        packet.answers.push(ResourceRecord::new(
+            Name::new_unchecked("_http._tcp.local"),
+            CLASS::IN,
+            10,
+            RData::PTR(Name::new_unchecked(instance).into()),
+        ));
+    }
+    packet.answers.push(ResourceRecord::new(
+        Name::new_unchecked("a._http._tcp.local"),
+        CLASS::IN,
+        10,
+        RData::A(A { address: 1 }),
+    ));
+
+    let data = packet.build_bytes_vec_compressed()?;
+
+    // the first PTR target is "a" followed by a pointer to the owner name, 4 bytes instead of 20
+    let rdlength_start = 12 + 18 + 8;
+    assert_eq!(&[0, 4], &data[rdlength_start..rdlength_start + 2]);
+
+    // a wrong RDLENGTH would make the following records unreadable
+    let parsed = Packet::parse(&data)?;
+    assert_eq!(packet.answers, parsed.answers);
+
+    Ok(())
+}
+
+#[test]
+fn root_name_round_trip() -> Result<(), SimpleDnsError> {
+    let root = Name::new(".")?;
+    assert_eq!(0, root.labels_count());
+    assert_eq!(root, Name::new_unchecked("."));
+
+    let mut packet = Packet::new_query(1);
+    packet.questions.push(simple_dns::Question::new(
+        root.clone(),
+        TYPE::NS.into(),
+        CLASS::IN.into(),
+        false,
+    ));
+    packet.answers.push(ResourceRecord::new(
+        root.clone(),
+        CLASS::IN,
+        10,
+        RData::NS(Name::new_unchecked("a.root-servers.net").into()),
+    ));
+
+    for data in [
+        packet.build_bytes_vec()?,
+        packet.build_bytes_vec_compressed()?,
+    ] {
+        // the root name is a single zero byte, not an empty label followed by the terminator
+        assert_eq!(b"\x00\x00\x02\x00\x01", &data[12..17]);
+        assert_eq!(b"\x00\x00\x02\x00\x01", &data[17..22]);
+
+        let parsed = Packet::parse(&data)?;
+        assert_eq!(packet.questions, parsed.questions);
+        assert_eq!(packet.answers, parsed.answers);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn compression_points_only_to_matching_suffixes() -> Result<(), SimpleDnsError> {
+    let mut packet = Packet::new_reply(1);
+    for (name, address) in [("a.local", 1), ("a.b.local", 2), ("b.local", 3)] {
+        packet.answers.push(ResourceRecord::new(
+            Name::new_unchecked(name),
+            CLASS::IN,
+            10,
+            RData::A(A { address }),
+        ));
+    }
+
+    let data = packet.build_bytes_vec_compressed()?;
+    let parsed = Packet::parse(&data)?;
+    assert_eq!(
+        vec!["a.local", "a.b.local", "b.local"],
+        parsed
+            .answers
+            .iter()
+            .map(|record| record.name.to_string())
+            .collect::<Vec<_>>()
+    );
+
+    // a.b.local is written as a, b and a pointer to local, b.local is a pointer to b in a.b.local
+    let second = 12 + 9 + 14;
+    assert_eq!(b"\x01a\x01b\xc0\x0e", &data[second..second + 6]);
+    let third = second + 6 + 14;
+    assert_eq!(
+        &(0xc000u16 | (second as u16 + 2)).to_be_bytes(),
+        &data[third..third + 2]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,120 @@
+#![cfg(feature = "serde")]
+
+use simple_dns::{
+    rdata::{RData, A, SRV, TXT},
+    CharacterString, Name, Packet, Question, ResourceRecord, CLASS, QCLASS, QTYPE, TYPE,
+};
+
+#[test]
+fn packet_round_trip_through_json() {
+    let name = Name::new_unchecked("inst._srv._tcp.local");
+
+    let mut packet = Packet::new_reply(42);
+    packet.set_authoritative_answer(true);
+    packet.questions.push(Question::new(
+        name.clone(),
+        QTYPE::ANY,
+        QCLASS::CLASS(CLASS::IN),
+        true,
+    ));
+    packet.answers.push(ResourceRecord::new(
+        name.clone(),
+        CLASS::IN,
+        120,
+        RData::SRV(SRV {
+            priority: 0,
+            weight: 0,
+            port: 8080,
+            target: Name::new_unchecked("host.local"),
+        }),
+    ));
+    packet.answers.push(ResourceRecord::new(
+        name.clone(),
+        CLASS::IN,
+        4500,
+        RData::TXT(TXT::new().with_string("key=value").unwrap()),
+    ));
+    packet.additional_records.push(ResourceRecord::new(
+        Name::new_unchecked("host.local"),
+        CLASS::IN,
+        120,
+        RData::A(A {
+            address: 0x7f000001,
+        }),
+    ));
+
+    let json = serde_json::to_string(&packet).unwrap();
+    assert!(json.contains("\"inst._srv._tcp.local\""));
+    assert!(json.contains("\"SRV\""));
+
+    let deserialized: Packet<'static> = serde_json::from_str(&json).unwrap();
+    assert_eq!(42, deserialized.id());
+    assert!(deserialized.authoritative_answer());
+    assert_eq!(packet.questions, deserialized.questions);
+    assert_eq!(packet.answers, deserialized.answers);
+    assert_eq!(packet.additional_records, deserialized.additional_records);
+    assert_eq!(
+        packet.build_bytes_vec().unwrap(),
+        deserialized.build_bytes_vec().unwrap()
+    );
+
+    let rtype: TYPE = serde_json::from_str(&serde_json::to_string(&TYPE::AAAA).unwrap()).unwrap();
+    assert_eq!(TYPE::AAAA, rtype);
+}
+
+#[test]
+fn name_with_escaped_labels_round_trips() {
+    let mut name = Name::new_unchecked("a\\.b.local");
+    name.append_label(&[0xff, b' ', b'\\']).unwrap();
+    assert_eq!(3, name.labels_count());
+
+    let json = serde_json::to_string(&name).unwrap();
+    assert_eq!(r#""a\\.b.local.\\255\\032\\\\""#, json);
+
+    let deserialized: Name = serde_json::from_str(&json).unwrap();
+    assert_eq!(name, deserialized);
+    assert_eq!(
+        name.iter().collect::<Vec<_>>(),
+        deserialized.iter().collect::<Vec<_>>()
+    );
+
+    assert!(serde_json::from_str::<Name>(r#""a\\256.local""#).is_err());
+    assert!(serde_json::from_str::<Name>(r#""local\\""#).is_err());
+}
+
+#[test]
+fn character_strings_are_validated() {
+    let too_long = format!(r#"{{"data":{:?}}}"#, vec![b'a'; 256]);
+    assert!(serde_json::from_str::<CharacterString>(&too_long).is_err());
+
+    let longest = format!(r#"{{"data":{:?}}}"#, vec![b'a'; 255]);
+    let character_string: CharacterString = serde_json::from_str(&longest).unwrap();
+    assert_eq!(&[b'a'; 255][..], character_string.as_bytes());
+}
+
+#[test]
+fn txt_length_is_computed_from_its_strings() {
+    let txt = TXT::new()
+        .with_string("key=value")
+        .unwrap()
+        .with_string("flag")
+        .unwrap();
+
+    let json = serde_json::to_string(&txt).unwrap();
+    assert!(!json.contains("size"));
+
+    let deserialized: TXT = serde_json::from_str(&json).unwrap();
+    assert_eq!(txt, deserialized);
+
+    let packet_with = |txt| {
+        let mut packet = Packet::new_reply(1);
+        packet.answers.push(ResourceRecord::new(
+            Name::new_unchecked("a.local"),
+            CLASS::IN,
+            10,
+            RData::TXT(txt),
+        ));
+        packet.build_bytes_vec().unwrap()
+    };
+    assert_eq!(packet_with(txt), packet_with(deserialized));
+}
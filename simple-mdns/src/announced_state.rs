@@ -0,0 +1,178 @@
+#[cfg(any(feature = "sync", feature = "async-tokio"))]
+use std::collections::HashSet;
+
+#[cfg(any(feature = "sync", feature = "async-tokio"))]
+use simple_dns::Packet;
+use simple_dns::ResourceRecord;
+
+/// Persists the records announced by a service discovery, so a restarted service only announces the records
+/// that changed since the last announcement
+pub trait AnnouncedStateStore: Send + Sync {
+    /// Returns the records saved by the last call to [`save_announced_state`](AnnouncedStateStore::save_announced_state)
+    fn load_announced_state(&self) -> Vec<ResourceRecord<'static>>;
+
+    /// Saves the records currently announced, replacing any previously saved state
+    fn save_announced_state(&self, records: &[ResourceRecord<'static>]);
+}
+
+/// Keeps track of the announced records when a [`AnnouncedStateStore`] is provided
+#[cfg(any(feature = "sync", feature = "async-tokio"))]
+#[derive(Default)]
+pub(crate) struct AnnouncedState {
+    store: Option<Box<dyn AnnouncedStateStore>>,
+    announced: HashSet<ResourceRecord<'static>>,
+}
+
+#[cfg(any(feature = "sync", feature = "async-tokio"))]
+impl AnnouncedState {
+    /// Replaces the store and loads the previously announced records from it
+    pub fn set_store(&mut self, store: Box<dyn AnnouncedStateStore>) {
+        self.announced = store.load_announced_state().into_iter().collect();
+        self.store = Some(store);
+    }
+
+    /// Removes the records that were already announced from `records`.
+    /// Nothing is removed when there is no store
+    pub fn retain_unannounced<'a>(&self, records: &mut Vec<ResourceRecord<'a>>) {
+        let announced: &HashSet<ResourceRecord<'a>> = &self.announced;
+        if self.store.is_some() {
+            records.retain(|record| !announced.contains(record));
+        }
+    }
+
+    /// Records the answers and additional records of `packet` as announced, then removes the ones that were
+    /// already announced. When only additional records changed, like the address of the host, they are moved to
+    /// the answers so the packet still announces them
+    pub fn retain_unannounced_packet(&mut self, packet: &mut Packet<'_>) {
+        let announced = packet
+            .answers
+            .iter()
+            .chain(packet.additional_records.iter())
+            .map(|r| r.clone().into_owned())
+            .collect();
+
+        self.retain_unannounced(&mut packet.answers);
+        self.retain_unannounced(&mut packet.additional_records);
+        if packet.answers.is_empty() {
+            packet.answers.append(&mut packet.additional_records);
+        }
+
+        self.set_announced(announced);
+    }
+
+    /// Replaces the announced records and saves them to the store
+    pub fn set_announced(&mut self, records: HashSet<ResourceRecord<'static>>) {
+        self.announced = records;
+        if let Some(store) = &self.store {
+            store.save_announced_state(&self.announced.iter().cloned().collect::<Vec<_>>());
+        }
+    }
+}
+
+#[cfg(all(test, any(feature = "sync", feature = "async-tokio")))]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use simple_dns::{
+        rdata::{RData, A},
+        Name, CLASS,
+    };
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct MemoryStore(Arc<Mutex<Vec<ResourceRecord<'static>>>>);
+
+    impl AnnouncedStateStore for MemoryStore {
+        fn load_announced_state(&self) -> Vec<ResourceRecord<'static>> {
+            self.0.lock().unwrap().clone()
+        }
+
+        fn save_announced_state(&self, records: &[ResourceRecord<'static>]) {
+            *self.0.lock().unwrap() = records.to_vec();
+        }
+    }
+
+    fn a_record(address: u32) -> ResourceRecord<'static> {
+        ResourceRecord::new(
+            Name::new_unchecked("my_inst._srv._udp.local"),
+            CLASS::IN,
+            10,
+            RData::A(A { address }),
+        )
+    }
+
+    fn announce(state: &mut AnnouncedState, records: &[ResourceRecord<'static>]) -> usize {
+        let mut pending = records.to_vec();
+        state.retain_unannounced(&mut pending);
+        state.set_announced(records.iter().cloned().collect());
+
+        pending.len()
+    }
+
+    #[test]
+    fn restart_with_identical_records_announces_nothing() {
+        let store = MemoryStore::default();
+        let records = [a_record(1), a_record(2)];
+
+        let mut state = AnnouncedState::default();
+        state.set_store(Box::new(store.clone()));
+        assert_eq!(2, announce(&mut state, &records));
+
+        let mut restarted = AnnouncedState::default();
+        restarted.set_store(Box::new(store.clone()));
+        assert_eq!(0, announce(&mut restarted, &records));
+
+        let mut restarted = AnnouncedState::default();
+        restarted.set_store(Box::new(store));
+        assert_eq!(1, announce(&mut restarted, &[a_record(1), a_record(3)]));
+    }
+
+    #[test]
+    fn address_change_is_announced() {
+        let store = MemoryStore::default();
+        let srv = ResourceRecord::new(
+            Name::new_unchecked("my_inst._srv._udp.local"),
+            CLASS::IN,
+            10,
+            RData::TXT(Default::default()),
+        );
+
+        let mut state = AnnouncedState::default();
+        state.set_store(Box::new(store.clone()));
+
+        let mut packet = Packet::new_reply(1);
+        packet.answers.push(srv.clone());
+        packet.additional_records.push(a_record(1));
+        state.retain_unannounced_packet(&mut packet);
+        assert_eq!(1, packet.answers.len());
+        assert_eq!(1, packet.additional_records.len());
+
+        let mut restarted = AnnouncedState::default();
+        restarted.set_store(Box::new(store.clone()));
+
+        let mut packet = Packet::new_reply(1);
+        packet.answers.push(srv.clone());
+        packet.additional_records.push(a_record(1));
+        restarted.retain_unannounced_packet(&mut packet);
+        assert!(packet.answers.is_empty());
+        assert!(packet.additional_records.is_empty());
+
+        let mut packet = Packet::new_reply(1);
+        packet.answers.push(srv);
+        packet.additional_records.push(a_record(2));
+        restarted.retain_unannounced_packet(&mut packet);
+        assert_eq!(vec![a_record(2)], packet.answers);
+        assert!(packet.additional_records.is_empty());
+        assert_eq!(2, store.load_announced_state().len());
+    }
+
+    #[test]
+    fn without_store_everything_is_announced() {
+        let records = [a_record(1), a_record(2)];
+
+        let mut state = AnnouncedState::default();
+        assert_eq!(2, announce(&mut state, &records));
+        assert_eq!(2, announce(&mut state, &records));
+    }
+}
@@ -1,9 +1,19 @@
-//! Contains the async (tokio) version of service discovery
+//! Contains the async version of service discovery, running on tokio with the `async-tokio` feature.
+//! With only the `async-std` feature enabled, [`SimpleMdnsResponder`] runs on async-std and the other types are not available
 
+#[cfg(feature = "async-tokio")]
 mod oneshot_resolver;
+mod runtime;
+#[cfg(feature = "async-tokio")]
+mod service_browser;
+#[cfg(feature = "async-tokio")]
 mod service_discovery;
 mod simple_responder;
 
+#[cfg(feature = "async-tokio")]
 pub use oneshot_resolver::OneShotMdnsResolver;
+#[cfg(feature = "async-tokio")]
+pub use service_browser::ServiceBrowser;
+#[cfg(feature = "async-tokio")]
 pub use service_discovery::ServiceDiscovery;
 pub use simple_responder::SimpleMdnsResponder;
@@ -1,14 +1,19 @@
+use super::runtime::nonblocking;
 use crate::{
-    socket_helper::{join_multicast, nonblocking, sender_socket},
-    NetworkScope, SimpleMdnsError, UNICAST_RESPONSE,
+    is_query_response, received_datagram,
+    socket_helper::{join_multicast, sender_socket},
+    NetworkScope, ReachabilityFilter, SimpleMdnsError, RECV_BUFFER_SIZE, UNICAST_RESPONSE,
 };
-use simple_dns::{header_buffer, rdata::RData, Name, Packet, Question, CLASS, TYPE};
+use simple_dns::{rdata::RData, Name, Packet, Question, ResourceRecord, CLASS, TYPE};
 
-use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::{
+    collections::HashSet,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+};
 
 use tokio::{
     net::UdpSocket,
-    time::{timeout_at, Duration, Instant},
+    time::{sleep_until, Duration, Instant},
 };
 
 /// Provides One Shot queries (legacy mDNS)
@@ -35,13 +40,14 @@ use tokio::{
 ///
 ///     # };
 /// ```
-
 pub struct OneShotMdnsResolver {
     query_timeout: Duration,
     unicast_response: bool,
     receiver_socket: UdpSocket,
     sender_socket: UdpSocket,
     network_scope: NetworkScope,
+    reachability_filter: Option<ReachabilityFilter>,
+    strict_id_matching: bool,
 }
 
 impl OneShotMdnsResolver {
@@ -55,8 +61,10 @@ impl OneShotMdnsResolver {
         Ok(Self {
             query_timeout: Duration::from_secs(3),
             unicast_response: UNICAST_RESPONSE,
-            sender_socket: sender_socket(network_scope.is_v4()).and_then(nonblocking)?,
+            sender_socket: sender_socket(network_scope).and_then(nonblocking)?,
             network_scope,
+            reachability_filter: None,
+            strict_id_matching: false,
             receiver_socket: join_multicast(network_scope).and_then(nonblocking)?,
         })
     }
@@ -81,7 +89,7 @@ impl OneShotMdnsResolver {
         &self,
         service_name: &str,
     ) -> Result<Option<std::net::IpAddr>, SimpleMdnsError> {
-        let mut packet = Packet::new_query(0);
+        let mut packet = Packet::new_query_with_random_id();
         let service_name = Name::new(service_name)?;
         packet.questions.push(Question::new(
             service_name.clone(),
@@ -121,11 +129,15 @@ impl OneShotMdnsResolver {
                     continue;
                 }
 
-                return match anwser.rdata {
-                    RData::A(a) => Ok(Some(IpAddr::V4(Ipv4Addr::from(a.address)))),
-                    RData::AAAA(aaaa) => Ok(Some(IpAddr::V6(Ipv6Addr::from(aaaa.address)))),
-                    _ => Ok(None),
+                let address = match anwser.rdata {
+                    RData::A(a) => IpAddr::V4(Ipv4Addr::from(a.address)),
+                    RData::AAAA(aaaa) => IpAddr::V6(Ipv6Addr::from(aaaa.address)),
+                    _ => return Ok(None),
                 };
+
+                if self.is_reachable(&address) {
+                    return Ok(Some(address));
+                }
             }
         }
 
@@ -137,7 +149,7 @@ impl OneShotMdnsResolver {
         &self,
         service_name: &str,
     ) -> Result<Option<std::net::SocketAddr>, SimpleMdnsError> {
-        let mut packet = Packet::new_query(0);
+        let mut packet = Packet::new_query_with_random_id();
         let parsed_name_service = Name::new(service_name)?;
         packet.questions.push(Question::new(
             parsed_name_service.clone(),
@@ -185,11 +197,12 @@ impl OneShotMdnsResolver {
                 .additional_records
                 .iter()
                 .filter(|a| a.name == parsed_name_service && a.match_qtype(TYPE::A.into()))
-                .find_map(|a| match &a.rdata {
+                .filter_map(|a| match &a.rdata {
                     RData::A(a) => Some(IpAddr::V4(Ipv4Addr::from(a.address))),
                     RData::AAAA(aaaa) => Some(IpAddr::V6(Ipv6Addr::from(aaaa.address))),
                     _ => None,
-                });
+                })
+                .find(|address| self.is_reachable(address));
 
             if port.is_some() && address.is_none() {
                 address = self.query_service_address(service_name).await?;
@@ -203,6 +216,76 @@ impl OneShotMdnsResolver {
         Ok(None)
     }
 
+    /// Send a query for `qtype` resources of `service_name` and collect the responses until the query timeout elapses.
+    ///
+    /// Returns the answers and additional records of every response received, records sent by more than one responder
+    /// (or sent more than once) are returned only once. Reaching the timeout is not an error, the records collected
+    /// so far are returned, which is an empty list when nothing answered
+    pub async fn query_service_records(
+        &self,
+        service_name: &str,
+        qtype: TYPE,
+    ) -> Result<Vec<ResourceRecord<'static>>, SimpleMdnsError> {
+        let mut packet = Packet::new_query_with_random_id();
+        packet.questions.push(Question::new(
+            Name::new(service_name)?,
+            qtype.into(),
+            CLASS::IN.into(),
+            self.unicast_response,
+        ));
+
+        self.collect_records(packet).await
+    }
+
+    /// Send `packet` and returns the answers and additional records of every response received before the query
+    /// timeout elapses, without duplicates
+    async fn collect_records(
+        &self,
+        packet: Packet<'_>,
+    ) -> Result<Vec<ResourceRecord<'static>>, SimpleMdnsError> {
+        self.sender_socket
+            .send_to(
+                &packet.build_bytes_vec_compressed()?,
+                self.network_scope.socket_address(),
+            )
+            .await?;
+
+        let mut seen = HashSet::new();
+        let mut records = Vec::new();
+        let deadline = Instant::now() + self.query_timeout;
+        loop {
+            let buffer = match self.get_next_response(packet.id(), deadline).await {
+                Ok(Some(buffer)) => buffer,
+                Ok(None) => break,
+                Err(err) => {
+                    log::error!("Received invalid packet: {}", err);
+                    continue;
+                }
+            };
+
+            let response = match Packet::parse(&buffer) {
+                Ok(packet) => packet,
+                Err(err) => {
+                    log::error!("Received invalid packet: {}", err);
+                    continue;
+                }
+            };
+
+            for record in response
+                .answers
+                .into_iter()
+                .chain(response.additional_records)
+            {
+                let record = record.into_owned();
+                if seen.insert(record.clone()) {
+                    records.push(record);
+                }
+            }
+        }
+
+        Ok(records)
+    }
+
     /// Set the one shot mdns resolver's query timeout.
     pub fn set_query_timeout(&mut self, query_timeout: Duration) {
         self.query_timeout = query_timeout;
@@ -213,27 +296,58 @@ impl OneShotMdnsResolver {
         self.unicast_response = unicast_response;
     }
 
+    /// Set a filter for the resolved addresses, addresses rejected by `filter` are never returned.  
+    /// This can be used to leave out addresses that are not reachable from this host,
+    /// like a loopback address advertised by a remote host
+    pub fn set_reachability_filter(
+        &mut self,
+        filter: impl Fn(&IpAddr) -> bool + Send + Sync + 'static,
+    ) {
+        self.reachability_filter = Some(Box::new(filter));
+    }
+
+    /// Require multicast responses to carry the ID of the query, disabled by default.  
+    /// Responses received via unicast always need the ID of the query, but mDNS responders send multicast responses
+    /// with the ID 0, which are accepted unless `strict_id_matching` is set
+    pub fn set_strict_id_matching(&mut self, strict_id_matching: bool) {
+        self.strict_id_matching = strict_id_matching;
+    }
+
+    fn is_reachable(&self, address: &IpAddr) -> bool {
+        match &self.reachability_filter {
+            Some(filter) => filter(address),
+            None => true,
+        }
+    }
+
+    /// Waits for the next response to the query `packet_id`, received via unicast on the sender socket or via
+    /// multicast on the receiver socket, until `deadline`
     async fn get_next_response(
         &self,
         packet_id: u16,
         deadline: Instant,
     ) -> Result<Option<Vec<u8>>, SimpleMdnsError> {
-        let mut buf = [0u8; 4096];
-
+        let mut unicast_buf = [0u8; RECV_BUFFER_SIZE];
+        let mut multicast_buf = [0u8; RECV_BUFFER_SIZE];
         loop {
-            match timeout_at(deadline, self.receiver_socket.recv_from(&mut buf[..])).await {
-                Ok(read_task) => {
-                    let (count, _) = read_task?;
-                    if header_buffer::has_flags(&buf, simple_dns::PacketFlag::RESPONSE)?
-                        && header_buffer::id(&buf)? == packet_id
-                        && header_buffer::answers(&buf)? > 0
-                    {
-                        return Ok(Some(buf[..count].to_vec()));
-                    }
-                }
-                Err(_) => {
-                    return Ok(None);
-                }
+            let (received, unicast) = tokio::select! {
+                received = self.sender_socket.recv(&mut unicast_buf) => (received, true),
+                received = self.receiver_socket.recv(&mut multicast_buf) => (received, false),
+                _ = sleep_until(deadline) => return Ok(None),
+            };
+
+            let buf = if unicast {
+                &unicast_buf
+            } else {
+                &multicast_buf
+            };
+            let datagram = match received_datagram(buf, received?) {
+                Some(datagram) => datagram,
+                None => continue,
+            };
+
+            if is_query_response(datagram, packet_id, unicast, self.strict_id_matching) {
+                return Ok(Some(datagram.to_vec()));
             }
         }
     }
@@ -0,0 +1,169 @@
+//! Sockets, tasks and timers of the async runtime used by the responder.
+//! tokio is used when the `async-tokio` feature is enabled, otherwise async-std is used
+
+#[cfg(feature = "async-tokio")]
+pub(crate) use tokio::{
+    net::UdpSocket,
+    sync::RwLock,
+    task::{spawn, JoinHandle},
+    time::{sleep, timeout},
+};
+
+#[cfg(not(feature = "async-tokio"))]
+pub(crate) use async_std::{
+    future::timeout,
+    net::UdpSocket,
+    sync::RwLock,
+    task::{sleep, spawn, JoinHandle},
+};
+
+/// Converts a socket created by [`socket_helper`](crate::socket_helper) to a socket of the runtime
+#[cfg(feature = "async-tokio")]
+pub(crate) fn nonblocking(socket: std::net::UdpSocket) -> std::io::Result<UdpSocket> {
+    socket.set_nonblocking(true)?;
+    UdpSocket::from_std(socket)
+}
+
+/// Converts a socket created by [`socket_helper`](crate::socket_helper) to a socket of the runtime
+#[cfg(not(feature = "async-tokio"))]
+pub(crate) fn nonblocking(socket: std::net::UdpSocket) -> std::io::Result<UdpSocket> {
+    socket.set_nonblocking(true)?;
+    Ok(UdpSocket::from(socket))
+}
+
+/// Receives a datagram from the first of `sockets` to have one, into the buffer with the same index.
+/// Returns the index of the socket along with the result of receiving from it
+pub(crate) async fn recv_any<const N: usize>(
+    sockets: &[&UdpSocket],
+    buffers: &mut [[u8; N]],
+) -> (usize, std::io::Result<usize>) {
+    use std::{future::Future, pin::Pin, task::Poll};
+
+    type Recv<'a> = Pin<Box<dyn Future<Output = std::io::Result<usize>> + Send + 'a>>;
+    let mut receives: Vec<Recv<'_>> = sockets
+        .iter()
+        .zip(buffers.iter_mut())
+        .map(|(socket, buffer)| Box::pin(socket.recv(buffer)) as Recv<'_>)
+        .collect();
+
+    std::future::poll_fn(|cx| {
+        receives
+            .iter_mut()
+            .enumerate()
+            .find_map(|(index, recv)| match recv.as_mut().poll(cx) {
+                Poll::Ready(received) => Some((index, received)),
+                Poll::Pending => None,
+            })
+            .map_or(Poll::Pending, Poll::Ready)
+    })
+    .await
+}
+
+/// Waits for `task` to finish, returns `None` when the task panicked or was cancelled
+#[cfg(feature = "async-tokio")]
+pub(crate) async fn join<T>(task: JoinHandle<T>) -> Option<T> {
+    match task.await {
+        Ok(result) => Some(result),
+        Err(err) => {
+            log::error!("Dns Responder task failed: {}", err);
+            None
+        }
+    }
+}
+
+/// Waits for `task` to finish
+#[cfg(not(feature = "async-tokio"))]
+pub(crate) async fn join<T>(task: JoinHandle<T>) -> Option<T> {
+    Some(task.await)
+}
+
+/// Stops every [`ShutdownListener`] created with it, dropping the signal leaves the listeners running
+#[cfg(feature = "async-tokio")]
+pub(crate) struct ShutdownSignal(tokio::sync::watch::Sender<bool>);
+/// Resolves when its [`ShutdownSignal`] is triggered
+#[cfg(feature = "async-tokio")]
+#[derive(Clone)]
+pub(crate) struct ShutdownListener(tokio::sync::watch::Receiver<bool>);
+
+/// Stops every [`ShutdownListener`] created with it, dropping the signal leaves the listeners running
+#[cfg(not(feature = "async-tokio"))]
+pub(crate) struct ShutdownSignal(async_std::channel::Sender<()>);
+/// Resolves when its [`ShutdownSignal`] is triggered
+#[cfg(not(feature = "async-tokio"))]
+#[derive(Clone)]
+pub(crate) struct ShutdownListener {
+    // nothing is sent through the channel, holding a sender keeps it open when the signal is dropped,
+    // so only closing it stops the listeners
+    _sender: async_std::channel::Sender<()>,
+    receiver: async_std::channel::Receiver<()>,
+}
+
+/// Creates a shutdown signal and its listener, clone the listener for every task that must be stopped
+#[cfg(feature = "async-tokio")]
+pub(crate) fn shutdown_signal() -> (ShutdownSignal, ShutdownListener) {
+    let (sender, receiver) = tokio::sync::watch::channel(false);
+    (ShutdownSignal(sender), ShutdownListener(receiver))
+}
+
+/// Creates a shutdown signal and its listener, clone the listener for every task that must be stopped
+#[cfg(not(feature = "async-tokio"))]
+pub(crate) fn shutdown_signal() -> (ShutdownSignal, ShutdownListener) {
+    let (sender, receiver) = async_std::channel::bounded(1);
+    let listener = ShutdownListener {
+        _sender: sender.clone(),
+        receiver,
+    };
+    (ShutdownSignal(sender), listener)
+}
+
+impl ShutdownSignal {
+    /// Stops every listener, waking the ones waiting in [`until_shutdown`]
+    #[cfg(feature = "async-tokio")]
+    pub fn trigger(&self) {
+        self.0.send_replace(true);
+    }
+
+    /// Stops every listener, waking the ones waiting in [`until_shutdown`]
+    #[cfg(not(feature = "async-tokio"))]
+    pub fn trigger(&self) {
+        self.0.close();
+    }
+}
+
+/// Runs `future` until it completes, returns `None` instead when the shutdown signal is triggered first
+#[cfg(feature = "async-tokio")]
+pub(crate) async fn until_shutdown<F: std::future::Future>(
+    listener: &mut ShutdownListener,
+    future: F,
+) -> Option<F::Output> {
+    if *listener.0.borrow() {
+        return None;
+    }
+
+    // an error means the signal was dropped without being triggered, which disables the branch
+    tokio::select! {
+        Ok(_) = listener.0.changed() => None,
+        output = future => Some(output),
+    }
+}
+
+/// Runs `future` until it completes, returns `None` instead when the shutdown signal is triggered first
+#[cfg(not(feature = "async-tokio"))]
+pub(crate) async fn until_shutdown<F: std::future::Future>(
+    listener: &mut ShutdownListener,
+    future: F,
+) -> Option<F::Output> {
+    use std::{future::Future, pin::pin, task::Poll};
+
+    // nothing is ever sent, so recv only returns when the channel is closed
+    let mut shutdown = pin!(listener.receiver.recv());
+    let mut future = pin!(future);
+    std::future::poll_fn(|cx| {
+        if shutdown.as_mut().poll(cx).is_ready() {
+            return Poll::Ready(None);
+        }
+
+        future.as_mut().poll(cx).map(Some)
+    })
+    .await
+}
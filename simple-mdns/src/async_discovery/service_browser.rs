@@ -0,0 +1,227 @@
+use simple_dns::{rdata::RData, Name, Packet, PacketFlag, Question, ResourceRecord, CLASS, TYPE};
+use tokio::{
+    net::UdpSocket,
+    select, spawn,
+    sync::mpsc::{channel, Receiver, Sender},
+    task::JoinHandle,
+    time::{sleep_until, Instant},
+};
+
+use std::{
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use super::runtime::nonblocking;
+use crate::{
+    received_datagram,
+    record_cache::RecordCache,
+    socket_helper::{join_multicast, sender_socket},
+    NetworkScope, ServiceEvent, ServiceInstanceName, SimpleMdnsError, RECV_BUFFER_SIZE,
+};
+
+/// How long the browser waits for the first expiration when no instance is known
+const IDLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Continuously browse the instances of a service type, reporting a [`ServiceEvent`] when an instance shows up
+/// or goes away.
+///
+/// Instances are found from the PTR records of the service type received in any response, not only in the answers of
+/// the browser queries. An instance is removed when a goodbye packet (TTL 0) is received or when its PTR record
+/// expires, the service type is queried again when 80% of the TTL of a known instance elapsed
+///
+/// ## Example
+/// ```no_run
+/// use simple_mdns::{async_discovery::ServiceBrowser, ServiceEvent};
+///
+/// # async {
+/// let mut browser = ServiceBrowser::browse("_mysrv._tcp.local").expect("Invalid service type");
+/// while let Some(event) = browser.next_event().await {
+///     match event {
+///         ServiceEvent::Added(instance) => println!("found {instance}"),
+///         ServiceEvent::Removed(instance) => println!("lost {instance}"),
+///     }
+/// }
+/// # };
+/// ```
+///
+/// Browsing stops when the browser is dropped
+pub struct ServiceBrowser {
+    events: Receiver<ServiceEvent>,
+    task: JoinHandle<()>,
+}
+
+impl ServiceBrowser {
+    /// Start browsing `service_type`, like `_mysrv._tcp.local`, using IPV4 scope with UNSPECIFIED Interface
+    pub fn browse(service_type: &str) -> Result<Self, SimpleMdnsError> {
+        Self::browse_with_scope(service_type, NetworkScope::V4)
+    }
+
+    /// Start browsing `service_type`, like `_mysrv._tcp.local`, with the specified scope
+    pub fn browse_with_scope(
+        service_type: &str,
+        network_scope: NetworkScope,
+    ) -> Result<Self, SimpleMdnsError> {
+        let executor = ServiceBrowserExecutor {
+            service_type: Name::new(service_type)?.into_owned(),
+            sender_socket: sender_socket(network_scope).and_then(nonblocking)?,
+            recv_socket: join_multicast(network_scope).and_then(nonblocking)?,
+            network_scope,
+            cache: RecordCache::new(),
+        };
+
+        let (tx, events) = channel(32);
+        let task = spawn(async move {
+            if let Err(err) = executor.browse_loop(tx).await {
+                log::error!("Service browser failed: {err}");
+            }
+        });
+
+        Ok(Self { events, task })
+    }
+
+    /// Wait for the next event, returns `None` if browsing stopped because of a network error
+    pub async fn next_event(&mut self) -> Option<ServiceEvent> {
+        self.events.recv().await
+    }
+
+    /// Poll for the next event, this allows using the browser as a `Stream` of events
+    pub fn poll_next_event(&mut self, cx: &mut Context<'_>) -> Poll<Option<ServiceEvent>> {
+        self.events.poll_recv(cx)
+    }
+}
+
+impl Drop for ServiceBrowser {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+struct ServiceBrowserExecutor {
+    service_type: Name<'static>,
+    sender_socket: UdpSocket,
+    recv_socket: UdpSocket,
+    network_scope: NetworkScope,
+    /// PTR records of the service type
+    cache: RecordCache,
+}
+
+impl ServiceBrowserExecutor {
+    async fn browse_loop(mut self, events: Sender<ServiceEvent>) -> Result<(), SimpleMdnsError> {
+        let mut recv_buffer = [0u8; RECV_BUFFER_SIZE];
+        self.query_service_type().await?;
+
+        loop {
+            let next_timer = self
+                .cache
+                .next_deadline()
+                .map(Instant::from_std)
+                .unwrap_or_else(|| Instant::now() + IDLE_INTERVAL);
+
+            let changes = select! {
+                packet = self.recv_socket.recv_from(&mut recv_buffer) => {
+                    let (count, _) = packet?;
+                    let datagram = match received_datagram(&recv_buffer, count) {
+                        Some(datagram) => datagram,
+                        None => continue,
+                    };
+
+                    match Packet::parse(datagram) {
+                        Ok(packet) if packet.has_flags(PacketFlag::RESPONSE) => self.process_response(packet),
+                        Ok(_) => continue,
+                        Err(err) => {
+                            log::error!("Received invalid packet: {err}");
+                            continue;
+                        }
+                    }
+                }
+                _ = sleep_until(next_timer) => self.refresh_known_instances().await?,
+            };
+
+            for event in changes {
+                if events.send(event).await.is_err() {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Cache the PTR records of the service type in `packet` and returns the resulting events
+    fn process_response(&mut self, packet: Packet) -> Vec<ServiceEvent> {
+        let now = std::time::Instant::now();
+        let mut changes = Vec::new();
+
+        for record in packet.answers.into_iter().chain(packet.additional_records) {
+            if record.name != self.service_type {
+                continue;
+            }
+
+            let instance = match instance_of(&record) {
+                Some(instance) => instance,
+                None => continue,
+            };
+
+            let is_goodbye = record.ttl == 0;
+            if self.cache.insert(record.into_owned(), now) {
+                changes.push(if is_goodbye {
+                    ServiceEvent::Removed(instance)
+                } else {
+                    ServiceEvent::Added(instance)
+                });
+            }
+        }
+
+        changes
+    }
+
+    /// Remove the expired instances and query the service type again if any instance needs to be refreshed
+    async fn refresh_known_instances(&mut self) -> Result<Vec<ServiceEvent>, SimpleMdnsError> {
+        let now = std::time::Instant::now();
+        let changes = self
+            .cache
+            .remove_expired(now)
+            .iter()
+            .filter_map(instance_of)
+            .map(ServiceEvent::Removed)
+            .collect();
+
+        if !self.cache.take_refreshes(now).is_empty() {
+            self.query_service_type().await?;
+        }
+
+        Ok(changes)
+    }
+    /// Query the service type, listing the cached instances as known answers so responders don't send them again
+    async fn query_service_type(&self) -> Result<(), SimpleMdnsError> {
+        let mut packet = Packet::new_query(0);
+        packet.questions.push(Question::new(
+            self.service_type.clone(),
+            TYPE::PTR.into(),
+            CLASS::IN.into(),
+            false,
+        ));
+        packet.answers = self.cache.get(
+            &self.service_type,
+            TYPE::PTR,
+            CLASS::IN,
+            std::time::Instant::now(),
+        );
+
+        self.sender_socket
+            .send_to(
+                &packet.build_bytes_vec_compressed()?,
+                self.network_scope.socket_address(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Returns the service instance a PTR record points to
+fn instance_of(record: &ResourceRecord) -> Option<ServiceInstanceName> {
+    match &record.rdata {
+        RData::PTR(ptr) => ServiceInstanceName::from_name(&ptr.0),
+        _ => None,
+    }
+}
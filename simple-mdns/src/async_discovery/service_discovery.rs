@@ -12,13 +12,15 @@ use tokio::{
 use std::{
     collections::{HashMap, HashSet},
     net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
-    sync::Arc,
+    sync::{Arc, Mutex},
     time::Duration,
 };
 
+use super::runtime::nonblocking;
 use crate::{
-    resource_record_manager::ResourceRecordManager, socket_helper::nonblocking,
-    InstanceInformation, NetworkScope, SimpleMdnsError,
+    announced_state::AnnouncedState, received_datagram,
+    resource_record_manager::ResourceRecordManager, AnnouncedStateStore, InstanceInformation,
+    NetworkScope, RecordTtls, SimpleMdnsError, RECV_BUFFER_SIZE,
 };
 
 /// Service Discovery implementation using DNS-SD.
@@ -41,7 +43,8 @@ pub struct ServiceDiscovery {
     resource_manager: Arc<RwLock<ResourceRecordManager<'static>>>,
     full_name: Name<'static>,
     service_name: Name<'static>,
-    resource_ttl: u32,
+    record_ttls: RecordTtls,
+    announced_state: Arc<Mutex<AnnouncedState>>,
 
     advertise_tx: Sender<bool>,
 }
@@ -68,11 +71,30 @@ impl ServiceDiscovery {
     /// `service_name` must be in the standard specified by the mdns RFC, example: **_my_service._tcp.local**
     /// `resource_ttl` refers to the amount of time in seconds your service will be cached in the dns responder.
     /// set `enable_loopback` to true if you may have more than one instance of your service running in the same machine
+    ///
+    /// `resource_ttl` is used by address and SRV records, PTR and TXT records use [`RecordTtls::DEFAULT_SHARED_TTL`]
     pub fn new_with_scope(
         instance_name: &str,
         service_name: &str,
         resource_ttl: u32,
         network_scope: NetworkScope,
+    ) -> Result<Self, SimpleMdnsError> {
+        let record_ttls = RecordTtls {
+            unique: resource_ttl,
+            ..Default::default()
+        };
+        Self::new_with_record_ttls(instance_name, service_name, record_ttls, network_scope)
+    }
+
+    /// Creates a new ServiceDiscovery by providing `instance`, `service_name`, `record_ttls` and `network_scope`.
+    ///
+    /// Address and SRV records are registered with the unique TTL, PTR and TXT records with the shared TTL,
+    /// [`RecordTtls::default`] uses the TTLs recommended by the mDNS RFC
+    pub fn new_with_record_ttls(
+        instance_name: &str,
+        service_name: &str,
+        record_ttls: RecordTtls,
+        network_scope: NetworkScope,
     ) -> Result<Self, SimpleMdnsError> {
         let full_name = format!("{}.{}", instance_name, service_name);
         let full_name = Name::new(&full_name)?.into_owned();
@@ -82,16 +104,18 @@ impl ServiceDiscovery {
         resource_manager.add_owned_resource(ResourceRecord::new(
             service_name.clone(),
             simple_dns::CLASS::IN,
-            0,
-            RData::PTR(service_name.clone().into()),
+            record_ttls.shared,
+            RData::PTR(full_name.clone().into()),
         ));
 
         let resource_manager = Arc::new(RwLock::new(resource_manager));
+        let announced_state = Arc::new(Mutex::new(AnnouncedState::default()));
         let service_discovery = ServiceDiscoveryExecutor {
             full_name: full_name.clone(),
             service_name: service_name.clone(),
             resource_manager: resource_manager.clone(),
-            sender_socket: crate::socket_helper::sender_socket(network_scope.is_v4())
+            announced_state: announced_state.clone(),
+            sender_socket: crate::socket_helper::sender_socket(network_scope)
                 .and_then(nonblocking)?,
             network_scope,
         };
@@ -107,11 +131,23 @@ impl ServiceDiscovery {
             resource_manager,
             full_name,
             service_name,
-            resource_ttl,
+            record_ttls,
+            announced_state,
             advertise_tx,
         })
     }
 
+    /// Sets the store used to remember the announced records across restarts.
+    ///
+    /// The records saved in `store` are considered already announced, only new or changed records are
+    /// announced by [`add_service_info`](ServiceDiscovery::add_service_info)
+    pub fn set_announced_state_store(&mut self, store: impl AnnouncedStateStore + 'static) {
+        self.announced_state
+            .lock()
+            .unwrap()
+            .set_store(Box::new(store));
+    }
+
     /// Add the  service info to discovery and immediately advertise the service
     pub async fn add_service_info(
         &mut self,
@@ -119,7 +155,9 @@ impl ServiceDiscovery {
     ) -> Result<(), SimpleMdnsError> {
         {
             let mut resource_manager = self.resource_manager.write().await;
-            for resource in service_info.into_records(&self.full_name.clone(), self.resource_ttl)? {
+            for resource in
+                service_info.into_records_with_ttls(&self.full_name.clone(), self.record_ttls)?
+            {
                 resource_manager.add_owned_resource(resource);
             }
         }
@@ -181,6 +219,7 @@ struct ServiceDiscoveryExecutor {
     full_name: Name<'static>,
     service_name: Name<'static>,
     resource_manager: Arc<RwLock<ResourceRecordManager<'static>>>,
+    announced_state: Arc<Mutex<AnnouncedState>>,
     sender_socket: UdpSocket,
     network_scope: NetworkScope,
 }
@@ -190,7 +229,7 @@ impl ServiceDiscoveryExecutor {
         let recv_socket =
             crate::socket_helper::join_multicast(self.network_scope).and_then(nonblocking)?;
 
-        let mut recv_buffer = [0u8; 9000];
+        let mut recv_buffer = [0u8; RECV_BUFFER_SIZE];
         let mut next_expiration = Instant::now() + Duration::from_secs(5);
 
         self.query_service_instances().await?;
@@ -199,8 +238,10 @@ impl ServiceDiscoveryExecutor {
             select! {
                 packet = recv_socket.recv_from(&mut recv_buffer) => {
                     let (count, addr) = packet?;
-                    if let Err(err) = self.process_packet(&recv_buffer[..count], addr).await {
-                        log::error!("Failed to process received packet {err}");
+                    if let Some(datagram) = received_datagram(&recv_buffer, count) {
+                        if let Err(err) = self.process_packet(datagram, addr).await {
+                            log::error!("Failed to process received packet {err}");
+                        }
                     }
                 }
                 _ = sleep_until(next_expiration) => {
@@ -326,6 +367,15 @@ impl ServiceDiscoveryExecutor {
             packet.additional_records.push(additional_record)
         }
 
+        {
+            let mut announced_state = self.announced_state.lock().unwrap();
+            if cache_flush {
+                announced_state.set_announced(HashSet::new());
+            } else {
+                announced_state.retain_unannounced_packet(&mut packet);
+            }
+        }
+
         if packet.answers.is_empty() {
             log::info!("Failed to advertise service");
             return Ok(());
@@ -375,7 +425,7 @@ fn add_response_to_resources(
     let resources = packet
         .answers
         .into_iter()
-        .chain(packet.additional_records.into_iter())
+        .chain(packet.additional_records)
         .filter(|aw| {
             aw.name.ne(full_name)
                 && aw.name.is_subdomain_of(service_name)
@@ -1,17 +1,31 @@
-use std::sync::Arc;
-use tokio::{spawn, sync::RwLock};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::{Duration, Instant};
 
-use simple_dns::{header_buffer, Packet, PacketFlag, ResourceRecord};
+use simple_dns::{Name, Packet, ResourceRecord};
 
+use super::runtime::{
+    join, nonblocking, recv_any, shutdown_signal, sleep, spawn, timeout, until_shutdown,
+    JoinHandle, RwLock, ShutdownListener, ShutdownSignal,
+};
 use crate::{
-    build_reply,
+    build_announcement, build_goodbye, build_probe,
+    conversion_utils::hashmap_to_txt,
+    encode_reply, is_probe_conflict, max_unicast_size,
+    network_scope::multicast_address_for,
+    parse_query, received_datagram,
     resource_record_manager::ResourceRecordManager,
-    socket_helper::{join_multicast, nonblocking, sender_socket},
-    NetworkScope, SimpleMdnsError,
+    response_delay::{PendingReply, ResponseDelay},
+    socket_helper::{join_multicast, sender_socket},
+    split_query, NetworkScope, QueryCallback, SimpleMdnsError, ANNOUNCEMENT_COUNT,
+    ANNOUNCEMENT_INTERVAL, PROBE_COUNT, PROBE_INTERVAL, RECV_BUFFER_SIZE,
 };
 
 const FIVE_MINUTES: u32 = 60 * 5;
-
 /// A simple mDNS responder aimed for service discovery.
 /// In case you don't have a mDNS responder in your network, or for some reason don't want to use the ones available.
 ///
@@ -49,37 +63,128 @@ const FIVE_MINUTES: u32 = 60 * 5;
 pub struct SimpleMdnsResponder {
     resources: Arc<RwLock<ResourceRecordManager<'static>>>,
     rr_ttl: u32,
+    amplification_limit: Arc<AtomicUsize>,
+    scopes: Vec<NetworkScope>,
+    shutdown: ShutdownSignal,
+    tasks: Vec<JoinHandle<Result<(), SimpleMdnsError>>>,
+    on_query: Arc<std::sync::RwLock<Option<QueryCallback>>>,
+    response_delay: Arc<std::sync::RwLock<ResponseDelay>>,
 }
 
 impl SimpleMdnsResponder {
-    /// Creates a new SimpleMdnsResponder with specified ttl and IPV4 scope with UNSPECIFIED
-    /// Interface
+    /// Creates a new SimpleMdnsResponder with specified ttl, listening on both IPV4 and IPV6 with UNSPECIFIED
+    /// Interface. Both listeners answer from the same resources, if one of them fails the other keeps running
     pub fn new(rr_ttl: u32) -> Self {
-        Self::new_with_scope(rr_ttl, NetworkScope::V4)
+        Self::new_with_scopes(rr_ttl, [NetworkScope::V4, NetworkScope::V6])
     }
 
     /// Creates a new SimpleMdnsResponder with specified ttl and network scope
+    ///
+    /// Use [`NetworkScope::V4WithInterface`] with a local address, or [`NetworkScope::V6WithInterface`] with an
+    /// interface index, to choose the interface used to join the multicast group and to send packets
+    /// (`IP_MULTICAST_IF`). The interface can't be changed afterwards, the listener is bound when the responder is created
     pub fn new_with_scope(rr_ttl: u32, scope: NetworkScope) -> Self {
-        let responder = Self {
+        Self::new_with_scopes(rr_ttl, [scope])
+    }
+
+    /// Creates a new SimpleMdnsResponder with specified ttl, with one listener for every network interface returned
+    /// by [`NetworkScope::all_interfaces`]. Queries are answered through the interface they arrived on, so the
+    /// resources are discoverable in every network the host is connected to
+    pub fn new_on_all_interfaces(rr_ttl: u32) -> Result<Self, SimpleMdnsError> {
+        Ok(Self::new_with_scopes(
+            rr_ttl,
+            NetworkScope::all_interfaces()?,
+        ))
+    }
+
+    /// Returns the network scopes this responder listens on, one listener is created for each scope
+    pub fn scopes(&self) -> &[NetworkScope] {
+        &self.scopes
+    }
+
+    fn new_with_scopes(rr_ttl: u32, scopes: impl IntoIterator<Item = NetworkScope>) -> Self {
+        let (shutdown, shutdown_listener) = shutdown_signal();
+        let mut responder = Self {
             resources: Arc::new(RwLock::new(ResourceRecordManager::new())),
             rr_ttl,
+            amplification_limit: Arc::new(AtomicUsize::new(0)),
+            scopes: scopes.into_iter().collect(),
+            shutdown,
+            tasks: Vec::new(),
+            on_query: Arc::new(std::sync::RwLock::new(None)),
+            response_delay: Arc::new(std::sync::RwLock::new(ResponseDelay::default())),
         };
 
-        let resources = responder.resources.clone();
-        spawn(async move {
-            if let Err(err) = Self::responder_loop(resources, scope).await {
-                log::error!("Dns Responder failed: {}", err);
-            }
-        });
+        for scope in responder.scopes.clone() {
+            let resources = responder.resources.clone();
+            let amplification_limit = responder.amplification_limit.clone();
+            let shutdown = shutdown_listener.clone();
+            let on_query = responder.on_query.clone();
+            let response_delay = responder.response_delay.clone();
+            responder.tasks.push(spawn(async move {
+                let result = Self::responder_loop(
+                    resources,
+                    scope,
+                    amplification_limit,
+                    shutdown,
+                    on_query,
+                    response_delay,
+                )
+                .await;
+                if let Err(err) = &result {
+                    log::error!("Dns Responder failed for {scope:?}: {}", err);
+                }
+                result
+            }));
+        }
         responder
     }
 
+    /// Stop answering queries and wait for the listeners to finish.
+    /// Returns the error of the first listener that failed, if any.
+    /// Call [`goodbye`](SimpleMdnsResponder::goodbye) before shutting down to remove the resources from other hosts caches
+    pub async fn shutdown(mut self) -> Result<(), SimpleMdnsError> {
+        self.shutdown.trigger();
+
+        let mut result = Ok(());
+        for task in self.tasks.drain(..) {
+            if let Some(Err(err)) = join(task).await {
+                if result.is_ok() {
+                    result = Err(err);
+                }
+            }
+        }
+
+        result
+    }
+
     /// Register a Resource Record
     pub async fn add_resource(&mut self, resource: ResourceRecord<'static>) {
         let mut resources = self.resources.write().await;
         resources.add_owned_resource(resource);
     }
 
+    /// Register a TXT record for `name` with the given attributes, using the configured TTL.  
+    /// DNS-SD clients expect the TXT record of a service instance alongside its SRV record, an attribute without
+    /// value is registered as a boolean attribute (`key` instead of `key=value`)
+    pub async fn add_service_txt(
+        &mut self,
+        name: &Name<'_>,
+        attributes: HashMap<String, Option<String>>,
+    ) -> Result<(), SimpleMdnsError> {
+        let txt = hashmap_to_txt(name, attributes, self.rr_ttl)?.into_owned();
+        let mut resources = self.resources.write().await;
+        resources.add_owned_resource(txt);
+        Ok(())
+    }
+
+    /// Register a Resource Record to be sent in the authority section, when a query has no answers for its name.  
+    /// For example, a NSEC record listing the record types that exist for a name
+    pub async fn add_authority_resource(&mut self, resource: ResourceRecord<'static>) {
+        let mut resources = self.resources.write().await;
+        resources.add_authority_resource(resource);
+    }
+
     /// Remove a resource record
     pub async fn remove_resource_record(&mut self, resource: ResourceRecord<'static>) {
         let mut resources = self.resources.write().await;
@@ -92,54 +197,237 @@ impl SimpleMdnsResponder {
         resources.clear();
     }
 
+    /// Set a callback invoked for every query received, before the reply is built.  
+    /// The callback receives the query and the address it came from, it can be used to log or collect metrics
+    /// about the queries seen by this responder. Replaces any previously set callback
+    pub fn on_query(&mut self, callback: impl Fn(&Packet, SocketAddr) + Send + Sync + 'static) {
+        *self.on_query.write().unwrap() = Some(Box::new(callback));
+    }
+
+    /// Set the range of the random delay applied to multicast replies (defaults to 20 to 120 milliseconds),
+    /// so responders answering the same query don't reply at the same time. Queries received during the delay
+    /// are answered by the same reply. Unicast replies are never delayed, set both to zero to reply immediately
+    pub fn set_response_delay(&mut self, min: Duration, max: Duration) {
+        *self.response_delay.write().unwrap() = ResponseDelay { min, max };
+    }
+
     async fn responder_loop(
         resources: Arc<RwLock<ResourceRecordManager<'_>>>,
         scope: NetworkScope,
+        amplification_limit: Arc<AtomicUsize>,
+        mut shutdown: ShutdownListener,
+        on_query: Arc<std::sync::RwLock<Option<QueryCallback>>>,
+        response_delay: Arc<std::sync::RwLock<ResponseDelay>>,
     ) -> Result<(), SimpleMdnsError> {
-        let mut recv_buffer = [0u8; 9000];
-        let sender_socket = sender_socket(scope.is_v4()).and_then(nonblocking)?;
+        let mut recv_buffer = [0u8; RECV_BUFFER_SIZE];
+        let sender_socket = sender_socket(scope).and_then(nonblocking)?;
 
         let recv_socket = join_multicast(scope).and_then(nonblocking)?;
 
+        let mut pending: Option<PendingReply> = None;
+
         loop {
-            let (count, addr) = recv_socket.recv_from(&mut recv_buffer).await?;
+            let recv = recv_socket.recv_from(&mut recv_buffer);
+            let received = match &pending {
+                Some(pending) => {
+                    let wait = pending.send_at.saturating_duration_since(Instant::now());
+                    until_shutdown(&mut shutdown, timeout(wait, recv))
+                        .await
+                        .map(Result::ok)
+                }
+                None => until_shutdown(&mut shutdown, recv).await.map(Some),
+            };
 
-            if header_buffer::has_flags(&recv_buffer[..count], PacketFlag::RESPONSE).unwrap_or(true)
-            {
-                continue;
-            }
+            let Some(received) = received else {
+                return Ok(());
+            };
 
-            match Packet::parse(&recv_buffer[..count]) {
-                Ok(packet) => {
-                    match build_reply(packet, &*resources.read().await) {
-                        Some((reply_packet, unicast_response)) => {
-                            let reply = match reply_packet.build_bytes_vec_compressed() {
-                                Ok(reply) => reply,
-                                Err(err) => {
-                                    log::error!("Failed to build reply {err}");
-                                    continue;
-                                }
-                            };
-
-                            let reply_addr = if unicast_response {
-                                addr
-                            } else {
-                                scope.socket_address()
-                            };
-
-                            sender_socket.send_to(&reply, reply_addr).await?;
+            if let Some(received) = received {
+                let (count, addr) = received?;
+                let query = match received_datagram(&recv_buffer, count)
+                    .and_then(|query| parse_query(query, Some(addr), &on_query.read().unwrap()))
+                {
+                    Some(query) => query,
+                    None => continue,
+                };
+
+                let (unicast_query, multicast_query) = split_query(query);
+                if let Some(query) = unicast_query {
+                    let max_size = max_unicast_size(count, &amplification_limit);
+                    let reply = encode_reply(query, &*resources.read().await, true, max_size);
+                    if let Some(reply) = reply {
+                        sender_socket.send_to(&reply, addr).await?;
+                    }
+                }
+
+                if let Some(query) = multicast_query {
+                    let delay = response_delay.read().unwrap().delay();
+                    match &mut pending {
+                        Some(pending) => pending.merge(query),
+                        None if delay.is_zero() => {
+                            let reply = encode_reply(query, &*resources.read().await, false, None);
+                            if let Some(reply) = reply {
+                                sender_socket
+                                    .send_to(&reply, multicast_address_for(&addr))
+                                    .await?;
+                            }
                         }
                         None => {
-                            log::trace!("No reply for query");
-                            continue;
+                            pending = Some(PendingReply::new(
+                                query,
+                                Instant::now() + delay,
+                                multicast_address_for(&addr),
+                            ))
+                        }
+                    }
+                }
+            }
+
+            match pending.take() {
+                Some(ready) if ready.send_at <= Instant::now() => {
+                    let reply_addr = ready.reply_addr;
+                    let reply =
+                        encode_reply(ready.into_query(), &*resources.read().await, false, None);
+                    if let Some(reply) = reply {
+                        sender_socket.send_to(&reply, reply_addr).await?;
+                    }
+                }
+                not_ready => pending = not_ready,
+            }
+        }
+    }
+
+    /// Probe `name`, making sure no other host uses it before announcing the resources registered for it.
+    /// Three probe queries are sent 250 milliseconds apart, with the resources registered for `name` as the proposed records.
+    ///
+    /// Returns [`SimpleMdnsError::NameConflict`] when another host answers with different records for `name`,
+    /// in which case a new name should be chosen, [RFC 6762 section 8.1](https://datatracker.ietf.org/doc/html/rfc6762#section-8.1)
+    pub async fn probe(&self, name: &Name<'_>) -> Result<(), SimpleMdnsError> {
+        let (probe, proposed) = {
+            let resources = self.resources.read().await;
+            let packet = build_probe(name, &resources);
+            let proposed: Vec<_> = packet
+                .name_servers
+                .iter()
+                .map(|r| r.clone().into_owned())
+                .collect();
+            (packet.build_bytes_vec_compressed()?, proposed)
+        };
+
+        let mut sockets = Vec::new();
+        for scope in self.scopes.iter() {
+            let sender = sender_socket(*scope).and_then(nonblocking)?;
+            let multicast = join_multicast(*scope).and_then(nonblocking)?;
+            sockets.push((sender, multicast, scope.socket_address()));
+        }
+
+        let receivers: Vec<_> = sockets.iter().flat_map(|(s, m, _)| [s, m]).collect();
+        let mut recv_buffers = vec![[0u8; RECV_BUFFER_SIZE]; receivers.len()];
+        for _ in 0..PROBE_COUNT {
+            for (sender, _, address) in sockets.iter() {
+                sender.send_to(&probe, address).await?;
+            }
+
+            let deadline = Instant::now() + PROBE_INTERVAL;
+            while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+                match timeout(remaining, recv_any(&receivers, &mut recv_buffers)).await {
+                    Ok((index, Ok(count))) => {
+                        if is_probe_conflict(&recv_buffers[index][..count], name, &proposed) {
+                            return Err(SimpleMdnsError::NameConflict(name.to_string()));
                         }
-                    };
+                    }
+                    Ok((_, Err(err))) => log::error!("Failed to receive probe responses {err}"),
+                    Err(_) => break,
                 }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Multicast every registered resource, so other hosts learn about them without querying.  
+    /// The announcement is sent twice, one second apart, resources other than PTR records have the cache-flush bit set.
+    /// Call this after adding the resources of a service
+    pub async fn announce(&self) -> Result<(), SimpleMdnsError> {
+        let bytes = {
+            let resources = self.resources.read().await;
+            let packet = build_announcement(&resources);
+            if packet.answers.is_empty() {
+                return Ok(());
+            }
+            packet.build_bytes_vec_compressed()?
+        };
+
+        self.multicast(&bytes, ANNOUNCEMENT_COUNT, ANNOUNCEMENT_INTERVAL)
+            .await
+    }
+
+    /// Multicast every registered resource with a TTL of 0, so other hosts remove them from their caches.
+    /// Call this before dropping the responder or removing its resources
+    pub async fn goodbye(&self) -> Result<(), SimpleMdnsError> {
+        let bytes = {
+            let resources = self.resources.read().await;
+            let packet = build_goodbye(&resources);
+            if packet.answers.is_empty() {
+                return Ok(());
+            }
+            packet.build_bytes_vec_compressed()?
+        };
+
+        self.multicast(&bytes, 1, Duration::ZERO).await
+    }
+
+    /// Multicast `bytes` `count` times through every scope, `interval` apart. Scopes that fail, like IPv6 on a host
+    /// without IPv6, are logged and skipped. Returns an error only when `bytes` could not be sent through any scope
+    async fn multicast(
+        &self,
+        bytes: &[u8],
+        count: usize,
+        interval: Duration,
+    ) -> Result<(), SimpleMdnsError> {
+        let mut last_error = None;
+        let sockets: Vec<_> = self
+            .scopes
+            .iter()
+            .filter_map(|scope| match sender_socket(*scope).and_then(nonblocking) {
+                Ok(socket) => Some((socket, *scope)),
                 Err(err) => {
-                    log::error!("Received Invalid packet {err}");
+                    log::error!("Failed to create socket for {scope:?}: {err}");
+                    last_error = Some(err);
+                    None
+                }
+            })
+            .collect();
+
+        let mut sent = false;
+        for i in 0..count {
+            if i > 0 {
+                sleep(interval).await;
+            }
+
+            for (socket, scope) in sockets.iter() {
+                match socket.send_to(bytes, scope.socket_address()).await {
+                    Ok(_) => sent = true,
+                    Err(err) => {
+                        log::error!("Failed to send packet to {scope:?}: {err}");
+                        last_error = Some(err);
+                    }
                 }
             }
         }
+
+        match last_error {
+            Some(err) if !sent => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Limit unicast replies to queries without EDNS to `ratio` times the size of the query, in order to
+    /// avoid being used for traffic amplification. Replies over the limit are truncated and have the TC flag set.
+    /// Multicast replies are never limited. Pass `None` to remove the limit (default)
+    pub fn set_amplification_limit(&mut self, ratio: Option<usize>) {
+        self.amplification_limit
+            .store(ratio.unwrap_or_default(), Ordering::Relaxed);
     }
 
     /// Set the simple mdns responder's rr default ttl in seconds (defaults to 300).
@@ -3,7 +3,10 @@ use std::{
     net::{IpAddr, SocketAddr},
 };
 
-use crate::conversion_utils::{hashmap_to_txt, ip_addr_to_resource_record, port_to_srv_record};
+use crate::{
+    conversion_utils::{hashmap_to_txt, ip_addr_to_resource_record, port_to_srv_record},
+    RecordTtls,
+};
 use simple_dns::{Name, ResourceRecord};
 
 /// Represents a single instance of the service.
@@ -34,23 +37,37 @@ impl InstanceInformation {
         }
     }
 
-    /// Transform into a [Vec<ResourceRecord>](`Vec<ResourceRecord>`)
+    /// Transform into a [Vec<ResourceRecord>](`Vec<ResourceRecord>`), using `ttl` for every record
     pub fn into_records<'a>(
         self,
         service_name: &Name<'a>,
         ttl: u32,
+    ) -> Result<Vec<ResourceRecord<'a>>, crate::SimpleMdnsError> {
+        self.into_records_with_ttls(service_name, RecordTtls::uniform(ttl))
+    }
+
+    /// Transform into a [Vec<ResourceRecord>](`Vec<ResourceRecord>`), address and SRV records use the unique TTL
+    /// and the TXT record uses the shared TTL
+    pub fn into_records_with_ttls<'a>(
+        self,
+        service_name: &Name<'a>,
+        ttls: RecordTtls,
     ) -> Result<Vec<ResourceRecord<'a>>, crate::SimpleMdnsError> {
         let mut records = Vec::new();
 
         for ip_address in self.ip_addresses {
-            records.push(ip_addr_to_resource_record(service_name, ip_address, ttl));
+            records.push(ip_addr_to_resource_record(
+                service_name,
+                ip_address,
+                ttls.unique,
+            ));
         }
 
         for port in self.ports {
-            records.push(port_to_srv_record(service_name, port, ttl));
+            records.push(port_to_srv_record(service_name, port, ttls.unique));
         }
 
-        records.push(hashmap_to_txt(service_name, self.attributes, ttl)?);
+        records.push(hashmap_to_txt(service_name, self.attributes, ttls.shared)?);
 
         Ok(records)
     }
@@ -85,3 +102,27 @@ impl From<SocketAddr> for InstanceInformation {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use simple_dns::TYPE;
+
+    use super::*;
+
+    #[test]
+    fn into_records_with_ttls() {
+        let name = Name::new_unchecked("inst._srv._tcp.local");
+        let info: InstanceInformation = "192.168.1.22:8090".parse::<SocketAddr>().unwrap().into();
+
+        let records = info
+            .into_records_with_ttls(&name, RecordTtls::default())
+            .unwrap();
+        for record in records {
+            let expected = match record.rdata.type_code() {
+                TYPE::TXT => RecordTtls::DEFAULT_SHARED_TTL,
+                _ => RecordTtls::DEFAULT_UNIQUE_TTL,
+            };
+            assert_eq!(expected, record.ttl);
+        }
+    }
+}
@@ -2,9 +2,19 @@
 #![warn(missing_docs)]
 extern crate lazy_static;
 
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
 use std::collections::HashSet;
 
-use simple_dns::{rdata::RData, Packet, TYPE};
+#[cfg(any(feature = "sync", feature = "async-tokio"))]
+use simple_dns::{header_buffer, PacketFlag};
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+use simple_dns::{
+    rdata::{RData, NSEC},
+    Name, Packet, ResourceRecord, QCLASS, QTYPE, TYPE,
+};
+
+mod announced_state;
+pub use announced_state::AnnouncedStateStore;
 
 pub mod conversion_utils;
 
@@ -12,72 +22,575 @@ mod instance_information;
 pub use instance_information::InstanceInformation;
 
 mod network_scope;
-pub use network_scope::NetworkScope;
+pub use network_scope::{NetworkScope, MULTICAST_ADDR_IPV4, MULTICAST_ADDR_IPV6, MULTICAST_PORT};
+
+mod record_ttls;
+pub use record_ttls::RecordTtls;
+
+#[cfg(feature = "async-tokio")]
+mod record_cache;
+
+mod resolved_service;
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+mod response_delay;
+pub use resolved_service::ResolvedService;
 
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
 mod resource_record_manager;
 
+mod service_event;
+pub use service_event::ServiceEvent;
+
+mod service_instance_name;
+pub use service_instance_name::{service_instances, ServiceInstanceName};
+
 mod simple_mdns_error;
 pub use simple_mdns_error::SimpleMdnsError;
 
 mod socket_helper;
+pub use socket_helper::{recv_message, ReceivedMessage};
 
-#[cfg(feature = "async-tokio")]
+#[cfg(any(feature = "async-tokio", feature = "async-std"))]
 pub mod async_discovery;
 
 #[cfg(feature = "sync")]
 pub mod sync_discovery;
 
+#[cfg(any(feature = "sync", feature = "async-tokio"))]
 const UNICAST_RESPONSE: bool = cfg!(not(test));
 
+/// Filter applied to resolved addresses, addresses are only returned when it returns true
+#[cfg(any(feature = "sync", feature = "async-tokio"))]
+pub(crate) type ReachabilityFilter = Box<dyn Fn(&std::net::IpAddr) -> bool + Send + Sync>;
+
+/// Callback invoked by the responders for every query received, with the address the query came from
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+pub(crate) type QueryCallback = Box<dyn Fn(&Packet, std::net::SocketAddr) + Send + Sync>;
+
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
 pub(crate) fn build_reply<'b>(
     packet: simple_dns::Packet,
     resources: &'b resource_record_manager::ResourceRecordManager<'b>,
 ) -> Option<(Packet<'b>, bool)> {
     let mut reply_packet = Packet::new_reply(packet.id());
+    // mDNS responses are always authoritative, RFC 6762 section 18.4
+    reply_packet.set_authoritative_answer(true);
 
     let mut unicast_response = false;
     let mut additional_records = HashSet::new();
+    let mut authority_records = HashSet::new();
+
+    // Known answers listed by the querier with at least half of the TTL are not sent again,
+    // RFC 6762 section 7.1
+    let is_known_answer = |answer: &ResourceRecord| {
+        packet.answers.iter().any(|known| {
+            known.name == answer.name
+                && known.class == answer.class
+                && known.rdata == answer.rdata
+                && known.ttl >= answer.ttl / 2
+        })
+    };
 
     // TODO: fill the questions for the response
-    // TODO: filter out questions with known answers
     for question in packet.questions.iter() {
         if question.unicast_response {
             unicast_response = question.unicast_response
         }
 
+        let answers_count = reply_packet.answers.len();
+
+        // Probes are ANY queries with the proposed records in the authority section, RFC 6762 section 8.1.
+        // Every record owned for the probed name is sent, so the prober can detect the conflict
+        if question.qtype == QTYPE::ANY && !packet.name_servers.is_empty() {
+            reply_packet.answers.extend(
+                resources
+                    .get_owned_resources(&question.qname)
+                    .filter(|r| r.match_qclass(question.qclass))
+                    .cloned(),
+            );
+            continue;
+        }
+
+        if question.qname == Name::new_unchecked(SERVICE_TYPE_ENUMERATION_NAME) {
+            reply_packet
+                .answers
+                .extend(service_type_records(resources).into_iter().filter(|r| {
+                    r.match_qclass(question.qclass)
+                        && r.match_qtype(question.qtype)
+                        && !is_known_answer(r)
+                }));
+        }
+
         for d_resources in resources.get_domain_resources(&question.qname, true, true) {
-            for answer in d_resources
-                .filter(|r| r.match_qclass(question.qclass) && r.match_qtype(question.qtype))
-            {
+            for answer in d_resources.filter(|r| {
+                r.match_qclass(question.qclass)
+                    && r.match_qtype(question.qtype)
+                    && !is_known_answer(r)
+            }) {
                 reply_packet.answers.push(answer.clone());
 
-                if let RData::SRV(srv) = &answer.rdata {
-                    let target = resources
-                        .get_domain_resources(&srv.target, false, true)
-                        .flatten()
-                        .filter(|r| {
-                            r.match_qtype(TYPE::A.into()) && r.match_qclass(question.qclass)
-                        })
-                        .cloned();
-
-                    additional_records.extend(target);
+                match &answer.rdata {
+                    RData::SRV(srv) => additional_records.extend(address_records(
+                        resources,
+                        &srv.target,
+                        question.qclass,
+                    )),
+                    RData::PTR(ptr) => {
+                        for instance_record in resources
+                            .get_domain_resources(&ptr.0, false, true)
+                            .flatten()
+                            .filter(|r| {
+                                r.match_qclass(question.qclass)
+                                    && (r.match_qtype(TYPE::SRV.into())
+                                        || r.match_qtype(TYPE::TXT.into()))
+                            })
+                        {
+                            if let RData::SRV(srv) = &instance_record.rdata {
+                                additional_records.extend(address_records(
+                                    resources,
+                                    &srv.target,
+                                    question.qclass,
+                                ));
+                            }
+                            additional_records.insert(instance_record.clone());
+                        }
+                    }
+                    _ => {}
                 }
             }
         }
+
+        let mut authority = resources
+            .get_authority_resources(&question.qname)
+            .filter(|r| r.match_qclass(question.qclass))
+            .peekable();
+
+        if authority.peek().is_none() {
+            additional_records.extend(negative_response(
+                resources,
+                &question.qname,
+                question.qtype,
+                question.qclass,
+            ));
+        } else if reply_packet.answers.len() == answers_count {
+            authority_records.extend(authority.cloned());
+        }
+    }
+
+    // overlapping questions, like ANY and A for the same name, match the same records
+    let mut unique_answers = HashSet::new();
+    reply_packet
+        .answers
+        .retain(|answer| unique_answers.insert(answer.clone()));
+
+    // DNSSEC records are only sent when the querier sets the DO bit or asks for their type, RFC 3225 section 3.
+    // NSEC records are not filtered, mDNS uses them for negative responses without DNSSEC
+    if !matches!(packet.opt(), Some(opt) if opt.dnssec_ok) {
+        let is_requested = |record: &ResourceRecord| {
+            packet
+                .questions
+                .iter()
+                .any(|question| u16::from(question.qtype) == u16::from(record.rdata.type_code()))
+        };
+        reply_packet
+            .answers
+            .retain(|answer| !is_dnssec_record(answer) || is_requested(answer));
+        additional_records.retain(|record| !is_dnssec_record(record));
+        authority_records.retain(|record| !is_dnssec_record(record));
     }
 
     for additional_record in additional_records {
         reply_packet.additional_records.push(additional_record);
     }
 
-    if !reply_packet.answers.is_empty() {
+    for authority_record in authority_records {
+        reply_packet.name_servers.push(authority_record);
+    }
+
+    if !reply_packet.answers.is_empty()
+        || !reply_packet.name_servers.is_empty()
+        || !reply_packet.additional_records.is_empty()
+    {
         Some((reply_packet, unicast_response))
     } else {
         None
     }
 }
 
-#[cfg(test)]
+/// Type codes of the DS, RRSIG and DNSKEY records, [RFC 4034](https://datatracker.ietf.org/doc/html/rfc4034)
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+const DNSSEC_TYPES: [u16; 3] = [43, 46, 48];
+
+/// Returns true for the records carrying DNSSEC signatures and keys
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+fn is_dnssec_record(record: &ResourceRecord) -> bool {
+    DNSSEC_TYPES.contains(&u16::from(record.rdata.type_code()))
+}
+
+/// Builds a NSEC record listing the types of the records owned for `name`, when `name` has records but none
+/// of `qtype`, so the querier knows the queried type does not exist,
+/// [RFC 6762 section 6.1](https://datatracker.ietf.org/doc/html/rfc6762#section-6.1).
+/// The type must be an exact match, a name with only A records gets a negative response for AAAA queries,
+/// even though the A records are sent as answers
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+fn negative_response<'b>(
+    resources: &'b resource_record_manager::ResourceRecordManager<'b>,
+    name: &Name<'_>,
+    qtype: QTYPE,
+    qclass: QCLASS,
+) -> Option<ResourceRecord<'b>> {
+    let owned: Vec<&ResourceRecord> = resources
+        .get_owned_resources(name)
+        .filter(|r| r.match_qclass(qclass))
+        .collect();
+    let has_qtype = match qtype {
+        QTYPE::TYPE(qtype) => owned.iter().any(|r| r.rdata.type_code() == qtype),
+        _ => true,
+    };
+    if owned.is_empty() || has_qtype {
+        return None;
+    }
+
+    let types: Vec<TYPE> = owned.iter().map(|r| r.rdata.type_code()).collect();
+    let mut nsec = ResourceRecord::new(
+        owned[0].name.clone(),
+        owned[0].class,
+        owned[0].ttl,
+        RData::NSEC(NSEC::new(owned[0].name.clone(), &types)),
+    );
+    nsec.cache_flush = true;
+
+    Some(nsec)
+}
+
+/// Number of probe queries sent before claiming a name, [RFC 6762 section 8.1](https://datatracker.ietf.org/doc/html/rfc6762#section-8.1)
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+pub(crate) const PROBE_COUNT: usize = 3;
+/// Interval between the probe queries, responses are awaited for the same interval after the last probe
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+pub(crate) const PROBE_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Builds a probe query for `name`, asking for any record with a unicast response,
+/// with the records owned for `name` in the authority section
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+pub(crate) fn build_probe<'b>(
+    name: &Name<'b>,
+    resources: &'b resource_record_manager::ResourceRecordManager<'b>,
+) -> Packet<'b> {
+    let mut packet = Packet::new_query(0).with_questions([simple_dns::Question::new(
+        name.clone(),
+        QTYPE::ANY,
+        simple_dns::CLASS::IN.into(),
+        true,
+    )]);
+    packet
+        .name_servers
+        .extend(resources.get_owned_resources(name).cloned());
+
+    packet
+}
+
+/// Returns true when `response` has records for `name` that are not in `proposed`, which means another host
+/// is already using the name. Records identical to the proposed ones, like answers from this host, are not a conflict
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+pub(crate) fn is_probe_conflict(response: &[u8], name: &Name, proposed: &[ResourceRecord]) -> bool {
+    if !simple_dns::header_buffer::has_flags(response, simple_dns::PacketFlag::RESPONSE)
+        .unwrap_or_default()
+    {
+        return false;
+    }
+
+    let response = match Packet::parse(response) {
+        Ok(response) => response,
+        Err(_) => return false,
+    };
+
+    response
+        .answers
+        .iter()
+        .chain(response.additional_records.iter())
+        .any(|answer| {
+            answer.name == *name
+                && !proposed
+                    .iter()
+                    .any(|record| record.class == answer.class && record.rdata == answer.rdata)
+        })
+}
+
+/// Number of unsolicited responses sent to announce the records, [RFC 6762 section 8.3](https://datatracker.ietf.org/doc/html/rfc6762#section-8.3)
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+pub(crate) const ANNOUNCEMENT_COUNT: usize = 2;
+/// Interval between the announcements
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+pub(crate) const ANNOUNCEMENT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Builds an unsolicited response with every owned record, announcing them to other hosts.
+/// Records are unique to this host and have the cache-flush bit set, except PTR records, which are shared
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+pub(crate) fn build_announcement<'b>(
+    resources: &'b resource_record_manager::ResourceRecordManager<'b>,
+) -> Packet<'b> {
+    let mut packet = Packet::new_reply(0);
+    packet.set_authoritative_answer(true);
+    packet
+        .answers
+        .extend(resources.get_all_owned_resources().map(|resource| {
+            if matches!(resource.rdata, RData::PTR(_)) {
+                resource.clone()
+            } else {
+                resource.to_cache_flush_record()
+            }
+        }));
+
+    packet
+}
+
+/// Builds an unsolicited response with every owned record with a TTL of 0, telling other hosts to remove these
+/// records from their caches, [RFC 6762 section 10.1](https://datatracker.ietf.org/doc/html/rfc6762#section-10.1)
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+pub(crate) fn build_goodbye<'b>(
+    resources: &'b resource_record_manager::ResourceRecordManager<'b>,
+) -> Packet<'b> {
+    let mut packet = Packet::new_reply(0);
+    packet.set_authoritative_answer(true);
+    packet
+        .answers
+        .extend(resources.get_all_owned_resources().map(|resource| {
+            let mut resource = resource.clone();
+            resource.ttl = 0;
+            resource
+        }));
+
+    packet
+}
+
+/// Encodes the reply packet, when `max_size` is provided, records that do not fit are left out
+/// and the TC flag is set
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+pub(crate) fn build_reply_bytes(
+    reply: Packet<'_>,
+    max_size: Option<usize>,
+) -> simple_dns::Result<Vec<u8>> {
+    match max_size {
+        Some(max_size) => reply.build_bytes_vec_with_limit(max_size),
+        None => reply.build_bytes_vec_compressed(),
+    }
+}
+
+/// Meta-query name used to enumerate the service types in the network,
+/// [RFC 6763 section 9](https://datatracker.ietf.org/doc/html/rfc6763#section-9)
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+pub(crate) const SERVICE_TYPE_ENUMERATION_NAME: &str = "_services._dns-sd._udp.local";
+
+/// Builds a PTR record from the service type enumeration name to every service type with a registered instance
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+fn service_type_records<'b>(
+    resources: &'b resource_record_manager::ResourceRecordManager<'b>,
+) -> Vec<ResourceRecord<'b>> {
+    let mut records: Vec<ResourceRecord> = Vec::new();
+    for resource in resources.get_all_owned_resources() {
+        let is_service_type = match &resource.rdata {
+            RData::PTR(ptr) => ServiceInstanceName::from_name(&ptr.0).is_some(),
+            _ => false,
+        };
+
+        let is_listed = records
+            .iter()
+            .any(|r| matches!(&r.rdata, RData::PTR(ptr) if ptr.0 == resource.name));
+
+        if is_service_type && !is_listed {
+            records.push(ResourceRecord::new(
+                Name::new_unchecked(SERVICE_TYPE_ENUMERATION_NAME),
+                resource.class,
+                resource.ttl,
+                RData::PTR(resource.name.clone().into()),
+            ));
+        }
+    }
+
+    records
+}
+
+/// Parse `query`, returns `None` for responses and invalid packets.  
+/// `on_query` is invoked with the parsed query when the `origin` address is known
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+pub(crate) fn parse_query<'q>(
+    query: &'q [u8],
+    origin: Option<std::net::SocketAddr>,
+    on_query: &Option<QueryCallback>,
+) -> Option<Packet<'q>> {
+    if simple_dns::header_buffer::has_flags(query, simple_dns::PacketFlag::RESPONSE).unwrap_or(true)
+    {
+        return None;
+    }
+
+    let packet = match Packet::parse(query) {
+        Ok(packet) => packet,
+        Err(err) => {
+            log::error!("Received Invalid packet {err}");
+            return None;
+        }
+    };
+
+    if let (Some(callback), Some(origin)) = (on_query, origin) {
+        callback(&packet, origin);
+    }
+
+    Some(packet)
+}
+
+/// Smallest UDP payload size an EDNS querier can advertise, [RFC 6891 section 6.2.5](https://datatracker.ietf.org/doc/html/rfc6891#section-6.2.5)
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+const MIN_EDNS_PAYLOAD_SIZE: usize = 512;
+
+/// Build the encoded reply for `query`, `unicast` is set when the reply is sent to the address of the querier
+/// whatever the questions request, like the replies sent by `listen_on_socket`.  
+/// Unicast replies to queries without EDNS are limited to `max_unicast_size`. Queries with EDNS are answered with
+/// an OPT record advertising [`MAX_MESSAGE_SIZE`], and their unicast replies are limited to the UDP payload size
+/// advertised by the querier instead
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+pub(crate) fn encode_reply(
+    query: Packet<'_>,
+    resources: &resource_record_manager::ResourceRecordManager<'_>,
+    unicast: bool,
+    max_unicast_size: Option<usize>,
+) -> Option<Vec<u8>> {
+    let edns_payload_size = query
+        .opt()
+        .map(|opt| usize::from(opt.udp_packet_size).clamp(MIN_EDNS_PAYLOAD_SIZE, MAX_MESSAGE_SIZE));
+    // the DO bit is copied to the response, RFC 3225 section 3
+    let dnssec_ok = matches!(query.opt(), Some(opt) if opt.dnssec_ok);
+    let (mut reply_packet, unicast_response) = match build_reply(query, resources) {
+        Some(reply) => reply,
+        None => {
+            log::trace!("No reply for query");
+            return None;
+        }
+    };
+
+    if edns_payload_size.is_some() {
+        *reply_packet.opt_mut() = Some(simple_dns::rdata::OPT {
+            opt_codes: Vec::new(),
+            udp_packet_size: MAX_MESSAGE_SIZE as u16,
+            version: 0,
+            dnssec_ok,
+        });
+    }
+
+    let max_size = edns_payload_size
+        .or(max_unicast_size)
+        .filter(|_| unicast || unicast_response);
+    match build_reply_bytes(reply_packet, max_size) {
+        Ok(reply) => Some(reply),
+        Err(err) => {
+            log::error!("Failed to build reply {err}");
+            None
+        }
+    }
+}
+
+/// Largest mDNS message, [RFC 6762 section 17](https://datatracker.ietf.org/doc/html/rfc6762#section-17)
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+pub(crate) const MAX_MESSAGE_SIZE: usize = 9000;
+/// Size of the buffers datagrams are received into, one byte larger than [`MAX_MESSAGE_SIZE`],
+/// so a larger datagram fills the whole buffer and can be detected by [`received_datagram`]
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+pub(crate) const RECV_BUFFER_SIZE: usize = MAX_MESSAGE_SIZE + 1;
+
+/// Returns the `count` bytes received into `buffer`, or `None` when the datagram filled the buffer.
+/// Datagrams larger than the buffer are truncated by the socket, parsing them would fail or return a partial packet
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+pub(crate) fn received_datagram(buffer: &[u8], count: usize) -> Option<&[u8]> {
+    if count >= buffer.len() {
+        log::warn!("Ignoring datagram larger than {MAX_MESSAGE_SIZE} bytes");
+        None
+    } else {
+        Some(&buffer[..count])
+    }
+}
+
+/// Returns true when `datagram` is a response with answers to the query `query_id`.
+/// Unicast responses must carry the ID of the query, multicast responses are accepted with the ID 0 as well, unless
+/// `strict_id` is set, since mDNS responders send them with the ID 0, [RFC 6762 section 18.1](https://datatracker.ietf.org/doc/html/rfc6762#section-18.1)
+#[cfg(any(feature = "sync", feature = "async-tokio"))]
+pub(crate) fn is_query_response(
+    datagram: &[u8],
+    query_id: u16,
+    unicast: bool,
+    strict_id: bool,
+) -> bool {
+    // the header_buffer functions index the buffer, datagrams shorter than a header are ignored first
+    if datagram.len() < 12 {
+        return false;
+    }
+
+    let id_matches = match header_buffer::id(datagram) {
+        Ok(id) => id == query_id || (id == 0 && !unicast && !strict_id),
+        Err(_) => false,
+    };
+
+    id_matches
+        && header_buffer::has_flags(datagram, PacketFlag::RESPONSE).unwrap_or(false)
+        && matches!(header_buffer::answers(datagram), Ok(answers) if answers > 0)
+}
+
+/// Splits `query` by the QU bit of its questions, since the unicast response is requested per question,
+/// [RFC 6762 section 5.4](https://datatracker.ietf.org/doc/html/rfc6762#section-5.4).
+/// Returns the query with the questions requesting a unicast response and the query with the questions requesting
+/// a multicast response, the known answers and authority records are kept in both
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+pub(crate) fn split_query<'a>(query: Packet<'a>) -> (Option<Packet<'a>>, Option<Packet<'a>>) {
+    let (unicast, multicast): (Vec<_>, Vec<_>) = query
+        .questions
+        .iter()
+        .cloned()
+        .partition(|question| question.unicast_response);
+
+    let with_questions = |questions: Vec<simple_dns::Question<'a>>| {
+        if questions.is_empty() {
+            return None;
+        }
+
+        let mut query = query.clone();
+        query.questions = questions;
+        Some(query)
+    };
+
+    (with_questions(unicast), with_questions(multicast))
+}
+
+/// Maximum size of the unicast replies to a query of `query_len` bytes, when an amplification limit is set
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+pub(crate) fn max_unicast_size(
+    query_len: usize,
+    amplification_limit: &std::sync::atomic::AtomicUsize,
+) -> Option<usize> {
+    match amplification_limit.load(std::sync::atomic::Ordering::Relaxed) {
+        0 => None,
+        ratio => Some(query_len * ratio),
+    }
+}
+
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+fn address_records<'b>(
+    resources: &'b resource_record_manager::ResourceRecordManager<'b>,
+    target: &Name<'_>,
+    qclass: QCLASS,
+) -> impl Iterator<Item = ResourceRecord<'b>> + 'b {
+    let found: Vec<_> = resources
+        .get_domain_resources(target, false, true)
+        .flatten()
+        .filter(|r| r.match_qtype(TYPE::A.into()) && r.match_qclass(qclass))
+        .cloned()
+        .collect();
+
+    found.into_iter()
+}
+
+#[cfg(all(
+    test,
+    any(feature = "sync", feature = "async-tokio", feature = "async-std")
+))]
 mod tests {
     use simple_dns::Name;
     use std::{
@@ -85,7 +598,7 @@ mod tests {
         net::{Ipv4Addr, Ipv6Addr},
     };
 
-    use simple_dns::Question;
+    use simple_dns::{Question, CLASS};
 
     use crate::{
         build_reply,
@@ -126,6 +639,89 @@ mod tests {
         resources
     }
 
+    #[test]
+    fn test_build_probe_and_detect_conflicts() {
+        let resources = get_resources();
+        let name = Name::new_unchecked("_res2._tcp.com");
+
+        let bytes = build_probe(&name, &resources).build_bytes_vec().unwrap();
+        let probe = Packet::parse(&bytes).unwrap();
+        assert_eq!(QTYPE::ANY, probe.questions[0].qtype);
+        assert!(probe.questions[0].unicast_response);
+        assert_eq!(2, probe.name_servers.len());
+
+        let proposed: Vec<_> = probe.name_servers.clone();
+        let (reply, _) = build_reply(probe, &resources).unwrap();
+        let own_reply = reply.build_bytes_vec().unwrap();
+        assert!(!is_probe_conflict(&own_reply, &name, &proposed));
+
+        let mut other = Packet::new_reply(0);
+        other.answers.push(ip_addr_to_resource_record(
+            &name,
+            Ipv4Addr::new(192, 168, 1, 2).into(),
+            0,
+        ));
+        let other = other.build_bytes_vec().unwrap();
+        assert!(is_probe_conflict(&other, &name, &proposed));
+        assert!(!is_probe_conflict(
+            &other,
+            &Name::new_unchecked("_res1._tcp.com"),
+            &proposed
+        ));
+        assert!(!is_probe_conflict(&bytes, &name, &[]));
+    }
+
+    #[test]
+    fn test_build_announcement() {
+        let mut resources = get_resources();
+        resources.add_owned_resource(ResourceRecord::new(
+            Name::new_unchecked("_res._tcp.com"),
+            CLASS::IN,
+            0,
+            RData::PTR(Name::new_unchecked("_res1._tcp.com").into()),
+        ));
+
+        let bytes = build_announcement(&resources).build_bytes_vec().unwrap();
+        let announcement = Packet::parse(&bytes).unwrap();
+
+        assert!(announcement.has_flags(simple_dns::PacketFlag::AUTHORITATIVE_ANSWER));
+        assert_eq!(6, announcement.answers.len());
+        for resource in resources.get_all_owned_resources() {
+            assert!(announcement
+                .answers
+                .iter()
+                .any(|r| r.name == resource.name && r.rdata == resource.rdata));
+        }
+
+        for answer in announcement.answers {
+            assert_eq!(!matches!(answer.rdata, RData::PTR(_)), answer.cache_flush);
+        }
+    }
+
+    #[test]
+    fn test_build_goodbye() {
+        let resources = get_resources();
+        let registered: HashSet<_> = resources
+            .get_all_owned_resources()
+            .map(|r| (r.name.clone(), r.rdata.clone()))
+            .collect();
+
+        let bytes = build_goodbye(&resources).build_bytes_vec().unwrap();
+        let goodbye = Packet::parse(&bytes).unwrap();
+
+        assert!(goodbye.has_flags(simple_dns::PacketFlag::RESPONSE));
+        assert_eq!(5, goodbye.answers.len());
+        assert!(goodbye.answers.iter().all(|r| r.ttl == 0));
+        assert_eq!(
+            registered,
+            goodbye
+                .answers
+                .iter()
+                .map(|r| (r.name.clone(), r.rdata.clone()))
+                .collect()
+        );
+    }
+
     #[test]
     fn test_build_reply_with_no_questions() {
         let resources = get_resources();
@@ -134,6 +730,110 @@ mod tests {
         assert!(build_reply(packet, &resources,).is_none());
     }
 
+    #[test]
+    fn test_build_reply_with_multiple_questions() {
+        let resources = get_resources();
+
+        let name = Name::new_unchecked("_res1._tcp.com");
+        let packet = Packet::new_query(1).with_questions([
+            Question::new(name.clone(), TYPE::A.into(), QCLASS::ANY, false),
+            Question::new(name, TYPE::AAAA.into(), QCLASS::ANY, false),
+        ]);
+        let bytes = packet.build_bytes_vec().unwrap();
+        let packet = Packet::parse(&bytes).unwrap();
+
+        let (reply, _) = build_reply(packet, &resources).unwrap();
+
+        assert!(reply.answers.iter().any(|r| matches!(r.rdata, RData::A(_))));
+        assert!(reply
+            .answers
+            .iter()
+            .any(|r| matches!(r.rdata, RData::AAAA(_))));
+    }
+
+    #[test]
+    fn test_build_reply_with_overlapping_questions() {
+        let resources = get_resources();
+
+        let mut packet = Packet::new_query(1);
+        for qtype in [
+            QTYPE::ANY,
+            simple_dns::TYPE::A.into(),
+            simple_dns::TYPE::A.into(),
+        ] {
+            packet.questions.push(Question::new(
+                "_res1._tcp.com".try_into().unwrap(),
+                qtype,
+                simple_dns::QCLASS::ANY,
+                false,
+            ));
+        }
+
+        let (reply, _) = build_reply(packet, &resources).unwrap();
+        let a_records = reply
+            .answers
+            .iter()
+            .filter(|answer| matches!(answer.rdata, RData::A(_)))
+            .count();
+
+        assert_eq!(1, a_records);
+        assert_eq!(3, reply.answers.len());
+    }
+
+    #[test]
+    fn test_build_reply_suppresses_known_answers() {
+        let mut resources = ResourceRecordManager::new();
+        let name = Name::new_unchecked("_res1._tcp.com");
+        for address in [Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 2)] {
+            resources.add_owned_resource(ip_addr_to_resource_record(&name, address.into(), 120));
+        }
+
+        let query_with_known_answer = |ttl| {
+            let mut packet = Packet::new_query(1).with_questions([Question::new(
+                name.clone(),
+                TYPE::A.into(),
+                QCLASS::ANY,
+                false,
+            )]);
+            packet.answers.push(ip_addr_to_resource_record(
+                &name,
+                Ipv4Addr::new(10, 0, 0, 1).into(),
+                ttl,
+            ));
+            packet.build_bytes_vec().unwrap()
+        };
+
+        let bytes = query_with_known_answer(60);
+        let (reply, _) = build_reply(Packet::parse(&bytes).unwrap(), &resources).unwrap();
+        assert_eq!(1, reply.answers.len());
+        assert_eq!(
+            RData::A(Ipv4Addr::new(10, 0, 0, 2).into()),
+            reply.answers[0].rdata
+        );
+
+        // known answers with less than half of the TTL are about to expire and are sent again
+        let bytes = query_with_known_answer(59);
+        let (reply, _) = build_reply(Packet::parse(&bytes).unwrap(), &resources).unwrap();
+        assert_eq!(2, reply.answers.len());
+    }
+
+    #[test]
+    fn test_build_reply_for_announcement() {
+        let resources = get_resources();
+
+        let mut packet = Packet::new_reply(1);
+        packet.answers.push(ip_addr_to_resource_record(
+            &Name::new_unchecked("_res1._tcp.com"),
+            Ipv4Addr::LOCALHOST.into(),
+            0,
+        ));
+
+        let bytes = packet.build_bytes_vec().unwrap();
+        let packet = Packet::parse(&bytes).unwrap();
+        assert_eq!(1, packet.answers.len());
+        assert!(build_reply(packet, &resources).is_none());
+    }
+
     #[test]
     fn test_build_reply_without_valid_answers() {
         let resources = get_resources();
@@ -149,6 +849,64 @@ mod tests {
         assert!(build_reply(packet, &resources,).is_none());
     }
 
+    #[test]
+    #[cfg(any(feature = "sync", feature = "async-tokio"))]
+    fn test_build_reply_for_probe() {
+        let mut resources = get_resources();
+        let name = Name::new_unchecked("_res1._tcp.com");
+        resources.add_owned_resource(ResourceRecord::new(
+            name.clone(),
+            CLASS::IN,
+            0,
+            RData::TXT(Default::default()),
+        ));
+        resources.add_owned_resource(ip_addr_to_resource_record(
+            &Name::new_unchecked("sub._res1._tcp.com"),
+            Ipv4Addr::LOCALHOST.into(),
+            0,
+        ));
+        resources.add_expirable_resource(ip_addr_to_resource_record(
+            &name,
+            Ipv4Addr::new(192, 168, 1, 2).into(),
+            10,
+        ));
+
+        let mut packet = Packet::new_query(1);
+        packet.questions.push(Question::new(
+            name.clone(),
+            QTYPE::ANY,
+            CLASS::IN.into(),
+            false,
+        ));
+        packet.name_servers.push(ip_addr_to_resource_record(
+            &name,
+            Ipv4Addr::new(192, 168, 1, 3).into(),
+            120,
+        ));
+
+        let (reply, _) = build_reply(packet, &resources).unwrap();
+
+        let mut types: Vec<u16> = reply
+            .answers
+            .iter()
+            .map(|r| {
+                assert_eq!(name, r.name);
+                r.rdata.type_code().into()
+            })
+            .collect();
+        types.sort_unstable();
+
+        let expected: Vec<u16> = [TYPE::A, TYPE::TXT, TYPE::AAAA, TYPE::SRV]
+            .into_iter()
+            .map(u16::from)
+            .collect();
+        assert_eq!(expected, types);
+        assert!(!reply.answers.iter().any(
+            |r| matches!(&r.rdata, RData::A(a) if a.address == u32::from(Ipv4Addr::new(192, 168, 1, 2)))
+        ));
+        assert!(reply.additional_records.is_empty());
+    }
+
     #[test]
     fn test_build_reply_with_valid_answer() {
         let resources = get_resources();
@@ -164,6 +922,7 @@ mod tests {
         let (reply, unicast_response) = build_reply(packet, &resources).unwrap();
 
         assert!(unicast_response);
+        assert!(reply.authoritative_answer());
         assert_eq!(2, reply.answers.len());
         assert_eq!(0, reply.additional_records.len());
     }
@@ -186,4 +945,439 @@ mod tests {
         assert_eq!(1, reply.answers.len());
         assert_eq!(2, reply.additional_records.len());
     }
+
+    #[test]
+    fn test_build_reply_bytes_compresses_shared_names() {
+        let resources = get_resources();
+        let name = Name::new_unchecked("_res1._tcp.com");
+
+        let mut packet = Packet::new_query(1);
+        packet.questions.push(Question::new(
+            name.clone(),
+            simple_dns::TYPE::SRV.into(),
+            simple_dns::QCLASS::ANY,
+            false,
+        ));
+
+        let (reply, _) = build_reply(packet, &resources).unwrap();
+        let plain = reply.build_bytes_vec().unwrap();
+        let compressed = build_reply_bytes(reply.clone(), None).unwrap();
+
+        // the SRV target and the owner names of the A and AAAA records are pointers to the SRV owner name,
+        // each pointer takes 2 bytes instead of the 16 bytes of the name
+        assert_eq!(plain.len() - 3 * 14, compressed.len());
+
+        let parsed = Packet::parse(&compressed).unwrap();
+        assert_eq!(reply.answers, parsed.answers);
+        assert_eq!(reply.additional_records, parsed.additional_records);
+    }
+
+    #[test]
+    fn test_build_reply_for_ptr_with_multiple_instances() {
+        let service_name = Name::new_unchecked("_http._tcp.local");
+        let mut resources = ResourceRecordManager::new();
+        for instance in ["inst1._http._tcp.local", "inst2._http._tcp.local"] {
+            let instance = Name::new_unchecked(instance);
+            resources.add_owned_resource(ResourceRecord::new(
+                service_name.clone(),
+                simple_dns::CLASS::IN,
+                0,
+                RData::PTR(instance.clone().into()),
+            ));
+            resources.add_owned_resource(port_to_srv_record(&instance, 8080, 0));
+            resources.add_owned_resource(ip_addr_to_resource_record(
+                &instance,
+                Ipv4Addr::LOCALHOST.into(),
+                0,
+            ));
+        }
+        resources.add_owned_resource(ResourceRecord::new(
+            Name::new_unchecked("_https._tcp.local"),
+            simple_dns::CLASS::IN,
+            0,
+            RData::PTR(Name::new_unchecked("inst3._https._tcp.local").into()),
+        ));
+
+        let mut packet = Packet::new_query(1);
+        packet.questions.push(Question::new(
+            service_name.clone(),
+            simple_dns::TYPE::PTR.into(),
+            simple_dns::QCLASS::ANY,
+            false,
+        ));
+
+        let (reply, _) = build_reply(packet, &resources).unwrap();
+
+        let mut instances: Vec<String> = reply
+            .answers
+            .iter()
+            .map(|answer| match &answer.rdata {
+                RData::PTR(ptr) => ptr.to_string(),
+                _ => panic!("expected only PTR answers"),
+            })
+            .collect();
+        instances.sort();
+
+        assert_eq!(
+            vec!["inst1._http._tcp.local", "inst2._http._tcp.local"],
+            instances
+        );
+        assert_eq!(4, reply.additional_records.len());
+    }
+
+    #[test]
+    fn test_build_reply_enumerates_service_types() {
+        let mut resources = ResourceRecordManager::new();
+        for (service_name, instance) in [
+            ("_http._tcp.local", "inst1._http._tcp.local"),
+            ("_http._tcp.local", "inst2._http._tcp.local"),
+            ("_ipp._udp.local", "inst1._ipp._udp.local"),
+            ("1.0.0.127.in-addr.arpa", "my-host.local"),
+        ] {
+            resources.add_owned_resource(ResourceRecord::new(
+                Name::new_unchecked(service_name),
+                simple_dns::CLASS::IN,
+                0,
+                RData::PTR(Name::new_unchecked(instance).into()),
+            ));
+        }
+
+        let mut packet = Packet::new_query(1);
+        packet.questions.push(Question::new(
+            Name::new_unchecked(SERVICE_TYPE_ENUMERATION_NAME),
+            simple_dns::TYPE::PTR.into(),
+            CLASS::IN.into(),
+            false,
+        ));
+
+        let (reply, _) = build_reply(packet, &resources).unwrap();
+
+        let mut service_types: Vec<String> = reply
+            .answers
+            .iter()
+            .map(|answer| {
+                assert_eq!(SERVICE_TYPE_ENUMERATION_NAME, answer.name.to_string());
+                match &answer.rdata {
+                    RData::PTR(ptr) => ptr.to_string(),
+                    _ => panic!("expected only PTR answers"),
+                }
+            })
+            .collect();
+        service_types.sort();
+
+        assert_eq!(vec!["_http._tcp.local", "_ipp._udp.local"], service_types);
+    }
+
+    #[test]
+    fn test_reply_truncated_by_amplification_limit() {
+        let service_name = Name::new_unchecked("_res1._tcp.com");
+        let mut resources = ResourceRecordManager::new();
+        for i in 0..100u32 {
+            resources.add_owned_resource(ip_addr_to_resource_record(
+                &service_name,
+                Ipv4Addr::from(i).into(),
+                0,
+            ));
+        }
+
+        let mut query = Packet::new_query(1);
+        query.questions.push(Question::new(
+            service_name.clone(),
+            simple_dns::QTYPE::ANY,
+            simple_dns::QCLASS::ANY,
+            true,
+        ));
+        let query_len = query.build_bytes_vec().unwrap().len();
+
+        let (reply, _) = build_reply(query.clone(), &resources).unwrap();
+        let full_reply = build_reply_bytes(reply, None).unwrap();
+        assert!(full_reply.len() > query_len * 3);
+        assert!(!Packet::parse(&full_reply).unwrap().truncated());
+
+        let (reply, _) = build_reply(query, &resources).unwrap();
+        let truncated = build_reply_bytes(reply, Some(query_len * 3)).unwrap();
+        assert!(truncated.len() <= query_len * 3);
+
+        let truncated = Packet::parse(&truncated).unwrap();
+        assert!(truncated.truncated());
+        assert!(!truncated.answers.is_empty());
+        assert!(truncated.answers.len() < 100);
+    }
+
+    #[test]
+    fn test_amplification_limit_applies_to_every_unicast_reply() {
+        let service_name = Name::new_unchecked("_res1._tcp.com");
+        let mut resources = ResourceRecordManager::new();
+        for i in 0..100u32 {
+            resources.add_owned_resource(ip_addr_to_resource_record(
+                &service_name,
+                Ipv4Addr::from(i).into(),
+                0,
+            ));
+        }
+
+        // the question does not request a unicast response, but the reply is sent to the querier
+        let query = Packet::new_query(1).with_questions([Question::new(
+            service_name.clone(),
+            simple_dns::QTYPE::ANY,
+            simple_dns::QCLASS::ANY,
+            false,
+        )]);
+        let limit = query.build_bytes_vec().unwrap().len() * 3;
+
+        let reply = encode_reply(query.clone(), &resources, true, Some(limit)).unwrap();
+        assert!(reply.len() <= limit);
+        assert!(Packet::parse(&reply).unwrap().truncated());
+
+        let reply = encode_reply(query, &resources, false, Some(limit)).unwrap();
+        assert!(reply.len() > limit);
+    }
+
+    #[test]
+    fn test_unicast_reply_limited_by_edns_payload_size() {
+        let service_name = Name::new_unchecked("_res1._tcp.com");
+        let mut resources = ResourceRecordManager::new();
+        for i in 0..100u32 {
+            resources.add_owned_resource(ip_addr_to_resource_record(
+                &service_name,
+                Ipv4Addr::from(i).into(),
+                0,
+            ));
+        }
+
+        let query = |udp_packet_size| {
+            let mut query = Packet::new_query(1).with_questions([Question::new(
+                service_name.clone(),
+                simple_dns::TYPE::A.into(),
+                CLASS::IN.into(),
+                true,
+            )]);
+            *query.opt_mut() = Some(simple_dns::rdata::OPT {
+                opt_codes: Vec::new(),
+                udp_packet_size,
+                version: 0,
+                dnssec_ok: false,
+            });
+            query
+        };
+
+        let reply = encode_reply(query(4096), &resources, false, Some(100)).unwrap();
+        assert!(reply.len() > 512);
+        assert!(reply.len() <= 4096);
+        let reply = Packet::parse(&reply).unwrap();
+        assert!(!reply.truncated());
+        assert_eq!(100, reply.answers.len());
+        assert_eq!(
+            MAX_MESSAGE_SIZE as u16,
+            reply.opt().unwrap().udp_packet_size
+        );
+
+        let reply = encode_reply(query(100), &resources, false, None).unwrap();
+        assert!(reply.len() <= 512);
+        assert!(Packet::parse(&reply).unwrap().truncated());
+    }
+
+    #[test]
+    // receiving a datagram larger than the buffer is an error on windows
+    #[cfg(not(windows))]
+    fn test_oversized_datagram_is_ignored() {
+        let receiver = std::net::UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let sender = std::net::UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let receiver_addr = receiver.local_addr().unwrap();
+
+        let mut reply = Packet::new_reply(1);
+        for i in 0..1000u32 {
+            reply.answers.push(ip_addr_to_resource_record(
+                &Name::new_unchecked("_res1._tcp.com"),
+                Ipv4Addr::from(i).into(),
+                0,
+            ));
+        }
+        let oversized = reply.build_bytes_vec().unwrap();
+        assert!(oversized.len() > MAX_MESSAGE_SIZE);
+        let valid = Packet::new_reply(2).build_bytes_vec().unwrap();
+
+        sender.send_to(&oversized, receiver_addr).unwrap();
+        sender.send_to(&valid, receiver_addr).unwrap();
+
+        let mut buffer = [0u8; RECV_BUFFER_SIZE];
+        let (count, _) = receiver.recv_from(&mut buffer).unwrap();
+        assert!(received_datagram(&buffer, count).is_none());
+
+        let (count, _) = receiver.recv_from(&mut buffer).unwrap();
+        let datagram = received_datagram(&buffer, count).unwrap();
+        assert_eq!(2, Packet::parse(datagram).unwrap().id());
+    }
+
+    #[test]
+    fn test_build_reply_dnssec_records_only_with_do_bit() {
+        let mut resources = get_resources();
+        let rrsig = ResourceRecord::new(
+            Name::new_unchecked("_res1._tcp.com"),
+            CLASS::IN,
+            0,
+            RData::NULL(46, simple_dns::rdata::NULL::new(&[1, 2, 3]).unwrap()),
+        );
+        resources.add_owned_resource(rrsig.clone());
+
+        let query = |qtype: QTYPE, dnssec_ok: Option<bool>| {
+            let mut query = Packet::new_query(1).with_questions([Question::new(
+                Name::new_unchecked("_res1._tcp.com"),
+                qtype,
+                QCLASS::ANY,
+                false,
+            )]);
+            *query.opt_mut() = dnssec_ok.map(|dnssec_ok| simple_dns::rdata::OPT {
+                opt_codes: Vec::new(),
+                udp_packet_size: 1232,
+                version: 0,
+                dnssec_ok,
+            });
+            query
+        };
+
+        for dnssec_ok in [None, Some(false)] {
+            let (reply, _) = build_reply(query(QTYPE::ANY, dnssec_ok), &resources).unwrap();
+            assert_eq!(3, reply.answers.len());
+            assert!(!reply.answers.contains(&rrsig));
+        }
+
+        let (reply, _) = build_reply(query(QTYPE::ANY, Some(true)), &resources).unwrap();
+        assert_eq!(4, reply.answers.len());
+        assert!(reply.answers.contains(&rrsig));
+
+        let (reply, _) = build_reply(query(TYPE::Unknown(46).into(), None), &resources).unwrap();
+        assert_eq!(vec![rrsig], reply.answers);
+
+        let reply = encode_reply(query(QTYPE::ANY, Some(true)), &resources, false, None).unwrap();
+        assert!(Packet::parse(&reply).unwrap().opt().unwrap().dnssec_ok);
+    }
+
+    #[test]
+    fn test_build_reply_with_negative_response() {
+        let resources = get_resources();
+
+        let mut packet = Packet::new_query(1);
+        packet.questions.push(Question::new(
+            "_res2._tcp.com".try_into().unwrap(),
+            simple_dns::TYPE::AAAA.into(),
+            simple_dns::QCLASS::ANY,
+            false,
+        ));
+
+        let (reply, _) = build_reply(packet, &resources).unwrap();
+        let nsec = reply
+            .additional_records
+            .iter()
+            .find(|r| matches!(r.rdata, RData::NSEC(_)))
+            .expect("expected a NSEC record");
+        assert_eq!(Name::new_unchecked("_res2._tcp.com"), nsec.name);
+        assert!(nsec.cache_flush);
+        match &nsec.rdata {
+            RData::NSEC(nsec) => {
+                assert_eq!(Name::new_unchecked("_res2._tcp.com"), nsec.next_name);
+                assert!(nsec.contains(TYPE::A));
+                assert!(nsec.contains(TYPE::SRV));
+                assert!(!nsec.contains(TYPE::AAAA));
+            }
+            _ => panic!("expected a NSEC record"),
+        }
+
+        // names without records and types that exist have no negative response
+        for (name, qtype) in [("_res3._tcp.com", TYPE::AAAA), ("_res2._tcp.com", TYPE::A)] {
+            let mut packet = Packet::new_query(1);
+            packet.questions.push(Question::new(
+                Name::new_unchecked(name),
+                qtype.into(),
+                simple_dns::QCLASS::ANY,
+                false,
+            ));
+
+            let has_nsec = build_reply(packet, &resources).is_some_and(|(reply, _)| {
+                reply
+                    .additional_records
+                    .iter()
+                    .any(|r| matches!(r.rdata, RData::NSEC(_)))
+            });
+            assert!(!has_nsec);
+        }
+    }
+
+    #[test]
+    fn test_build_reply_with_authority_records() {
+        let mut resources = get_resources();
+        resources.add_authority_resource(ResourceRecord::new(
+            Name::new_unchecked("_res1._tcp.com"),
+            simple_dns::CLASS::IN,
+            0,
+            RData::NSEC(NSEC::new(
+                Name::new_unchecked("_res1._tcp.com"),
+                &[TYPE::A, TYPE::AAAA, TYPE::SRV],
+            )),
+        ));
+
+        let mut packet = Packet::new_query(1);
+        packet.questions.push(Question::new(
+            "_res1._tcp.com".try_into().unwrap(),
+            simple_dns::TYPE::TXT.into(),
+            simple_dns::QCLASS::ANY,
+            false,
+        ));
+
+        let (reply, _) = build_reply(packet, &resources).unwrap();
+        assert!(reply.answers.is_empty());
+        assert_eq!(1, reply.name_servers.len());
+        assert!(
+            matches!(&reply.name_servers[0].rdata, RData::NSEC(nsec) if nsec.contains(TYPE::SRV))
+        );
+
+        let bytes = reply.build_bytes_vec_compressed().unwrap();
+        assert_eq!(1, simple_dns::header_buffer::name_servers(&bytes).unwrap());
+
+        let mut packet = Packet::new_query(1);
+        packet.questions.push(Question::new(
+            "_res1._tcp.com".try_into().unwrap(),
+            simple_dns::TYPE::SRV.into(),
+            simple_dns::QCLASS::ANY,
+            false,
+        ));
+
+        let (reply, _) = build_reply(packet, &resources).unwrap();
+        assert_eq!(1, reply.answers.len());
+        assert!(reply.name_servers.is_empty());
+    }
+
+    #[test]
+    #[cfg(any(feature = "sync", feature = "async-tokio"))]
+    fn test_query_response_id_matching() {
+        let response = |id: u16| {
+            let mut reply = Packet::new_reply(id);
+            reply.answers.push(ResourceRecord::new(
+                Name::new_unchecked("host.local"),
+                CLASS::IN,
+                10,
+                RData::A(Ipv4Addr::LOCALHOST.into()),
+            ));
+            reply.build_bytes_vec().unwrap()
+        };
+
+        assert!(is_query_response(&response(42), 42, true, false));
+        assert!(!is_query_response(&response(43), 42, true, false));
+        assert!(!is_query_response(&response(0), 42, true, false));
+
+        assert!(is_query_response(&response(42), 42, false, false));
+        assert!(is_query_response(&response(0), 42, false, false));
+        assert!(!is_query_response(&response(43), 42, false, false));
+        assert!(!is_query_response(&response(0), 42, false, true));
+
+        let query = Packet::new_query(42).build_bytes_vec().unwrap();
+        assert!(!is_query_response(&query, 42, true, false));
+        assert!(!is_query_response(
+            &Packet::new_reply(42).build_bytes_vec().unwrap(),
+            42,
+            true,
+            false
+        ));
+        assert!(!is_query_response(&[0, 42, 0x80], 42, true, false));
+    }
 }
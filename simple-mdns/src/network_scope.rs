@@ -1,20 +1,27 @@
-use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+use std::net::{IpAddr, SocketAddr};
+use std::net::{Ipv4Addr, Ipv6Addr};
 
+/// Port used by mDNS, [RFC 6762 section 3](https://datatracker.ietf.org/doc/html/rfc6762#section-3)
 pub const MULTICAST_PORT: u16 = 5353;
+/// IPv4 multicast group of mDNS, `224.0.0.251`
 pub const MULTICAST_ADDR_IPV4: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+/// IPv6 multicast group of mDNS, `ff02::fb`
 pub const MULTICAST_ADDR_IPV6: Ipv6Addr = Ipv6Addr::new(0xFF02, 0, 0, 0, 0, 0, 0, 0xFB);
 
 /// Network scope to be used by service discovery
 /// Default scope for services is to use IPV4 protocol
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum NetworkScope {
     /// Uses IPV4 protocol with UNSPECIFIED network interface (0.0.0.0)
     V4,
-    /// Uses IPV4 protocol and provided network interface
+    /// Uses IPV4 protocol and the network interface with the provided local address, for joining the multicast group
+    /// and for sending packets
     V4WithInterface(Ipv4Addr),
     /// Uses IPV6 protocol with UNSPECIFIED network interface (0)
     V6,
-    /// Uses IPV6 protocol with provided network interface
+    /// Uses IPV6 protocol and the network interface with the provided index, for joining the multicast group and for
+    /// sending packets
     V6WithInterface(u32),
 }
 
@@ -28,6 +35,19 @@ impl NetworkScope {
         matches!(&self, Self::V4 | Self::V4WithInterface(..))
     }
 
+    /// Returns a scope for every network interface that is up, supports multicast and is not a loopback
+    /// interface, IPV4 scopes first.
+    ///
+    /// Interfaces are enumerated only on Linux and Android, other platforms return the UNSPECIFIED [`V4`] and
+    /// [`V6`] scopes
+    ///
+    /// [`V4`]: NetworkScope::V4
+    /// [`V6`]: NetworkScope::V6
+    pub fn all_interfaces() -> std::io::Result<Vec<NetworkScope>> {
+        crate::socket_helper::interface_scopes()
+    }
+
+    #[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
     pub(crate) fn socket_address(&self) -> SocketAddr {
         if self.is_v4() {
             SocketAddr::new(IpAddr::V4(MULTICAST_ADDR_IPV4), MULTICAST_PORT)
@@ -37,6 +57,15 @@ impl NetworkScope {
     }
 }
 
+/// Returns the multicast group of the same address family as `origin`, used to send multicast replies to a query
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+pub(crate) fn multicast_address_for(origin: &SocketAddr) -> SocketAddr {
+    match origin {
+        SocketAddr::V4(_) => SocketAddr::new(IpAddr::V4(MULTICAST_ADDR_IPV4), MULTICAST_PORT),
+        SocketAddr::V6(_) => SocketAddr::new(IpAddr::V6(MULTICAST_ADDR_IPV6), MULTICAST_PORT),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -50,4 +79,17 @@ mod tests {
     fn test_ipv6_multicast() {
         assert!(MULTICAST_ADDR_IPV6.is_multicast());
     }
+
+    #[test]
+    #[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+    fn test_multicast_address_for_origin_family() {
+        assert_eq!(
+            NetworkScope::V4.socket_address(),
+            multicast_address_for(&"192.168.0.2:5353".parse().unwrap())
+        );
+        assert_eq!(
+            NetworkScope::V6.socket_address(),
+            multicast_address_for(&"[fe80::1]:5353".parse().unwrap())
+        );
+    }
 }
@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use simple_dns::{Name, ResourceRecord, CLASS, TYPE};
+
+use crate::resource_record_manager::get_key;
+
+/// Cache of records received from other hosts, each record expires when its TTL elapses.
+///
+/// Records are grouped by name (ignoring case), type and class. Every method receives the current time,
+/// expired records are left out on access and removed by [`remove_expired`](RecordCache::remove_expired)
+#[derive(Debug, Default)]
+pub(crate) struct RecordCache {
+    entries: HashMap<CacheKey, Vec<CachedRecord>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    name: Vec<u8>,
+    rtype: TYPE,
+    class: CLASS,
+}
+
+impl CacheKey {
+    fn new(name: &Name, rtype: TYPE, class: CLASS) -> Self {
+        Self {
+            name: get_key(name),
+            rtype,
+            class,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CachedRecord {
+    record: ResourceRecord<'static>,
+    expire_at: Instant,
+    /// When the record should be queried again, `None` after [`RecordCache::take_refreshes`] returned it
+    refresh_at: Option<Instant>,
+}
+
+impl CachedRecord {
+    fn new(record: ResourceRecord<'static>, now: Instant) -> Self {
+        let ttl = Duration::from_secs(record.ttl as u64);
+        Self {
+            expire_at: now + ttl,
+            // Records are queried again at 80% of their TTL, RFC 6762 section 5.2
+            refresh_at: Some(now + ttl / 10 * 8),
+            record,
+        }
+    }
+}
+
+impl RecordCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cache `record` received at `now`, a copy of a cached record (same rdata) replaces its TTL.
+    /// Records with a TTL of 0 are goodbye records and remove the cached copy instead.
+    ///
+    /// Returns true when a new record was cached, or a cached record was removed by a goodbye record
+    pub fn insert(&mut self, record: ResourceRecord<'static>, now: Instant) -> bool {
+        let key = CacheKey::new(&record.name, record.rdata.type_code(), record.class);
+        let records = self.entries.entry(key.clone()).or_default();
+        let position = records
+            .iter()
+            .position(|cached| cached.record.rdata == record.rdata && cached.expire_at > now);
+
+        let changed = match (position, record.ttl) {
+            (Some(position), 0) => {
+                records.remove(position);
+                true
+            }
+            (None, 0) => false,
+            (Some(position), _) => {
+                records[position] = CachedRecord::new(record, now);
+                false
+            }
+            (None, _) => {
+                records.retain(|cached| cached.record.rdata != record.rdata);
+                records.push(CachedRecord::new(record, now));
+                true
+            }
+        };
+
+        if records.is_empty() {
+            self.entries.remove(&key);
+        }
+
+        changed
+    }
+
+    /// Returns the records cached for `name`, `rtype` and `class` that did not expire at `now`,
+    /// with their TTL reduced by the time elapsed since they were received
+    pub fn get(
+        &self,
+        name: &Name,
+        rtype: TYPE,
+        class: CLASS,
+        now: Instant,
+    ) -> Vec<ResourceRecord<'static>> {
+        self.entries
+            .get(&CacheKey::new(name, rtype, class))
+            .into_iter()
+            .flatten()
+            .filter(|cached| cached.expire_at > now)
+            .map(|cached| {
+                let mut record = cached.record.clone();
+                record.ttl = (cached.expire_at - now).as_secs() as u32;
+                record
+            })
+            .collect()
+    }
+
+    /// Remove the records expired at `now`, returning them
+    pub fn remove_expired(&mut self, now: Instant) -> Vec<ResourceRecord<'static>> {
+        let mut expired = Vec::new();
+        self.entries.retain(|_, records| {
+            records.retain(|cached| {
+                let is_expired = cached.expire_at <= now;
+                if is_expired {
+                    expired.push(cached.record.clone());
+                }
+                !is_expired
+            });
+
+            !records.is_empty()
+        });
+
+        expired
+    }
+
+    /// Returns the records that should be queried again at `now`, each record is returned only once per TTL
+    pub fn take_refreshes(&mut self, now: Instant) -> Vec<ResourceRecord<'static>> {
+        let mut refreshes = Vec::new();
+        for cached in self.entries.values_mut().flatten() {
+            if matches!(cached.refresh_at, Some(refresh_at) if refresh_at <= now) {
+                cached.refresh_at = None;
+                refreshes.push(cached.record.clone());
+            }
+        }
+
+        refreshes
+    }
+
+    /// Returns the next time a record expires or should be refreshed
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.entries
+            .values()
+            .flatten()
+            .map(|cached| cached.refresh_at.unwrap_or(cached.expire_at))
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use simple_dns::rdata::{RData, A};
+
+    use super::*;
+
+    fn a_record(name: &str, address: u32, ttl: u32) -> ResourceRecord<'static> {
+        ResourceRecord::new(
+            Name::new_unchecked(name).into_owned(),
+            CLASS::IN,
+            ttl,
+            RData::A(A { address }),
+        )
+    }
+
+    fn cached_addresses(cache: &RecordCache, name: &str, now: Instant) -> Vec<(u32, u32)> {
+        cache
+            .get(&Name::new_unchecked(name), TYPE::A, CLASS::IN, now)
+            .into_iter()
+            .map(|record| match record.rdata {
+                RData::A(a) => (a.address, record.ttl),
+                _ => panic!("expected only A records"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn insert_and_get_before_expiration() {
+        let now = Instant::now();
+        let mut cache = RecordCache::new();
+
+        assert!(cache.insert(a_record("host.local", 1, 10), now));
+        assert!(cache.insert(a_record("host.local", 2, 20), now));
+        assert!(!cache.insert(a_record("host.local", 1, 10), now));
+
+        assert_eq!(
+            vec![(1, 6), (2, 16)],
+            cached_addresses(&cache, "HOST.local", now + Duration::from_secs(4))
+        );
+        assert!(cache
+            .get(
+                &Name::new_unchecked("host.local"),
+                TYPE::AAAA,
+                CLASS::IN,
+                now
+            )
+            .is_empty());
+    }
+
+    #[test]
+    fn fresher_copy_updates_ttl() {
+        let now = Instant::now();
+        let mut cache = RecordCache::new();
+
+        cache.insert(a_record("host.local", 1, 10), now);
+        assert!(!cache.insert(a_record("host.local", 1, 10), now + Duration::from_secs(8)));
+
+        let later = now + Duration::from_secs(12);
+        assert_eq!(vec![(1, 6)], cached_addresses(&cache, "host.local", later));
+        assert!(cache.remove_expired(later).is_empty());
+    }
+
+    #[test]
+    fn remove_expired_records() {
+        let now = Instant::now();
+        let mut cache = RecordCache::new();
+
+        cache.insert(a_record("host.local", 1, 10), now);
+        cache.insert(a_record("host.local", 2, 20), now);
+        assert_eq!(Some(now + Duration::from_secs(8)), cache.next_deadline());
+
+        let later = now + Duration::from_secs(10);
+        assert_eq!(vec![(2, 10)], cached_addresses(&cache, "host.local", later));
+        assert_eq!(
+            vec![a_record("host.local", 1, 10)],
+            cache.remove_expired(later)
+        );
+        assert!(cache.remove_expired(later).is_empty());
+
+        // an expired copy is replaced and reported as a new record
+        assert!(cache.insert(a_record("host.local", 2, 20), now + Duration::from_secs(20)));
+    }
+
+    #[test]
+    fn goodbye_removes_cached_record() {
+        let now = Instant::now();
+        let mut cache = RecordCache::new();
+
+        cache.insert(a_record("host.local", 1, 10), now);
+        assert!(cache.insert(a_record("host.local", 1, 0), now));
+        assert!(!cache.insert(a_record("host.local", 1, 0), now));
+        assert!(cached_addresses(&cache, "host.local", now).is_empty());
+        assert_eq!(None, cache.next_deadline());
+    }
+
+    #[test]
+    fn refresh_records_once_per_ttl() {
+        let now = Instant::now();
+        let mut cache = RecordCache::new();
+
+        cache.insert(a_record("host.local", 1, 10), now);
+        assert!(cache.take_refreshes(now).is_empty());
+
+        let refresh = now + Duration::from_secs(8);
+        assert_eq!(
+            vec![a_record("host.local", 1, 10)],
+            cache.take_refreshes(refresh)
+        );
+        assert!(cache.take_refreshes(refresh).is_empty());
+        assert_eq!(Some(now + Duration::from_secs(10)), cache.next_deadline());
+    }
+}
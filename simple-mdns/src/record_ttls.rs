@@ -0,0 +1,35 @@
+/// TTLs used by the records registered for a service instance.
+///
+/// [RFC 6762](https://datatracker.ietf.org/doc/html/rfc6762#section-10) recommends a TTL of 120 seconds for records
+/// containing a host name (A, AAAA and SRV) and 75 minutes for other records (PTR and TXT)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RecordTtls {
+    /// TTL of unique records, like A, AAAA and SRV
+    pub unique: u32,
+    /// TTL of shared records, like PTR and TXT
+    pub shared: u32,
+}
+
+impl RecordTtls {
+    /// Default TTL of unique records, 120 seconds
+    pub const DEFAULT_UNIQUE_TTL: u32 = 120;
+    /// Default TTL of shared records, 75 minutes
+    pub const DEFAULT_SHARED_TTL: u32 = 4500;
+
+    /// Creates a RecordTtls that uses `ttl` for every record
+    pub fn uniform(ttl: u32) -> Self {
+        Self {
+            unique: ttl,
+            shared: ttl,
+        }
+    }
+}
+
+impl Default for RecordTtls {
+    fn default() -> Self {
+        Self {
+            unique: Self::DEFAULT_UNIQUE_TTL,
+            shared: Self::DEFAULT_SHARED_TTL,
+        }
+    }
+}
@@ -0,0 +1,129 @@
+#[cfg(feature = "sync")]
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::{collections::HashMap, net::IpAddr};
+
+use simple_dns::Name;
+#[cfg(feature = "sync")]
+use simple_dns::{rdata::RData, ResourceRecord};
+
+/// A service instance resolved from its SRV, TXT and address records
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedService {
+    /// Target host of the SRV record
+    pub host: Name<'static>,
+    /// Port of the SRV record
+    pub port: u16,
+    /// Addresses of the target host, IP v4 addresses first
+    pub addresses: Vec<IpAddr>,
+    /// Attributes of the TXT records of the instance
+    pub txt_attributes: HashMap<String, Option<String>>,
+}
+
+impl ResolvedService {
+    /// Resolve `instance_name` from `records`, returns `None` when there is no SRV record for the instance.
+    /// Addresses are taken from the A and AAAA records of the SRV target found in `records`
+    #[cfg(feature = "sync")]
+    pub(crate) fn from_records(
+        instance_name: &Name,
+        records: &[ResourceRecord<'static>],
+    ) -> Option<Self> {
+        let srv = records.iter().find_map(|record| match &record.rdata {
+            RData::SRV(srv) if record.name == *instance_name => Some(srv),
+            _ => None,
+        })?;
+
+        let mut addresses: Vec<IpAddr> = Vec::new();
+        let mut txt_attributes = HashMap::new();
+        for record in records {
+            let address = match &record.rdata {
+                RData::A(a) if record.name == srv.target => IpAddr::V4(Ipv4Addr::from(a.address)),
+                RData::AAAA(aaaa) if record.name == srv.target => {
+                    IpAddr::V6(Ipv6Addr::from(aaaa.address))
+                }
+                RData::TXT(txt) if record.name == *instance_name => {
+                    txt_attributes.extend(txt.attributes());
+                    continue;
+                }
+                _ => continue,
+            };
+
+            if !addresses.contains(&address) {
+                addresses.push(address);
+            }
+        }
+        addresses.sort_by_key(|address| address.is_ipv6());
+
+        Some(Self {
+            host: srv.target.clone(),
+            port: srv.port,
+            addresses,
+            txt_attributes,
+        })
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "sync")]
+mod tests {
+    use simple_dns::{
+        rdata::{A, AAAA, SRV, TXT},
+        CLASS,
+    };
+
+    use super::*;
+
+    fn record(name: &'static str, rdata: RData<'static>) -> ResourceRecord<'static> {
+        ResourceRecord::new(Name::new_unchecked(name), CLASS::IN, 10, rdata)
+    }
+
+    #[test]
+    fn resolve_from_srv_txt_and_address_records() {
+        let records = [
+            record(
+                "inst1._http._tcp.local",
+                RData::SRV(SRV {
+                    priority: 0,
+                    weight: 0,
+                    port: 8080,
+                    target: Name::new_unchecked("my-host.local"),
+                }),
+            ),
+            record(
+                "inst1._http._tcp.local",
+                RData::TXT(TXT::new().with_string("path=/api").unwrap()),
+            ),
+            record(
+                "my-host.local",
+                RData::AAAA(AAAA::from(Ipv6Addr::LOCALHOST)),
+            ),
+            record("my-host.local", RData::A(A::from(Ipv4Addr::LOCALHOST))),
+            record(
+                "other-host.local",
+                RData::A(A::from(Ipv4Addr::new(10, 0, 0, 1))),
+            ),
+        ];
+
+        let service =
+            ResolvedService::from_records(&Name::new_unchecked("inst1._http._tcp.local"), &records)
+                .unwrap();
+
+        assert_eq!(Name::new_unchecked("my-host.local"), service.host);
+        assert_eq!(8080, service.port);
+        assert_eq!(
+            vec![
+                IpAddr::V4(Ipv4Addr::LOCALHOST),
+                IpAddr::V6(Ipv6Addr::LOCALHOST)
+            ],
+            service.addresses
+        );
+        assert_eq!(
+            Some(&Some("/api".to_string())),
+            service.txt_attributes.get("path")
+        );
+
+        assert_eq!(
+            None,
+            ResolvedService::from_records(&Name::new_unchecked("inst2._http._tcp.local"), &records)
+        );
+    }
+}
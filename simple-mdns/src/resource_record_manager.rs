@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+#[cfg(any(feature = "sync", feature = "async-tokio"))]
 use std::time::{Duration, Instant};
 
 use radix_trie::{Trie, TrieCommon};
@@ -20,24 +21,18 @@ impl<'a> ResourceRecordManager<'a> {
 
     /// Register a Resource Record
     pub fn add_owned_resource(&mut self, resource: ResourceRecord<'a>) {
-        let key = get_key(&resource.name);
-        match self.resources.get_mut(&key) {
-            Some(resources) => {
-                resources.insert(resource, ResourceRecordType::Owned);
-            }
-            None => {
-                let mut resources = HashMap::new();
-                resources.insert(resource, ResourceRecordType::Owned);
+        self.add_resource(resource, ResourceRecordType::Owned);
+    }
 
-                self.resources.insert(key, resources);
-            }
-        }
+    /// Register a Resource Record to be sent in the authority section of replies,
+    /// like a NSEC record proving the non-existence of record types
+    pub fn add_authority_resource(&mut self, resource: ResourceRecord<'a>) {
+        self.add_resource(resource, ResourceRecordType::Authority);
     }
 
+    #[cfg(any(feature = "sync", feature = "async-tokio"))]
     pub fn add_expirable_resource(&mut self, resource: ResourceRecord<'a>) {
         log::debug!("adding expirable resouce");
-        let key = get_key(&resource.name);
-
         let ttl = if resource.cache_flush {
             1
         } else {
@@ -45,13 +40,18 @@ impl<'a> ResourceRecordManager<'a> {
         };
 
         let exp_info = ExpirationInfo::new(ttl);
+        self.add_resource(resource, ResourceRecordType::Expirable(exp_info));
+    }
+
+    fn add_resource(&mut self, resource: ResourceRecord<'a>, resource_type: ResourceRecordType) {
+        let key = get_key(&resource.name);
         match self.resources.get_mut(&key) {
             Some(resources) => {
-                resources.insert(resource, ResourceRecordType::Expirable(exp_info));
+                resources.insert(resource, resource_type);
             }
             None => {
                 let mut resources = HashMap::new();
-                resources.insert(resource, ResourceRecordType::Expirable(exp_info));
+                resources.insert(resource, resource_type);
 
                 self.resources.insert(key, resources);
             }
@@ -70,6 +70,7 @@ impl<'a> ResourceRecordManager<'a> {
         self.resources = Trie::new();
     }
 
+    #[cfg(any(feature = "sync", feature = "async-tokio"))]
     pub fn get_next_refresh(&self) -> Option<Instant> {
         self.resources
             .iter()
@@ -79,7 +80,7 @@ impl<'a> ResourceRecordManager<'a> {
                         return None;
                     }
                     match resource_type {
-                        ResourceRecordType::Owned => None,
+                        ResourceRecordType::Owned | ResourceRecordType::Authority => None,
                         ResourceRecordType::Expirable(exp_info) => Some(exp_info.refresh_at),
                     }
                 })
@@ -101,7 +102,10 @@ impl<'a> ResourceRecordManager<'a> {
         )|
          -> Option<&ResourceRecord> {
             let (resource, resource_type) = resource_pair;
-            if !include_owned && resource_type.is_owned() || resource_type.is_expired() {
+            if !include_owned && resource_type.is_owned()
+                || resource_type.is_expired()
+                || resource_type.is_authority()
+            {
                 None
             } else {
                 Some(resource)
@@ -133,19 +137,75 @@ impl<'a> ResourceRecordManager<'a> {
             .filter(|resources| !resources.is_empty())
             .map(|inner| inner.into_iter())
     }
+
+    /// Returns the owned resources registered for `name`, without the resources of subdomains
+    pub fn get_owned_resources<'b>(
+        &'a self,
+        name: &'b Name,
+    ) -> impl Iterator<Item = &'a ResourceRecord<'a>> {
+        self.resources
+            .get(&get_key(name))
+            .into_iter()
+            .flat_map(|resources| resources.iter())
+            .filter_map(|(resource, resource_type)| {
+                if resource_type.is_owned() {
+                    Some(resource)
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// Returns every owned resource, for all names
+    pub fn get_all_owned_resources(&self) -> impl Iterator<Item = &ResourceRecord<'a>> {
+        self.resources
+            .values()
+            .flat_map(|resources| resources.iter())
+            .filter_map(|(resource, resource_type)| {
+                if resource_type.is_owned() {
+                    Some(resource)
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// Returns the authority resources registered for `name`
+    pub fn get_authority_resources<'b>(
+        &'a self,
+        name: &'b Name,
+    ) -> impl Iterator<Item = &'a ResourceRecord<'a>> {
+        self.resources
+            .get(&get_key(name))
+            .into_iter()
+            .flat_map(|resources| resources.iter())
+            .filter_map(|(resource, resource_type)| {
+                if resource_type.is_authority() {
+                    Some(resource)
+                } else {
+                    None
+                }
+            })
+    }
 }
 
-fn get_key(name: &Name) -> Vec<u8> {
-    name.get_labels()
-        .iter()
+/// Builds the trie key for `name`, labels are length prefixed, so a key is only a prefix of
+/// another when the name is a parent domain, `_http._tcp.local` must not match `_https._tcp.local`.  
+/// Labels are lowercased, since names are compared ignoring ASCII case
+pub(crate) fn get_key(name: &Name) -> Vec<u8> {
+    name.iter()
         .rev()
-        .flat_map(|label| label.to_string().into_bytes())
+        .flat_map(|label| {
+            std::iter::once(label.len() as u8).chain(label.iter().map(|b| b.to_ascii_lowercase()))
+        })
         .collect()
 }
 
 #[derive(Debug)]
 enum ResourceRecordType {
     Owned,
+    Authority,
+    #[cfg(any(feature = "sync", feature = "async-tokio"))]
     Expirable(ExpirationInfo),
 }
 
@@ -153,28 +213,35 @@ impl ResourceRecordType {
     pub fn is_owned(&self) -> bool {
         matches!(self, &ResourceRecordType::Owned)
     }
+    pub fn is_authority(&self) -> bool {
+        matches!(self, &ResourceRecordType::Authority)
+    }
     pub fn is_expired(&self) -> bool {
         match self {
-            ResourceRecordType::Owned => false,
+            ResourceRecordType::Owned | ResourceRecordType::Authority => false,
+            #[cfg(any(feature = "sync", feature = "async-tokio"))]
             ResourceRecordType::Expirable(exp_info) => exp_info.expire_at < Instant::now(),
         }
     }
 
+    #[cfg(any(feature = "sync", feature = "async-tokio"))]
     pub fn should_refresh(&self) -> bool {
         match self {
-            ResourceRecordType::Owned => false,
+            ResourceRecordType::Owned | ResourceRecordType::Authority => false,
             ResourceRecordType::Expirable(exp_info) => exp_info.refresh_at < Instant::now(),
         }
     }
 }
 
 /// Provides known service expiration and refresh times
+#[cfg(any(feature = "sync", feature = "async-tokio"))]
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 struct ExpirationInfo {
     refresh_at: Instant,
     expire_at: Instant,
 }
 
+#[cfg(any(feature = "sync", feature = "async-tokio"))]
 impl ExpirationInfo {
     pub fn new(ttl: u32) -> Self {
         let ttl = ttl as u64;
@@ -274,5 +341,34 @@ mod tests {
 
         let records = get_records("v._tcp.local", true);
         assert_eq!(0, records.len());
+
+        let records = get_records("A._SRV._tcp.Local", false);
+        assert_eq!(1, records.len());
+        compare_ips(records[0][0], "127.0.0.1");
+    }
+
+    #[test]
+    pub fn test_get_resources_with_binary_labels() {
+        let mut name = Name::new_unchecked("instance");
+        name.append_label(&[0xff, b'X']).unwrap();
+        name.append_label(b"local").unwrap();
+
+        let mut resources = ResourceRecordManager::new();
+        resources.add_owned_resource(ResourceRecord::new(
+            name,
+            simple_dns::CLASS::IN,
+            0,
+            RData::A(A::from(Ipv4Addr::LOCALHOST)),
+        ));
+
+        let query = Name::parse_normalized(b"\x08instance\x02\xffx\x05local\x00", 0).unwrap();
+        assert_eq!(1, resources.get_owned_resources(&query).count());
+        assert_eq!(
+            1,
+            resources.get_domain_resources(&query, false, true).count()
+        );
+
+        let other = Name::parse_normalized(b"\x08instance\x02\xfex\x05local\x00", 0).unwrap();
+        assert_eq!(0, resources.get_owned_resources(&other).count());
     }
 }
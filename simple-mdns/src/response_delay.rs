@@ -0,0 +1,146 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    net::SocketAddr,
+    time::{Duration, Instant, SystemTime},
+};
+
+use simple_dns::Packet;
+
+/// Random delay applied to multicast replies, so responders answering the same query don't reply at the same time,
+/// [RFC 6762 section 6](https://datatracker.ietf.org/doc/html/rfc6762#section-6).
+/// Unicast replies are never delayed, only the answers to questions requesting a multicast response are
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ResponseDelay {
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl Default for ResponseDelay {
+    fn default() -> Self {
+        Self {
+            min: Duration::from_millis(20),
+            max: Duration::from_millis(120),
+        }
+    }
+}
+
+impl ResponseDelay {
+    /// Returns how long a multicast reply must be delayed, a random duration between `min` and `max`
+    pub fn delay(&self) -> Duration {
+        if self.max.is_zero() {
+            return Duration::ZERO;
+        }
+
+        let range = self.max.saturating_sub(self.min).as_nanos() as u64;
+        if range == 0 {
+            return self.min;
+        }
+
+        let mut hasher = RandomState::new().build_hasher();
+        if let Ok(now) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+            hasher.write_u128(now.as_nanos());
+        }
+        self.min + Duration::from_nanos(hasher.finish() % (range + 1))
+    }
+}
+
+/// A multicast reply waiting for its delay to elapse, queries received meanwhile are answered by the same reply
+#[derive(Debug)]
+pub(crate) struct PendingReply {
+    pub send_at: Instant,
+    pub reply_addr: SocketAddr,
+    query: Packet<'static>,
+}
+
+impl PendingReply {
+    pub fn new(query: Packet<'_>, send_at: Instant, reply_addr: SocketAddr) -> Self {
+        Self {
+            send_at,
+            reply_addr,
+            query: query.into_owned(),
+        }
+    }
+
+    /// Add the questions of `query` to the pending reply.
+    /// Known answers are kept only when listed by every query, since they don't apply to the other queriers
+    pub fn merge(&mut self, query: Packet<'_>) {
+        for question in query.questions {
+            if !self.query.questions.contains(&question) {
+                self.query.questions.push(question.into_owned());
+            }
+        }
+
+        self.query
+            .answers
+            .retain(|known| query.answers.contains(known));
+    }
+
+    /// Returns the query with the questions of every merged query
+    pub fn into_query(self) -> Packet<'static> {
+        self.query
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use simple_dns::{
+        rdata::{RData, A},
+        Name, Question, ResourceRecord, CLASS, TYPE,
+    };
+
+    use super::*;
+
+    #[test]
+    fn delay_within_range() {
+        let response_delay = ResponseDelay::default();
+        for _ in 0..100 {
+            let delay = response_delay.delay();
+            assert!(delay >= Duration::from_millis(20));
+            assert!(delay <= Duration::from_millis(120));
+        }
+
+        let disabled = ResponseDelay {
+            min: Duration::ZERO,
+            max: Duration::ZERO,
+        };
+        assert_eq!(Duration::ZERO, disabled.delay());
+    }
+
+    #[test]
+    fn merge_questions_and_common_known_answers() {
+        let question = |name: &'static str| {
+            Question::new(
+                Name::new_unchecked(name),
+                TYPE::A.into(),
+                CLASS::IN.into(),
+                false,
+            )
+        };
+        let known = |address: u32| {
+            ResourceRecord::new(
+                Name::new_unchecked("a.local"),
+                CLASS::IN,
+                10,
+                RData::A(A { address }),
+            )
+        };
+
+        let mut first = Packet::new_query(0).with_questions([question("a.local")]);
+        first.answers = vec![known(1), known(2)];
+        let mut second =
+            Packet::new_query(0).with_questions([question("a.local"), question("b.local")]);
+        second.answers = vec![known(2)];
+
+        let mut pending =
+            PendingReply::new(first, Instant::now(), "224.0.0.251:5353".parse().unwrap());
+        pending.merge(second);
+
+        let query = pending.into_query();
+        assert_eq!(
+            vec![question("a.local"), question("b.local")],
+            query.questions
+        );
+        assert_eq!(vec![known(2)], query.answers);
+    }
+}
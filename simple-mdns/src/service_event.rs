@@ -0,0 +1,10 @@
+use crate::ServiceInstanceName;
+
+/// Changes of the service instances available in the network, reported while browsing a service type
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ServiceEvent {
+    /// A new service instance was announced or answered a query
+    Added(ServiceInstanceName),
+    /// A service instance sent a goodbye packet (TTL 0), or its records expired without being refreshed
+    Removed(ServiceInstanceName),
+}
@@ -0,0 +1,146 @@
+use std::fmt::Display;
+
+use simple_dns::{rdata::RData, Name, ResourceRecord};
+
+/// A DNS-SD service instance name split into its instance and service parts,
+/// [RFC 6763 section 4.1](https://datatracker.ietf.org/doc/html/rfc6763#section-4.1)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ServiceInstanceName {
+    /// The instance label, like `my instance`
+    pub instance: String,
+    /// The service name, including the protocol and domain, like `_http._tcp.local`
+    pub service: String,
+}
+
+impl ServiceInstanceName {
+    /// Splits `name` in the form `<instance>.<_service>.<_tcp|_udp>.<domain>`.
+    /// Returns `None` when `name` is not a service instance name, like host names `my-host.local`
+    pub fn from_name(name: &Name) -> Option<Self> {
+        if name.labels_count() < 4 {
+            return None;
+        }
+
+        let mut labels = name.iter();
+        let instance = labels.next()?;
+        let service = labels.next()?;
+        let protocol = labels.next()?;
+
+        if !service.starts_with(b"_")
+            || !(protocol.eq_ignore_ascii_case(b"_tcp") || protocol.eq_ignore_ascii_case(b"_udp"))
+        {
+            return None;
+        }
+
+        let service_name = std::iter::once(service)
+            .chain(std::iter::once(protocol))
+            .chain(labels)
+            .map(String::from_utf8_lossy)
+            .collect::<Vec<_>>()
+            .join(".");
+
+        Some(Self {
+            instance: String::from_utf8_lossy(instance).into_owned(),
+            service: service_name,
+        })
+    }
+}
+
+impl Display for ServiceInstanceName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.instance, self.service)
+    }
+}
+
+/// Returns the service instances found in `records`, from the targets of PTR records and the names of SRV records.
+/// Records that are not part of a service instance, like host address records, are skipped
+pub fn service_instances<'a, 'b: 'a>(
+    records: impl IntoIterator<Item = &'a ResourceRecord<'b>>,
+) -> Vec<ServiceInstanceName> {
+    let mut instances = Vec::new();
+
+    for record in records {
+        let name = match &record.rdata {
+            RData::PTR(ptr) => &ptr.0,
+            RData::SRV(_) => &record.name,
+            _ => continue,
+        };
+
+        if let Some(instance) = ServiceInstanceName::from_name(name) {
+            if !instances.contains(&instance) {
+                instances.push(instance);
+            }
+        }
+    }
+
+    instances
+}
+
+#[cfg(test)]
+mod tests {
+    use simple_dns::{
+        rdata::{A, PTR},
+        CLASS,
+    };
+
+    use crate::conversion_utils::port_to_srv_record;
+
+    use super::*;
+
+    #[test]
+    fn split_service_instance_names() {
+        let instance =
+            ServiceInstanceName::from_name(&Name::new_unchecked("inst1._http._TCP.local")).unwrap();
+        assert_eq!("inst1", instance.instance);
+        assert_eq!("_http._TCP.local", instance.service);
+        assert_eq!("inst1._http._TCP.local", instance.to_string());
+
+        for name in [
+            "my-host.local",
+            "_http._tcp.local",
+            "inst1.http._tcp.local",
+            "inst1._http.tcp.local",
+            "a.b.c.d.local",
+        ] {
+            assert_eq!(
+                None,
+                ServiceInstanceName::from_name(&Name::new_unchecked(name))
+            );
+        }
+    }
+
+    #[test]
+    fn enumerate_only_service_instances() {
+        let host = Name::new_unchecked("my-host.local");
+        let inst1 = Name::new_unchecked("inst1._http._tcp.local");
+        let inst2 = Name::new_unchecked("inst2._ipp._udp.local");
+
+        let records = [
+            ResourceRecord::new(host.clone(), CLASS::IN, 0, RData::A(A { address: 1 })),
+            ResourceRecord::new(
+                Name::new_unchecked("_http._tcp.local"),
+                CLASS::IN,
+                0,
+                RData::PTR(PTR(inst1.clone())),
+            ),
+            ResourceRecord::new(
+                Name::new_unchecked("1.0.0.127.in-addr.arpa"),
+                CLASS::IN,
+                0,
+                RData::PTR(PTR(host.clone())),
+            ),
+            port_to_srv_record(&inst1, 8080, 0),
+            port_to_srv_record(&host, 22, 0),
+            port_to_srv_record(&inst2, 631, 0),
+        ];
+
+        let instances: Vec<String> = service_instances(records.iter())
+            .into_iter()
+            .map(|instance| instance.to_string())
+            .collect();
+
+        assert_eq!(
+            vec!["inst1._http._tcp.local", "inst2._ipp._udp.local"],
+            instances
+        );
+    }
+}
@@ -11,6 +11,8 @@ pub enum SimpleMdnsError {
     DnsParsing(SimpleDnsError),
     /// Service discovery is no longer running
     ServiceDiscoveryStopped,
+    /// Probing found another host using the name
+    NameConflict(String),
 }
 
 impl Error for SimpleMdnsError {}
@@ -27,6 +29,9 @@ impl Display for SimpleMdnsError {
             SimpleMdnsError::ServiceDiscoveryStopped => {
                 write!(f, "Service discovery is no longer running")
             }
+            SimpleMdnsError::NameConflict(name) => {
+                write!(f, "Name {} is already in use by another host", name)
+            }
         }
     }
 }
@@ -1,42 +1,249 @@
 use std::{
     io,
-    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket},
+    net::{Ipv4Addr, SocketAddr, UdpSocket},
+};
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+use std::{
+    net::{IpAddr, Ipv6Addr},
     time::Duration,
 };
 
-use socket2::{Domain, Protocol, SockAddr, Socket, Type};
+use socket2::SockAddr;
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+use socket2::{Domain, Protocol, Socket, Type};
 
-use crate::{
-    network_scope::{MULTICAST_ADDR_IPV4, MULTICAST_ADDR_IPV6, MULTICAST_PORT},
-    NetworkScope,
-};
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+use crate::network_scope::{MULTICAST_ADDR_IPV4, MULTICAST_ADDR_IPV6, MULTICAST_PORT};
+use crate::NetworkScope;
+
+/// Creates the socket used to send multicast packets, packets leave through the interface of `network_scope`,
+/// when one is provided
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+pub fn sender_socket(network_scope: NetworkScope) -> io::Result<UdpSocket> {
+    let socket = match network_scope {
+        NetworkScope::V4 | NetworkScope::V4WithInterface(_) => {
+            let socket = create_socket(Domain::IPV4)?;
+            if let NetworkScope::V4WithInterface(interface) = network_scope {
+                socket.set_multicast_if_v4(&interface)?;
+            }
+
+            socket.bind(&SockAddr::from(SocketAddr::new(
+                Ipv4Addr::UNSPECIFIED.into(),
+                0,
+            )))?;
+            socket
+        }
+        NetworkScope::V6 | NetworkScope::V6WithInterface(_) => {
+            let socket = create_socket(Domain::IPV6)?;
+            if let NetworkScope::V6WithInterface(interface) = network_scope {
+                socket.set_multicast_if_v6(interface)?;
+            }
+
+            socket.bind(&SockAddr::from(SocketAddr::new(
+                Ipv6Addr::UNSPECIFIED.into(),
+                0,
+            )))?;
+            socket
+        }
+    };
+
+    Ok(socket.into())
+}
+
+/// Information about a datagram received by [`recv_message`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReceivedMessage {
+    /// Number of bytes received
+    pub len: usize,
+    /// Address of the sender
+    pub origin: SocketAddr,
+    /// Index of the network interface the datagram arrived on.
+    /// Only available on Linux and Android, for sockets with `IP_PKTINFO` or `IPV6_RECVPKTINFO` enabled,
+    /// like the multicast sockets used by this crate
+    pub interface_index: Option<u32>,
+}
+
+/// Receives a single datagram from `socket` into `buf`, along with the interface it arrived on, when available
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn recv_message(socket: &UdpSocket, buf: &mut [u8]) -> io::Result<ReceivedMessage> {
+    use std::os::unix::io::AsRawFd;
+
+    // large enough for a in_pktinfo or in6_pktinfo control message, u64 keeps it aligned for cmsghdr
+    let mut control = [0u64; 8];
+    let mut iov = libc::iovec {
+        iov_base: buf.as_mut_ptr().cast(),
+        iov_len: buf.len(),
+    };
+
+    // Safety: the message header only points to buffers that outlive the recvmsg call and the returned
+    // control messages are read within the bounds reported by the kernel
+    let ((len, interface_index), origin) = unsafe {
+        SockAddr::try_init(|storage, storage_len| {
+            let mut msg: libc::msghdr = std::mem::zeroed();
+            msg.msg_name = storage.cast();
+            msg.msg_namelen = *storage_len;
+            msg.msg_iov = &mut iov;
+            msg.msg_iovlen = 1;
+            msg.msg_control = control.as_mut_ptr().cast();
+            msg.msg_controllen = std::mem::size_of_val(&control) as _;
+
+            let len = libc::recvmsg(socket.as_raw_fd(), &mut msg, 0);
+            if len < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            *storage_len = msg.msg_namelen;
 
-pub fn sender_socket(ipv4: bool) -> io::Result<UdpSocket> {
-    if ipv4 {
-        let socket = create_socket(Domain::IPV4)?;
-        socket.bind(&SockAddr::from(SocketAddr::new(
-            Ipv4Addr::UNSPECIFIED.into(),
-            0,
-        )))?;
+            let mut interface_index = None;
+            let mut cmsg = libc::CMSG_FIRSTHDR(&msg);
+            while !cmsg.is_null() {
+                match ((*cmsg).cmsg_level, (*cmsg).cmsg_type) {
+                    (libc::IPPROTO_IP, libc::IP_PKTINFO) => {
+                        let info = std::ptr::read_unaligned(
+                            libc::CMSG_DATA(cmsg) as *const libc::in_pktinfo
+                        );
+                        interface_index = Some(info.ipi_ifindex as u32);
+                    }
+                    (libc::IPPROTO_IPV6, libc::IPV6_PKTINFO) => {
+                        let info = std::ptr::read_unaligned(
+                            libc::CMSG_DATA(cmsg) as *const libc::in6_pktinfo
+                        );
+                        interface_index = Some(info.ipi6_ifindex);
+                    }
+                    _ => {}
+                }
+                cmsg = libc::CMSG_NXTHDR(&msg, cmsg);
+            }
 
-        Ok(socket.into())
+            Ok((len as usize, interface_index))
+        })?
+    };
+
+    Ok(ReceivedMessage {
+        len,
+        origin: origin
+            .as_socket()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid origin address"))?,
+        interface_index,
+    })
+}
+
+/// Receives a single datagram from `socket` into `buf`, the interface is not reported on this platform
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn recv_message(socket: &UdpSocket, buf: &mut [u8]) -> io::Result<ReceivedMessage> {
+    let (len, origin) = socket.recv_from(buf)?;
+    Ok(ReceivedMessage {
+        len,
+        origin,
+        interface_index: None,
+    })
+}
+
+/// Enables the control messages used by [`recv_message`] to report the receiving interface
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+fn enable_packet_info(socket: &Socket, ipv4: bool) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let (level, name) = if ipv4 {
+        (libc::IPPROTO_IP, libc::IP_PKTINFO)
     } else {
-        let socket = create_socket(Domain::IPV6)?;
-        socket.bind(&SockAddr::from(SocketAddr::new(
-            Ipv6Addr::UNSPECIFIED.into(),
-            0,
-        )))?;
+        (libc::IPPROTO_IPV6, libc::IPV6_RECVPKTINFO)
+    };
+
+    let enable: libc::c_int = 1;
+    // Safety: the option value is a valid c_int for the duration of the call
+    let result = unsafe {
+        libc::setsockopt(
+            socket.as_raw_fd(),
+            level,
+            name,
+            (&enable as *const libc::c_int).cast(),
+            std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+        )
+    };
 
-        Ok(socket.into())
+    if result < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
     }
 }
 
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+fn enable_packet_info(_socket: &Socket, _ipv4: bool) -> io::Result<()> {
+    Ok(())
+}
+
+/// Returns a scope for every network interface that is up, supports multicast and is not a loopback interface.
+/// IPV4 scopes come first, each IPV4 address of an interface results in its own scope
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn interface_scopes() -> io::Result<Vec<NetworkScope>> {
+    let mut interfaces: *mut libc::ifaddrs = std::ptr::null_mut();
+    // Safety: getifaddrs initializes the list on success, which is released by freeifaddrs below
+    if unsafe { libc::getifaddrs(&mut interfaces) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut v4_scopes = Vec::new();
+    let mut v6_scopes = Vec::new();
+    let required_flags = (libc::IFF_UP | libc::IFF_MULTICAST) as libc::c_uint;
+
+    let mut current = interfaces;
+    // Safety: every entry of the list, and the addresses they point to, are valid until freeifaddrs is called
+    unsafe {
+        while !current.is_null() {
+            let interface = &*current;
+            current = interface.ifa_next;
+
+            if interface.ifa_addr.is_null()
+                || interface.ifa_flags & required_flags != required_flags
+                || interface.ifa_flags & libc::IFF_LOOPBACK as libc::c_uint != 0
+            {
+                continue;
+            }
+
+            match (*interface.ifa_addr).sa_family as libc::c_int {
+                libc::AF_INET => {
+                    let addr = &*(interface.ifa_addr as *const libc::sockaddr_in);
+                    v4_scopes.push(NetworkScope::V4WithInterface(Ipv4Addr::from(u32::from_be(
+                        addr.sin_addr.s_addr,
+                    ))));
+                }
+                libc::AF_INET6 => {
+                    let scope = match libc::if_nametoindex(interface.ifa_name) {
+                        0 => continue,
+                        index => NetworkScope::V6WithInterface(index),
+                    };
+                    if !v6_scopes.contains(&scope) {
+                        v6_scopes.push(scope);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        libc::freeifaddrs(interfaces);
+    }
+
+    v4_scopes.extend(v6_scopes);
+    Ok(v4_scopes)
+}
+
+/// Interfaces are not enumerated on this platform, the UNSPECIFIED IPV4 and IPV6 scopes are returned instead
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn interface_scopes() -> io::Result<Vec<NetworkScope>> {
+    Ok(vec![NetworkScope::V4, NetworkScope::V6])
+}
+
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
 pub fn join_multicast(network_scope: NetworkScope) -> io::Result<UdpSocket> {
     // depending on the IP protocol we have slightly different work
     match network_scope {
         NetworkScope::V4 => {
             let socket = create_socket(Domain::IPV4)?;
             socket.join_multicast_v4(&MULTICAST_ADDR_IPV4, &Ipv4Addr::UNSPECIFIED)?;
+            enable_packet_info(&socket, true)?;
 
             bind_multicast(socket, &MULTICAST_ADDR_IPV4.into(), MULTICAST_PORT)
                 .map(|socket| socket.into())
@@ -44,6 +251,10 @@ pub fn join_multicast(network_scope: NetworkScope) -> io::Result<UdpSocket> {
         NetworkScope::V4WithInterface(ref interface) => {
             let socket = create_socket(Domain::IPV4)?;
             socket.join_multicast_v4(&MULTICAST_ADDR_IPV4, interface)?;
+            // only receive the queries that arrive on the interface, other listeners answer the other interfaces
+            #[cfg(target_os = "linux")]
+            socket.set_multicast_all_v4(false)?;
+            enable_packet_info(&socket, true)?;
 
             bind_multicast(socket, &MULTICAST_ADDR_IPV4.into(), MULTICAST_PORT)
                 .map(|socket| socket.into())
@@ -52,6 +263,7 @@ pub fn join_multicast(network_scope: NetworkScope) -> io::Result<UdpSocket> {
             let socket = create_socket(Domain::IPV6)?;
             socket.join_multicast_v6(&MULTICAST_ADDR_IPV6, 0)?;
             socket.set_only_v6(true)?;
+            enable_packet_info(&socket, false)?;
 
             bind_multicast(socket, &IpAddr::V6(MULTICAST_ADDR_IPV6), MULTICAST_PORT)
                 .map(|socket| socket.into())
@@ -59,7 +271,10 @@ pub fn join_multicast(network_scope: NetworkScope) -> io::Result<UdpSocket> {
         NetworkScope::V6WithInterface(interface) => {
             let socket = create_socket(Domain::IPV6)?;
             socket.join_multicast_v6(&MULTICAST_ADDR_IPV6, interface)?;
+            #[cfg(target_os = "linux")]
+            socket.set_multicast_all_v6(false)?;
             socket.set_only_v6(true)?;
+            enable_packet_info(&socket, false)?;
 
             bind_multicast(socket, &IpAddr::V6(MULTICAST_ADDR_IPV6), MULTICAST_PORT)
                 .map(|socket| socket.into())
@@ -67,12 +282,64 @@ pub fn join_multicast(network_scope: NetworkScope) -> io::Result<UdpSocket> {
     }
 }
 
-#[cfg(feature = "async-tokio")]
-pub fn nonblocking(socket: UdpSocket) -> io::Result<tokio::net::UdpSocket> {
-    socket.set_nonblocking(true)?;
-    tokio::net::UdpSocket::from_std(socket)
+/// Waits for at most `timeout` until one of `sockets` has a datagram to receive, returns the index of the first
+/// readable socket. Returns `None` when `timeout` elapses or the wait is interrupted by a signal
+#[cfg(unix)]
+#[cfg(feature = "sync")]
+pub fn wait_readable(sockets: &[&UdpSocket], timeout: Duration) -> io::Result<Option<usize>> {
+    use std::os::unix::io::AsRawFd;
+
+    let mut fds: Vec<libc::pollfd> = sockets
+        .iter()
+        .map(|socket| libc::pollfd {
+            fd: socket.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        })
+        .collect();
+    // rounded up, a timeout under one millisecond would return immediately and spin
+    let timeout = timeout
+        .as_nanos()
+        .div_ceil(1_000_000)
+        .min(libc::c_int::MAX as u128) as libc::c_int;
+
+    // Safety: fds holds fds.len() initialized pollfd structs for the duration of the call
+    let result = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout) };
+    if result < 0 {
+        let err = io::Error::last_os_error();
+        return match err.kind() {
+            io::ErrorKind::Interrupted => Ok(None),
+            _ => Err(err),
+        };
+    }
+
+    Ok(fds.iter().position(|fd| fd.revents != 0))
+}
+
+/// Waits for at most `timeout` until one of `sockets` has a datagram to receive, returns the index of the first
+/// readable socket. Without `poll` on this platform, each socket is waited on in turn for a short time
+#[cfg(not(unix))]
+#[cfg(feature = "sync")]
+pub fn wait_readable(sockets: &[&UdpSocket], timeout: Duration) -> io::Result<Option<usize>> {
+    let wait = (timeout / sockets.len().max(1) as u32)
+        .clamp(Duration::from_millis(1), Duration::from_millis(10));
+    for (index, socket) in sockets.iter().enumerate() {
+        socket.set_read_timeout(Some(wait))?;
+        match socket.peek(&mut [0u8; 1]) {
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) => {}
+            // a datagram larger than the buffer is reported as an error, but it is still there to be received
+            _ => return Ok(Some(index)),
+        }
+    }
+
+    Ok(None)
 }
 
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
 fn create_socket(domain: Domain) -> io::Result<Socket> {
     let socket = Socket::new(domain, Type::DGRAM, Some(Protocol::UDP))?;
     socket.set_read_timeout(Some(Duration::from_millis(100)))?;
@@ -85,6 +352,7 @@ fn create_socket(domain: Domain) -> io::Result<Socket> {
 }
 
 #[cfg(unix)]
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
 fn bind_multicast(socket: Socket, address: &IpAddr, port: u16) -> io::Result<Socket> {
     // FIXME: this should not be necessary, why is it not possible to bind on the address for ipv6?
     let addr = match address {
@@ -98,6 +366,7 @@ fn bind_multicast(socket: Socket, address: &IpAddr, port: u16) -> io::Result<Soc
 }
 
 #[cfg(windows)]
+#[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
 fn bind_multicast(socket: Socket, address: &IpAddr, port: u16) -> io::Result<Socket> {
     let addr = match address {
         IpAddr::V4(_) => SockAddr::from(SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), port)),
@@ -114,8 +383,103 @@ mod tests {
 
     #[test]
     #[cfg(not(target_os = "macos"))]
+    #[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
     pub fn test_can_bind_multicast() {
         join_multicast(NetworkScope::V4).expect("Failed to join IPV4 multicast");
         join_multicast(NetworkScope::V6).expect("Failed to join IPV6 multicast");
     }
+
+    #[test]
+    #[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+    pub fn test_sender_socket_uses_scope_interface() {
+        let socket = sender_socket(NetworkScope::V4WithInterface(Ipv4Addr::LOCALHOST)).unwrap();
+        assert_eq!(
+            Ipv4Addr::LOCALHOST,
+            socket2::SockRef::from(&socket).multicast_if_v4().unwrap()
+        );
+
+        let socket = sender_socket(NetworkScope::V4).unwrap();
+        assert_eq!(
+            Ipv4Addr::UNSPECIFIED,
+            socket2::SockRef::from(&socket).multicast_if_v4().unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+    pub fn test_sender_socket_uses_scope_interface_v6() {
+        // the loopback interface
+        let socket = sender_socket(NetworkScope::V6WithInterface(1)).unwrap();
+        assert_eq!(
+            1,
+            socket2::SockRef::from(&socket).multicast_if_v6().unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn test_interface_scopes_skip_loopback() {
+        let scopes = interface_scopes().unwrap();
+        assert!(!scopes.contains(&NetworkScope::V4WithInterface(Ipv4Addr::LOCALHOST)));
+        // the loopback interface
+        assert!(!scopes.contains(&NetworkScope::V6WithInterface(1)));
+
+        let v6_position = scopes.iter().position(|scope| !scope.is_v4());
+        assert!(scopes
+            .iter()
+            .skip(v6_position.unwrap_or(scopes.len()))
+            .all(|scope| !scope.is_v4()));
+    }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[cfg(any(feature = "sync", feature = "async-tokio", feature = "async-std"))]
+    pub fn test_recv_message_reports_interface() {
+        let socket = create_socket(Domain::IPV4).unwrap();
+        enable_packet_info(&socket, true).unwrap();
+        socket
+            .bind(&SockAddr::from(SocketAddr::new(
+                Ipv4Addr::LOCALHOST.into(),
+                0,
+            )))
+            .unwrap();
+        let socket: UdpSocket = socket.into();
+
+        let sender = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        sender
+            .send_to(b"hello", socket.local_addr().unwrap())
+            .unwrap();
+
+        let mut buf = [0u8; 16];
+        let message = recv_message(&socket, &mut buf).unwrap();
+
+        assert_eq!(5, message.len);
+        assert_eq!(b"hello", &buf[..message.len]);
+        assert_eq!(sender.local_addr().unwrap(), message.origin);
+        assert!(matches!(message.interface_index, Some(index) if index > 0));
+    }
+
+    #[test]
+    #[cfg(feature = "sync")]
+    pub fn test_wait_readable_reports_the_ready_socket() {
+        let first = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let second = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let sockets = [&first, &second];
+
+        assert_eq!(
+            None,
+            wait_readable(&sockets, Duration::from_millis(20)).unwrap()
+        );
+
+        let sender = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        sender
+            .send_to(b"hello", second.local_addr().unwrap())
+            .unwrap();
+
+        assert_eq!(
+            Some(1),
+            wait_readable(&sockets, Duration::from_secs(1)).unwrap()
+        );
+    }
 }
@@ -0,0 +1,55 @@
+use std::net::{SocketAddr, UdpSocket};
+
+/// A datagram socket that can be used by [`SimpleMdnsResponder::listen_on_socket`](super::SimpleMdnsResponder::listen_on_socket)
+/// to receive queries and send replies
+pub trait DatagramSocket {
+    /// Address type of the peers of this socket
+    type Addr;
+
+    /// Receives a single datagram, returning the number of bytes read and the origin address
+    fn recv_from(&self, buf: &mut [u8]) -> std::io::Result<(usize, Self::Addr)>;
+
+    /// Sends a single datagram to `addr`
+    fn send_to(&self, buf: &[u8], addr: &Self::Addr) -> std::io::Result<usize>;
+
+    /// Returns `addr` as an IP socket address, used to report the origin of queries to
+    /// [`SimpleMdnsResponder::on_query`](super::SimpleMdnsResponder::on_query).
+    /// The default implementation returns `None`, which skips the callback
+    fn socket_addr(_addr: &Self::Addr) -> Option<SocketAddr> {
+        None
+    }
+}
+
+impl DatagramSocket for UdpSocket {
+    type Addr = SocketAddr;
+
+    fn recv_from(&self, buf: &mut [u8]) -> std::io::Result<(usize, Self::Addr)> {
+        UdpSocket::recv_from(self, buf)
+    }
+
+    fn send_to(&self, buf: &[u8], addr: &Self::Addr) -> std::io::Result<usize> {
+        UdpSocket::send_to(self, buf, addr)
+    }
+
+    fn socket_addr(addr: &Self::Addr) -> Option<SocketAddr> {
+        Some(*addr)
+    }
+}
+
+#[cfg(unix)]
+impl DatagramSocket for std::os::unix::net::UnixDatagram {
+    type Addr = std::os::unix::net::SocketAddr;
+
+    fn recv_from(&self, buf: &mut [u8]) -> std::io::Result<(usize, Self::Addr)> {
+        std::os::unix::net::UnixDatagram::recv_from(self, buf)
+    }
+
+    /// Sends to the path of `addr`, unnamed addresses (like the ones from [`UnixDatagram::pair`](std::os::unix::net::UnixDatagram::pair))
+    /// are sent to the connected peer
+    fn send_to(&self, buf: &[u8], addr: &Self::Addr) -> std::io::Result<usize> {
+        match addr.as_pathname() {
+            Some(path) => std::os::unix::net::UnixDatagram::send_to(self, buf, path),
+            None => self.send(buf),
+        }
+    }
+}
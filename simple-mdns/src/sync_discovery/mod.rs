@@ -1,9 +1,11 @@
 //! Contains the sync (blocking) version of service discovery
 
+mod datagram_socket;
 mod oneshot_resolver;
 mod service_discovery;
 mod simple_responder;
 
+pub use datagram_socket::DatagramSocket;
 pub use oneshot_resolver::OneShotMdnsResolver;
 pub use service_discovery::ServiceDiscovery;
 pub use simple_responder::SimpleMdnsResponder;
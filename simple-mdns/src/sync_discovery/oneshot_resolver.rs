@@ -1,10 +1,13 @@
 use crate::{
-    socket_helper::{join_multicast, sender_socket},
-    NetworkScope, SimpleMdnsError, UNICAST_RESPONSE,
+    is_query_response, received_datagram,
+    socket_helper::{join_multicast, sender_socket, wait_readable},
+    NetworkScope, ReachabilityFilter, ResolvedService, SimpleMdnsError, RECV_BUFFER_SIZE,
+    SERVICE_TYPE_ENUMERATION_NAME, UNICAST_RESPONSE,
 };
-use simple_dns::{header_buffer, rdata::RData, Name, Packet, Question, CLASS, TYPE};
+use simple_dns::{rdata::RData, Name, Packet, Question, ResourceRecord, CLASS, TYPE};
 
 use std::{
+    collections::HashSet,
     net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket},
     time::{Duration, Instant},
 };
@@ -30,13 +33,14 @@ use std::{
 ///     println!("{:?}", answer);
 ///     // SocketAddr, "127.0.0.1:8080", with a ipv4 or ipv6
 /// ```
-#[derive(Debug)]
 pub struct OneShotMdnsResolver {
     query_timeout: Duration,
     unicast_response: bool,
     receiver_socket: UdpSocket,
     sender_socket: UdpSocket,
     network_scope: NetworkScope,
+    reachability_filter: Option<ReachabilityFilter>,
+    strict_id_matching: bool,
 }
 
 impl OneShotMdnsResolver {
@@ -47,12 +51,17 @@ impl OneShotMdnsResolver {
 
     /// Creates a new OneShotMdnsResolver with the specified scope
     pub fn new_with_scope(network_scope: NetworkScope) -> Result<Self, SimpleMdnsError> {
+        let sender_socket = sender_socket(network_scope)?;
+        let receiver_socket = join_multicast(network_scope)?;
+
         Ok(Self {
             query_timeout: Duration::from_secs(3),
             unicast_response: UNICAST_RESPONSE,
-            sender_socket: sender_socket(network_scope.is_v4())?,
+            sender_socket,
             network_scope,
-            receiver_socket: join_multicast(network_scope)?,
+            reachability_filter: None,
+            strict_id_matching: false,
+            receiver_socket,
         })
     }
 
@@ -71,7 +80,7 @@ impl OneShotMdnsResolver {
         &self,
         service_name: &str,
     ) -> Result<Option<std::net::IpAddr>, SimpleMdnsError> {
-        let mut packet = Packet::new_query(0);
+        let mut packet = Packet::new_query_with_random_id();
         let service_name = Name::new(service_name)?;
         packet.questions.push(Question::new(
             service_name.clone(),
@@ -109,11 +118,15 @@ impl OneShotMdnsResolver {
                     continue;
                 }
 
-                return match anwser.rdata {
-                    RData::A(a) => Ok(Some(IpAddr::V4(Ipv4Addr::from(a.address)))),
-                    RData::AAAA(aaaa) => Ok(Some(IpAddr::V6(Ipv6Addr::from(aaaa.address)))),
-                    _ => Ok(None),
+                let address = match anwser.rdata {
+                    RData::A(a) => IpAddr::V4(Ipv4Addr::from(a.address)),
+                    RData::AAAA(aaaa) => IpAddr::V6(Ipv6Addr::from(aaaa.address)),
+                    _ => return Ok(None),
                 };
+
+                if self.is_reachable(&address) {
+                    return Ok(Some(address));
+                }
             }
         }
 
@@ -125,7 +138,7 @@ impl OneShotMdnsResolver {
         &self,
         service_name: &str,
     ) -> Result<Option<std::net::SocketAddr>, SimpleMdnsError> {
-        let mut packet = Packet::new_query(0);
+        let mut packet = Packet::new_query_with_random_id();
         let parsed_name_service = Name::new(service_name)?;
         packet.questions.push(Question::new(
             parsed_name_service.clone(),
@@ -171,11 +184,12 @@ impl OneShotMdnsResolver {
                 .additional_records
                 .iter()
                 .filter(|a| a.name == parsed_name_service && a.match_qtype(TYPE::A.into()))
-                .find_map(|a| match &a.rdata {
+                .filter_map(|a| match &a.rdata {
                     RData::A(a) => Some(IpAddr::V4(Ipv4Addr::from(a.address))),
                     RData::AAAA(aaaa) => Some(IpAddr::V6(Ipv6Addr::from(aaaa.address))),
                     _ => None,
-                });
+                })
+                .find(|address| self.is_reachable(address));
 
             if port.is_some() && address.is_none() {
                 address = self.query_service_address(service_name)?;
@@ -189,6 +203,172 @@ impl OneShotMdnsResolver {
         Ok(None)
     }
 
+    /// Send a query for `qtype` resources of `service_name` and collect the responses until the query timeout elapses.
+    ///
+    /// Returns the answers and additional records of every response received, records sent by more than one responder
+    /// (or sent more than once) are returned only once
+    pub fn query_service_records(
+        &self,
+        service_name: &str,
+        qtype: TYPE,
+    ) -> Result<Vec<ResourceRecord<'static>>, SimpleMdnsError> {
+        let mut packet = Packet::new_query_with_random_id();
+        packet.questions.push(Question::new(
+            Name::new(service_name)?,
+            qtype.into(),
+            CLASS::IN.into(),
+            self.unicast_response,
+        ));
+
+        self.collect_records(packet)
+    }
+
+    /// Send a query for A and AAAA resources of `host_name`, like `my-host.local`, and collect the addresses
+    /// from the responses until the query timeout elapses.
+    ///
+    /// IP v4 addresses are returned before IP v6 addresses, each address is returned only once
+    pub fn resolve_host(&self, host_name: &str) -> Result<Vec<IpAddr>, SimpleMdnsError> {
+        self.resolve_host_name(&Name::new(host_name)?)
+    }
+
+    fn resolve_host_name(&self, host_name: &Name<'_>) -> Result<Vec<IpAddr>, SimpleMdnsError> {
+        let mut packet = Packet::new_query_with_random_id();
+        for qtype in [TYPE::A, TYPE::AAAA] {
+            packet.questions.push(Question::new(
+                host_name.clone(),
+                qtype.into(),
+                CLASS::IN.into(),
+                self.unicast_response,
+            ));
+        }
+
+        let mut addresses: Vec<IpAddr> = Vec::new();
+        for record in self.collect_records(packet)? {
+            let address = match record.rdata {
+                RData::A(a) if &record.name == host_name => IpAddr::V4(Ipv4Addr::from(a.address)),
+                RData::AAAA(aaaa) if &record.name == host_name => {
+                    IpAddr::V6(Ipv6Addr::from(aaaa.address))
+                }
+                _ => continue,
+            };
+
+            if self.is_reachable(&address) && !addresses.contains(&address) {
+                addresses.push(address);
+            }
+        }
+
+        addresses.sort_by_key(|address| address.is_ipv6());
+        Ok(addresses)
+    }
+
+    /// Send a query for SRV and TXT resources of `instance_name`, like `my_inst._http._tcp.local`, and resolve
+    /// the target host, port, addresses and TXT attributes of the instance, returns `None` when no SRV record is received.
+    ///
+    /// Addresses are taken from the additional records sent with the SRV record, when the responses have no
+    /// address for the target host, it is resolved with [`resolve_host`](OneShotMdnsResolver::resolve_host),
+    /// which waits for the query timeout once more. When the received addresses are all rejected by the
+    /// [reachability filter](OneShotMdnsResolver::set_reachability_filter), no addresses are returned
+    pub fn resolve_service(
+        &self,
+        instance_name: &str,
+    ) -> Result<Option<ResolvedService>, SimpleMdnsError> {
+        let instance_name = Name::new(instance_name)?;
+        let mut packet = Packet::new_query_with_random_id();
+        for qtype in [TYPE::SRV, TYPE::TXT] {
+            packet.questions.push(Question::new(
+                instance_name.clone(),
+                qtype.into(),
+                CLASS::IN.into(),
+                self.unicast_response,
+            ));
+        }
+
+        let records = self.collect_records(packet)?;
+        let mut service = match ResolvedService::from_records(&instance_name, &records) {
+            Some(service) => service,
+            None => return Ok(None),
+        };
+
+        if service.addresses.is_empty() {
+            service.addresses = self.resolve_host_name(&service.host)?;
+        } else {
+            service
+                .addresses
+                .retain(|address| self.is_reachable(address));
+        }
+
+        Ok(Some(service))
+    }
+
+    /// Send `packet` and returns the answers and additional records of every response received before the query
+    /// timeout elapses, without duplicates
+    fn collect_records(
+        &self,
+        packet: Packet,
+    ) -> Result<Vec<ResourceRecord<'static>>, SimpleMdnsError> {
+        self.sender_socket.send_to(
+            &packet.build_bytes_vec_compressed()?,
+            self.network_scope.socket_address(),
+        )?;
+
+        let mut seen = HashSet::new();
+        let mut records = Vec::new();
+        let deadline = Instant::now() + self.query_timeout;
+        loop {
+            let buffer = match self.get_next_response(packet.id(), deadline) {
+                Ok(Some(buffer)) => buffer,
+                Ok(None) => break,
+                Err(err) => {
+                    log::error!("Received invalid packet: {}", err);
+                    continue;
+                }
+            };
+
+            let response = match Packet::parse(&buffer) {
+                Ok(packet) => packet,
+                Err(err) => {
+                    log::error!("Received invalid packet: {}", err);
+                    continue;
+                }
+            };
+
+            for record in response
+                .answers
+                .into_iter()
+                .chain(response.additional_records)
+            {
+                let record = record.into_owned();
+                if seen.insert(record.clone()) {
+                    records.push(record);
+                }
+            }
+        }
+
+        Ok(records)
+    }
+
+    /// Query the service types advertised in the network, like `_http._tcp.local`, by querying the meta-service
+    /// `_services._dns-sd._udp.local`, [RFC 6763 section 9](https://datatracker.ietf.org/doc/html/rfc6763#section-9).
+    ///
+    /// Responses are collected until the query timeout elapses, every service type is returned only once
+    pub fn query_service_types(&self) -> Result<Vec<Name<'static>>, SimpleMdnsError> {
+        let meta_service = Name::new_unchecked(SERVICE_TYPE_ENUMERATION_NAME);
+        let mut service_types: Vec<Name<'static>> = Vec::new();
+
+        for record in self.query_service_records(SERVICE_TYPE_ENUMERATION_NAME, TYPE::PTR)? {
+            match record.rdata {
+                RData::PTR(ptr)
+                    if record.name == meta_service && !service_types.contains(&ptr.0) =>
+                {
+                    service_types.push(ptr.0)
+                }
+                _ => {}
+            }
+        }
+
+        Ok(service_types)
+    }
+
     /// Set the one shot mdns resolver's query timeout.
     pub fn set_query_timeout(&mut self, query_timeout: Duration) {
         self.query_timeout = query_timeout;
@@ -199,28 +379,75 @@ impl OneShotMdnsResolver {
         self.unicast_response = unicast_response;
     }
 
+    /// Set a filter for the resolved addresses, addresses rejected by `filter` are never returned.  
+    /// This can be used to leave out addresses that are not reachable from this host,
+    /// like a loopback address advertised by a remote host
+    pub fn set_reachability_filter(
+        &mut self,
+        filter: impl Fn(&IpAddr) -> bool + Send + Sync + 'static,
+    ) {
+        self.reachability_filter = Some(Box::new(filter));
+    }
+
+    /// Require multicast responses to carry the ID of the query, disabled by default.  
+    /// Responses received via unicast always need the ID of the query, but mDNS responders send multicast responses
+    /// with the ID 0, which are accepted unless `strict_id_matching` is set
+    pub fn set_strict_id_matching(&mut self, strict_id_matching: bool) {
+        self.strict_id_matching = strict_id_matching;
+    }
+
+    fn is_reachable(&self, address: &IpAddr) -> bool {
+        match &self.reachability_filter {
+            Some(filter) => filter(address),
+            None => true,
+        }
+    }
+
+    /// Waits for the next response to the query `packet_id`, received via unicast on the sender socket or via
+    /// multicast on the receiver socket, until `query_deadline`
     fn get_next_response(
         &self,
         packet_id: u16,
         query_deadline: std::time::Instant,
     ) -> Result<Option<Vec<u8>>, SimpleMdnsError> {
-        let mut buf = [0u8; 4096];
-        loop {
-            match self.receiver_socket.recv_from(&mut buf[..]) {
-                Ok((count, _)) => {
-                    if header_buffer::has_flags(&buf, simple_dns::PacketFlag::RESPONSE)?
-                        && header_buffer::id(&buf)? == packet_id
-                        && header_buffer::answers(&buf)? > 0
-                    {
-                        return Ok(Some(buf[..count].to_vec()));
-                    }
-                }
-                Err(_) => {
-                    if std::time::Instant::now() > query_deadline {
-                        return Ok(None);
-                    }
-                }
+        let mut buf = [0u8; RECV_BUFFER_SIZE];
+        let sockets = [&self.sender_socket, &self.receiver_socket];
+        while let Some(remaining) = query_deadline.checked_duration_since(Instant::now()) {
+            let index = match wait_readable(&sockets, remaining)? {
+                Some(index) => index,
+                None => continue,
+            };
+
+            let count = match sockets[index].recv(&mut buf[..]) {
+                Ok(count) => count,
+                Err(_) => continue,
+            };
+
+            let datagram = match received_datagram(&buf, count) {
+                Some(datagram) => datagram,
+                None => continue,
+            };
+
+            // the sender socket receives the unicast responses
+            let unicast = index == 0;
+            if is_query_response(datagram, packet_id, unicast, self.strict_id_matching) {
+                return Ok(Some(datagram.to_vec()));
             }
         }
+
+        Ok(None)
+    }
+}
+
+impl std::fmt::Debug for OneShotMdnsResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OneShotMdnsResolver")
+            .field("query_timeout", &self.query_timeout)
+            .field("unicast_response", &self.unicast_response)
+            .field("receiver_socket", &self.receiver_socket)
+            .field("sender_socket", &self.sender_socket)
+            .field("network_scope", &self.network_scope)
+            .field("reachability_filter", &self.reachability_filter.is_some())
+            .finish()
     }
 }
@@ -9,8 +9,9 @@ use std::{
 };
 
 use crate::{
-    resource_record_manager::ResourceRecordManager, InstanceInformation, NetworkScope,
-    SimpleMdnsError,
+    announced_state::AnnouncedState, received_datagram,
+    resource_record_manager::ResourceRecordManager, AnnouncedStateStore, InstanceInformation,
+    NetworkScope, RecordTtls, SimpleMdnsError, RECV_BUFFER_SIZE,
 };
 
 /// Service Discovery implementation using DNS-SD.
@@ -33,9 +34,10 @@ pub struct ServiceDiscovery {
     full_name: Name<'static>,
     service_name: Name<'static>,
     resource_manager: Arc<RwLock<ResourceRecordManager<'static>>>,
-    resource_ttl: u32,
+    record_ttls: RecordTtls,
     sender_socket: UdpSocket,
     network_scope: NetworkScope,
+    announced_state: AnnouncedState,
 }
 
 impl ServiceDiscovery {
@@ -61,11 +63,30 @@ impl ServiceDiscovery {
     /// `service_name` must be in the standard specified by the mdns RFC, example: **_my_service._tcp.local**
     /// `resource_ttl` refers to the amount of time in seconds your service will be cached in the dns responder.
     /// set `enable_loopback` to true if you may have more than one instance of your service running in the same machine
+    ///
+    /// `resource_ttl` is used by address and SRV records, PTR and TXT records use [`RecordTtls::DEFAULT_SHARED_TTL`]
     pub fn new_with_scope(
         instance_name: &str,
         service_name: &str,
         resource_ttl: u32,
         network_scope: NetworkScope,
+    ) -> Result<Self, SimpleMdnsError> {
+        let record_ttls = RecordTtls {
+            unique: resource_ttl,
+            ..Default::default()
+        };
+        Self::new_with_record_ttls(instance_name, service_name, record_ttls, network_scope)
+    }
+
+    /// Creates a new ServiceDiscovery by providing `instance`, `service_name`, `record_ttls` and `network_scope`.
+    ///
+    /// Address and SRV records are registered with the unique TTL, PTR and TXT records with the shared TTL,
+    /// [`RecordTtls::default`] uses the TTLs recommended by the mDNS RFC
+    pub fn new_with_record_ttls(
+        instance_name: &str,
+        service_name: &str,
+        record_ttls: RecordTtls,
+        network_scope: NetworkScope,
     ) -> Result<Self, SimpleMdnsError> {
         let full_name = format!("{}.{}", instance_name, service_name);
         let full_name = Name::new(&full_name)?.into_owned();
@@ -75,17 +96,18 @@ impl ServiceDiscovery {
         resource_manager.add_owned_resource(ResourceRecord::new(
             service_name.clone(),
             simple_dns::CLASS::IN,
-            0,
-            RData::PTR(service_name.clone().into()),
+            record_ttls.shared,
+            RData::PTR(full_name.clone().into()),
         ));
 
         let service_discovery = Self {
             full_name,
             service_name,
             resource_manager: Arc::new(RwLock::new(resource_manager)),
-            resource_ttl,
-            sender_socket: crate::socket_helper::sender_socket(network_scope.is_v4())?,
+            record_ttls,
+            sender_socket: crate::socket_helper::sender_socket(network_scope)?,
             network_scope,
+            announced_state: AnnouncedState::default(),
         };
 
         service_discovery.receive_packets_loop()?;
@@ -102,6 +124,14 @@ impl ServiceDiscovery {
         Ok(service_discovery)
     }
 
+    /// Sets the store used to remember the announced records across restarts.
+    ///
+    /// The records saved in `store` are considered already announced, only new or changed records are
+    /// announced by [`add_service_info`](ServiceDiscovery::add_service_info)
+    pub fn set_announced_state_store(&mut self, store: impl AnnouncedStateStore + 'static) {
+        self.announced_state.set_store(Box::new(store));
+    }
+
     /// Add the  service info to discovery and immediately advertise the service
     pub fn add_service_info(
         &mut self,
@@ -109,7 +139,9 @@ impl ServiceDiscovery {
     ) -> Result<(), Box<dyn Error>> {
         {
             let mut resource_manager = self.resource_manager.write().unwrap();
-            for resource in service_info.into_records(&self.full_name.clone(), self.resource_ttl)? {
+            for resource in
+                service_info.into_records_with_ttls(&self.full_name.clone(), self.record_ttls)?
+            {
                 resource_manager.add_owned_resource(resource);
             }
         }
@@ -196,7 +228,7 @@ impl ServiceDiscovery {
         Ok(())
     }
 
-    fn advertise_service(&self, cache_flush: bool) {
+    fn advertise_service(&mut self, cache_flush: bool) {
         log::info!("Advertising service");
         let mut packet = Packet::new_reply(1);
         let resource_manager = self.resource_manager.read().unwrap();
@@ -239,6 +271,12 @@ impl ServiceDiscovery {
             packet.additional_records.push(additional_record)
         }
 
+        if cache_flush {
+            self.announced_state.set_announced(HashSet::new());
+        } else {
+            self.announced_state.retain_unannounced_packet(&mut packet);
+        }
+
         if !packet.answers.is_empty()
             && packet
                 .build_bytes_vec_compressed()
@@ -266,16 +304,28 @@ impl ServiceDiscovery {
         recv_socket.set_read_timeout(None)?;
 
         std::thread::spawn(move || loop {
-            let mut recv_buffer = [0u8; 9000];
-            let (count, addr) = match recv_socket.recv_from(&mut recv_buffer) {
-                Ok(received) => received,
+            let mut recv_buffer = [0u8; RECV_BUFFER_SIZE];
+            let (count, addr) = match crate::recv_message(&recv_socket, &mut recv_buffer) {
+                Ok(received) => {
+                    log::trace!(
+                        "received packet from {} on interface {:?}",
+                        received.origin,
+                        received.interface_index
+                    );
+                    (received.len, received.origin)
+                }
                 Err(err) => {
                     log::error!("Failed to read network information {err}");
                     continue;
                 }
             };
 
-            match Packet::parse(&recv_buffer[..count]) {
+            let datagram = match received_datagram(&recv_buffer, count) {
+                Some(datagram) => datagram,
+                None => continue,
+            };
+
+            match Packet::parse(datagram) {
                 Ok(packet) => {
                     if packet.has_flags(simple_dns::PacketFlag::RESPONSE) {
                         add_response_to_resources(
@@ -360,7 +410,7 @@ fn add_response_to_resources(
     let resources = packet
         .answers
         .into_iter()
-        .chain(packet.additional_records.into_iter())
+        .chain(packet.additional_records)
         .filter(|aw| {
             aw.name.ne(full_name)
                 && aw.name.is_subdomain_of(service_name)
@@ -374,3 +424,38 @@ fn add_response_to_resources(
         owned_resources.add_expirable_resource(resource.into_owned());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn registered_records_use_rfc_ttls_by_default() {
+        let mut discovery = ServiceDiscovery::new_with_record_ttls(
+            "inst",
+            "_ttl._tcp.local",
+            RecordTtls::default(),
+            NetworkScope::V4,
+        )
+        .unwrap();
+        discovery
+            .add_service_info(SocketAddr::from_str("192.168.1.22:8090").unwrap().into())
+            .unwrap();
+
+        let resource_manager = discovery.resource_manager.read().unwrap();
+        let ttl_of = |qtype: TYPE| {
+            resource_manager
+                .get_domain_resources(&discovery.service_name, true, true)
+                .flatten()
+                .find(|r| r.match_qtype(qtype.into()))
+                .map(|r| r.ttl)
+        };
+
+        assert_eq!(Some(4500), ttl_of(TYPE::PTR));
+        assert_eq!(Some(4500), ttl_of(TYPE::TXT));
+        assert_eq!(Some(120), ttl_of(TYPE::SRV));
+        assert_eq!(Some(120), ttl_of(TYPE::A));
+    }
+}
@@ -1,16 +1,28 @@
-use std::sync::{Arc, RwLock};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, RwLock,
+};
+use std::time::{Duration, Instant};
 
-use simple_dns::{header_buffer, Packet, PacketFlag, ResourceRecord};
+use simple_dns::{Name, Packet, ResourceRecord};
 
+use super::DatagramSocket;
 use crate::{
-    build_reply,
+    build_announcement, build_goodbye, build_probe,
+    conversion_utils::hashmap_to_txt,
+    encode_reply, is_probe_conflict, max_unicast_size,
+    network_scope::multicast_address_for,
+    parse_query, received_datagram,
     resource_record_manager::ResourceRecordManager,
-    socket_helper::{join_multicast, sender_socket},
-    NetworkScope, SimpleMdnsError,
+    response_delay::{PendingReply, ResponseDelay},
+    socket_helper::{join_multicast, sender_socket, wait_readable},
+    split_query, NetworkScope, QueryCallback, SimpleMdnsError, ANNOUNCEMENT_COUNT,
+    ANNOUNCEMENT_INTERVAL, PROBE_COUNT, PROBE_INTERVAL, RECV_BUFFER_SIZE,
 };
 
 const FIVE_MINUTES: u32 = 60 * 5;
-
 /// A simple mDNS responder aimed for service discovery.
 /// In case you don't have a mDNS responder in your network, or for some reason don't want to use the ones available.
 ///
@@ -46,32 +58,73 @@ const FIVE_MINUTES: u32 = 60 * 5;
 /// ```
 ///
 /// This struct heavily relies on [`simple_dns`] crate and the same must be added as a dependency
-#[derive(Debug)]
 pub struct SimpleMdnsResponder {
     resources: Arc<RwLock<ResourceRecordManager<'static>>>,
     rr_ttl: u32,
+    amplification_limit: Arc<AtomicUsize>,
+    scopes: Vec<NetworkScope>,
+    on_query: Arc<RwLock<Option<QueryCallback>>>,
+    response_delay: Arc<RwLock<ResponseDelay>>,
 }
 
 impl SimpleMdnsResponder {
-    /// Creates a new SimpleMdnsResponder with specified ttl and IPV4 scope with UNSPECIFIED
-    /// Interface
+    /// Creates a new SimpleMdnsResponder with specified ttl, listening on both IPV4 and IPV6 with UNSPECIFIED
+    /// Interface. Both listeners answer from the same resources, if one of them fails the other keeps running
     pub fn new(rr_ttl: u32) -> Self {
-        Self::new_with_scope(rr_ttl, NetworkScope::V4)
+        Self::new_with_scopes(rr_ttl, [NetworkScope::V4, NetworkScope::V6])
     }
 
     /// Creates a new SimpleMdnsResponder with specified ttl and network scope
+    ///
+    /// Use [`NetworkScope::V4WithInterface`] with a local address, or [`NetworkScope::V6WithInterface`] with an
+    /// interface index, to choose the interface used to join the multicast group and to send packets
+    /// (`IP_MULTICAST_IF`). The interface can't be changed afterwards, the listener is bound when the responder is created
     pub fn new_with_scope(rr_ttl: u32, scope: NetworkScope) -> Self {
+        Self::new_with_scopes(rr_ttl, [scope])
+    }
+
+    /// Creates a new SimpleMdnsResponder with specified ttl, with one listener for every network interface returned
+    /// by [`NetworkScope::all_interfaces`]. Queries are answered through the interface they arrived on, so the
+    /// resources are discoverable in every network the host is connected to
+    pub fn new_on_all_interfaces(rr_ttl: u32) -> Result<Self, SimpleMdnsError> {
+        Ok(Self::new_with_scopes(
+            rr_ttl,
+            NetworkScope::all_interfaces()?,
+        ))
+    }
+
+    /// Returns the network scopes this responder listens on, one listener is created for each scope
+    pub fn scopes(&self) -> &[NetworkScope] {
+        &self.scopes
+    }
+
+    fn new_with_scopes(rr_ttl: u32, scopes: impl IntoIterator<Item = NetworkScope>) -> Self {
         let responder = Self {
             resources: Arc::new(RwLock::new(ResourceRecordManager::new())),
             rr_ttl,
+            amplification_limit: Arc::new(AtomicUsize::new(0)),
+            scopes: scopes.into_iter().collect(),
+            on_query: Arc::new(RwLock::new(None)),
+            response_delay: Arc::new(RwLock::new(ResponseDelay::default())),
         };
 
-        let resources = responder.resources.clone();
-        std::thread::spawn(move || {
-            if let Err(err) = Self::responder_loop(resources, scope) {
-                log::error!("Dns Responder failed: {}", err);
-            }
-        });
+        for scope in responder.scopes.iter().copied() {
+            let resources = responder.resources.clone();
+            let amplification_limit = responder.amplification_limit.clone();
+            let on_query = responder.on_query.clone();
+            let response_delay = responder.response_delay.clone();
+            std::thread::spawn(move || {
+                if let Err(err) = Self::responder_loop(
+                    resources,
+                    scope,
+                    amplification_limit,
+                    on_query,
+                    response_delay,
+                ) {
+                    log::error!("Dns Responder failed for {scope:?}: {}", err);
+                }
+            });
+        }
         responder
     }
 
@@ -81,6 +134,27 @@ impl SimpleMdnsResponder {
         resources.add_owned_resource(resource);
     }
 
+    /// Register a TXT record for `name` with the given attributes, using the configured TTL.  
+    /// DNS-SD clients expect the TXT record of a service instance alongside its SRV record, an attribute without
+    /// value is registered as a boolean attribute (`key` instead of `key=value`)
+    pub fn add_service_txt(
+        &mut self,
+        name: &Name<'_>,
+        attributes: HashMap<String, Option<String>>,
+    ) -> Result<(), SimpleMdnsError> {
+        let txt = hashmap_to_txt(name, attributes, self.rr_ttl)?.into_owned();
+        let mut resources = self.resources.write().unwrap();
+        resources.add_owned_resource(txt);
+        Ok(())
+    }
+
+    /// Register a Resource Record to be sent in the authority section, when a query has no answers for its name.  
+    /// For example, a NSEC record listing the record types that exist for a name
+    pub fn add_authority_resource(&mut self, resource: ResourceRecord<'static>) {
+        let mut resources = self.resources.write().unwrap();
+        resources.add_authority_resource(resource);
+    }
+
     /// Remove a resource record
     pub fn remove_resource_record(&mut self, resource: ResourceRecord<'static>) {
         let mut resources = self.resources.write().unwrap();
@@ -93,61 +167,284 @@ impl SimpleMdnsResponder {
         resources.clear();
     }
 
+    /// Set a callback invoked for every query received, before the reply is built.  
+    /// The callback receives the query and the address it came from, it can be used to log or collect metrics
+    /// about the queries seen by this responder. Replaces any previously set callback
+    pub fn on_query(&mut self, callback: impl Fn(&Packet, SocketAddr) + Send + Sync + 'static) {
+        *self.on_query.write().unwrap() = Some(Box::new(callback));
+    }
+
+    /// Set the range of the random delay applied to multicast replies (defaults to 20 to 120 milliseconds),
+    /// so responders answering the same query don't reply at the same time. Queries received during the delay
+    /// are answered by the same reply. Unicast replies are never delayed, set both to zero to reply immediately
+    pub fn set_response_delay(&mut self, min: Duration, max: Duration) {
+        *self.response_delay.write().unwrap() = ResponseDelay { min, max };
+    }
+
     fn responder_loop(
         resources: Arc<RwLock<ResourceRecordManager<'_>>>,
         scope: NetworkScope,
+        amplification_limit: Arc<AtomicUsize>,
+        on_query: Arc<RwLock<Option<QueryCallback>>>,
+        response_delay: Arc<RwLock<ResponseDelay>>,
     ) -> Result<(), SimpleMdnsError> {
-        let mut recv_buffer = [0u8; 9000];
-        let sender_socket = sender_socket(scope.is_v4())?;
+        let mut recv_buffer = [0u8; RECV_BUFFER_SIZE];
+        let sender_socket = sender_socket(scope)?;
 
         let recv_socket = join_multicast(scope)?;
-        recv_socket.set_read_timeout(None)?;
+        let mut pending: Option<PendingReply> = None;
 
         loop {
-            let (count, addr) = match recv_socket.recv_from(&mut recv_buffer) {
-                Ok(received) => received,
+            let read_timeout = pending.as_ref().map(|pending| {
+                pending
+                    .send_at
+                    .saturating_duration_since(Instant::now())
+                    .max(Duration::from_millis(1))
+            });
+            recv_socket.set_read_timeout(read_timeout)?;
+
+            match recv_socket.recv_from(&mut recv_buffer) {
+                Ok((count, addr)) => {
+                    let query = match received_datagram(&recv_buffer, count)
+                        .and_then(|query| parse_query(query, Some(addr), &on_query.read().unwrap()))
+                    {
+                        Some(query) => query,
+                        None => continue,
+                    };
+
+                    let (unicast_query, multicast_query) = split_query(query);
+                    if let Some(query) = unicast_query {
+                        let max_size = max_unicast_size(count, &amplification_limit);
+                        if let Some(reply) =
+                            encode_reply(query, &resources.read().unwrap(), true, max_size)
+                        {
+                            sender_socket.send_to(&reply, addr)?;
+                        }
+                    }
+
+                    if let Some(query) = multicast_query {
+                        let delay = response_delay.read().unwrap().delay();
+                        match &mut pending {
+                            Some(pending) => pending.merge(query),
+                            None if delay.is_zero() => {
+                                if let Some(reply) =
+                                    encode_reply(query, &resources.read().unwrap(), false, None)
+                                {
+                                    sender_socket.send_to(&reply, multicast_address_for(&addr))?;
+                                }
+                            }
+                            None => {
+                                pending = Some(PendingReply::new(
+                                    query,
+                                    Instant::now() + delay,
+                                    multicast_address_for(&addr),
+                                ))
+                            }
+                        }
+                    }
+                }
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) => {}
                 Err(err) => {
                     log::error!("Failed to read network information {err}");
                     continue;
                 }
-            };
+            }
 
-            if header_buffer::has_flags(&recv_buffer[..count], PacketFlag::RESPONSE).unwrap_or(true)
-            {
-                continue;
+            match pending.take() {
+                Some(ready) if ready.send_at <= Instant::now() => {
+                    let reply_addr = ready.reply_addr;
+                    if let Some(reply) =
+                        encode_reply(ready.into_query(), &resources.read().unwrap(), false, None)
+                    {
+                        sender_socket.send_to(&reply, reply_addr)?;
+                    }
+                }
+                not_ready => pending = not_ready,
             }
+        }
+    }
 
-            match Packet::parse(&recv_buffer[..count]) {
-                Ok(packet) => {
-                    match build_reply(packet, &resources.read().unwrap()) {
-                        Some((reply_packet, unicast_response)) => {
-                            let reply = match reply_packet.build_bytes_vec_compressed() {
-                                Ok(reply) => reply,
-                                Err(err) => {
-                                    log::error!("Failed to build reply {err}");
-                                    continue;
-                                }
-                            };
+    /// Answer the queries received by `socket` using the resources of this responder, replies are always sent back to the
+    /// address of the query. This allows running the responder over any datagram socket, like an Unix datagram socket
+    ///
+    /// The socket is handled in a new thread, which stops when receiving from the socket fails
+    pub fn listen_on_socket<S>(&self, socket: S)
+    where
+        S: DatagramSocket + Send + 'static,
+    {
+        let resources = self.resources.clone();
+        let amplification_limit = self.amplification_limit.clone();
+        let on_query = self.on_query.clone();
+
+        std::thread::spawn(move || {
+            let mut recv_buffer = [0u8; RECV_BUFFER_SIZE];
+            loop {
+                let (count, addr) = match socket.recv_from(&mut recv_buffer) {
+                    Ok(received) => received,
+                    Err(err) => {
+                        log::error!("Failed to read from socket {err}");
+                        return;
+                    }
+                };
 
-                            let reply_addr = if unicast_response {
-                                addr
-                            } else {
-                                scope.socket_address()
-                            };
+                let query = match received_datagram(&recv_buffer, count).and_then(|query| {
+                    parse_query(query, S::socket_addr(&addr), &on_query.read().unwrap())
+                }) {
+                    Some(query) => query,
+                    None => continue,
+                };
 
-                            sender_socket.send_to(&reply, reply_addr)?;
-                        }
-                        None => {
-                            log::trace!("No reply for query");
-                            continue;
+                let max_size = max_unicast_size(count, &amplification_limit);
+                let reply = match encode_reply(query, &resources.read().unwrap(), true, max_size) {
+                    Some(reply) => reply,
+                    None => continue,
+                };
+
+                if let Err(err) = socket.send_to(&reply, &addr) {
+                    log::error!("Failed to send reply {err}");
+                }
+            }
+        });
+    }
+
+    /// Probe `name`, making sure no other host uses it before announcing the resources registered for it.
+    /// Three probe queries are sent 250 milliseconds apart, with the resources registered for `name` as the proposed records.
+    ///
+    /// Returns [`SimpleMdnsError::NameConflict`] when another host answers with different records for `name`,
+    /// in which case a new name should be chosen, [RFC 6762 section 8.1](https://datatracker.ietf.org/doc/html/rfc6762#section-8.1)
+    pub fn probe(&self, name: &Name<'_>) -> Result<(), SimpleMdnsError> {
+        let (probe, proposed) = {
+            let resources = self.resources.read().unwrap();
+            let packet = build_probe(name, &resources);
+            let proposed: Vec<_> = packet
+                .name_servers
+                .iter()
+                .map(|r| r.clone().into_owned())
+                .collect();
+            (packet.build_bytes_vec_compressed()?, proposed)
+        };
+
+        let mut sockets = Vec::new();
+        for scope in self.scopes.iter() {
+            let sender = sender_socket(*scope)?;
+            let multicast = join_multicast(*scope)?;
+            sockets.push((sender, multicast, scope.socket_address()));
+        }
+
+        let receivers: Vec<_> = sockets.iter().flat_map(|(s, m, _)| [s, m]).collect();
+        let mut recv_buffer = [0u8; RECV_BUFFER_SIZE];
+        for _ in 0..PROBE_COUNT {
+            for (sender, _, address) in sockets.iter() {
+                sender.send_to(&probe, address)?;
+            }
+
+            let deadline = Instant::now() + PROBE_INTERVAL;
+            while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+                let index = match wait_readable(&receivers, remaining)? {
+                    Some(index) => index,
+                    None => continue,
+                };
+
+                match receivers[index].recv(&mut recv_buffer) {
+                    Ok(count) => {
+                        if is_probe_conflict(&recv_buffer[..count], name, &proposed) {
+                            return Err(SimpleMdnsError::NameConflict(name.to_string()));
                         }
-                    };
+                    }
+                    Err(err) => log::error!("Failed to receive probe responses {err}"),
                 }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Multicast every registered resource, so other hosts learn about them without querying.  
+    /// The announcement is sent twice, one second apart, resources other than PTR records have the cache-flush bit set.
+    /// Call this after adding the resources of a service
+    pub fn announce(&self) -> Result<(), SimpleMdnsError> {
+        let bytes = {
+            let resources = self.resources.read().unwrap();
+            let packet = build_announcement(&resources);
+            if packet.answers.is_empty() {
+                return Ok(());
+            }
+            packet.build_bytes_vec_compressed()?
+        };
+
+        self.multicast(&bytes, ANNOUNCEMENT_COUNT, ANNOUNCEMENT_INTERVAL)
+    }
+
+    /// Multicast every registered resource with a TTL of 0, so other hosts remove them from their caches.
+    /// Call this before dropping the responder or removing its resources
+    pub fn goodbye(&self) -> Result<(), SimpleMdnsError> {
+        let bytes = {
+            let resources = self.resources.read().unwrap();
+            let packet = build_goodbye(&resources);
+            if packet.answers.is_empty() {
+                return Ok(());
+            }
+            packet.build_bytes_vec_compressed()?
+        };
+
+        self.multicast(&bytes, 1, Duration::ZERO)
+    }
+
+    /// Multicast `bytes` `count` times through every scope, `interval` apart. Scopes that fail, like IPv6 on a host
+    /// without IPv6, are logged and skipped. Returns an error only when `bytes` could not be sent through any scope
+    fn multicast(
+        &self,
+        bytes: &[u8],
+        count: usize,
+        interval: Duration,
+    ) -> Result<(), SimpleMdnsError> {
+        let mut last_error = None;
+        let sockets: Vec<_> = self
+            .scopes
+            .iter()
+            .filter_map(|scope| match sender_socket(*scope) {
+                Ok(socket) => Some((socket, *scope)),
                 Err(err) => {
-                    log::error!("Received Invalid packet {err}");
+                    log::error!("Failed to create socket for {scope:?}: {err}");
+                    last_error = Some(err);
+                    None
+                }
+            })
+            .collect();
+
+        let mut sent = false;
+        for i in 0..count {
+            if i > 0 {
+                std::thread::sleep(interval);
+            }
+
+            for (socket, scope) in sockets.iter() {
+                match socket.send_to(bytes, scope.socket_address()) {
+                    Ok(_) => sent = true,
+                    Err(err) => {
+                        log::error!("Failed to send packet to {scope:?}: {err}");
+                        last_error = Some(err);
+                    }
                 }
             }
         }
+
+        match last_error {
+            Some(err) if !sent => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Limit unicast replies to queries without EDNS to `ratio` times the size of the query, in order to
+    /// avoid being used for traffic amplification. Replies over the limit are truncated and have the TC flag set.
+    /// Multicast replies are never limited. Pass `None` to remove the limit (default)
+    pub fn set_amplification_limit(&mut self, ratio: Option<usize>) {
+        self.amplification_limit
+            .store(ratio.unwrap_or_default(), Ordering::Relaxed);
     }
 
     /// Set the simple mdns responder's rr default ttl in seconds (defaults to 300).
@@ -156,8 +453,65 @@ impl SimpleMdnsResponder {
     }
 }
 
+impl std::fmt::Debug for SimpleMdnsResponder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SimpleMdnsResponder")
+            .field("resources", &self.resources)
+            .field("rr_ttl", &self.rr_ttl)
+            .field("amplification_limit", &self.amplification_limit)
+            .field("scopes", &self.scopes)
+            .field("on_query", &self.on_query.read().unwrap().is_some())
+            .finish()
+    }
+}
+
 impl Default for SimpleMdnsResponder {
     fn default() -> Self {
         Self::new(FIVE_MINUTES)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use simple_dns::{rdata::RData, CLASS};
+
+    use super::*;
+
+    #[test]
+    fn announce_skips_failed_scopes() {
+        // the interface address does not belong to this host, so no socket can be created for the scope
+        let unavailable = NetworkScope::V4WithInterface(Ipv4Addr::new(203, 0, 113, 1));
+
+        let mut responder = SimpleMdnsResponder::new_with_scopes(10, [unavailable]);
+        responder.add_resource(ResourceRecord::new(
+            Name::new_unchecked("_announce._tcp.local"),
+            CLASS::IN,
+            10,
+            RData::A(Ipv4Addr::LOCALHOST.into()),
+        ));
+        assert!(responder.announce().is_err());
+
+        responder.scopes = vec![unavailable, NetworkScope::V4];
+        assert!(responder.announce().is_ok());
+    }
+
+    #[test]
+    fn goodbye_skips_failed_scopes() {
+        // the interface address does not belong to this host, so no socket can be created for the scope
+        let unavailable = NetworkScope::V4WithInterface(Ipv4Addr::new(203, 0, 113, 1));
+
+        let mut responder = SimpleMdnsResponder::new_with_scopes(10, [unavailable]);
+        responder.add_resource(ResourceRecord::new(
+            Name::new_unchecked("_goodbye._tcp.local"),
+            CLASS::IN,
+            10,
+            RData::A(Ipv4Addr::LOCALHOST.into()),
+        ));
+        assert!(responder.goodbye().is_err());
+
+        responder.scopes = vec![unavailable, NetworkScope::V4];
+        assert!(responder.goodbye().is_ok());
+    }
+}
@@ -1,17 +1,25 @@
 #![cfg(feature = "sync")]
 
 use std::{
-    net::{IpAddr, Ipv4Addr, SocketAddr},
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket},
     str::FromStr,
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
-use simple_dns::Name;
+use simple_dns::{
+    rdata::{RData, A},
+    Name, Packet, PacketFlag, ResourceRecord, CLASS, TYPE,
+};
 use simple_mdns::{
-    conversion_utils::socket_addr_to_srv_and_address,
+    conversion_utils::{
+        hashmap_to_txt, ip_addr_to_resource_record, socket_addr_to_srv_and_address,
+    },
     sync_discovery::{OneShotMdnsResolver, SimpleMdnsResponder},
+    MULTICAST_ADDR_IPV4, MULTICAST_PORT,
 };
+use socket2::{Domain, Protocol, Socket, Type};
 
 fn get_oneshot_responder(srv_name: Name<'static>) -> SimpleMdnsResponder {
     let mut responder = SimpleMdnsResponder::default();
@@ -49,6 +57,90 @@ fn one_shot_resolver_address_query() {
     )
 }
 
+/// Answers the first query for `name` with two unicast replies sent to the querier, the first one with a
+/// different ID than the query and `wrong_address`, the second one with the ID of the query and `address`
+fn unicast_replier(
+    name: Name<'static>,
+    wrong_address: Ipv4Addr,
+    address: Ipv4Addr,
+) -> thread::JoinHandle<()> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP)).unwrap();
+    socket.set_reuse_address(true).unwrap();
+    #[cfg(not(windows))]
+    socket.set_reuse_port(true).unwrap();
+    socket
+        .bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, MULTICAST_PORT)).into())
+        .unwrap();
+    socket
+        .join_multicast_v4(&MULTICAST_ADDR_IPV4, &Ipv4Addr::UNSPECIFIED)
+        .unwrap();
+    socket
+        .set_read_timeout(Some(Duration::from_millis(100)))
+        .unwrap();
+    let socket: UdpSocket = socket.into();
+
+    thread::spawn(move || {
+        let deadline = Instant::now() + Duration::from_secs(3);
+        let mut buf = [0u8; 9000];
+        while Instant::now() < deadline {
+            let (count, origin) = match socket.recv_from(&mut buf) {
+                Ok(received) => received,
+                Err(_) => continue,
+            };
+
+            let query = match Packet::parse(&buf[..count]) {
+                Ok(query) if !query.has_flags(PacketFlag::RESPONSE) => query,
+                _ => continue,
+            };
+            if !query
+                .questions
+                .iter()
+                .any(|question| question.qname == name)
+            {
+                continue;
+            }
+
+            for (id, address) in [
+                (query.id().wrapping_add(1), wrong_address),
+                (query.id(), address),
+            ] {
+                let mut reply = Packet::new_reply(id);
+                reply.answers.push(ResourceRecord::new(
+                    name.clone(),
+                    CLASS::IN,
+                    10,
+                    RData::A(address.into()),
+                ));
+                socket
+                    .send_to(&reply.build_bytes_vec().unwrap(), origin)
+                    .unwrap();
+            }
+            return;
+        }
+    })
+}
+
+#[test]
+#[cfg(not(target_os = "macos"))]
+fn one_shot_resolver_discards_unicast_response_with_other_id() {
+    let name = Name::new_unchecked("_sync_id._tcp.local");
+    let replier = unicast_replier(
+        name.clone(),
+        Ipv4Addr::new(10, 0, 0, 1),
+        Ipv4Addr::LOCALHOST,
+    );
+
+    let mut resolver = OneShotMdnsResolver::new().expect("Failed to create resolver");
+    resolver.set_unicast_response(true);
+    resolver.set_query_timeout(Duration::from_secs(2));
+
+    let answer = resolver
+        .query_service_address("_sync_id._tcp.local")
+        .expect("Failed to query service address");
+    assert_eq!(Some(IpAddr::V4(Ipv4Addr::LOCALHOST)), answer);
+    replier.join().unwrap();
+}
+
 #[test]
 fn one_shot_resolver_timeout() {
     let resolver = OneShotMdnsResolver::new().expect("Failed to create resolver");
@@ -57,3 +149,184 @@ fn one_shot_resolver_timeout() {
     let answer = answer.unwrap();
     assert!(answer.is_none());
 }
+
+#[test]
+fn one_shot_resolver_reachability_filter() {
+    let _responder = get_oneshot_responder(Name::new_unchecked("_sync_filter._tcp.local"));
+    thread::sleep(Duration::from_millis(500));
+
+    let mut resolver = OneShotMdnsResolver::new().expect("Failed to create resolver");
+    resolver.set_unicast_response(false);
+    resolver.set_query_timeout(Duration::from_secs(1));
+    resolver.set_reachability_filter(|address| !address.is_loopback());
+
+    let answer = resolver.query_service_address("_sync_filter._tcp.local");
+    assert!(answer.unwrap().is_none());
+
+    let answer = resolver.query_service_address_and_port("_sync_filter._tcp.local");
+    assert!(answer.unwrap().is_none());
+}
+
+#[test]
+fn one_shot_resolver_records_query() {
+    let _responder = get_oneshot_responder(Name::new_unchecked("_sync_records._tcp.local"));
+    thread::sleep(Duration::from_millis(500));
+
+    let mut resolver = OneShotMdnsResolver::new().expect("Failed to create resolver");
+    resolver.set_unicast_response(false);
+    resolver.set_query_timeout(Duration::from_secs(1));
+
+    let records = resolver
+        .query_service_records("_sync_records._tcp.local", TYPE::A)
+        .expect("Failed to query service records");
+
+    assert_eq!(
+        vec![RData::A(A {
+            address: Ipv4Addr::LOCALHOST.into()
+        })],
+        records
+            .into_iter()
+            .filter(|record| record.name == Name::new_unchecked("_sync_records._tcp.local"))
+            .map(|record| record.rdata)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn one_shot_resolver_service_types_query() {
+    let mut responder = SimpleMdnsResponder::default();
+    responder.add_resource(ResourceRecord::new(
+        Name::new_unchecked("_http._tcp.local"),
+        CLASS::IN,
+        10,
+        RData::PTR(Name::new_unchecked("inst1._http._tcp.local").into()),
+    ));
+    thread::sleep(Duration::from_millis(500));
+
+    let mut resolver = OneShotMdnsResolver::new().expect("Failed to create resolver");
+    resolver.set_unicast_response(false);
+    resolver.set_query_timeout(Duration::from_secs(1));
+
+    let service_types = resolver
+        .query_service_types()
+        .expect("Failed to query service types");
+    assert!(service_types.contains(&Name::new_unchecked("_http._tcp.local")));
+}
+
+#[test]
+fn one_shot_resolver_resolve_host() {
+    let host_name = Name::new_unchecked("_res1._tcp.com");
+    let mut responder = SimpleMdnsResponder::default();
+    responder.add_resource(ip_addr_to_resource_record(
+        &host_name,
+        Ipv6Addr::LOCALHOST.into(),
+        10,
+    ));
+    responder.add_resource(ip_addr_to_resource_record(
+        &host_name,
+        Ipv4Addr::LOCALHOST.into(),
+        10,
+    ));
+    thread::sleep(Duration::from_millis(500));
+
+    let mut resolver = OneShotMdnsResolver::new().expect("Failed to create resolver");
+    resolver.set_unicast_response(false);
+    resolver.set_query_timeout(Duration::from_secs(1));
+
+    let addresses = resolver
+        .resolve_host("_res1._tcp.com")
+        .expect("Failed to resolve host");
+    assert_eq!(
+        vec![
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            IpAddr::V6(Ipv6Addr::LOCALHOST)
+        ],
+        addresses
+    );
+}
+
+#[test]
+fn one_shot_resolver_resolve_service() {
+    let instance_name = Name::new_unchecked("inst1._sync_resolve._tcp.local");
+    let mut responder = get_oneshot_responder(instance_name.clone());
+    responder.add_resource(
+        hashmap_to_txt(
+            &instance_name,
+            HashMap::from([("path".to_string(), Some("/api".to_string()))]),
+            10,
+        )
+        .unwrap(),
+    );
+    thread::sleep(Duration::from_millis(500));
+
+    let mut resolver = OneShotMdnsResolver::new().expect("Failed to create resolver");
+    resolver.set_unicast_response(false);
+    resolver.set_query_timeout(Duration::from_secs(1));
+
+    let service = resolver
+        .resolve_service("inst1._sync_resolve._tcp.local")
+        .expect("Failed to resolve service")
+        .expect("Service not found");
+
+    assert_eq!(instance_name, service.host);
+    assert_eq!(8080, service.port);
+    assert_eq!(vec![IpAddr::V4(Ipv4Addr::LOCALHOST)], service.addresses);
+    assert_eq!(
+        Some(&Some("/api".to_string())),
+        service.txt_attributes.get("path")
+    );
+}
+
+#[test]
+fn one_shot_resolver_resolve_service_filtered_addresses() {
+    let instance_name = Name::new_unchecked("inst1._sync_resolve_filter._tcp.local");
+    let _responder = get_oneshot_responder(instance_name.clone());
+    thread::sleep(Duration::from_millis(500));
+
+    let mut resolver = OneShotMdnsResolver::new().expect("Failed to create resolver");
+    resolver.set_unicast_response(false);
+    resolver.set_query_timeout(Duration::from_secs(1));
+    resolver.set_reachability_filter(|address| !address.is_loopback());
+
+    let started = Instant::now();
+    let service = resolver
+        .resolve_service("inst1._sync_resolve_filter._tcp.local")
+        .expect("Failed to resolve service")
+        .expect("Service not found");
+
+    assert!(service.addresses.is_empty());
+    assert!(started.elapsed() < Duration::from_secs(2));
+}
+
+#[test]
+fn one_shot_resolver_service_txt_query() {
+    let name = Name::new_unchecked("inst1._sync_txt._tcp.local");
+    let attributes = HashMap::from([
+        ("path".to_string(), Some("/api".to_string())),
+        ("secure".to_string(), None),
+    ]);
+
+    let mut responder = SimpleMdnsResponder::default();
+    responder
+        .add_service_txt(&name, attributes.clone())
+        .expect("Failed to add TXT record");
+    thread::sleep(Duration::from_millis(500));
+
+    let mut resolver = OneShotMdnsResolver::new().expect("Failed to create resolver");
+    resolver.set_unicast_response(false);
+    resolver.set_query_timeout(Duration::from_secs(1));
+
+    let records = resolver
+        .query_service_records("inst1._sync_txt._tcp.local", TYPE::TXT)
+        .expect("Failed to query service records");
+
+    let txt_attributes: Vec<_> = records
+        .iter()
+        .filter(|record| record.name == name)
+        .filter_map(|record| match &record.rdata {
+            RData::TXT(txt) => Some(txt.attributes()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(vec![attributes], txt_attributes);
+}
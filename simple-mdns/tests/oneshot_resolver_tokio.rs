@@ -2,9 +2,12 @@
 
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::str::FromStr;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use simple_dns::Name;
+use simple_dns::{
+    rdata::{RData, A},
+    Name, TYPE,
+};
 use simple_mdns::async_discovery::{OneShotMdnsResolver, SimpleMdnsResponder};
 
 use simple_mdns::conversion_utils::socket_addr_to_srv_and_address;
@@ -57,3 +60,69 @@ async fn one_shot_resolver_timeout() {
     let answer = answer.unwrap();
     assert!(answer.is_none());
 }
+
+#[tokio::test]
+async fn one_shot_resolver_reachability_filter() {
+    let _responder = get_oneshot_responder(Name::new_unchecked("_async_filter._tcp.local")).await;
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let mut resolver = OneShotMdnsResolver::new().expect("Failed to create resolver");
+    resolver.set_unicast_response(false);
+    resolver.set_query_timeout(Duration::from_secs(1));
+    resolver.set_reachability_filter(|address| !address.is_loopback());
+
+    let answer = resolver
+        .query_service_address("_async_filter._tcp.local")
+        .await;
+    assert!(answer.unwrap().is_none());
+
+    let answer = resolver
+        .query_service_address_and_port("_async_filter._tcp.local")
+        .await;
+    assert!(answer.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn one_shot_resolver_records_query() {
+    let _responder = get_oneshot_responder(Name::new_unchecked("_async_records._tcp.local")).await;
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let mut resolver = OneShotMdnsResolver::new().expect("Failed to create resolver");
+    resolver.set_unicast_response(false);
+    resolver.set_query_timeout(Duration::from_secs(1));
+
+    let records = resolver
+        .query_service_records("_async_records._tcp.local", TYPE::A)
+        .await
+        .expect("Failed to query service records");
+
+    assert_eq!(
+        vec![RData::A(A {
+            address: Ipv4Addr::LOCALHOST.into()
+        })],
+        records
+            .into_iter()
+            .filter(|record| record.name == Name::new_unchecked("_async_records._tcp.local"))
+            .map(|record| record.rdata)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn one_shot_resolver_records_query_timeout() {
+    let mut resolver = OneShotMdnsResolver::new().expect("Failed to create resolver");
+    resolver.set_query_timeout(Duration::from_millis(500));
+
+    let started = Instant::now();
+    let records = resolver
+        .query_service_records("_async_records_miss._tcp.local", TYPE::A)
+        .await
+        .expect("Failed to query service records");
+    let elapsed = started.elapsed();
+
+    assert!(records
+        .iter()
+        .all(|record| record.name != Name::new_unchecked("_async_records_miss._tcp.local")));
+    assert!(elapsed >= Duration::from_millis(500));
+    assert!(elapsed < Duration::from_secs(2));
+}
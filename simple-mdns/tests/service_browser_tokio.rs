@@ -0,0 +1,36 @@
+#![cfg(feature = "async-tokio")]
+
+use std::{error::Error, time::Duration};
+
+use simple_dns::{rdata::RData, Name, ResourceRecord, CLASS};
+use simple_mdns::{
+    async_discovery::{ServiceBrowser, SimpleMdnsResponder},
+    NetworkScope, ServiceEvent, ServiceInstanceName,
+};
+
+#[tokio::test]
+async fn browser_reports_added_and_removed_instances() -> Result<(), Box<dyn Error>> {
+    let instance_name = Name::new_unchecked("inst1._browse._tcp.local");
+    let mut responder = SimpleMdnsResponder::new_with_scope(10, NetworkScope::V4);
+    responder
+        .add_resource(ResourceRecord::new(
+            Name::new_unchecked("_browse._tcp.local"),
+            CLASS::IN,
+            10,
+            RData::PTR(instance_name.clone().into()),
+        ))
+        .await;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let mut browser = ServiceBrowser::browse("_browse._tcp.local")?;
+    let instance = ServiceInstanceName::from_name(&instance_name).unwrap();
+
+    let event = tokio::time::timeout(Duration::from_secs(2), browser.next_event()).await?;
+    assert_eq!(Some(ServiceEvent::Added(instance.clone())), event);
+
+    responder.goodbye().await?;
+    let event = tokio::time::timeout(Duration::from_secs(2), browser.next_event()).await?;
+    assert_eq!(Some(ServiceEvent::Removed(instance)), event);
+
+    Ok(())
+}
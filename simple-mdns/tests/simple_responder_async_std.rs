@@ -0,0 +1,33 @@
+#![cfg(all(feature = "async-std", not(feature = "async-tokio")))]
+
+use std::{net::Ipv4Addr, time::Duration};
+
+use simple_dns::{
+    rdata::{RData, A},
+    Name, ResourceRecord, CLASS,
+};
+use simple_mdns::{async_discovery::SimpleMdnsResponder, NetworkScope};
+
+#[test]
+fn responder_runs_on_async_std() {
+    async_std::task::block_on(async {
+        let mut responder = SimpleMdnsResponder::new_with_scope(10, NetworkScope::V4);
+        responder
+            .add_resource(ResourceRecord::new(
+                Name::new_unchecked("_async_std._tcp.local"),
+                CLASS::IN,
+                10,
+                RData::A(A {
+                    address: Ipv4Addr::LOCALHOST.into(),
+                }),
+            ))
+            .await;
+        async_std::task::sleep(Duration::from_millis(200)).await;
+
+        responder.goodbye().await.expect("Failed to send goodbye");
+        let result = async_std::future::timeout(Duration::from_secs(1), responder.shutdown())
+            .await
+            .expect("Responder did not shut down in time");
+        assert!(result.is_ok());
+    });
+}
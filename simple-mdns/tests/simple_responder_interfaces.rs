@@ -0,0 +1,59 @@
+#![cfg(feature = "sync")]
+
+use std::{net::Ipv4Addr, thread, time::Duration};
+
+use simple_dns::{
+    rdata::{RData, A},
+    Name, Packet, Question, ResourceRecord, CLASS, TYPE,
+};
+use simple_mdns::{
+    sync_discovery::{OneShotMdnsResolver, SimpleMdnsResponder},
+    NetworkScope,
+};
+
+#[test]
+fn responder_listens_on_every_interface() {
+    let srv_name = Name::new_unchecked("_interfaces._tcp.local");
+
+    let mut responder =
+        SimpleMdnsResponder::new_on_all_interfaces(10).expect("Failed to create responder");
+    assert_eq!(
+        NetworkScope::all_interfaces().expect("Failed to list the network interfaces"),
+        responder.scopes()
+    );
+
+    responder.add_resource(ResourceRecord::new(
+        srv_name.clone(),
+        CLASS::IN,
+        10,
+        RData::A(A {
+            address: Ipv4Addr::LOCALHOST.into(),
+        }),
+    ));
+    thread::sleep(Duration::from_millis(500));
+
+    // a query sent through an interface is only answered when a listener joined the group on that interface
+    for scope in responder
+        .scopes()
+        .iter()
+        .filter(|scope| matches!(scope, NetworkScope::V4WithInterface(_)))
+    {
+        let mut resolver =
+            OneShotMdnsResolver::new_with_scope(*scope).expect("Failed to create resolver");
+        resolver.set_query_timeout(Duration::from_secs(2));
+
+        let query = Packet::new_query(9).with_questions([Question::new(
+            srv_name.clone(),
+            TYPE::A.into(),
+            CLASS::IN.into(),
+            false,
+        )]);
+
+        let reply = resolver
+            .query_packet(query)
+            .unwrap()
+            .unwrap_or_else(|| panic!("No listener answered on {scope:?}"));
+        let reply = Packet::parse(&reply).unwrap();
+        assert!(reply.answers.iter().any(|answer| answer.name == srv_name));
+    }
+}
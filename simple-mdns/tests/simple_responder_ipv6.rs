@@ -0,0 +1,111 @@
+#![cfg(feature = "sync")]
+
+use std::{
+    net::{Ipv4Addr, Ipv6Addr},
+    thread,
+    time::Duration,
+};
+
+use simple_dns::{
+    rdata::{RData, A, AAAA},
+    Name, Packet, Question, ResourceRecord, CLASS, TYPE,
+};
+use simple_mdns::{
+    sync_discovery::{OneShotMdnsResolver, SimpleMdnsResponder},
+    NetworkScope,
+};
+
+/// Creates a resolver for `scope`, or returns `None` when the host can't join the multicast group of the scope,
+/// like CI runners and containers without IPv6
+fn resolver_for(scope: NetworkScope) -> Option<OneShotMdnsResolver> {
+    match OneShotMdnsResolver::new_with_scope(scope) {
+        Ok(mut resolver) => {
+            resolver.set_query_timeout(Duration::from_secs(2));
+            Some(resolver)
+        }
+        Err(err) => {
+            eprintln!("skipping, can't join the multicast group of {scope:?}: {err}");
+            None
+        }
+    }
+}
+
+#[test]
+#[cfg(not(target_os = "macos"))]
+fn responder_answers_aaaa_query_over_ipv6_multicast() {
+    let resolver = match resolver_for(NetworkScope::V6) {
+        Some(resolver) => resolver,
+        None => return,
+    };
+
+    let srv_name = Name::new_unchecked("_sync_ipv6._tcp.local");
+    let address: Ipv6Addr = "fe80::26fc:f50f:6755:7d67".parse().unwrap();
+
+    let mut responder = SimpleMdnsResponder::new_with_scope(10, NetworkScope::V6);
+    responder.add_resource(ResourceRecord::new(
+        srv_name.clone(),
+        CLASS::IN,
+        10,
+        RData::AAAA(AAAA::from(address)),
+    ));
+    thread::sleep(Duration::from_millis(500));
+
+    let query = Packet::new_query(7).with_questions([Question::new(
+        srv_name.clone(),
+        TYPE::AAAA.into(),
+        CLASS::IN.into(),
+        false,
+    )]);
+
+    let reply = resolver
+        .query_packet(query)
+        .unwrap()
+        .expect("No reply over IPv6 multicast");
+    let reply = Packet::parse(&reply).unwrap();
+
+    assert!(reply
+        .answers
+        .iter()
+        .any(|answer| answer.name == srv_name && answer.rdata == RData::AAAA(AAAA::from(address))));
+}
+
+#[test]
+#[cfg(not(target_os = "macos"))]
+fn dual_stack_responder_answers_both_families() {
+    let resolvers = match (
+        resolver_for(NetworkScope::V4),
+        resolver_for(NetworkScope::V6),
+    ) {
+        (Some(v4), Some(v6)) => [(NetworkScope::V4, v4), (NetworkScope::V6, v6)],
+        _ => return,
+    };
+
+    let srv_name = Name::new_unchecked("_sync_dual._tcp.local");
+
+    let mut responder = SimpleMdnsResponder::new(10);
+    responder.add_resource(ResourceRecord::new(
+        srv_name.clone(),
+        CLASS::IN,
+        10,
+        RData::A(A {
+            address: Ipv4Addr::LOCALHOST.into(),
+        }),
+    ));
+    thread::sleep(Duration::from_millis(500));
+
+    for (scope, resolver) in resolvers {
+        let query = Packet::new_query(8).with_questions([Question::new(
+            srv_name.clone(),
+            TYPE::A.into(),
+            CLASS::IN.into(),
+            false,
+        )]);
+
+        let reply = resolver
+            .query_packet(query)
+            .unwrap()
+            .unwrap_or_else(|| panic!("No reply for {scope:?}"));
+        let reply = Packet::parse(&reply).unwrap();
+        assert!(reply.answers.iter().any(|answer| answer.name == srv_name));
+    }
+}
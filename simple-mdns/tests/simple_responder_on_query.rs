@@ -0,0 +1,48 @@
+#![cfg(feature = "sync")]
+
+use std::{
+    error::Error,
+    net::{Ipv4Addr, UdpSocket},
+    sync::mpsc,
+    time::Duration,
+};
+
+use simple_dns::{Name, Packet, Question, CLASS, QTYPE, TYPE};
+use simple_mdns::sync_discovery::SimpleMdnsResponder;
+
+#[test]
+fn on_query_is_called_with_the_question_and_origin() -> Result<(), Box<dyn Error>> {
+    let responder_socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0))?;
+    let responder_addr = responder_socket.local_addr()?;
+    let client_socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0))?;
+
+    let (sender, receiver) = mpsc::channel();
+    let mut responder = SimpleMdnsResponder::default();
+    responder.on_query(move |query, origin| {
+        let questions: Vec<(String, QTYPE)> = query
+            .questions
+            .iter()
+            .map(|question| (question.qname.to_string(), question.qtype))
+            .collect();
+        let _ = sender.send((questions, origin));
+    });
+    responder.listen_on_socket(responder_socket);
+
+    let mut query = Packet::new_query(42);
+    query.questions.push(Question::new(
+        Name::new_unchecked("_srv._tcp.local"),
+        TYPE::SRV.into(),
+        CLASS::IN.into(),
+        false,
+    ));
+    client_socket.send_to(&query.build_bytes_vec()?, responder_addr)?;
+
+    let (questions, origin) = receiver.recv_timeout(Duration::from_secs(5))?;
+    assert_eq!(
+        vec![("_srv._tcp.local".to_string(), QTYPE::TYPE(TYPE::SRV))],
+        questions
+    );
+    assert_eq!(client_socket.local_addr()?, origin);
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+#![cfg(feature = "sync")]
+
+use std::{net::Ipv4Addr, thread, time::Duration};
+
+use simple_dns::{
+    rdata::{RData, A},
+    Name, ResourceRecord, CLASS,
+};
+use simple_mdns::{sync_discovery::SimpleMdnsResponder, NetworkScope, SimpleMdnsError};
+
+fn address_record(name: &Name<'static>, address: Ipv4Addr) -> ResourceRecord<'static> {
+    ResourceRecord::new(
+        name.clone(),
+        CLASS::IN,
+        10,
+        RData::A(A {
+            address: address.into(),
+        }),
+    )
+}
+
+#[test]
+fn probe_fails_when_another_host_owns_the_name() {
+    let conflicting = Name::new_unchecked("probe-conflict.local");
+    let unique = Name::new_unchecked("probe-unique.local");
+
+    let mut existing = SimpleMdnsResponder::new_with_scope(10, NetworkScope::V4);
+    existing.add_resource(address_record(&conflicting, Ipv4Addr::new(10, 0, 0, 1)));
+
+    let mut prober = SimpleMdnsResponder::new_with_scope(10, NetworkScope::V4);
+    prober.add_resource(address_record(&conflicting, Ipv4Addr::new(10, 0, 0, 2)));
+    prober.add_resource(address_record(&unique, Ipv4Addr::new(10, 0, 0, 2)));
+    thread::sleep(Duration::from_millis(500));
+
+    assert!(matches!(
+        prober.probe(&conflicting),
+        Err(SimpleMdnsError::NameConflict(name)) if name == "probe-conflict.local"
+    ));
+    assert!(prober.probe(&unique).is_ok());
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "async-tokio")]
+
+use std::{net::Ipv4Addr, time::Duration};
+
+use simple_dns::{
+    rdata::{RData, A},
+    Name, ResourceRecord, CLASS,
+};
+use simple_mdns::{async_discovery::SimpleMdnsResponder, NetworkScope, SimpleMdnsError};
+
+#[tokio::test]
+async fn responder_shuts_down_within_timeout() {
+    let responder = SimpleMdnsResponder::new_with_scope(10, NetworkScope::V4);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let result = tokio::time::timeout(Duration::from_secs(1), responder.shutdown())
+        .await
+        .expect("Responder did not shut down in time");
+    assert!(result.is_ok());
+}
+
+fn address_record(name: &Name<'static>, address: Ipv4Addr) -> ResourceRecord<'static> {
+    ResourceRecord::new(
+        name.clone(),
+        CLASS::IN,
+        10,
+        RData::A(A {
+            address: address.into(),
+        }),
+    )
+}
+
+#[tokio::test]
+async fn probe_fails_when_another_host_owns_the_name() {
+    let conflicting = Name::new_unchecked("async-probe-conflict.local");
+    let unique = Name::new_unchecked("async-probe-unique.local");
+
+    let mut existing = SimpleMdnsResponder::new_with_scope(10, NetworkScope::V4);
+    existing
+        .add_resource(address_record(&conflicting, Ipv4Addr::new(10, 0, 0, 1)))
+        .await;
+
+    let mut prober = SimpleMdnsResponder::new_with_scope(10, NetworkScope::V4);
+    prober
+        .add_resource(address_record(&conflicting, Ipv4Addr::new(10, 0, 0, 2)))
+        .await;
+    prober
+        .add_resource(address_record(&unique, Ipv4Addr::new(10, 0, 0, 2)))
+        .await;
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    assert!(matches!(
+        prober.probe(&conflicting).await,
+        Err(SimpleMdnsError::NameConflict(name)) if name == "async-probe-conflict.local"
+    ));
+    assert!(prober.probe(&unique).await.is_ok());
+}
@@ -0,0 +1,98 @@
+#![cfg(feature = "sync")]
+
+use std::{
+    error::Error,
+    net::{Ipv4Addr, SocketAddr, UdpSocket},
+    thread,
+    time::{Duration, Instant},
+};
+
+use simple_dns::{
+    rdata::{RData, A},
+    Name, Packet, PacketFlag, Question, ResourceRecord, CLASS, TYPE,
+};
+use simple_mdns::{
+    sync_discovery::SimpleMdnsResponder, NetworkScope, MULTICAST_ADDR_IPV4, MULTICAST_PORT,
+};
+use socket2::{Domain, Protocol, Socket, Type};
+
+const QUERY_ID: u16 = 4242;
+
+fn multicast_listener() -> Result<UdpSocket, Box<dyn Error>> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    #[cfg(not(windows))]
+    socket.set_reuse_port(true)?;
+    socket.bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, MULTICAST_PORT)).into())?;
+    socket.join_multicast_v4(&MULTICAST_ADDR_IPV4, &Ipv4Addr::UNSPECIFIED)?;
+    socket.set_read_timeout(Some(Duration::from_millis(100)))?;
+
+    Ok(socket.into())
+}
+
+/// Returns the owner names of the answers of the first reply to the test query received by `socket`
+fn reply_answers(socket: &UdpSocket) -> Result<Vec<String>, Box<dyn Error>> {
+    let deadline = Instant::now() + Duration::from_secs(3);
+    let mut buf = [0u8; 9000];
+    while Instant::now() < deadline {
+        let count = match socket.recv(&mut buf) {
+            Ok(count) => count,
+            Err(_) => continue,
+        };
+
+        match Packet::parse(&buf[..count]) {
+            Ok(reply) if reply.has_flags(PacketFlag::RESPONSE) && reply.id() == QUERY_ID => {
+                return Ok(reply
+                    .answers
+                    .iter()
+                    .map(|answer| answer.name.to_string())
+                    .collect());
+            }
+            _ => continue,
+        }
+    }
+
+    Err("no reply received".into())
+}
+
+#[test]
+#[cfg(not(target_os = "macos"))]
+fn qu_and_qm_questions_are_answered_separately() -> Result<(), Box<dyn Error>> {
+    let unicast_name = Name::new_unchecked("qu-host._sync_split._tcp.local");
+    let multicast_name = Name::new_unchecked("qm-host._sync_split._tcp.local");
+
+    let mut responder = SimpleMdnsResponder::new_with_scope(10, NetworkScope::V4);
+    responder.set_response_delay(Duration::ZERO, Duration::ZERO);
+    for name in [&unicast_name, &multicast_name] {
+        responder.add_resource(ResourceRecord::new(
+            name.clone(),
+            CLASS::IN,
+            10,
+            RData::A(A::from(Ipv4Addr::LOCALHOST)),
+        ));
+    }
+
+    let listener = multicast_listener()?;
+    let client = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+    client.set_read_timeout(Some(Duration::from_millis(100)))?;
+    thread::sleep(Duration::from_millis(500));
+
+    let query = Packet::new_query(QUERY_ID).with_questions([
+        Question::new(unicast_name.clone(), TYPE::A.into(), CLASS::IN.into(), true),
+        Question::new(
+            multicast_name.clone(),
+            TYPE::A.into(),
+            CLASS::IN.into(),
+            false,
+        ),
+    ]);
+    client.send_to(
+        &query.build_bytes_vec()?,
+        (MULTICAST_ADDR_IPV4, MULTICAST_PORT),
+    )?;
+
+    assert_eq!(vec![unicast_name.to_string()], reply_answers(&client)?);
+    assert_eq!(vec![multicast_name.to_string()], reply_answers(&listener)?);
+
+    Ok(())
+}
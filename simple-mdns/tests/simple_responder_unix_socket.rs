@@ -0,0 +1,50 @@
+#![cfg(all(feature = "sync", unix))]
+
+use std::{error::Error, net::Ipv4Addr, os::unix::net::UnixDatagram, time::Duration};
+
+use simple_dns::{
+    rdata::{RData, A},
+    Name, Packet, Question, ResourceRecord, CLASS, TYPE,
+};
+use simple_mdns::sync_discovery::SimpleMdnsResponder;
+
+#[test]
+fn responder_replies_over_unix_socket() -> Result<(), Box<dyn Error>> {
+    let (responder_socket, client_socket) = UnixDatagram::pair()?;
+    client_socket.set_read_timeout(Some(Duration::from_secs(5)))?;
+
+    let mut responder = SimpleMdnsResponder::default();
+    responder.add_resource(ResourceRecord::new(
+        Name::new_unchecked("_srv._tcp.local"),
+        CLASS::IN,
+        10,
+        RData::A(A {
+            address: Ipv4Addr::LOCALHOST.into(),
+        }),
+    ));
+    responder.listen_on_socket(responder_socket);
+
+    let mut query = Packet::new_query(42);
+    query.questions.push(Question::new(
+        Name::new_unchecked("_srv._tcp.local"),
+        TYPE::A.into(),
+        CLASS::IN.into(),
+        false,
+    ));
+    client_socket.send(&query.build_bytes_vec()?)?;
+
+    let mut buffer = [0u8; 9000];
+    let count = client_socket.recv(&mut buffer)?;
+    let reply = Packet::parse(&buffer[..count])?;
+
+    assert_eq!(42, reply.id());
+    assert_eq!(1, reply.answers.len());
+    assert_eq!(
+        RData::A(A {
+            address: Ipv4Addr::LOCALHOST.into()
+        }),
+        reply.answers[0].rdata
+    );
+
+    Ok(())
+}